@@ -21,6 +21,8 @@ fi
  fi
 
  cp target/release/anim_to_vtk "$EXEC_DIR/anim_to_vtk_linux64_gf"
+ cp target/release/anim_to_vtk "$EXEC_DIR/anim_header_diff_linux64_gf"
+ cp target/release/anim_to_vtk "$EXEC_DIR/anim_info_linux64_gf"
 
  echo " " 
  echo "Build succeeded"
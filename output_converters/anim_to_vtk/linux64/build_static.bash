@@ -0,0 +1,37 @@
+#!/bin/bash
+
+#
+# Static musl build with every optional backend (--features static-full).
+# Needs the musl target (rustup target add x86_64-unknown-linux-musl)
+# and a musl C compiler for the vendored C sources (musl-gcc or x86_64-linux-musl-gcc).
+#
+
+#
+# check if exec directory exists, create if not
+#
+if [ ! -d ../../../exec ]
+then
+   mkdir ../../../exec
+fi
+
+ EXEC_DIR=$(cd ../../../exec && pwd)
+ cd ..
+ TARGET=x86_64-unknown-linux-musl
+ cargo build --release --target $TARGET --features static-full
+ export BUILD_RETURN_CODE=$?
+ if [ $BUILD_RETURN_CODE -ne 0 ]
+ then
+    echo " " 
+    echo "Build failed"
+    echo " " 
+    exit $BUILD_RETURN_CODE
+ fi
+
+ cp target/$TARGET/release/anim_to_vtk "$EXEC_DIR/anim_to_vtk_linux64_static"
+ cp target/$TARGET/release/anim_to_vtk "$EXEC_DIR/anim_header_diff_linux64_static"
+ cp target/$TARGET/release/anim_to_vtk "$EXEC_DIR/anim_info_linux64_static"
+
+ echo " " 
+ echo "Build succeeded"
+ echo " "
+ exit 0
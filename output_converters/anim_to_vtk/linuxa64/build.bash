@@ -21,6 +21,8 @@ fi
  fi
 
  cp target/release/anim_to_vtk "$EXEC_DIR/anim_to_vtk_linuxa64"
+ cp target/release/anim_to_vtk "$EXEC_DIR/anim_header_diff_linuxa64"
+ cp target/release/anim_to_vtk "$EXEC_DIR/anim_info_linuxa64"
 
  echo " " 
  echo "Build succeeded"
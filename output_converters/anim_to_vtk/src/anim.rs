@@ -0,0 +1,1229 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Radioss animation file (A-file) reader
+// ****************************************
+
+// The reader keeps every section of the format, even the ones no output uses yet
+#![allow(dead_code)]
+
+use std::borrow::Cow;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use crate::layout::{format_layout, Block, FormatLayout};
+use crate::read_ahead::ReadAhead;
+
+pub const FASTMAGI10: i32 = 0x542c;
+
+// ****************************************
+// AnimReadError - why an A-file could not be read, with the section
+// being read and its offset, so that a truncated or corrupted file is
+// reported and the run goes on with the next input
+// ****************************************
+#[derive(Debug)]
+pub enum AnimReadError {
+    Open(io::Error),
+    UnknownVersion(i32),
+    // section larger than what is left of the file
+    Truncated { section: String, offset: u64, count: usize, kind: &'static str, size: u64, available: u64 },
+    // negative element, part or field count
+    InvalidCount { section: String, offset: u64, value: i32 },
+    Io { section: String, offset: u64, source: io::Error },
+    // section left on disk that can't be read back: the file changed during the run
+    Reread { offset: u64, source: io::Error },
+}
+
+impl fmt::Display for AnimReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnimReadError::Open(e) => write!(f, "can't open input file: {}", e),
+            AnimReadError::UnknownVersion(magic) => write!(
+                f,
+                "unknown format version 0x{:x}, only FASTMAGI10 (0x{:x}) is supported",
+                magic, FASTMAGI10
+            ),
+            AnimReadError::Truncated { section, offset, count, kind, size, available } => write!(
+                f,
+                "truncated file: section {} at offset 0x{:08x} expects {} {} ({} bytes), only {} bytes left",
+                section, offset, count, kind, size, available
+            ),
+            AnimReadError::InvalidCount { section, offset, value } => {
+                write!(f, "invalid {} {} at offset 0x{:08x}", section, value, offset)
+            }
+            AnimReadError::Io { section, offset, source } => {
+                write!(f, "can't read section {} at offset 0x{:08x}: {}", section, offset, source)
+            }
+            AnimReadError::Reread { offset, source } => {
+                write!(f, "input file changed while converting, can't read it back at offset 0x{:08x}: {}", offset, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AnimReadError {}
+
+impl AnimReadError {
+    // diagnostic code of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            AnimReadError::Open(_) => report::codes::CANT_OPEN,
+            AnimReadError::UnknownVersion(_) => report::codes::UNKNOWN_VERSION,
+            AnimReadError::Truncated { .. } => report::codes::TRUNCATED_SECTION,
+            AnimReadError::InvalidCount { .. } => report::codes::INVALID_COUNT,
+            AnimReadError::Io { .. } | AnimReadError::Reread { .. } => report::codes::READ_FAILED,
+        }
+    }
+}
+
+// for the outputs reading the sections left on disk as they write
+impl From<AnimReadError> for io::Error {
+    fn from(e: AnimReadError) -> Self {
+        io::Error::other(e)
+    }
+}
+
+// the read error behind an output error, if the output failed on the input
+pub fn read_error_of(e: &io::Error) -> Option<&AnimReadError> {
+    e.get_ref().and_then(|source| source.downcast_ref::<AnimReadError>())
+}
+
+// ****************************************
+// Byte order of the values of an A-file: big endian as the solver
+// writes them, little endian from tools rewriting A-files in the
+// native order of their platform
+// ****************************************
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Endian {
+    #[default]
+    Big,
+    Little,
+}
+
+impl Endian {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "big" => Some(Endian::Big),
+            "little" => Some(Endian::Little),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Endian::Big => "big endian",
+            Endian::Little => "little endian",
+        }
+    }
+}
+
+// The magic number tells the byte order: a known format version read
+// big endian, or read little endian, unless the byte order is given
+fn detect_endian(magic: i32, forced: Option<Endian>) -> Result<(Endian, &'static FormatLayout), AnimReadError> {
+    let candidates = match forced {
+        Some(endian) => vec![endian],
+        None => vec![Endian::Big, Endian::Little],
+    };
+    let value = |endian| if endian == Endian::Little { magic.swap_bytes() } else { magic };
+    for endian in candidates {
+        if let Some(layout) = format_layout(value(endian)) {
+            return Ok((endian, layout));
+        }
+    }
+    Err(AnimReadError::UnknownVersion(value(forced.unwrap_or_default())))
+}
+
+// ****************************************
+// read data from file, in the byte order of the A-file
+// ****************************************
+fn read_i32<R: Read>(reader: &mut R, endian: Endian) -> io::Result<i32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(match endian {
+        Endian::Big => i32::from_be_bytes(buf),
+        Endian::Little => i32::from_le_bytes(buf),
+    })
+}
+
+fn read_f32<R: Read>(reader: &mut R, endian: Endian) -> io::Result<f32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(match endian {
+        Endian::Big => f32::from_be_bytes(buf),
+        Endian::Little => f32::from_le_bytes(buf),
+    })
+}
+
+// bytes converted per read_exact: arrays are swapped chunk by chunk
+// into their vector, without a copy of the whole section
+const CHUNK_SIZE: usize = 1 << 16;
+
+fn read_vec<R: Read, T, const N: usize>(reader: &mut R, count: usize, from_bytes: fn([u8; N]) -> T) -> io::Result<Vec<T>> {
+    let mut result = Vec::with_capacity(count);
+    let mut chunk = vec![0u8; CHUNK_SIZE.min(count * N)];
+    let mut remaining = count * N;
+    while remaining > 0 {
+        let bytes = &mut chunk[..remaining.min(CHUNK_SIZE)];
+        reader.read_exact(bytes)?;
+        result.extend(bytes.chunks_exact(N).map(|value| from_bytes(value.try_into().unwrap())));
+        remaining -= bytes.len();
+    }
+    Ok(result)
+}
+
+fn read_i32_vec<R: Read>(reader: &mut R, count: usize, endian: Endian) -> io::Result<Vec<i32>> {
+    match endian {
+        Endian::Big => read_vec(reader, count, i32::from_be_bytes),
+        Endian::Little => read_vec(reader, count, i32::from_le_bytes),
+    }
+}
+
+fn read_f32_vec<R: Read>(reader: &mut R, count: usize, endian: Endian) -> io::Result<Vec<f32>> {
+    match endian {
+        Endian::Big => read_vec(reader, count, f32::from_be_bytes),
+        Endian::Little => read_vec(reader, count, f32::from_le_bytes),
+    }
+}
+
+fn read_u16_vec<R: Read>(reader: &mut R, count: usize, endian: Endian) -> io::Result<Vec<u16>> {
+    match endian {
+        Endian::Big => read_vec(reader, count, u16::from_be_bytes),
+        Endian::Little => read_vec(reader, count, u16::from_le_bytes),
+    }
+}
+
+fn read_bytes<R: Read>(reader: &mut R, count: usize) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; count];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+// ****************************************
+// Encoding of the text records (titles, part, material and field
+// names): the solver copies them from the input deck as they are
+// ****************************************
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum TextEncoding {
+    #[default]
+    Utf8, // invalid sequences replaced by U+FFFD
+    Latin1,
+    #[cfg(feature = "encodings")]
+    ShiftJis,
+}
+
+impl TextEncoding {
+    fn decode(self, bytes: &[u8]) -> String {
+        match self {
+            TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            TextEncoding::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+            #[cfg(feature = "encodings")]
+            TextEncoding::ShiftJis => encoding_rs::SHIFT_JIS.decode_without_bom_handling(bytes).0.into_owned(),
+        }
+    }
+}
+
+fn read_text<R: Read>(reader: &mut R, count: usize, encoding: TextEncoding) -> io::Result<String> {
+    let buf = read_bytes(reader, count)?;
+    Ok(encoding.decode(&buf).trim_end_matches('\0').to_string())
+}
+
+fn read_texts<R: Read>(reader: &mut R, count: usize, len: usize, encoding: TextEncoding) -> io::Result<Vec<String>> {
+    (0..count).map(|_| read_text(reader, len, encoding)).collect()
+}
+
+// ****************************************
+// Memory budget: geometry is always loaded, a field section is kept
+// in memory only when its size, known from the section header, fits
+// in what is left. Other sections stay on disk and are read back one
+// field at a time when written.
+// ****************************************
+struct MemoryBudget {
+    remaining: Option<u64>,
+}
+
+impl MemoryBudget {
+    fn take(&mut self, bytes: usize) {
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(bytes as u64);
+        }
+    }
+
+    fn fits(&mut self, bytes: usize) -> bool {
+        match self.remaining.as_mut() {
+            Some(remaining) if *remaining < bytes as u64 => false,
+            _ => {
+                self.take(bytes);
+                true
+            }
+        }
+    }
+}
+
+// ****************************************
+// AnimReader - reads the named sections of an A-file in order.
+// Every section is checked against what is left of the file before
+// it is allocated, so that a truncated file or a corrupted count
+// gives an AnimReadError naming the section.
+// With dump set, every section is printed with its byte range,
+// count and first values (--dump-layout).
+// ****************************************
+struct AnimReader<R: Read + Seek> {
+    reader: R,
+    offset: u64,
+    file_size: u64,
+    dump: bool,
+    encoding: TextEncoding,
+    headers_only: bool, // per node and per element sections skipped
+    endian: Endian,     // byte order, known once the magic number is read
+}
+
+// first values shown by --dump-layout
+const DUMP_VALUES: usize = 4;
+
+type ReadResult<T> = Result<T, AnimReadError>;
+
+impl<R: Read + Seek> AnimReader<R> {
+    fn new(reader: R, file_size: u64, dump: bool, encoding: TextEncoding, headers_only: bool) -> Self {
+        AnimReader { reader, offset: 0, file_size, dump, encoding, headers_only, endian: Endian::Big }
+    }
+
+    // reads a section of <count> values of <size> bytes in all
+    fn section<T>(
+        &mut self,
+        name: &str,
+        kind: &'static str,
+        count: usize,
+        size: usize,
+        read: impl FnOnce(&mut R) -> io::Result<T>,
+    ) -> ReadResult<T> {
+        let available = self.file_size.saturating_sub(self.offset);
+        if size as u64 > available {
+            return Err(AnimReadError::Truncated {
+                section: name.to_string(),
+                offset: self.offset,
+                count,
+                kind,
+                size: size as u64,
+                available,
+            });
+        }
+        read(&mut self.reader).map_err(|source| AnimReadError::Io { section: name.to_string(), offset: self.offset, source })
+    }
+
+    fn trace(&mut self, name: &str, kind: &str, count: usize, size: usize, preview: impl FnOnce() -> String) {
+        let start = self.offset;
+        self.offset += size as u64;
+        if self.dump {
+            println!(
+                "0x{:08x}-0x{:08x} {:>10}  {:<24} {:<4} x{:<9} {}",
+                start,
+                self.offset,
+                size,
+                name,
+                kind,
+                count,
+                preview()
+            );
+        }
+    }
+
+    fn i32(&mut self, name: &str) -> ReadResult<i32> {
+        let endian = self.endian;
+        let value = self.section(name, "i32", 1, 4, |reader| read_i32(reader, endian))?;
+        self.trace(name, "i32", 1, 4, || format_i32s(&[value]));
+        Ok(value)
+    }
+
+    // number of elements, parts or fields, never negative
+    fn count(&mut self, name: &str) -> ReadResult<usize> {
+        let offset = self.offset;
+        let value = self.i32(name)?;
+        usize::try_from(value).map_err(|_| AnimReadError::InvalidCount { section: name.to_string(), offset, value })
+    }
+
+    fn f32(&mut self, name: &str) -> ReadResult<f32> {
+        let endian = self.endian;
+        let value = self.section(name, "f32", 1, 4, |reader| read_f32(reader, endian))?;
+        self.trace(name, "f32", 1, 4, || format_f32s(&[value]));
+        Ok(value)
+    }
+
+    fn i32_vec(&mut self, name: &str, count: usize) -> ReadResult<Vec<i32>> {
+        let endian = self.endian;
+        let values = self.section(name, "i32", count, 4 * count, |reader| read_i32_vec(reader, count, endian))?;
+        self.trace(name, "i32", count, 4 * count, || format_i32s(&values));
+        Ok(values)
+    }
+
+    fn f32_vec(&mut self, name: &str, count: usize) -> ReadResult<Vec<f32>> {
+        let endian = self.endian;
+        let values = self.section(name, "f32", count, 4 * count, |reader| read_f32_vec(reader, count, endian))?;
+        self.trace(name, "f32", count, 4 * count, || format_f32s(&values));
+        Ok(values)
+    }
+
+    fn u16_vec(&mut self, name: &str, count: usize) -> ReadResult<Vec<u16>> {
+        let endian = self.endian;
+        let values = self.section(name, "u16", count, 2 * count, |reader| read_u16_vec(reader, count, endian))?;
+        self.trace(name, "u16", count, 2 * count, || {
+            let shown: Vec<String> = values.iter().take(DUMP_VALUES).map(|v| format!("{} [0x{:04x}]", v, v)).collect();
+            shown.join(", ")
+        });
+        Ok(values)
+    }
+
+    fn bytes(&mut self, name: &str, count: usize) -> ReadResult<Vec<u8>> {
+        let values = self.section(name, "u8", count, count, |reader| read_bytes(reader, count))?;
+        self.trace(name, "u8", count, count, || {
+            let shown: Vec<String> = values.iter().take(DUMP_VALUES).map(|v| format!("{} [0x{:02x}]", v, v)).collect();
+            shown.join(", ")
+        });
+        Ok(values)
+    }
+
+    fn text(&mut self, name: &str, len: usize) -> ReadResult<String> {
+        let encoding = self.encoding;
+        let text = self.section(name, "char", len, len, |reader| read_text(reader, len, encoding))?;
+        self.trace(name, "char", len, len, || format!("{:?}", text));
+        Ok(text)
+    }
+
+    fn texts(&mut self, name: &str, count: usize, len: usize) -> ReadResult<Vec<String>> {
+        let encoding = self.encoding;
+        let texts = self.section(name, "char", count, count * len, |reader| read_texts(reader, count, len, encoding))?;
+        self.trace(name, "char", count, count * len, || {
+            let shown: Vec<String> = texts.iter().take(DUMP_VALUES).map(|t| format!("{:?}", t)).collect();
+            shown.join(", ")
+        });
+        Ok(texts)
+    }
+
+    // Per node or per element sections (coordinates, connectivity,
+    // flags, masses, numbering) are skipped when only the headers are
+    // read: counts, part tables and field names
+    fn skipped(&mut self, name: &str, kind: &'static str, count: usize, size: usize) -> ReadResult<bool> {
+        if !self.headers_only {
+            return Ok(false);
+        }
+        self.section(name, kind, count, size, |reader| reader.seek(SeekFrom::Current(size as i64)))?;
+        self.trace(name, kind, count, size, || "(skipped)".to_string());
+        Ok(true)
+    }
+
+    fn bulk_i32_vec(&mut self, name: &str, count: usize) -> ReadResult<Vec<i32>> {
+        if self.skipped(name, "i32", count, 4 * count)? {
+            return Ok(Vec::new());
+        }
+        self.i32_vec(name, count)
+    }
+
+    fn bulk_f32_vec(&mut self, name: &str, count: usize) -> ReadResult<Vec<f32>> {
+        if self.skipped(name, "f32", count, 4 * count)? {
+            return Ok(Vec::new());
+        }
+        self.f32_vec(name, count)
+    }
+
+    fn bulk_u16_vec(&mut self, name: &str, count: usize) -> ReadResult<Vec<u16>> {
+        if self.skipped(name, "u16", count, 2 * count)? {
+            return Ok(Vec::new());
+        }
+        self.u16_vec(name, count)
+    }
+
+    fn bulk_bytes(&mut self, name: &str, count: usize) -> ReadResult<Vec<u8>> {
+        if self.skipped(name, "u8", count, count)? {
+            return Ok(Vec::new());
+        }
+        self.bytes(name, count)
+    }
+
+    // reads <count> floats, or records their offset and skips them
+    fn f32_or_defer(
+        &mut self,
+        name: &str,
+        count: usize,
+        budget: &mut MemoryBudget,
+        offset: &mut Option<u64>,
+    ) -> ReadResult<Vec<f32>> {
+        if budget.fits(count * 4) {
+            return self.f32_vec(name, count);
+        }
+        *offset = Some(self.offset);
+        self.section(name, "f32", count, 4 * count, |reader| reader.seek(SeekFrom::Current(count as i64 * 4)))?;
+        self.trace(name, "f32", count, 4 * count, || "(left on disk)".to_string());
+        Ok(Vec::new())
+    }
+}
+
+fn format_i32s(values: &[i32]) -> String {
+    let shown: Vec<String> = values.iter().take(DUMP_VALUES).map(|v| format!("{} [0x{:08x}]", v, v)).collect();
+    shown.join(", ")
+}
+
+fn format_f32s(values: &[f32]) -> String {
+    let shown: Vec<String> = values
+        .iter()
+        .take(DUMP_VALUES)
+        .map(|v| format!("{} [0x{:08x}]", v, v.to_bits()))
+        .collect();
+    shown.join(", ")
+}
+
+// sections left on disk were found complete when the file was read:
+// failing to read them back means the file changed during the run
+fn read_f32_at(file_name: &str, offset: u64, count: usize, endian: Endian) -> Result<Vec<f32>, AnimReadError> {
+    let read = || -> io::Result<Vec<f32>> {
+        let mut reader = File::open(file_name)?;
+        reader.seek(SeekFrom::Start(offset))?;
+        read_f32_vec(&mut BufReader::new(reader), count, endian)
+    };
+    read().map_err(|source| AnimReadError::Reread { offset, source })
+}
+
+// ****************************************
+// ElementBlock - one geometry section (1D, 2D, 3D or SPH)
+// ****************************************
+#[derive(Clone, Default)]
+pub struct ElementBlock {
+    pub count: usize,                // number of elements
+    pub nodes_per_elt: usize,        // 2 (1D), 4 (2D), 8 (3D), 1 (SPH)
+    pub tens_stride: usize,          // 9 (1D torseur), 3 (2D), 6 (3D/SPH)
+    pub connect: Vec<i32>,           // nodes_per_elt * count
+    pub del_elt: Vec<u8>,            // erosion flag per element
+    pub def_part: Vec<i32>,          // cumulative element index ending each part
+    pub part_text: Vec<String>,      // part names ("<id> <title>")
+    pub efunc_text: Vec<String>,     // elemental scalar names
+    pub efunc: Vec<f32>,             // efunc_text.len() * count
+    pub tens_text: Vec<String>,      // tensor (or torseur) names
+    pub tens_val: Vec<f32>,          // tens_text.len() * tens_stride * count
+    pub e_mass: Vec<f32>,            // element mass (flag_a[0] == 1)
+    pub el_num: Vec<i32>,            // element user ids (flag_a[1])
+    pub part2subset: Vec<i32>,       // hierarchy (flag_a[4])
+    pub part_material: Vec<i32>,
+    pub part_properties: Vec<i32>,
+    pub int_arrays: Vec<(String, Vec<i32>)>, // integer cell arrays added by conversion steps
+    pub efunc_offset: Option<u64>,   // file offset of efunc when left on disk
+    pub tens_offset: Option<u64>,    // file offset of tens_val when left on disk
+}
+
+impl ElementBlock {
+    fn new(nodes_per_elt: usize, tens_stride: usize) -> Self {
+        ElementBlock {
+            nodes_per_elt,
+            tens_stride,
+            ..Default::default()
+        }
+    }
+
+    pub fn nodes(&self, iel: usize) -> &[i32] {
+        &self.connect[iel * self.nodes_per_elt..(iel + 1) * self.nodes_per_elt]
+    }
+
+    // EROSION_STATUS convention: del_elt 1 is an active element, 0 an eroded one
+    pub fn is_active(&self, iel: usize) -> bool {
+        self.del_elt.get(iel).is_none_or(|&del| del == 1)
+    }
+
+    // part index of every element, following the PART_ID resolution;
+    // parts without element (equal consecutive ends) are stepped over
+    pub fn part_indices(&self) -> Vec<usize> {
+        let mut part_index: usize = 0;
+        let mut indices = Vec::with_capacity(self.count);
+        for iel in 0..self.count {
+            while part_index < self.def_part.len() && iel >= self.def_part[part_index] as usize {
+                part_index += 1;
+            }
+            indices.push(part_index);
+        }
+        indices
+    }
+
+    // index in the material table of every element, -1 when its part has none
+    pub fn material_indices(&self, nb_materials: usize) -> Vec<i32> {
+        self.part_indices()
+            .iter()
+            .map(|&ipart| match self.part_material.get(ipart) {
+                Some(&imat) if imat >= 0 && (imat as usize) < nb_materials => imat,
+                _ => -1,
+            })
+            .collect()
+    }
+
+    // ****************************************
+    // Build a new block from a list of source elements (in part order).
+    // An element may be repeated or dropped: connectivity, flags and
+    // every elemental array follow, and the part table is rebuilt.
+    // ****************************************
+    pub fn gather(&self, sources: &[usize]) -> ElementBlock {
+        // rebuild the cumulative part ends from the part of each kept element
+        let part_of = self.part_indices();
+        let mut def_part = vec![0i32; self.def_part.len()];
+        for &iel in sources {
+            if part_of[iel] < def_part.len() {
+                def_part[part_of[iel]] += 1;
+            }
+        }
+        for ipart in 1..def_part.len() {
+            def_part[ipart] += def_part[ipart - 1];
+        }
+        self.gather_parts(sources, def_part, None)
+    }
+
+    // block of source elements all of part <ipart>, with a part table of that part only
+    pub fn gather_part(&self, ipart: usize, sources: &[usize]) -> ElementBlock {
+        self.gather_parts(sources, vec![sources.len() as i32], Some(ipart..ipart + 1))
+    }
+
+    // part tables restricted to the given parts, whole without
+    fn gather_parts(&self, sources: &[usize], def_part: Vec<i32>, parts: Option<Range<usize>>) -> ElementBlock {
+        let npe = self.nodes_per_elt;
+        let stride = self.tens_stride;
+        let count = sources.len();
+
+        let mut connect = Vec::with_capacity(count * npe);
+        for &iel in sources {
+            connect.extend_from_slice(self.nodes(iel));
+        }
+
+        let gather_u8 = |values: &[u8]| -> Vec<u8> {
+            if values.is_empty() { Vec::new() } else { sources.iter().map(|&i| values[i]).collect() }
+        };
+        let gather_i32 = |values: &[i32]| -> Vec<i32> {
+            if values.is_empty() { Vec::new() } else { sources.iter().map(|&i| values[i]).collect() }
+        };
+        let gather_f32 = |values: &[f32]| -> Vec<f32> {
+            if values.is_empty() { Vec::new() } else { sources.iter().map(|&i| values[i]).collect() }
+        };
+
+        let mut efunc = Vec::with_capacity(self.efunc_text.len() * count);
+        for ifun in 0..self.efunc_text.len() {
+            efunc.extend(gather_f32(&self.efunc[ifun * self.count..(ifun + 1) * self.count]));
+        }
+
+        let mut tens_val = Vec::with_capacity(self.tens_text.len() * stride * count);
+        for itens in 0..self.tens_text.len() {
+            let tens = &self.tens_val[itens * stride * self.count..(itens + 1) * stride * self.count];
+            for &iel in sources {
+                tens_val.extend_from_slice(&tens[iel * stride..(iel + 1) * stride]);
+            }
+        }
+
+        fn part_table<T: Clone>(values: &[T], parts: &Option<Range<usize>>) -> Vec<T> {
+            match parts {
+                Some(parts) => values.get(parts.clone()).map_or(Vec::new(), <[T]>::to_vec),
+                None => values.to_vec(),
+            }
+        }
+
+        ElementBlock {
+            count,
+            nodes_per_elt: npe,
+            tens_stride: stride,
+            connect,
+            del_elt: gather_u8(&self.del_elt),
+            def_part,
+            part_text: part_table(&self.part_text, &parts),
+            efunc_text: self.efunc_text.clone(),
+            efunc,
+            tens_text: self.tens_text.clone(),
+            tens_val,
+            e_mass: gather_f32(&self.e_mass),
+            el_num: gather_i32(&self.el_num),
+            part2subset: part_table(&self.part2subset, &parts),
+            part_material: part_table(&self.part_material, &parts),
+            part_properties: part_table(&self.part_properties, &parts),
+            int_arrays: self
+                .int_arrays
+                .iter()
+                .map(|(name, values)| (name.clone(), gather_i32(values)))
+                .collect(),
+            // gathering needs every field in memory (no memory budget)
+            efunc_offset: None,
+            tens_offset: None,
+        }
+    }
+}
+
+// ****************************************
+// Subset - one node of the part hierarchy tree
+// ****************************************
+#[derive(Clone)]
+pub struct Subset {
+    pub text: String,
+    pub num_parent: i32,
+    pub sons: Vec<i32>,
+    pub parts_2d: Vec<i32>,
+    pub parts_3d: Vec<i32>,
+    pub parts_1d: Vec<i32>,
+}
+
+#[derive(Clone, Default)]
+pub struct Hierarchy {
+    pub subsets: Vec<Subset>,
+    pub material_texts: Vec<String>,
+    pub material_types: Vec<i32>,
+    pub properties_texts: Vec<String>,
+    pub properties_types: Vec<i32>,
+}
+
+// ****************************************
+// Time history node/element lists
+// ****************************************
+#[derive(Clone, Default)]
+pub struct ThLists {
+    pub nodes: Vec<i32>,
+    pub node_texts: Vec<String>,
+    pub elts_2d: Vec<i32>,
+    pub elts_2d_texts: Vec<String>,
+    pub elts_3d: Vec<i32>,
+    pub elts_3d_texts: Vec<String>,
+    pub elts_1d: Vec<i32>,
+    pub elts_1d_texts: Vec<String>,
+}
+
+// ****************************************
+// AnimFile - content of one A-file state
+// ****************************************
+#[derive(Clone, Default)]
+pub struct AnimFile {
+    pub time: f32,
+    pub time_text: String,
+    pub mod_anim_text: String,
+    pub radioss_run_text: String,
+    pub flag_a: Vec<i32>,
+
+    pub nb_nodes: usize,
+    pub coor: Vec<f32>,              // 3 * nb_nodes
+    pub nod_num: Vec<i32>,           // node user ids (flag_a[1])
+    pub n_mass: Vec<f32>,            // nodal mass (flag_a[0] == 1)
+    pub skew: Vec<u16>,
+    pub func_text: Vec<String>,      // nodal scalar names
+    pub func: Vec<f32>,              // func_text.len() * nb_nodes
+    pub vect_text: Vec<String>,      // nodal vector names
+    pub vect_val: Vec<f32>,          // 3 * vect_text.len() * nb_nodes
+
+    pub elts_1d: ElementBlock,
+    pub elts_2d: ElementBlock,
+    pub elts_3d: ElementBlock,
+    pub elts_sph: ElementBlock,
+    pub elt2_skew_1d: Vec<i32>,
+
+    pub hierarchy: Option<Hierarchy>,
+    pub th: Option<ThLists>,
+
+    pub source: String,              // A-file the sections left on disk are read from
+    pub endian: Endian,              // byte order of its values
+    pub func_offset: Option<u64>,    // file offset of func when left on disk
+    pub vect_offset: Option<u64>,    // file offset of vect_val when left on disk
+
+    pub layout_name: &'static str,   // format layout the file was read with
+    pub unknown_flags: Vec<usize>,   // set flag_a bits unknown to that layout
+    pub trailing_bytes: u64,         // bytes left after the last section read
+}
+
+impl AnimFile {
+    // values of a field, read back from the file when left on disk
+    fn field<'a>(&self, values: &'a [f32], offset: Option<u64>, ifield: usize, len: usize) -> Result<Cow<'a, [f32]>, AnimReadError> {
+        match offset {
+            Some(offset) => Ok(Cow::Owned(read_f32_at(&self.source, offset + (4 * ifield * len) as u64, len, self.endian)?)),
+            None => Ok(Cow::Borrowed(&values[ifield * len..(ifield + 1) * len])),
+        }
+    }
+
+    pub fn nodal_scalar(&self, ifunc: usize) -> Result<Cow<'_, [f32]>, AnimReadError> {
+        self.field(&self.func, self.func_offset, ifunc, self.nb_nodes)
+    }
+
+    pub fn nodal_vector(&self, ivect: usize) -> Result<Cow<'_, [f32]>, AnimReadError> {
+        self.field(&self.vect_val, self.vect_offset, ivect, 3 * self.nb_nodes)
+    }
+
+    pub fn elemental_scalar<'a>(&self, block: &'a ElementBlock, iefun: usize) -> Result<Cow<'a, [f32]>, AnimReadError> {
+        self.field(&block.efunc, block.efunc_offset, iefun, block.count)
+    }
+
+    pub fn elemental_tensor<'a>(&self, block: &'a ElementBlock, itens: usize) -> Result<Cow<'a, [f32]>, AnimReadError> {
+        self.field(&block.tens_val, block.tens_offset, itens, block.tens_stride * block.count)
+    }
+
+    // material names of the hierarchy block, empty without it
+    pub fn material_names(&self) -> &[String] {
+        self.hierarchy.as_ref().map_or(&[], |hierarchy| &hierarchy.material_texts)
+    }
+
+    // elements of every type, the cells of the output
+    pub fn nb_elements(&self) -> usize {
+        self.elts_1d.count + self.elts_2d.count + self.elts_3d.count + self.elts_sph.count
+    }
+
+    // number of field sections left on disk by the memory budget
+    pub fn deferred_sections(&self) -> usize {
+        let blocks = [&self.elts_1d, &self.elts_2d, &self.elts_3d, &self.elts_sph];
+        [self.func_offset, self.vect_offset].iter().filter(|o| o.is_some()).count()
+            + blocks
+                .iter()
+                .map(|b| b.efunc_offset.is_some() as usize + b.tens_offset.is_some() as usize)
+                .sum::<usize>()
+    }
+}
+
+// ****************************************
+// read an A-File into memory
+// ****************************************
+pub fn read_anim_file(file_name: &str) -> Result<AnimFile, AnimReadError> {
+    read_anim_file_with_budget(file_name, None, TextEncoding::default(), None)
+}
+
+// with a budget in bytes, field sections that do not fit stay on disk
+pub fn read_anim_file_with_budget(
+    file_name: &str,
+    max_memory: Option<u64>,
+    encoding: TextEncoding,
+    endian: Option<Endian>, // detected from the magic number without
+) -> Result<AnimFile, AnimReadError> {
+    read_sections(file_name, max_memory, false, encoding, false, endian)
+}
+
+// counts, flags, part tables and field names of an A-file, without
+// its per node and per element sections, which are skipped
+pub fn read_anim_headers(file_name: &str, encoding: TextEncoding, endian: Option<Endian>) -> Result<AnimFile, AnimReadError> {
+    read_sections(file_name, Some(0), false, encoding, true, endian)
+}
+
+// ****************************************
+// time of an A-file, read from its header only
+// ****************************************
+pub fn read_header_time(file_name: &str, endian: Option<Endian>) -> Result<f32, String> {
+    let mut header = [0u8; 8];
+    File::open(file_name)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map_err(|e| format!("can't read header: {}", e))?;
+    let magic = i32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let (endian, _) = detect_endian(magic, endian).map_err(|e| e.to_string())?;
+    read_f32(&mut &header[4..], endian).map_err(|e| format!("can't read header: {}", e))
+}
+
+// ****************************************
+// print the layout of an A-file: every section read, in file order,
+// up to the section that can't be read, if any
+// ****************************************
+pub fn dump_layout(file_name: &str, encoding: TextEncoding, endian: Option<Endian>) -> Result<(), AnimReadError> {
+    println!("{}", file_name);
+    println!("{:<21} {:>10}  {:<24} {:<4} {:<10} first values", "byte range", "bytes", "section", "type", "count");
+    let read = read_sections(file_name, None, true, encoding, false, endian);
+    let file_size = std::fs::metadata(file_name).map_or(0, |metadata| metadata.len());
+    println!("file size 0x{:08x} ({} bytes)", file_size, file_size);
+    read.map(|_| ())
+}
+
+fn read_sections(
+    file_name: &str,
+    max_memory: Option<u64>,
+    dump: bool,
+    encoding: TextEncoding,
+    headers_only: bool,
+    endian: Option<Endian>,
+) -> Result<AnimFile, AnimReadError> {
+    let mut budget = MemoryBudget { remaining: max_memory };
+    let input_file = File::open(file_name).map_err(AnimReadError::Open)?;
+    let file_size = input_file.metadata().map_err(AnimReadError::Open)?.len();
+    let mut inf = AnimReader::new(ReadAhead::new(input_file), file_size, dump, encoding, headers_only);
+
+    // read big endian first, it gives the byte order of the rest
+    let magic = inf.i32("magic")?;
+    let (endian, layout) = detect_endian(magic, endian)?;
+    inf.endian = endian;
+    if dump {
+        println!("byte order {}", endian.name());
+    }
+
+    let mut anim = AnimFile {
+        elts_1d: ElementBlock::new(2, 9),
+        elts_2d: ElementBlock::new(4, 3),
+        elts_3d: ElementBlock::new(8, 6),
+        elts_sph: ElementBlock::new(1, 6),
+        source: file_name.to_string(),
+        endian,
+        ..Default::default()
+    };
+
+    anim.time = inf.f32("time")?;
+    anim.time_text = inf.text("time_text", 81)?;
+    anim.mod_anim_text = inf.text("mod_anim_text", 81)?;
+    anim.radioss_run_text = inf.text("radioss_run_text", 81)?;
+
+    // flag_a[8] is not used by FASTMAGI10: there is no 8-node facet
+    // layout, facets always have 4 nodes (triangles repeat the last one)
+    anim.flag_a = inf.i32_vec("flag_a", 10)?;
+    let flag_a = anim.flag_a.clone();
+
+    // ********************
+    // 2D GEOMETRY
+    // ********************
+    let nb_nodes = inf.count("nb_nodes")?;
+    let nb_facets = inf.count("nb_facets")?;
+    let nb_parts = inf.count("nb_parts")?;
+    let nb_func = inf.count("nb_func")?;
+    let nb_efunc = inf.count("nb_efunc")?;
+    let nb_vect = inf.count("nb_vect")?;
+    let nb_tens = inf.count("nb_tens")?;
+    let nb_skew = inf.count("nb_skew")?;
+    anim.nb_nodes = nb_nodes;
+
+    if nb_skew > 0 {
+        // skew values are read but only used internally, not in VTK output
+        anim.skew = inf.u16_vec("skew", nb_skew * 6)?;
+    }
+
+    anim.coor = inf.bulk_f32_vec("coor", 3 * nb_nodes)?;
+    // geometry, masses and numbering of the 2D section
+    budget.take(4 * (3 * nb_nodes + nb_facets * 9 + 2 * nb_nodes + 2 * nb_facets));
+
+    let shells = &mut anim.elts_2d;
+    shells.count = nb_facets;
+    if nb_facets > 0 {
+        shells.connect = inf.bulk_i32_vec("2d.connect", nb_facets * 4)?;
+        shells.del_elt = inf.bulk_bytes("2d.del_elt", nb_facets)?;
+    }
+
+    if nb_parts > 0 {
+        shells.def_part = inf.i32_vec("2d.def_part", nb_parts)?;
+        shells.part_text = inf.texts("2d.part_text", nb_parts, 50)?;
+    }
+
+    let _norm_short_a = inf.bulk_u16_vec("norm_short_a", 3 * nb_nodes)?;
+
+    if nb_func + nb_efunc > 0 {
+        let mut f_text_a = inf.texts("f_text_a", nb_func + nb_efunc, 81)?;
+        shells.efunc_text = f_text_a.split_off(nb_func);
+        anim.func_text = f_text_a;
+        if nb_func > 0 {
+            anim.func = inf.f32_or_defer("func", nb_nodes * nb_func, &mut budget, &mut anim.func_offset)?;
+        }
+        if nb_efunc > 0 {
+            shells.efunc = inf.f32_or_defer("2d.efunc", nb_facets * nb_efunc, &mut budget, &mut shells.efunc_offset)?;
+        }
+    }
+
+    if nb_vect > 0 {
+        anim.vect_text = inf.texts("vect_text", nb_vect, 81)?;
+    }
+    anim.vect_val = inf.f32_or_defer("vect_val", 3 * nb_nodes * nb_vect, &mut budget, &mut anim.vect_offset)?;
+
+    if nb_tens > 0 {
+        shells.tens_text = inf.texts("2d.tens_text", nb_tens, 81)?;
+        shells.tens_val = inf.f32_or_defer("2d.tens_val", nb_facets * 3 * nb_tens, &mut budget, &mut shells.tens_offset)?;
+    }
+
+    if flag_a[0] == 1 {
+        shells.e_mass = inf.bulk_f32_vec("2d.e_mass", nb_facets)?;
+        anim.n_mass = inf.bulk_f32_vec("n_mass", nb_nodes)?;
+    }
+
+    if flag_a[1] != 0 {
+        anim.nod_num = inf.bulk_i32_vec("nod_num", nb_nodes)?;
+        shells.el_num = inf.bulk_i32_vec("2d.el_num", nb_facets)?;
+    }
+
+    if flag_a[4] != 0 {
+        shells.part2subset = inf.i32_vec("2d.part2subset", nb_parts)?;
+        shells.part_material = inf.i32_vec("2d.part_material", nb_parts)?;
+        shells.part_properties = inf.i32_vec("2d.part_properties", nb_parts)?;
+    }
+
+    // optional blocks, in file order
+    for &(flag, block) in layout.blocks {
+        if flag_a[flag] == 0 {
+            continue;
+        }
+        match block {
+            Block::Geometry3d => read_geometry_3d(&mut inf, &mut anim, &mut budget)?,
+            Block::Geometry1d => read_geometry_1d(&mut inf, &mut anim, &mut budget)?,
+            Block::Hierarchy => read_hierarchy(&mut inf, &mut anim)?,
+            Block::ThLists => read_th_lists(&mut inf, &mut anim)?,
+            Block::Sph => read_sph(&mut inf, &mut anim, &mut budget)?,
+        }
+    }
+
+    if dump {
+        println!("0x{:08x} end of sections", inf.offset);
+    }
+
+    // Blocks announced by newer flags follow the known ones: what was
+    // read is complete, the rest of the file is skipped by its size
+    // (reported by the validation, see validate.rs)
+    anim.unknown_flags = layout.unknown_flags(&flag_a);
+    anim.layout_name = layout.name;
+    anim.trailing_bytes = file_size.saturating_sub(inf.offset);
+    Ok(anim)
+}
+
+// ********************
+// 3D GEOMETRY
+// ********************
+fn read_geometry_3d<R: Read + Seek>(inf: &mut AnimReader<R>, anim: &mut AnimFile, budget: &mut MemoryBudget) -> ReadResult<()> {
+    let flag_a = anim.flag_a.clone();
+    let solids = &mut anim.elts_3d;
+    let nb_elts_3d = inf.count("nb_elts_3d")?;
+    let nb_parts_3d = inf.count("nb_parts_3d")?;
+    let nb_efunc_3d = inf.count("nb_efunc_3d")?;
+    let nb_tens_3d = inf.count("nb_tens_3d")?;
+    solids.count = nb_elts_3d;
+    budget.take(4 * nb_elts_3d * 11);
+
+    solids.connect = inf.bulk_i32_vec("3d.connect", nb_elts_3d * 8)?;
+    solids.del_elt = inf.bulk_bytes("3d.del_elt", nb_elts_3d)?;
+
+    solids.def_part = inf.i32_vec("3d.def_part", nb_parts_3d)?;
+    solids.part_text = inf.texts("3d.part_text", nb_parts_3d, 50)?;
+
+    if nb_efunc_3d > 0 {
+        solids.efunc_text = inf.texts("3d.efunc_text", nb_efunc_3d, 81)?;
+        solids.efunc = inf.f32_or_defer("3d.efunc", nb_efunc_3d * nb_elts_3d, budget, &mut solids.efunc_offset)?;
+    }
+
+    if nb_tens_3d > 0 {
+        solids.tens_text = inf.texts("3d.tens_text", nb_tens_3d, 81)?;
+        solids.tens_val = inf.f32_or_defer("3d.tens_val", nb_elts_3d * 6 * nb_tens_3d, budget, &mut solids.tens_offset)?;
+    }
+
+    if flag_a[0] == 1 {
+        solids.e_mass = inf.bulk_f32_vec("3d.e_mass", nb_elts_3d)?;
+    }
+    if flag_a[1] == 1 {
+        solids.el_num = inf.bulk_i32_vec("3d.el_num", nb_elts_3d)?;
+    }
+    if flag_a[4] != 0 {
+        solids.part2subset = inf.i32_vec("3d.part2subset", nb_parts_3d)?;
+        solids.part_material = inf.i32_vec("3d.part_material", nb_parts_3d)?;
+        solids.part_properties = inf.i32_vec("3d.part_properties", nb_parts_3d)?;
+    }
+    Ok(())
+}
+
+// ********************
+// 1D GEOMETRY
+// ********************
+fn read_geometry_1d<R: Read + Seek>(inf: &mut AnimReader<R>, anim: &mut AnimFile, budget: &mut MemoryBudget) -> ReadResult<()> {
+    let flag_a = anim.flag_a.clone();
+    let beams = &mut anim.elts_1d;
+    let nb_elts_1d = inf.count("nb_elts_1d")?;
+    let nb_parts_1d = inf.count("nb_parts_1d")?;
+    let nb_efunc_1d = inf.count("nb_efunc_1d")?;
+    let nb_tors_1d = inf.count("nb_tors_1d")?;
+    let is_skew_1d = inf.i32("is_skew_1d")?;
+    beams.count = nb_elts_1d;
+    budget.take(4 * nb_elts_1d * 6);
+
+    beams.connect = inf.bulk_i32_vec("1d.connect", nb_elts_1d * 2)?;
+    beams.del_elt = inf.bulk_bytes("1d.del_elt", nb_elts_1d)?;
+
+    beams.def_part = inf.i32_vec("1d.def_part", nb_parts_1d)?;
+    beams.part_text = inf.texts("1d.part_text", nb_parts_1d, 50)?;
+
+    if nb_efunc_1d > 0 {
+        beams.efunc_text = inf.texts("1d.efunc_text", nb_efunc_1d, 81)?;
+        beams.efunc = inf.f32_or_defer("1d.efunc", nb_efunc_1d * nb_elts_1d, budget, &mut beams.efunc_offset)?;
+    }
+
+    if nb_tors_1d > 0 {
+        beams.tens_text = inf.texts("1d.tens_text", nb_tors_1d, 81)?;
+        beams.tens_val = inf.f32_or_defer("1d.tens_val", nb_elts_1d * 9 * nb_tors_1d, budget, &mut beams.tens_offset)?;
+    }
+
+    if is_skew_1d != 0 {
+        anim.elt2_skew_1d = inf.bulk_i32_vec("elt2_skew_1d", nb_elts_1d)?;
+    }
+    if flag_a[0] == 1 {
+        beams.e_mass = inf.bulk_f32_vec("1d.e_mass", nb_elts_1d)?;
+    }
+    if flag_a[1] == 1 {
+        beams.el_num = inf.bulk_i32_vec("1d.el_num", nb_elts_1d)?;
+    }
+    if flag_a[4] != 0 {
+        beams.part2subset = inf.i32_vec("1d.part2subset", nb_parts_1d)?;
+        beams.part_material = inf.i32_vec("1d.part_material", nb_parts_1d)?;
+        beams.part_properties = inf.i32_vec("1d.part_properties", nb_parts_1d)?;
+    }
+    Ok(())
+}
+
+// ********************
+// hierarchy
+// ********************
+fn read_hierarchy<R: Read + Seek>(inf: &mut AnimReader<R>, anim: &mut AnimFile) -> ReadResult<()> {
+    let mut hierarchy = Hierarchy::default();
+    let nb_subsets = inf.count("nb_subsets")?;
+    for _ in 0..nb_subsets {
+        let text = inf.text("subset.text", 50)?;
+        let num_parent = inf.i32("subset.num_parent")?;
+        let nb_subset_son = inf.count("subset.nb_subset_son")?;
+        let sons = inf.i32_vec("subset.sons", nb_subset_son)?;
+        let nb_sub_part_2d = inf.count("subset.nb_sub_part_2d")?;
+        let parts_2d = inf.i32_vec("subset.parts_2d", nb_sub_part_2d)?;
+        let nb_sub_part_3d = inf.count("subset.nb_sub_part_3d")?;
+        let parts_3d = inf.i32_vec("subset.parts_3d", nb_sub_part_3d)?;
+        let nb_sub_part_1d = inf.count("subset.nb_sub_part_1d")?;
+        let parts_1d = inf.i32_vec("subset.parts_1d", nb_sub_part_1d)?;
+        hierarchy.subsets.push(Subset {
+            text,
+            num_parent,
+            sons,
+            parts_2d,
+            parts_3d,
+            parts_1d,
+        });
+    }
+
+    let nb_materials = inf.count("nb_materials")?;
+    let nb_properties = inf.count("nb_properties")?;
+    hierarchy.material_texts = inf.texts("hierarchy.material_texts", nb_materials, 50)?;
+    hierarchy.material_types = inf.i32_vec("hierarchy.material_types", nb_materials)?;
+    hierarchy.properties_texts = inf.texts("hierarchy.properties_texts", nb_properties, 50)?;
+    hierarchy.properties_types = inf.i32_vec("hierarchy.properties_types", nb_properties)?;
+    anim.hierarchy = Some(hierarchy);
+    Ok(())
+}
+
+// ********************
+// NODES/ELTS FOR Time History
+// ********************
+fn read_th_lists<R: Read + Seek>(inf: &mut AnimReader<R>, anim: &mut AnimFile) -> ReadResult<()> {
+    let nb_nodes_th = inf.count("nb_nodes_th")?;
+    let nb_elts_2d_th = inf.count("nb_elts_2d_th")?;
+    let nb_elts_3d_th = inf.count("nb_elts_3d_th")?;
+    let nb_elts_1d_th = inf.count("nb_elts_1d_th")?;
+
+    anim.th = Some(ThLists {
+        nodes: inf.i32_vec("th.nodes", nb_nodes_th)?,
+        node_texts: inf.texts("th.node_texts", nb_nodes_th, 50)?,
+        elts_2d: inf.i32_vec("th.elts_2d", nb_elts_2d_th)?,
+        elts_2d_texts: inf.texts("th.elts_2d_texts", nb_elts_2d_th, 50)?,
+        elts_3d: inf.i32_vec("th.elts_3d", nb_elts_3d_th)?,
+        elts_3d_texts: inf.texts("th.elts_3d_texts", nb_elts_3d_th, 50)?,
+        elts_1d: inf.i32_vec("th.elts_1d", nb_elts_1d_th)?,
+        elts_1d_texts: inf.texts("th.elts_1d_texts", nb_elts_1d_th, 50)?,
+    });
+    Ok(())
+}
+
+// ********************
+// READ SPH PART
+// ********************
+fn read_sph<R: Read + Seek>(inf: &mut AnimReader<R>, anim: &mut AnimFile, budget: &mut MemoryBudget) -> ReadResult<()> {
+    let flag_a = anim.flag_a.clone();
+    let sph = &mut anim.elts_sph;
+    let nb_elts_sph = inf.count("nb_elts_sph")?;
+    let nb_parts_sph = inf.count("nb_parts_sph")?;
+    let nb_efunc_sph = inf.count("nb_efunc_sph")?;
+    let nb_tens_sph = inf.count("nb_tens_sph")?;
+    sph.count = nb_elts_sph;
+    budget.take(4 * nb_elts_sph * 5);
+
+    if nb_elts_sph > 0 {
+        sph.connect = inf.bulk_i32_vec("sph.connect", nb_elts_sph)?;
+        sph.del_elt = inf.bulk_bytes("sph.del_elt", nb_elts_sph)?;
+    }
+    if nb_parts_sph > 0 {
+        sph.def_part = inf.i32_vec("sph.def_part", nb_parts_sph)?;
+        sph.part_text = inf.texts("sph.part_text", nb_parts_sph, 50)?;
+    }
+    if nb_efunc_sph > 0 {
+        sph.efunc_text = inf.texts("sph.efunc_text", nb_efunc_sph, 81)?;
+        sph.efunc = inf.f32_or_defer("sph.efunc", nb_efunc_sph * nb_elts_sph, budget, &mut sph.efunc_offset)?;
+    }
+    if nb_tens_sph > 0 {
+        sph.tens_text = inf.texts("sph.tens_text", nb_tens_sph, 81)?;
+        sph.tens_val = inf.f32_or_defer("sph.tens_val", nb_elts_sph * nb_tens_sph * 6, budget, &mut sph.tens_offset)?;
+    }
+    if flag_a[0] == 1 {
+        sph.e_mass = inf.bulk_f32_vec("sph.e_mass", nb_elts_sph)?;
+    }
+    if flag_a[1] == 1 {
+        sph.el_num = inf.bulk_i32_vec("sph.el_num", nb_elts_sph)?;
+    }
+    if flag_a[4] != 0 {
+        sph.part2subset = inf.i32_vec("sph.part2subset", nb_parts_sph)?;
+        sph.part_material = inf.i32_vec("sph.part_material", nb_parts_sph)?;
+        sph.part_properties = inf.i32_vec("sph.part_properties", nb_parts_sph)?;
+    }
+    Ok(())
+}
+
+// ****************************************
+// Field names given on the command line match ignoring case,
+// with '_' standing for a space ("Von_Mises" for "VON MISES")
+// ****************************************
+pub fn field_name_matches(text: &str, name: &str) -> bool {
+    let normalize = |s: &str| s.trim().replace('_', " ").to_ascii_uppercase();
+    normalize(text) == normalize(name)
+}
+
+// ****************************************
+// Helper function: resolve part ID for an element
+// Advances part_index at part boundaries and parses part ID from text
+// ****************************************
+pub fn resolve_part_id(
+    iel: usize,           // Element index
+    part_index: &mut usize, // Current part index (mutated at boundaries)
+    def_part: &[i32],     // Cumulative element index ending each part
+    p_text: &[String],    // Part ID strings (to be parsed as integers)
+) -> i32 {
+    while *part_index < def_part.len() && iel >= def_part[*part_index] as usize {
+        *part_index += 1;
+    }
+    if *part_index < p_text.len() {
+        atoi_prefix(&p_text[*part_index])
+    } else {
+        0
+    }
+}
+
+// Match C/C++ atoi behavior: parse leading integer prefix, ignore trailing text.
+pub fn atoi_prefix(text: &str) -> i32 {
+    let bytes = text.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+        idx += 1;
+    }
+    let mut sign: i32 = 1;
+    if idx < bytes.len() {
+        if bytes[idx] == b'-' {
+            sign = -1;
+            idx += 1;
+        } else if bytes[idx] == b'+' {
+            idx += 1;
+        }
+    }
+    let mut value: i32 = 0;
+    let mut seen_digit = false;
+    while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+        seen_digit = true;
+        value = value.saturating_mul(10)
+            .saturating_add((bytes[idx] - b'0') as i32);
+        idx += 1;
+    }
+    if seen_digit { sign.saturating_mul(value) } else { 0 }
+}
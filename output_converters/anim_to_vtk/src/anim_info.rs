@@ -0,0 +1,303 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+
+// ****************************************
+// anim_info [--json] <A-file>...: what an A-file holds, read without
+// its per node and per element sections: time, counts, field lists,
+// parts and subsets, as text or as one JSON array of the files.
+// The anim_to_vtk binary runs it when called under that name.
+// ****************************************
+
+use std::process;
+
+use clap::Parser;
+use report::json_string;
+
+use crate::anim::{read_anim_headers, AnimFile, TextEncoding};
+use crate::header_diff::{blocks, parts};
+use crate::layout::LAYOUTS;
+
+pub const PROGRAM_NAME: &str = "anim_info";
+
+// names of the flags set, after the layout the file was read with
+fn flag_names(anim: &AnimFile) -> Vec<String> {
+    let layout = LAYOUTS.iter().find(|layout| layout.name == anim.layout_name);
+    (0..anim.flag_a.len())
+        .filter(|&flag| anim.flag_a[flag] != 0)
+        .map(|flag| match layout.and_then(|layout| layout.flag_name(flag)) {
+            Some(name) => name.to_string(),
+            None => format!("unknown flag {}", flag),
+        })
+        .collect()
+}
+
+fn trimmed(names: &[String]) -> Vec<&str> {
+    names.iter().map(|name| name.trim()).collect()
+}
+
+// subset title and parent title ("-" for the root)
+fn subsets(anim: &AnimFile) -> Vec<(&str, &str, usize)> {
+    let Some(hierarchy) = &anim.hierarchy else {
+        return Vec::new();
+    };
+    let subsets = &hierarchy.subsets;
+    subsets
+        .iter()
+        .map(|subset| {
+            let parent = usize::try_from(subset.num_parent).ok().and_then(|parent| subsets.get(parent));
+            let nb_parts = subset.parts_1d.len() + subset.parts_2d.len() + subset.parts_3d.len();
+            (subset.text.trim(), parent.map_or("-", |parent| parent.text.trim()), nb_parts)
+        })
+        .collect()
+}
+
+fn print_text(file_name: &str, anim: &AnimFile) {
+    println!("{}", file_name);
+    println!("  format      {}, {}", anim.layout_name, anim.endian.name());
+    println!("  time        {}", anim.time);
+    for (what, text) in [("run", &anim.radioss_run_text), ("title", &anim.mod_anim_text), ("time text", &anim.time_text)] {
+        if !text.trim().is_empty() {
+            println!("  {:<11} {}", what, text.trim());
+        }
+    }
+    println!("  flags       {}", flag_names(anim).join(", "));
+    println!("  nodes       {}", anim.nb_nodes);
+    for (label, block) in blocks(anim) {
+        if block.count > 0 || !block.part_text.is_empty() {
+            println!("  {:<11} {} elements, {} parts", label, block.count, block.part_text.len());
+        }
+    }
+
+    let list = |what: &str, names: &[String]| {
+        if !names.is_empty() {
+            println!("  {}: {}", what, trimmed(names).join(", "));
+        }
+    };
+    list("nodal scalars", &anim.func_text);
+    list("nodal vectors", &anim.vect_text);
+    for (label, block) in blocks(anim) {
+        list(&format!("{} elemental scalars", label), &block.efunc_text);
+        list(&format!("{} {}", label, if label == "1D" { "torseurs" } else { "tensors" }), &block.tens_text);
+    }
+
+    let mut rows = Vec::new();
+    for (label, block) in blocks(anim) {
+        for part in parts(anim, block) {
+            rows.push(vec![label.to_string(), part.id.to_string(), part.title, part.elements.to_string(), part.material]);
+        }
+    }
+    if !rows.is_empty() {
+        println!("  parts");
+        print_rows(&["type", "id", "title", "elements", "material"], rows);
+    }
+    let rows: Vec<Vec<String>> = subsets(anim)
+        .into_iter()
+        .map(|(title, parent, nb_parts)| vec![title.to_string(), parent.to_string(), nb_parts.to_string()])
+        .collect();
+    if !rows.is_empty() {
+        println!("  subsets");
+        print_rows(&["title", "parent", "parts"], rows);
+    }
+}
+
+// left aligned columns, indented under their heading
+fn print_rows(headers: &[&str], rows: Vec<Vec<String>>) {
+    let header = headers.iter().map(|header| header.to_string()).collect();
+    let rows: Vec<Vec<String>> = std::iter::once(header).chain(rows).collect();
+    let mut widths = vec![0; headers.len()];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in &rows {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, &width)| format!("{:<width$}", cell)).collect();
+        println!("    {}", cells.join("  ").trim_end());
+    }
+}
+
+fn json_list(names: &[String]) -> String {
+    let names: Vec<String> = trimmed(names).into_iter().map(json_string).collect();
+    format!("[{}]", names.join(", "))
+}
+
+fn json(file_name: &str, anim: &AnimFile) -> String {
+    let flags: Vec<String> = flag_names(anim).iter().map(|name| json_string(name)).collect();
+    let mut json = format!(
+        "  {{\n    \"file\": {},\n    \"format\": {},\n    \"byte_order\": {},\n    \"time\": {},\n",
+        json_string(file_name),
+        json_string(anim.layout_name),
+        json_string(anim.endian.name()),
+        anim.time
+    );
+    json.push_str(&format!(
+        "    \"run\": {},\n    \"title\": {},\n    \"time_text\": {},\n    \"flags\": [{}],\n",
+        json_string(anim.radioss_run_text.trim()),
+        json_string(anim.mod_anim_text.trim()),
+        json_string(anim.time_text.trim()),
+        flags.join(", ")
+    ));
+    json.push_str(&format!(
+        "    \"nodes\": {},\n    \"nodal_scalars\": {},\n    \"nodal_vectors\": {},\n    \"elements\": {{",
+        anim.nb_nodes,
+        json_list(&anim.func_text),
+        json_list(&anim.vect_text)
+    ));
+    for (i, (label, block)) in blocks(anim).into_iter().enumerate() {
+        json.push_str(if i == 0 { "\n" } else { ",\n" });
+        json.push_str(&format!(
+            "      \"{}\": {{\"count\": {}, \"scalars\": {}, \"tensors\": {}, \"parts\": [",
+            label,
+            block.count,
+            json_list(&block.efunc_text),
+            json_list(&block.tens_text)
+        ));
+        let parts: Vec<String> = parts(anim, block)
+            .iter()
+            .map(|part| {
+                format!(
+                    "{{\"id\": {}, \"title\": {}, \"elements\": {}, \"material\": {}}}",
+                    part.id,
+                    json_string(&part.title),
+                    part.elements,
+                    json_string(&part.material)
+                )
+            })
+            .collect();
+        json.push_str(&parts.join(", "));
+        json.push_str("]}");
+    }
+    let subsets: Vec<String> = subsets(anim)
+        .into_iter()
+        .map(|(title, parent, nb_parts)| {
+            format!("{{\"title\": {}, \"parent\": {}, \"parts\": {}}}", json_string(title), json_string(parent), nb_parts)
+        })
+        .collect();
+    json.push_str(&format!("\n    }},\n    \"subsets\": [{}]\n  }}", subsets.join(", ")));
+    json
+}
+
+// ****************************************
+// anim_info entry point: exits 0 when every file is read, 1 otherwise
+// ****************************************
+#[derive(Parser)]
+#[command(
+    name = PROGRAM_NAME,
+    about = "Print what each A-file holds: format, time, titles, flags, numbers of nodes and elements, field \
+             lists, part table (type, id, title, elements and material of each part) and subsets. The nodes and \
+             elements themselves are not read, and nothing is written."
+)]
+pub struct InfoArgs {
+    #[arg(long, help = "One JSON array of the files, on stdout")]
+    json: bool,
+    #[arg(value_name = "A-FILE", required = true)]
+    files: Vec<String>,
+}
+
+// called as anim_info
+pub fn main(args: &[String]) -> ! {
+    run(crate::parse_or_exit(args, report::EXIT_FAILURE))
+}
+
+pub fn run(args: InfoArgs) -> ! {
+    let (as_json, files) = (args.json, &args.files);
+    let mut exit_code = report::EXIT_SUCCESS;
+    let mut objects = Vec::new();
+    for (i, file_name) in files.iter().enumerate() {
+        match read_anim_headers(file_name, TextEncoding::default(), None) {
+            Ok(anim) if as_json => objects.push(json(file_name, &anim)),
+            Ok(anim) => {
+                if i > 0 {
+                    println!();
+                }
+                print_text(file_name, &anim);
+            }
+            Err(e) => {
+                report::for_input(file_name, || report::error(e.code(), e));
+                exit_code = report::EXIT_FAILURE;
+            }
+        }
+    }
+    if as_json {
+        println!("[\n{}\n]", objects.join(",\n"));
+    }
+    process::exit(exit_code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::{Hierarchy, Subset};
+    use crate::test_files::{quad_parts, AnimBuilder, TempDir};
+
+    #[test]
+    fn headers_of_a_file_as_json() {
+        let dir = TempDir::new("anim_info");
+        let path = dir.file("runA001");
+        AnimBuilder::new(0.5)
+            .nodes(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+            .node_ids(&[7, 8])
+            .scalar("Temperature ", &[1.0, 2.0])
+            .vector("Velocity", &[0.0; 6])
+            .write(&path);
+        let anim = read_anim_headers(&path, TextEncoding::default(), None).unwrap();
+        let empty = "{\"count\": 0, \"scalars\": [], \"tensors\": [], \"parts\": []}";
+        let expected = format!(
+            "  {{\n    \"file\": \"runA001\",\n    \"format\": \"FASTMAGI10\",\n    \"byte_order\": \"big endian\",\n    \
+             \"time\": 0.5,\n    \"run\": \"\",\n    \"title\": \"\",\n    \"time_text\": \"\",\n    \
+             \"flags\": [\"user numbering\"],\n    \"nodes\": 2,\n    \"nodal_scalars\": [\"Temperature\"],\n    \
+             \"nodal_vectors\": [\"Velocity\"],\n    \"elements\": {{\n      \"1D\": {e},\n      \"2D\": {e},\n      \
+             \"3D\": {e},\n      \"SPH\": {e}\n    }},\n    \"subsets\": []\n  }}",
+            e = empty
+        );
+        assert_eq!(json("runA001", &anim), expected);
+    }
+
+    #[test]
+    fn parts_and_subsets_as_json() {
+        let mut anim = quad_parts(&[("10 Front door", 2), ("20 Rear", 1)]);
+        let subset = |text: &str, num_parent: i32, parts_2d: Vec<i32>| Subset {
+            text: text.to_string(),
+            num_parent,
+            sons: Vec::new(),
+            parts_2d,
+            parts_3d: Vec::new(),
+            parts_1d: Vec::new(),
+        };
+        anim.hierarchy = Some(Hierarchy {
+            subsets: vec![subset("Model", -1, vec![]), subset("Doors", 0, vec![0])],
+            material_texts: vec!["Steel".to_string()],
+            ..Default::default()
+        });
+        anim.elts_2d.part_material = vec![0, -1];
+        let json = json("runA001", &anim);
+        assert!(json.contains(
+            "\"2D\": {\"count\": 3, \"scalars\": [], \"tensors\": [], \"parts\": [\
+             {\"id\": 10, \"title\": \"Front door\", \"elements\": 2, \"material\": \"Steel\"}, \
+             {\"id\": 20, \"title\": \"Rear\", \"elements\": 1, \"material\": \"-1\"}]}"
+        ));
+        assert!(json.contains(
+            "\"subsets\": [{\"title\": \"Model\", \"parent\": \"-\", \"parts\": 0}, \
+             {\"title\": \"Doors\", \"parent\": \"Model\", \"parts\": 1}]"
+        ));
+    }
+}
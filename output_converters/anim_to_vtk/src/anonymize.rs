@@ -0,0 +1,292 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Anonymization, to share a converted model without its confidential
+// details:
+// - titles, part, subset, material, property and time history names
+//   replaced by generic ones
+// - node, element and part ids renumbered by a keyed permutation, so an
+//   id gets the same new id in every state of the run, and in every run
+//   with the same seed
+// - optionally, a rigid transform drawn from the seed, applied to the
+//   coordinates, the nodal vectors and the solid and SPH tensors
+// Field names are kept: they are solver outputs, not model data.
+// ****************************************
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::anim::{atoi_prefix, AnimFile, ElementBlock};
+
+// id kinds, each renumbered with its own key
+const NODE: u64 = 1;
+const ELEMENT: u64 = 2;
+const PART: u64 = 3;
+
+const TRANSLATION_RANGE: f64 = 100.0;
+
+pub struct Anonymizer {
+    seed: u64,
+    transform: Option<RigidTransform>,
+}
+
+// rotation about the origin, then translation
+struct RigidTransform {
+    rotation: [[f64; 3]; 3],
+    translation: [f64; 3],
+}
+
+// splitmix64 finalizer
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+// uniform in [0, 1)
+fn unit(seed: u64, index: u64) -> f64 {
+    (mix(seed ^ mix(index)) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// four round Feistel network over 16 bit halves: a bijection of the u32
+fn permute(key: u64, value: u32) -> u32 {
+    let (mut left, mut right) = (value >> 16, value & 0xffff);
+    for round in 0..4u64 {
+        let f = (mix(key ^ (round << 32) ^ right as u64) & 0xffff) as u32;
+        (left, right) = (right, left ^ f);
+    }
+    (left << 16) | right
+}
+
+impl RigidTransform {
+    // uniform random rotation from a unit quaternion (Shoemake)
+    fn new(seed: u64) -> Self {
+        let (u1, u2, u3) = (unit(seed, 1), unit(seed, 2), unit(seed, 3));
+        let tau = 2.0 * std::f64::consts::PI;
+        let (a, b) = ((1.0 - u1).sqrt(), u1.sqrt());
+        let (x, y, z, w) = (a * (tau * u2).sin(), a * (tau * u2).cos(), b * (tau * u3).sin(), b * (tau * u3).cos());
+        let rotation = [
+            [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w), 2.0 * (x * z + y * w)],
+            [2.0 * (x * y + z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w)],
+            [2.0 * (x * z - y * w), 2.0 * (y * z + x * w), 1.0 - 2.0 * (x * x + y * y)],
+        ];
+        let translation = [4, 5, 6].map(|index| TRANSLATION_RANGE * (2.0 * unit(seed, index) - 1.0));
+        RigidTransform { rotation, translation }
+    }
+
+    fn rotate(&self, v: [f64; 3]) -> [f64; 3] {
+        let r = &self.rotation;
+        [0, 1, 2].map(|i| r[i][0] * v[0] + r[i][1] * v[1] + r[i][2] * v[2])
+    }
+
+    fn points(&self, values: &mut [f32]) {
+        for p in values.chunks_exact_mut(3) {
+            let q = self.rotate([p[0] as f64, p[1] as f64, p[2] as f64]);
+            for k in 0..3 {
+                p[k] = (q[k] + self.translation[k]) as f32;
+            }
+        }
+    }
+
+    fn vectors(&self, values: &mut [f32]) {
+        for v in values.chunks_exact_mut(3) {
+            let q = self.rotate([v[0] as f64, v[1] as f64, v[2] as f64]);
+            for k in 0..3 {
+                v[k] = q[k] as f32;
+            }
+        }
+    }
+
+    // R T R^t of symmetric tensors [xx, yy, zz, xy, xz, yz]
+    fn tensors(&self, values: &mut [f32]) {
+        let r = &self.rotation;
+        for t in values.chunks_exact_mut(6) {
+            let m = [
+                [t[0] as f64, t[3] as f64, t[4] as f64],
+                [t[3] as f64, t[1] as f64, t[5] as f64],
+                [t[4] as f64, t[5] as f64, t[2] as f64],
+            ];
+            let rm = [0, 1, 2].map(|i| [0, 1, 2].map(|j| (0..3).map(|k| r[i][k] * m[k][j]).sum::<f64>()));
+            let out = |i: usize, j: usize| (0..3).map(|k| rm[i][k] * r[j][k]).sum::<f64>() as f32;
+            t.copy_from_slice(&[out(0, 0), out(1, 1), out(2, 2), out(0, 1), out(0, 2), out(1, 2)]);
+        }
+    }
+}
+
+impl Anonymizer {
+    // a random seed when none is given, so that ids differ at every run
+    pub fn new(seed: Option<u64>, transform: bool) -> Self {
+        let seed = seed.unwrap_or_else(|| RandomState::new().build_hasher().finish());
+        let transform = transform.then(|| RigidTransform::new(mix(seed ^ 0x5eed)));
+        Anonymizer { seed, transform }
+    }
+
+    // positive ids are mapped to positive ids, walking the permutation
+    // until it comes back into that range; others are kept
+    fn id(&self, kind: u64, id: i32) -> i32 {
+        if id <= 0 {
+            return id;
+        }
+        let key = mix(self.seed ^ kind);
+        let mut value = id as u32;
+        loop {
+            value = permute(key, value);
+            if (1..=i32::MAX as u32).contains(&value) {
+                return value as i32;
+            }
+        }
+    }
+
+    fn block(&self, block: &mut ElementBlock, tensors: bool) {
+        for id in block.el_num.iter_mut() {
+            *id = self.id(ELEMENT, *id);
+        }
+        for text in block.part_text.iter_mut() {
+            let id = self.id(PART, atoi_prefix(text));
+            *text = format!("{} PART_{}", id, id);
+        }
+        if let (Some(transform), true) = (&self.transform, tensors) {
+            transform.tensors(&mut block.tens_val);
+        }
+    }
+}
+
+pub fn anonymize(anim: &mut AnimFile, anonymizer: &Anonymizer) {
+    anim.mod_anim_text = "ANONYMIZED".to_string();
+    anim.radioss_run_text = "ANONYMIZED".to_string();
+    for id in anim.nod_num.iter_mut() {
+        *id = anonymizer.id(NODE, *id);
+    }
+    // shell and beam tensors are in element frames, left as they are
+    anonymizer.block(&mut anim.elts_1d, false);
+    anonymizer.block(&mut anim.elts_2d, false);
+    anonymizer.block(&mut anim.elts_3d, true);
+    anonymizer.block(&mut anim.elts_sph, true);
+    if let Some(transform) = &anonymizer.transform {
+        transform.points(&mut anim.coor);
+        transform.vectors(&mut anim.vect_val);
+    }
+    if let Some(hierarchy) = &mut anim.hierarchy {
+        for (k, subset) in hierarchy.subsets.iter_mut().enumerate() {
+            subset.text = format!("SUBSET_{}", k + 1);
+        }
+        for (k, text) in hierarchy.material_texts.iter_mut().enumerate() {
+            *text = format!("MATERIAL_{}", k + 1);
+        }
+        for (k, text) in hierarchy.properties_texts.iter_mut().enumerate() {
+            *text = format!("PROPERTY_{}", k + 1);
+        }
+    }
+    if let Some(th) = &mut anim.th {
+        let lists = [&mut th.node_texts, &mut th.elts_1d_texts, &mut th.elts_2d_texts, &mut th.elts_3d_texts];
+        for texts in lists {
+            for (k, text) in texts.iter_mut().enumerate() {
+                *text = format!("TH_{}", k + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::{Hierarchy, ThLists};
+    use crate::test_files::{int_cell_array, legacy_vtk, quad_parts};
+    use crate::vtk::WriteOptions;
+
+    fn confidential() -> AnimFile {
+        let mut anim = quad_parts(&[("10 Secret door", 2), ("20 Secret roof", 1)]);
+        anim.mod_anim_text = "Secret crash model".to_string();
+        anim.radioss_run_text = "Secret run".to_string();
+        anim.hierarchy = Some(Hierarchy {
+            material_texts: vec!["Secret steel".to_string()],
+            properties_texts: vec!["Secret shell".to_string()],
+            ..Default::default()
+        });
+        anim.th = Some(ThLists { nodes: vec![1001], node_texts: vec!["Secret sensor".to_string()], ..Default::default() });
+        anim
+    }
+
+    #[test]
+    fn no_title_or_id_is_left() {
+        let mut anim = confidential();
+        let text = legacy_vtk(&anim, &WriteOptions::default());
+        assert!(text.contains("MATERIAL_NAME 1 1 string\nSecret%20steel\n"));
+        let (node_ids, element_ids) = (int_cell_array(&text, "NODE_ID"), int_cell_array(&text, "ELEMENT_ID"));
+        anonymize(&mut anim, &Anonymizer::new(Some(42), false));
+
+        let text = legacy_vtk(&anim, &WriteOptions::default());
+        let hierarchy = anim.hierarchy.as_ref().unwrap();
+        let th = anim.th.as_ref().unwrap();
+        let titles = [&anim.mod_anim_text, &anim.radioss_run_text]
+            .into_iter()
+            .chain(&anim.elts_2d.part_text)
+            .chain(&hierarchy.material_texts)
+            .chain(&hierarchy.properties_texts)
+            .chain(&th.node_texts);
+        for title in titles.chain([&text]) {
+            assert!(!title.to_ascii_uppercase().contains("SECRET"), "{}", title);
+        }
+        assert_eq!((&hierarchy.material_texts[0][..], &th.node_texts[0][..]), ("MATERIAL_1", "TH_1"));
+
+        let new_node_ids = int_cell_array(&text, "NODE_ID");
+        assert_eq!(new_node_ids.len(), node_ids.len());
+        assert!(new_node_ids.iter().all(|id| *id > 0 && !node_ids.contains(id)));
+        let new_element_ids = int_cell_array(&text, "ELEMENT_ID");
+        assert!(new_element_ids.iter().all(|id| *id > 0 && !element_ids.contains(id)));
+        let part_ids = int_cell_array(&text, "PART_ID");
+        assert!(part_ids.iter().all(|id| ![10, 20].contains(id)));
+        assert_eq!(anim.elts_2d.part_text[0], format!("{} PART_{}", part_ids[0], part_ids[0]));
+        // renumbering keeps ids apart
+        let mut distinct = new_node_ids.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), new_node_ids.len());
+    }
+
+    #[test]
+    fn a_seed_gives_the_same_ids_in_every_state() {
+        let (mut first, mut second) = (confidential(), confidential());
+        second.coor[0] = 0.5;
+        anonymize(&mut first, &Anonymizer::new(Some(42), false));
+        anonymize(&mut second, &Anonymizer::new(Some(42), false));
+        assert_eq!(first.nod_num, second.nod_num);
+        assert_eq!(first.elts_2d.el_num, second.elts_2d.el_num);
+
+        let mut other = confidential();
+        anonymize(&mut other, &Anonymizer::new(Some(43), false));
+        assert_ne!(first.nod_num, other.nod_num);
+    }
+
+    #[test]
+    fn rigid_transform_keeps_the_distances() {
+        let mut anim = confidential();
+        let coor = anim.coor.clone();
+        anonymize(&mut anim, &Anonymizer::new(Some(42), true));
+        assert_ne!(anim.coor, coor);
+        let distance = |c: &[f32], a: usize, b: usize| (0..3).map(|k| (c[3 * a + k] - c[3 * b + k]).powi(2)).sum::<f32>().sqrt();
+        for (a, b) in [(0, 1), (0, 4), (2, 9)] {
+            assert!((distance(&anim.coor, a, b) - distance(&coor, a, b)).abs() < 1e-4);
+        }
+    }
+}
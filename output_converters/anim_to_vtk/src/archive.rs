@@ -0,0 +1,105 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Compressed archive of the outputs of a run (.tar.zst). Every output
+// is appended as soon as its input is converted, the MANIFEST with the
+// CRC32 and size of each member closes the archive.
+// ****************************************
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Component, Path};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crc32fast::Hasher;
+use tar::{Builder, Header};
+use zstd::stream::write::Encoder;
+
+const LEVEL: i32 = 3;
+
+// CRC32 of the bytes read through it
+struct CrcReader<R: Read> {
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R: Read> Read for CrcReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+// relative paths are kept, others are reduced to their file name
+fn member_name(path: &str) -> String {
+    let path = Path::new(path);
+    let relative = path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if relative {
+        path.to_string_lossy().into_owned()
+    } else {
+        path.file_name().map_or_else(|| path.to_string_lossy().into_owned(), |name| name.to_string_lossy().into_owned())
+    }
+}
+
+fn header(size: u64, mtime: u64) -> Header {
+    let mut header = Header::new_gnu();
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header
+}
+
+pub struct Archive {
+    builder: Builder<Encoder<'static, File>>,
+    manifest: String,
+}
+
+impl Archive {
+    pub fn create(path: &str) -> io::Result<Archive> {
+        let mut encoder = Encoder::new(File::create(path)?, LEVEL)?;
+        encoder.include_checksum(true)?;
+        Ok(Archive { builder: Builder::new(encoder), manifest: String::new() })
+    }
+
+    pub fn add_files(&mut self, paths: &[String]) -> io::Result<()> {
+        for path in paths {
+            let file = File::open(path)?;
+            let metadata = file.metadata()?;
+            let mtime = metadata.modified()?.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            let name = member_name(path);
+            let mut reader = CrcReader { inner: file, hasher: Hasher::new() };
+            self.builder.append_data(&mut header(metadata.len(), mtime), &name, &mut reader)?;
+            let crc = reader.hasher.finalize();
+            self.manifest.push_str(&format!("{:08x}  {}  {}\n", crc, metadata.len(), name));
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        let mtime = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let manifest = std::mem::take(&mut self.manifest);
+        self.builder.append_data(&mut header(manifest.len() as u64, mtime), "MANIFEST", manifest.as_bytes())?;
+        let encoder = self.builder.into_inner()?;
+        encoder.finish()?.sync_all()
+    }
+}
@@ -0,0 +1,959 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Conversion of a batch of A-files. Each input file is converted on
+// its own into a ConversionOutcome; the run state shared by all the
+// files (report, metrics, per-state outputs, archive) is only updated
+// from outcomes, and a failed file adds nothing but its failure
+// ****************************************
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use report::{codes, Diagnostic, Progress, Report};
+
+use crate::anim::{read_anim_headers, read_error_of, AnimFile};
+use crate::derive::StateHistory;
+#[cfg(feature = "archive")]
+use crate::archive;
+#[cfg(feature = "catalog")]
+use crate::catalog;
+#[cfg(feature = "usd")]
+use crate::usd;
+use crate::group_by::{self, GroupBy};
+use crate::vtk::{self, LegacyVtkWriter};
+use crate::{cog, dashboard, debris, ensight, frames, geometry_report, lod, metrics, modes, multiblock, ndjson, npz, obj, paraview_script, part_sums, part_table, pvd, resample, sph_project, sph_split, trajectories};
+use crate::{load_prepared_anim, output_error, read_radioss_anim, CliOptions, OutputFormat};
+
+pub struct Converted {
+    pub outputs: Vec<String>,
+    pub time: f32,
+    pub datasets: usize, // leading outputs holding the state, the steps of a time series
+    pub anim: Option<AnimFile>, // converted state, when it feeds the per-state outputs
+    pub history: Option<StateHistory>, // arrays the next state derives fields from
+    pub variables: Vec<ensight::Variable>, // variables of the EnSight files, listed by the case
+}
+
+pub struct ConversionOutcome {
+    pub input: String,
+    pub output_base: String, // path the outputs are named after
+    pub duration: Duration,
+    pub result: Result<Converted, String>,
+}
+
+// error printed now, recorded in the outcome for the report
+fn failed(error: Diagnostic) -> Result<Converted, String> {
+    error.print();
+    Err(error.message)
+}
+
+// output that can't be created or written
+fn write_error(message: impl std::fmt::Display) -> Diagnostic {
+    Diagnostic::new(codes::WRITE_FAILED, message)
+}
+
+// output of the whole series, failing on its own or on a field read back from the input
+fn state_error(e: &std::io::Error, what: &str) {
+    match read_error_of(e) {
+        Some(read_error) => report::error(read_error.code(), read_error),
+        None => report::error(codes::WRITE_FAILED, format_args!("{}: {}", what, e)),
+    }
+}
+
+// the state is kept for the per-state outputs (center of gravity, part table, ...)
+fn converted(outputs: Vec<String>, datasets: usize, anim: AnimFile, history: Option<StateHistory>) -> Result<Converted, String> {
+    Ok(Converted { outputs, time: anim.time, datasets, anim: Some(anim), history, variables: Vec::new() })
+}
+
+fn convert(
+    file_name: &str,
+    cli: &CliOptions,
+    previous: Option<&StateHistory>,
+    progress: &Progress,
+) -> Result<Converted, String> {
+    let capture = |anim: &AnimFile| cli.keeps_history().then(|| StateHistory::capture(anim));
+    let output_base = cli.output_base(file_name);
+    // Always append the extension of the format to create output filename
+    let extension = match cli.format {
+        OutputFormat::Npz => "npz",
+        OutputFormat::Ndjson => "ndjson",
+        _ => "vtk",
+    };
+    let output_file_name = format!("{}.{}", output_base, extension);
+
+    // Verify input file exists before creating output file
+    if !Path::new(file_name).exists() {
+        report::error(codes::CANT_OPEN, "input file does not exist");
+        return Err("input file does not exist".to_string());
+    }
+
+    // from the headers only; a file they can't be read from fails in the conversion
+    if cli.skip_empty {
+        if let Ok(anim) = read_anim_headers(file_name, cli.text_encoding, cli.endian) {
+            if anim.nb_elements() == 0 {
+                progress.message(format_args!("Skipping {}: empty state, no element", file_name));
+                return Ok(Converted { outputs: Vec::new(), time: anim.time, datasets: 0, anim: None, history: None, variables: Vec::new() });
+            }
+        }
+    }
+
+    if let (Some(names), OutputFormat::Vtk) = (&cli.modes, cli.format) {
+        let mut anim = match load_prepared_anim(file_name, cli, previous) {
+            Ok(anim) => anim,
+            Err(e) => return failed(e),
+        };
+        let modes = match modes::mode_vectors(&anim, names) {
+            Ok(modes) => modes,
+            Err(e) => return failed(e),
+        };
+        let history = capture(&anim);
+        if !modes.is_empty() {
+            return match modes::write_mode_outputs(&mut anim, &modes, &output_base, &cli.write, cli.mode_scale, cli.mode_phases) {
+                Ok(outputs) => converted(outputs, 0, anim, history),
+                Err(e) => failed(e),
+            };
+        }
+        // no vector named like a mode: the state as it is
+        report::warning(
+            codes::NO_MATCH,
+            "no nodal vector named like MODE, converting the state as it is (list the mode vectors with --modes=<names>)",
+        );
+        progress.message(format_args!("Converting {} to {}", file_name, output_file_name));
+        return match vtk::write_vtk_file(&anim, &cli.write, &output_file_name) {
+            Ok(()) => converted(vec![output_file_name], 1, anim, history),
+            Err(e) => failed(write_error(e)),
+        };
+    }
+
+    if cli.format == OutputFormat::Vti {
+        let anim = match load_prepared_anim(file_name, cli, previous) {
+            Ok(anim) => anim,
+            Err(e) => return failed(e),
+        };
+        let output_file_name = format!("{}.vti", output_base);
+        progress.message(format_args!("Resampling {} to {}", file_name, output_file_name));
+        let spacing = cli.spacing.unwrap_or_default();
+        return match resample::write_vti(&anim, &output_file_name, spacing, &cli.fields, &cli.write) {
+            Ok(_) => {
+                let history = capture(&anim);
+                converted(vec![output_file_name], 1, anim, history)
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&output_file_name);
+                failed(write_error(e))
+            }
+        };
+    }
+
+    if cli.format == OutputFormat::Obj {
+        let anim = match load_prepared_anim(file_name, cli, previous) {
+            Ok(anim) => anim,
+            Err(e) => return failed(e),
+        };
+        let output_file_name = format!("{}.obj", output_base);
+        progress.message(format_args!("Converting {} to {}", file_name, output_file_name));
+        return match obj::write_obj(&anim, &output_file_name, &cli.write) {
+            Ok(_) => {
+                let history = capture(&anim);
+                converted(vec![output_file_name], 0, anim, history)
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&output_file_name);
+                failed(output_error(&e, &output_file_name))
+            }
+        };
+    }
+
+    if cli.format == OutputFormat::Ensight {
+        let anim = match load_prepared_anim(file_name, cli, previous) {
+            Ok(anim) => anim,
+            Err(e) => return failed(e),
+        };
+        progress.message(format_args!("Converting {} to {}.geo", file_name, output_base));
+        return match ensight::write_state(&anim, &output_base, &cli.fields, &cli.write) {
+            Ok((outputs, variables)) => {
+                let history = capture(&anim);
+                converted(outputs, 0, anim, history).map(|mut converted| {
+                    converted.variables = variables;
+                    converted
+                })
+            }
+            Err(e) => failed(write_error(e)),
+        };
+    }
+
+    if cli.group_by == Some(GroupBy::Material) && cli.format == OutputFormat::Vtk {
+        let mut anim = match load_prepared_anim(file_name, cli, previous) {
+            Ok(anim) => anim,
+            Err(e) => return failed(e),
+        };
+        let history = capture(&anim);
+        return match group_by::write_material_outputs(&mut anim, &output_base, &cli.write) {
+            Ok(outputs) => {
+                let datasets = outputs.len();
+                converted(outputs, datasets, anim, history)
+            }
+            Err(e) => failed(e),
+        };
+    }
+
+    if cli.writes_blocks() && cli.format == OutputFormat::Vtk {
+        let anim = match load_prepared_anim(file_name, cli, previous) {
+            Ok(anim) => anim,
+            Err(e) => return failed(e),
+        };
+        let extension = if cli.multiblock || cli.no_pad { "vtm" } else { "pvtu" };
+        progress.message(format_args!("Converting {} to {}.{}", file_name, output_base, extension));
+        let written = if cli.multiblock {
+            multiblock::write_multiblock(&anim, &output_base, &cli.write, !cli.no_pad)
+        } else if cli.no_pad {
+            multiblock::write_element_types(&anim, &output_base, &cli.write)
+        } else {
+            multiblock::write_partitioned(&anim, &output_base, &cli.write)
+        };
+        return match written {
+            Ok(outputs) => {
+                let history = capture(&anim);
+                converted(outputs, 1, anim, history)
+            }
+            Err(e) => failed(write_error(e)),
+        };
+    }
+
+    let output_file = match File::create(&output_file_name) {
+        Ok(f) => f,
+        Err(e) => {
+            return failed(write_error(format_args!("can't create output file {}: {}", output_file_name, e)));
+        }
+    };
+
+    progress.message(format_args!("Converting {} to {}", file_name, output_file_name));
+    let loaded = match cli.format {
+        OutputFormat::Npz => {
+            let mut writer = npz::NpzWriter::new(output_file, &cli.fields);
+            read_radioss_anim(file_name, cli, previous, &mut writer, &output_file_name)
+        }
+        OutputFormat::Ndjson => {
+            let mut writer = ndjson::NdjsonWriter::new(output_file, &cli.fields);
+            read_radioss_anim(file_name, cli, previous, &mut writer, &output_file_name)
+        }
+        _ => {
+            let mut writer = LegacyVtkWriter::new(output_file, &cli.write);
+            read_radioss_anim(file_name, cli, previous, &mut writer, &output_file_name).and_then(|anim| {
+                if cli.write.checksums {
+                    let sidecar = format!("{}.crc32", output_file_name);
+                    vtk::write_checksums(&sidecar, writer.checksums())
+                        .map_err(|e| write_error(format_args!("can't write {}: {}", sidecar, e)))?;
+                }
+                Ok(anim)
+            })
+        }
+    };
+    let anim = match loaded {
+        Ok(anim) => anim,
+        Err(e) => {
+            let _ = std::fs::remove_file(&output_file_name);
+            if cli.write.checksums {
+                let _ = std::fs::remove_file(format!("{}.crc32", output_file_name));
+            }
+            return failed(e);
+        }
+    };
+    let mut outputs = vec![output_file_name];
+    if cli.write.checksums && cli.format == OutputFormat::Vtk {
+        outputs.push(format!("{}.crc32", outputs[0]));
+    }
+    if cli.lod_levels > 1 && cli.format == OutputFormat::Vtk {
+        match lod::write_lod_outputs(&anim, &output_base, &outputs[0], &cli.write, cli.lod_levels) {
+            Ok(lod_outputs) => outputs.extend(lod_outputs),
+            Err(e) => {
+                vtk::remove_files(&outputs);
+                return failed(e);
+            }
+        }
+    }
+    if let Some(projection) = &cli.sph_project {
+        let sph_file_name = format!("{}_sph.vti", output_base);
+        progress.message(format_args!("Projecting the SPH particles of {} to {}", file_name, sph_file_name));
+        match sph_project::write_vti(&anim, &sph_file_name, projection, cli.write.binary) {
+            Ok(_) => outputs.push(sph_file_name),
+            Err(e) => {
+                let _ = std::fs::remove_file(&sph_file_name);
+                vtk::remove_files(&outputs);
+                return failed(write_error(e));
+            }
+        }
+    }
+    if cli.write.split_sph {
+        let sph_file_name = format!("{}_sph.vtp", output_base);
+        progress.message(format_args!("Writing the SPH particles of {} to {}", file_name, sph_file_name));
+        match sph_split::write_vtp(&anim, &sph_file_name, &cli.write) {
+            Ok(_) => outputs.push(sph_file_name),
+            Err(e) => {
+                let _ = std::fs::remove_file(&sph_file_name);
+                vtk::remove_files(&outputs);
+                return failed(write_error(e));
+            }
+        }
+    }
+    let datasets = (cli.format == OutputFormat::Vtk) as usize;
+    let history = capture(&anim);
+    converted(outputs, datasets, anim, history)
+}
+
+// converts one input file, touching nothing shared by the batch
+pub fn convert_file(
+    file_name: &str,
+    cli: &CliOptions,
+    previous: Option<&StateHistory>,
+    progress: &Progress,
+) -> ConversionOutcome {
+    let start = Instant::now();
+    let result = report::for_input(file_name, || convert(file_name, cli, previous, progress));
+    ConversionOutcome {
+        input: file_name.to_string(),
+        output_base: cli.output_base(file_name),
+        duration: start.elapsed(),
+        result,
+    }
+}
+
+// converts the files on `jobs` threads, each taking the next file not
+// taken yet; the lines of a file are printed in one block and its outcome
+// recorded in input order, so the batch sees the same run as a serial one
+pub fn convert_parallel(input_files: &[&String], cli: &CliOptions, jobs: usize, progress: &Progress, batch: &mut Batch) {
+    let next_file = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(input_files.len()) {
+            let sender = sender.clone();
+            let next_file = &next_file;
+            scope.spawn(move || loop {
+                let index = next_file.fetch_add(1, Ordering::Relaxed);
+                let Some(file_name) = input_files.get(index) else {
+                    break;
+                };
+                let (outcome, captured) = report::capture(|| convert_file(file_name, cli, None, &progress.at(index)));
+                if sender.send((index, outcome, captured)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // outcomes of the files finished ahead of a slower one
+        let mut waiting = BTreeMap::new();
+        let mut next_recorded = 0;
+        for (index, outcome, captured) in receiver {
+            waiting.insert(index, (outcome, captured));
+            while let Some((outcome, captured)) = waiting.remove(&next_recorded) {
+                captured.print();
+                batch.record(outcome);
+                next_recorded += 1;
+            }
+        }
+    });
+}
+
+// ****************************************
+// Run state shared by the files of a batch, updated from their
+// outcomes in input order
+// ****************************************
+pub struct Batch {
+    report: Report,
+    report_json: Option<String>,
+    metrics: Option<metrics::Metrics>,
+    debris_tracker: Option<debris::DebrisTracker>,
+    part_sums: Option<part_sums::PartSums>,
+    sum_output: String,
+    cog_tracker: Option<cog::CogTracker>,
+    trajectories: Option<trajectories::TrajectoryTracker>,
+    geometry_report: Option<geometry_report::GeometryReport>,
+    part_table: Option<part_table::PartTable>,
+    dashboard: Option<dashboard::Dashboard>,
+    collection: Option<pvd::Collection>,
+    obj_manifest: Option<obj::Manifest>,
+    ensight_case: Option<ensight::Case>,
+    frames: Option<frames::FrameInterpolator>,
+    paraview_script: Option<paraview_script::ParaviewScript>,
+    history: Option<StateHistory>,
+    #[cfg(feature = "catalog")]
+    catalog: Option<catalog::Catalog>,
+    #[cfg(feature = "usd")]
+    usd_stage: Option<usd::UsdStage>,
+    #[cfg(feature = "archive")]
+    archive: Option<archive::Archive>,
+}
+
+impl Batch {
+    // creates every output of the run, exits when one can't be created
+    pub fn new(cli: &CliOptions) -> Self {
+        let debris_tracker = cli.track_debris.as_ref().map(|path| {
+            debris::DebrisTracker::create(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create debris file {}: {}", path, e))
+            })
+        });
+
+        let part_sums = if cli.sum_vectors.is_empty() {
+            None
+        } else {
+            match part_sums::PartSums::create(&cli.sum_output, cli.sum_vectors.clone()) {
+                Ok(sums) => Some(sums),
+                Err(e) => {
+                    report::fail(format_args!("Can't create output file {}: {}", cli.sum_output, e))
+                }
+            }
+        };
+
+        let cog_tracker = cli.cog_output.as_ref().map(|path| {
+            cog::CogTracker::create(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output file {}: {}", path, e))
+            })
+        });
+
+        let trajectories = cli.trajectory_nodes.as_ref().map(|ids| {
+            let ids: Vec<i32> = ids.iter().copied().collect();
+            trajectories::TrajectoryTracker::create(&cli.trajectory_output, &ids).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output files {}.csv/.vtp: {}", cli.trajectory_output, e))
+            })
+        });
+
+        let geometry_report = cli.geometry_report.as_ref().map(|path| {
+            geometry_report::GeometryReport::create(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output file {}: {}", path, e))
+            })
+        });
+
+        let part_table = cli.part_table.as_ref().map(|path| {
+            part_table::PartTable::create(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output file {}: {}", path, e))
+            })
+        });
+
+        let dashboard = cli.dashboard.as_ref().map(|path| {
+            dashboard::Dashboard::create(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output file {}: {}", path, e))
+            })
+        });
+
+        let collection = cli.pvd.as_ref().map(|path| {
+            pvd::Collection::create(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output file {}: {}", path, e))
+            })
+        });
+
+        let obj_manifest = cli.obj_manifest.as_ref().filter(|_| cli.format == OutputFormat::Obj).map(|path| {
+            obj::Manifest::create(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output file {}: {}", path, e))
+            })
+        });
+
+        let ensight_case = (cli.format == OutputFormat::Ensight).then(ensight::Case::default);
+
+        let interpolates = cli.interpolate_frames > 0 && cli.writes_single_grid();
+        let frames = interpolates.then(|| frames::FrameInterpolator::new(cli.interpolate_frames, &cli.write));
+
+        let paraview_script = cli.paraview_script.as_ref().filter(|_| cli.writes_single_grid()).map(|path| {
+            paraview_script::ParaviewScript::create(path, cli.pvd.as_deref(), cli.color_by.as_deref()).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output file {}: {}", path, e))
+            })
+        });
+
+        #[cfg(feature = "catalog")]
+        let catalog = cli.catalog.as_ref().map(|path| {
+            catalog::Catalog::open(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't open catalog {}: {}", path, e))
+            })
+        });
+
+        #[cfg(feature = "usd")]
+        let usd_stage = cli.usd.as_ref().map(|path| {
+            usd::UsdStage::create(path, &cli.usd_primvars).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create output file {}: {}", path, e))
+            })
+        });
+
+        #[cfg(feature = "archive")]
+        let archive = cli.archive.as_ref().map(|path| {
+            archive::Archive::create(path).unwrap_or_else(|e| {
+                report::fail(format_args!("Can't create archive {}: {}", path, e))
+            })
+        });
+
+        let batch = Batch {
+            report: Report::new("anim_to_vtk"),
+            report_json: cli.report_json.clone(),
+            metrics: cli.metrics.as_deref().map(metrics::Metrics::new),
+            debris_tracker,
+            part_sums,
+            sum_output: cli.sum_output.clone(),
+            cog_tracker,
+            trajectories,
+            geometry_report,
+            part_table,
+            dashboard,
+            collection,
+            obj_manifest,
+            ensight_case,
+            frames,
+            paraview_script,
+            history: None,
+            #[cfg(feature = "catalog")]
+            catalog,
+            #[cfg(feature = "usd")]
+            usd_stage,
+            #[cfg(feature = "archive")]
+            archive,
+        };
+        batch.write_metrics();
+        batch
+    }
+
+    fn write_metrics(&self) {
+        if let Some(metrics) = &self.metrics {
+            if let Err(e) = metrics.write() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write metrics file: {}", e));
+            }
+        }
+    }
+
+    // the errors are those of the input file
+    fn add_state(&mut self, file_name: &str, output_file_name: &str, anim: &AnimFile) {
+        if let Some(tracker) = self.debris_tracker.as_mut() {
+            if let Err(e) = tracker.add_state(anim) {
+                state_error(&e, "Can't write debris data");
+            }
+        }
+        if let Some(sums) = self.part_sums.as_mut() {
+            if let Err(e) = sums.add_state(anim) {
+                state_error(&e, "Can't write part sums");
+            }
+        }
+        if let Some(tracker) = self.cog_tracker.as_mut() {
+            if let Err(e) = tracker.add_state(anim) {
+                state_error(&e, "Can't write center of gravity");
+            }
+        }
+        if let Some(tracker) = self.trajectories.as_mut() {
+            if let Err(e) = tracker.add_state(anim) {
+                state_error(&e, "Can't write trajectories");
+            }
+        }
+        if let Some(geometry) = self.geometry_report.as_mut() {
+            if let Err(e) = geometry.add_state(anim) {
+                state_error(&e, "Can't write geometry report");
+            }
+        }
+        if let Some(table) = self.part_table.as_mut() {
+            if let Err(e) = table.add_state(anim) {
+                state_error(&e, "Can't write part table");
+            }
+        }
+        if let Some(dashboard) = self.dashboard.as_mut() {
+            if let Err(e) = dashboard.add_state(file_name, anim) {
+                report::error(e.code(), e);
+            }
+        }
+        if let Some(script) = self.paraview_script.as_mut() {
+            script.describe(anim);
+        }
+        #[cfg(feature = "usd")]
+        if let Some(stage) = self.usd_stage.as_mut() {
+            if let Err(e) = stage.add_state(anim) {
+                state_error(&e, "Can't write USD stage");
+            }
+        }
+        #[cfg(feature = "catalog")]
+        if let Some(catalog) = self.catalog.as_mut() {
+            if let Err(e) = catalog.add_state(file_name, output_file_name, anim) {
+                state_error(&e, "Can't record the state in the catalog");
+            }
+        }
+        #[cfg(not(feature = "catalog"))]
+        let _ = output_file_name;
+    }
+
+    // arrays of the last converted state, none before the first one
+    pub fn history(&self) -> Option<&StateHistory> {
+        self.history.as_ref()
+    }
+
+    pub fn record(&mut self, outcome: ConversionOutcome) {
+        match outcome.result {
+            // empty state skipped: no output for the per-state outputs
+            Ok(converted) if converted.outputs.is_empty() => {
+                if let Some(metrics) = self.metrics.as_mut() {
+                    metrics.record_success(&outcome.input, &[], outcome.duration);
+                }
+                self.report.success(&outcome.input, &[], outcome.duration);
+            }
+            Ok(mut converted) => {
+                if let Some(anim) = &converted.anim {
+                    report::for_input(&outcome.input, || self.add_state(&outcome.input, &converted.outputs[0], anim));
+                }
+                // frames leading to this state, written with it
+                let frames = match (self.frames.as_mut(), converted.anim.take()) {
+                    (Some(frames), Some(anim)) => frames.add_state(&outcome.output_base, anim),
+                    _ => Vec::new(),
+                };
+                if let Some(collection) = self.collection.as_mut() {
+                    for (time, output) in &frames {
+                        collection.add_state(*time, std::slice::from_ref(output));
+                    }
+                    collection.add_state(converted.time, &converted.outputs[..converted.datasets]);
+                }
+                if let Some(manifest) = self.obj_manifest.as_mut() {
+                    manifest.add_state(converted.time, &converted.outputs[0]);
+                }
+                if let Some(case) = self.ensight_case.as_mut() {
+                    case.add_state(&outcome.output_base, converted.time, &converted.variables);
+                }
+                if let Some(script) = self.paraview_script.as_mut() {
+                    for (_, output) in &frames {
+                        script.add_file(output);
+                    }
+                    script.add_file(&converted.outputs[0]);
+                }
+                converted.outputs.extend(frames.into_iter().map(|(_, output)| output));
+                if converted.history.is_some() {
+                    self.history = converted.history;
+                }
+                #[cfg(feature = "archive")]
+                if let Some(archive) = self.archive.as_mut() {
+                    if let Err(e) = archive.add_files(&converted.outputs) {
+                        let message = format_args!("Can't archive the outputs of {}: {}", outcome.input, e);
+                        report::error(codes::WRITE_FAILED, message);
+                    }
+                }
+                if let Some(metrics) = self.metrics.as_mut() {
+                    metrics.record_success(&outcome.input, &converted.outputs, outcome.duration);
+                }
+                self.report.success(&outcome.input, &converted.outputs, outcome.duration);
+            }
+            Err(e) => self.report.failure(&outcome.input, e),
+        }
+        if let Some(metrics) = self.metrics.as_mut() {
+            metrics.set_failures(self.report.failed());
+        }
+        self.write_metrics();
+    }
+
+    // closes the outputs of the run, returns its report
+    pub fn finish(self) -> Report {
+        if let Some(tracker) = self.debris_tracker {
+            if let Err(e) = tracker.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write debris file: {}", e));
+            }
+        }
+        if let Some(sums) = self.part_sums {
+            if let Err(e) = sums.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write {}: {}", self.sum_output, e));
+            }
+        }
+        if let Some(tracker) = self.cog_tracker {
+            if let Err(e) = tracker.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write center of gravity file: {}", e));
+            }
+        }
+        if let Some(tracker) = self.trajectories {
+            if let Err(e) = tracker.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write trajectories: {}", e));
+            }
+        }
+        if let Some(geometry) = self.geometry_report {
+            if let Err(e) = geometry.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write geometry report: {}", e));
+            }
+        }
+        if let Some(collection) = self.collection {
+            if let Err(e) = collection.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write collection file: {}", e));
+            }
+        }
+        if let Some(manifest) = self.obj_manifest {
+            if let Err(e) = manifest.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write OBJ manifest: {}", e));
+            }
+        }
+        if let Some(case) = self.ensight_case {
+            if let Err(e) = case.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write EnSight case: {}", e));
+            }
+        }
+        if let Some(script) = self.paraview_script {
+            if let Err(e) = script.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write ParaView script: {}", e));
+            }
+        }
+        #[cfg(feature = "usd")]
+        if let Some(stage) = self.usd_stage {
+            if let Err(e) = stage.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write USD stage: {}", e));
+            }
+        }
+        #[cfg(feature = "archive")]
+        if let Some(archive) = self.archive {
+            if let Err(e) = archive.finish() {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write archive: {}", e));
+            }
+        }
+        if let Some(path) = &self.report_json {
+            if let Err(e) = self.report.write_json(path) {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write report {}: {}", path, e));
+            }
+        }
+        if let Some(dashboard) = self.dashboard {
+            if let Err(e) = dashboard.finish(&self.report) {
+                report::error(codes::WRITE_FAILED, format_args!("Can't write dashboard: {}", e));
+            }
+        }
+        self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{AnimBuilder, TempDir};
+    use std::fs;
+
+    // run directory with ok A001 (time 0.5), missing A002, A003 with
+    // trailing bytes (fails with --strict) and ok A004 (time 1.5)
+    fn mixed_batch(name: &str) -> (TempDir, Vec<String>) {
+        let dir = TempDir::new(name);
+        AnimBuilder::new(0.5).write(&dir.file("runA001"));
+        AnimBuilder::new(1.0).trailing(&[0; 4]).write(&dir.file("runA003"));
+        AnimBuilder::new(1.5).write(&dir.file("runA004"));
+        let files = ["runA001", "runA002", "runA003", "runA004"];
+        let files = files.iter().map(|file| dir.file(file)).collect();
+        (dir, files)
+    }
+
+    fn cli_options(args: &[&str]) -> CliOptions {
+        let args: Vec<String> = std::iter::once("anim_to_vtk").chain(args.iter().copied()).map(String::from).collect();
+        crate::parse_args(&args)
+    }
+
+    #[test]
+    fn failed_files_do_not_stop_the_batch() {
+        let (_dir, files) = mixed_batch("continue");
+        let cli = cli_options(&["--strict"]);
+        let progress = Progress::new(files.len());
+        let outcomes: Vec<ConversionOutcome> = files.iter().map(|file| convert_file(file, &cli, None, &progress)).collect();
+
+        let status: Vec<bool> = outcomes.iter().map(|outcome| outcome.result.is_ok()).collect();
+        assert_eq!(status, [true, false, false, true]);
+        assert_eq!(outcomes[1].result.as_ref().err().map(String::as_str), Some("input file does not exist"));
+        for (outcome, file) in outcomes.iter().zip(&files) {
+            assert_eq!(&outcome.input, file);
+            let output = format!("{}.vtk", file);
+            assert_eq!(Path::new(&output).exists(), outcome.result.is_ok(), "{}", output);
+            if let Ok(converted) = &outcome.result {
+                assert_eq!(converted.outputs, [output]);
+                assert!(converted.anim.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn failed_files_add_nothing_to_the_run_outputs() {
+        let (dir, files) = mixed_batch("isolate");
+        let geometry = dir.file("geometry.csv");
+        let report_json = dir.file("report.json");
+        let cli = cli_options(&["--strict", "--geometry-report", &geometry, "--report-json", &report_json]);
+        let progress = Progress::new(files.len());
+        let mut batch = Batch::new(&cli);
+        for file in &files {
+            batch.record(convert_file(file, &cli, None, &progress));
+        }
+        let report = batch.finish();
+
+        assert_eq!((report.succeeded(), report.failed()), (2, 2));
+        assert_eq!(report.exit_code(), report::EXIT_FAILURE);
+        let failed: Vec<&str> = report.outcomes().filter(|outcome| outcome.2.is_some()).map(|outcome| outcome.0).collect();
+        assert_eq!(failed, [files[1].as_str(), files[2].as_str()]);
+
+        // states numbered over the converted files only
+        let csv = fs::read_to_string(&geometry).unwrap();
+        let states: Vec<&str> = csv.lines().skip(1).map(|line| &line[..line.match_indices(',').nth(1).unwrap().0]).collect();
+        assert_eq!(states, ["0,0.5", "1,1.5"]);
+        let json = fs::read_to_string(&report_json).unwrap();
+        assert!(json.contains("\"succeeded\": 2,\n  \"failed\": 2,"));
+    }
+
+    #[test]
+    fn other_formats_feed_the_per_state_outputs() {
+        for format in ["ensight", "obj"] {
+            let (dir, files) = mixed_batch(&format!("per_state_{}", format));
+            let geometry = dir.file("geometry.csv");
+            let format_option = format!("--format={}", format);
+            let cli = cli_options(&[&format_option, "--geometry-report", &geometry]);
+            let progress = Progress::new(files.len());
+            let mut batch = Batch::new(&cli);
+            for file in &files {
+                let outcome = convert_file(file, &cli, None, &progress);
+                if let Ok(converted) = &outcome.result {
+                    assert!(converted.anim.is_some(), "{}", format);
+                }
+                batch.record(outcome);
+            }
+            batch.finish();
+
+            let csv = fs::read_to_string(&geometry).unwrap();
+            let states: Vec<&str> = csv.lines().skip(1).map(|line| &line[..line.match_indices(',').nth(1).unwrap().0]).collect();
+            assert_eq!(states, ["0,0.5", "1,1", "2,1.5"], "{}", format);
+        }
+    }
+
+    #[test]
+    fn truncated_files_fail_with_the_section_read() {
+        let dir = TempDir::new("truncated");
+        let complete = AnimBuilder::new(0.5);
+        complete.write(&dir.file("runA001"));
+        // cut in the middle of the nb_nodes count, after magic, time, titles and flags
+        fs::write(dir.file("runA002"), &complete.bytes()[..8 + 3 * 81 + 40 + 2]).unwrap();
+        let files = [dir.file("runA002"), dir.file("runA001")];
+        let cli = cli_options(&[]);
+        let progress = Progress::new(files.len());
+        let outcomes: Vec<ConversionOutcome> = files.iter().map(|file| convert_file(file, &cli, None, &progress)).collect();
+
+        let error = outcomes[0].result.as_ref().err().unwrap();
+        assert_eq!(error, "truncated file: section nb_nodes at offset 0x00000123 expects 1 i32 (4 bytes), only 2 bytes left");
+        assert!(!Path::new(&format!("{}.vtk", files[0])).exists());
+        assert!(outcomes[1].result.is_ok());
+    }
+
+    #[test]
+    fn fields_left_on_disk_that_cant_be_read_back_fail_the_input() {
+        let dir = TempDir::new("reread");
+        // one node with a nodal scalar, the last section of the file
+        let bytes = AnimBuilder::new(0.5).nodes(&[0.0; 3]).scalar("Temperature", &[20.0]).bytes();
+        let file = dir.file("runA001");
+        fs::write(&file, &bytes).unwrap();
+
+        let cli = cli_options(&[]);
+        let anim = crate::anim::read_anim_file_with_budget(&file, Some(0), cli.text_encoding, cli.endian).unwrap();
+        assert_eq!(anim.deferred_sections(), 1);
+        assert_eq!(anim.nodal_scalar(0).unwrap()[..], [20.0]);
+
+        // cut while the outputs are written
+        fs::write(&file, &bytes[..bytes.len() - 2]).unwrap();
+        assert_eq!(anim.nodal_scalar(0).unwrap_err().code(), codes::READ_FAILED);
+        let mut writer = LegacyVtkWriter::new(Vec::new(), &cli.write);
+        let e = crate::mesh_writer::write_mesh(&anim, &cli.write, &mut writer).unwrap_err();
+        let error = output_error(&e, "runA001.vtk");
+        assert_eq!(error.code, codes::READ_FAILED);
+        assert!(error.message.starts_with("input file changed while converting, can't read it back at offset 0x"), "{}", error.message);
+    }
+
+    #[test]
+    fn fields_are_read_while_writing_unless_modified() {
+        let dir = TempDir::new("two_pass");
+        let file = dir.file("runA001");
+        AnimBuilder::new(0.5).nodes(&[0.0; 3]).scalar("Temperature", &[20.0]).vector("Velocity", &[3.0, 4.0, 0.0]).write(&file);
+        let deferred = |args: &[&str]| {
+            let converted = convert_file(&file, &cli_options(args), None, &Progress::new(1)).result.unwrap();
+            converted.anim.unwrap().deferred_sections()
+        };
+        assert_eq!(deferred(&[]), 2);
+        // the geometry counts first, 20 bytes: the scalar fits, not the vector
+        assert_eq!(deferred(&["--max-memory", "24"]), 1);
+        assert_eq!(deferred(&["--derive", "magnitude"]), 0);
+        let text = fs::read_to_string(format!("{}.vtk", file)).unwrap();
+        assert!(text.contains("SCALARS Temperature float 1\nLOOKUP_TABLE default\n20.0\n"), "{}", text);
+    }
+
+    #[test]
+    fn failed_level_of_detail_removes_the_outputs_already_written() {
+        let dir = TempDir::new("lod");
+        let file = dir.file("runA001");
+        AnimBuilder::new(0.5).write(&file);
+        // a directory where the first level goes
+        fs::create_dir_all(format!("{}_lod1.vtk", file)).unwrap();
+        let outcome = convert_file(&file, &cli_options(&["--lod", "3", "--checksums"]), None, &Progress::new(1));
+
+        let error = outcome.result.as_ref().err().unwrap();
+        assert!(error.starts_with(&format!("can't create output file {}_lod1.vtk", file)), "{}", error);
+        assert!(!Path::new(&format!("{}.vtk", file)).exists());
+        assert!(!Path::new(&format!("{}.vtk.crc32", file)).exists());
+        assert!(!Path::new(&format!("{}_lod.json", file)).exists());
+    }
+
+    #[test]
+    fn mode_shapes_are_found_by_name_or_fall_back_to_the_state() {
+        let dir = TempDir::new("modes");
+        let file = dir.file("runA001");
+        AnimBuilder::new(0.25).nodes(&[0.0; 3]).vector("Eigen vector 1", &[1.0, 0.0, 0.0]).write(&file);
+        let outputs = |args: &[&str]| convert_file(&file, &cli_options(args), None, &Progress::new(1)).result.map(|converted| converted.outputs);
+
+        // no vector named like MODE: the state as it is
+        assert_eq!(outputs(&["--modes"]).unwrap(), [format!("{}.vtk", file)]);
+        // listed: one output per phase step, TIME kept and the phase in MODE_PHASE
+        let steps = outputs(&["--modes=eigen_vector_1", "--mode-phases", "2"]).unwrap();
+        assert_eq!(steps, [format!("{}_mode1_000.vtk", file), format!("{}_mode1_001.vtk", file)]);
+        let text = fs::read_to_string(&steps[1]).unwrap();
+        assert!(text.contains("FIELD FieldData 3\nTIME 1 1 double\n0.25\n"), "{}", text);
+        assert!(text.contains("MODE_PHASE 1 1 double\n0.5\n"), "{}", text);
+        // a listed name without vector fails
+        let error = outputs(&["--modes=MODE 1"]).unwrap_err();
+        assert_eq!(error, "no nodal vector named MODE 1 for --modes, the vectors are: Eigen vector 1");
+    }
+
+    #[test]
+    fn other_format_versions_are_rejected() {
+        let dir = TempDir::new("version");
+        // FASTMAGI9 magic number, whose layout is not documented
+        let file = dir.file("runA001");
+        let mut bytes = AnimBuilder::new(0.5).bytes();
+        bytes[..4].copy_from_slice(&0x542bi32.to_be_bytes());
+        fs::write(&file, bytes).unwrap();
+        let outcome = convert_file(&file, &cli_options(&[]), None, &Progress::new(1));
+
+        let error = outcome.result.as_ref().err().unwrap();
+        assert_eq!(error, "unknown format version 0x542b, only FASTMAGI10 (0x542c) is supported");
+        assert!(!Path::new(&format!("{}.vtk", file)).exists());
+    }
+
+    #[test]
+    fn parallel_runs_record_the_files_in_input_order() {
+        let (dir, files) = mixed_batch("parallel");
+        let geometry = dir.file("geometry.csv");
+        let cli = cli_options(&["--strict", "--geometry-report", &geometry, "--jobs", "3"]);
+        let progress = Progress::new(files.len());
+        let mut batch = Batch::new(&cli);
+        let input_files: Vec<&String> = files.iter().collect();
+        convert_parallel(&input_files, &cli, cli.jobs, &progress, &mut batch);
+        let report = batch.finish();
+
+        let inputs: Vec<&str> = report.outcomes().map(|outcome| outcome.0).collect();
+        assert_eq!(inputs, files.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!((report.succeeded(), report.failed()), (2, 2));
+        let csv = fs::read_to_string(&geometry).unwrap();
+        let states: Vec<&str> = csv.lines().skip(1).map(|line| &line[..line.match_indices(',').nth(1).unwrap().0]).collect();
+        assert_eq!(states, ["0,0.5", "1,1.5"]);
+    }
+}
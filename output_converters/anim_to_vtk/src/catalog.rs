@@ -0,0 +1,150 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// SQLite catalog of the converted states: one row per state with
+// the range of every field, so results can be queried without
+// reading the VTK files back
+// ****************************************
+
+use std::io;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::anim::{AnimFile, AnimReadError};
+use crate::vtk::replace_underscore;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS states (
+    id INTEGER PRIMARY KEY,
+    run TEXT NOT NULL,
+    state INTEGER NOT NULL,
+    time REAL NOT NULL,
+    input TEXT NOT NULL UNIQUE,
+    output TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS fields (
+    state_id INTEGER NOT NULL REFERENCES states(id),
+    name TEXT NOT NULL,
+    location TEXT NOT NULL,
+    min REAL,
+    max REAL
+);
+CREATE INDEX IF NOT EXISTS fields_by_name ON fields(name);
+";
+
+// "path/crashA012" -> ("path/crash", 12)
+fn split_state_name(file_name: &str) -> (String, i64) {
+    let digits = file_name.len() - file_name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let run_end = file_name.len().saturating_sub(digits + 1);
+    let state = file_name[file_name.len() - digits..].parse().unwrap_or(0);
+    (file_name[..run_end].to_string(), state)
+}
+
+fn range(values: impl Iterator<Item = f64>) -> (Option<f64>, Option<f64>) {
+    values.fold((None, None), |(min, max), v| {
+        (Some(min.map_or(v, |m: f64| m.min(v))), Some(max.map_or(v, |m: f64| m.max(v))))
+    })
+}
+
+// (name as written in the VTK file, location, min, max)
+type FieldRange = (String, &'static str, Option<f64>, Option<f64>);
+
+fn field_ranges(anim: &AnimFile) -> Result<Vec<FieldRange>, AnimReadError> {
+    let mut fields = Vec::new();
+    for (ifunc, text) in anim.func_text.iter().enumerate() {
+        let values = anim.nodal_scalar(ifunc)?;
+        let (min, max) = range(values.iter().map(|&v| v as f64));
+        fields.push((replace_underscore(text), "point", min, max));
+    }
+    // vectors are cataloged by magnitude
+    for (ivect, text) in anim.vect_text.iter().enumerate() {
+        let values = anim.nodal_vector(ivect)?;
+        let magnitudes = values.chunks_exact(3).map(|v| {
+            let (x, y, z) = (v[0] as f64, v[1] as f64, v[2] as f64);
+            (x * x + y * y + z * z).sqrt()
+        });
+        let (min, max) = range(magnitudes);
+        fields.push((replace_underscore(text), "point", min, max));
+    }
+    let blocks = [("1DELEM_", &anim.elts_1d), ("2DELEM_", &anim.elts_2d), ("3DELEM_", &anim.elts_3d), ("SPHELEM_", &anim.elts_sph)];
+    for (prefix, block) in blocks {
+        for (iefun, text) in block.efunc_text.iter().enumerate() {
+            let values = anim.elemental_scalar(block, iefun)?;
+            let (min, max) = range(values.iter().map(|&v| v as f64));
+            fields.push((format!("{}{}", prefix, replace_underscore(text)), "cell", min, max));
+        }
+    }
+    Ok(fields)
+}
+
+fn absolute_path(file_name: &str) -> String {
+    Path::new(file_name)
+        .canonicalize()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| file_name.to_string())
+}
+
+pub struct Catalog {
+    connection: Connection,
+}
+
+impl Catalog {
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Catalog { connection })
+    }
+
+    // the ranges first, the fields left on disk may fail to read back
+    pub fn add_state(&mut self, input: &str, output: &str, anim: &AnimFile) -> io::Result<()> {
+        let fields = field_ranges(anim)?;
+        self.insert_state(input, output, anim.time, fields).map_err(io::Error::other)
+    }
+
+    // a state converted again replaces its previous entry
+    fn insert_state(&mut self, input: &str, output: &str, time: f32, fields: Vec<FieldRange>) -> rusqlite::Result<()> {
+        let input = absolute_path(input);
+        let output = absolute_path(output);
+        let (run, state) = split_state_name(&input);
+
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "DELETE FROM fields WHERE state_id IN (SELECT id FROM states WHERE input = ?1)",
+            params![input],
+        )?;
+        transaction.execute("DELETE FROM states WHERE input = ?1", params![input])?;
+        transaction.execute(
+            "INSERT INTO states (run, state, time, input, output) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![run, state, time as f64, input, output],
+        )?;
+        let state_id = transaction.last_insert_rowid();
+        {
+            let mut insert = transaction
+                .prepare("INSERT INTO fields (state_id, name, location, min, max) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+            for (name, location, min, max) in fields {
+                insert.execute(params![state_id, name, location, min, max])?;
+            }
+        }
+        transaction.commit()
+    }
+}
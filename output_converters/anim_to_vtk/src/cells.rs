@@ -0,0 +1,443 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Cell degeneration and orientation logic
+// ****************************************
+
+use crate::triangulate::HEXA_TETS;
+
+pub const VTK_VERTEX: i32 = 1;
+pub const VTK_LINE: i32 = 3;
+pub const VTK_TRIANGLE: i32 = 5;
+pub const VTK_QUAD: i32 = 9;
+pub const VTK_TETRA: i32 = 10;
+pub const VTK_HEXAHEDRON: i32 = 12;
+
+// ****************************************
+// Small fixed-size dedup helpers
+// ****************************************
+pub fn unique_count(nodes: &[i32]) -> usize {
+    let mut uniq = [0i32; 8];
+    let mut count = 0usize;
+    for &n in nodes {
+        if !uniq[..count].contains(&n) {
+            uniq[count] = n;
+            count += 1;
+        }
+    }
+    count
+}
+
+pub fn unique_sorted_4(nodes: &[i32]) -> Option<[i32; 4]> {
+    let mut uniq = [0i32; 8];
+    let mut count = 0usize;
+    for &n in nodes {
+        if !uniq[..count].contains(&n) {
+            uniq[count] = n;
+            count += 1;
+        }
+    }
+    if count == 4 {
+        let mut arr = [uniq[0], uniq[1], uniq[2], uniq[3]];
+        arr.sort_unstable();
+        Some(arr)
+    } else {
+        None
+    }
+}
+
+// ****************************************
+// ShellCell - VTK cell emitted for one Radioss facet
+// Facets keep their 4 nodes (triangles repeat the last one)
+// ****************************************
+#[derive(Clone, Copy)]
+pub enum ShellCell {
+    Triangle([i32; 4]),
+    Quad([i32; 4]),
+}
+
+impl ShellCell {
+    pub fn from_facet(nodes: &[i32]) -> Self {
+        let mut corners = [0i32; 4];
+        corners.copy_from_slice(&nodes[0..4]);
+        if unique_count(&corners) == 3 {
+            ShellCell::Triangle(corners)
+        } else {
+            ShellCell::Quad(corners)
+        }
+    }
+
+    pub fn nodes(&self) -> &[i32] {
+        match self {
+            ShellCell::Triangle(nodes) | ShellCell::Quad(nodes) => nodes,
+        }
+    }
+
+    pub fn vtk_type(&self) -> i32 {
+        match self {
+            ShellCell::Triangle(_) => VTK_TRIANGLE,
+            ShellCell::Quad(_) => VTK_QUAD,
+        }
+    }
+}
+
+// ****************************************
+// SolidCell - VTK cell emitted for one Radioss brick
+// Bricks collapsing to 4 distinct nodes become tetrahedra,
+// everything else is written as an 8-node hexahedron
+// ****************************************
+#[derive(Clone, Copy)]
+pub enum SolidCell {
+    Tetra([i32; 4]),
+    Hexa([i32; 8]),
+}
+
+impl SolidCell {
+    pub fn from_brick(nodes: &[i32]) -> Self {
+        if let Some(tet) = unique_sorted_4(nodes) {
+            SolidCell::Tetra(tet)
+        } else {
+            let mut hex = [0i32; 8];
+            hex.copy_from_slice(&nodes[0..8]);
+            SolidCell::Hexa(hex)
+        }
+    }
+
+    // the cell of a VTK tetrahedron or hexahedron
+    pub fn from_vtk(cell_type: i32, nodes: &[i32]) -> Option<Self> {
+        match cell_type {
+            VTK_TETRA => nodes.try_into().ok().map(SolidCell::Tetra),
+            VTK_HEXAHEDRON => nodes.try_into().ok().map(SolidCell::Hexa),
+            _ => None,
+        }
+    }
+
+    pub fn nodes(&self) -> &[i32] {
+        match self {
+            SolidCell::Tetra(tet) => tet,
+            SolidCell::Hexa(hex) => hex,
+        }
+    }
+
+    pub fn vtk_type(&self) -> i32 {
+        match self {
+            SolidCell::Tetra(_) => VTK_TETRA,
+            SolidCell::Hexa(_) => VTK_HEXAHEDRON,
+        }
+    }
+
+    // collapsed bricks give flat tetrahedra
+    pub fn tetrahedra(&self) -> Vec<[i32; 4]> {
+        match self {
+            SolidCell::Tetra(tet) => vec![*tet],
+            SolidCell::Hexa(hex) => HEXA_TETS.iter().map(|t| [hex[t[0]], hex[t[1]], hex[t[2]], hex[t[3]]]).collect(),
+        }
+    }
+}
+
+pub fn solid_cells(connect_3d: &[i32]) -> Vec<SolidCell> {
+    connect_3d.chunks_exact(8).map(SolidCell::from_brick).collect()
+}
+
+// ****************************************
+// Orientation checks
+// ****************************************
+fn point(coor: &[f32], node: i32) -> [f64; 3] {
+    let i = node as usize * 3;
+    [coor[i] as f64, coor[i + 1] as f64, coor[i + 2] as f64]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn triple_product(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> f64 {
+    a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+        + a[2] * (b[0] * c[1] - b[1] * c[0])
+}
+
+// six times the signed volume, positive for the VTK_TETRA convention
+pub fn tetra_signed_volume(coor: &[f32], tet: &[i32; 4]) -> f64 {
+    let p0 = point(coor, tet[0]);
+    triple_product(
+        sub(point(coor, tet[1]), p0),
+        sub(point(coor, tet[2]), p0),
+        sub(point(coor, tet[3]), p0),
+    )
+}
+
+// Jacobian determinants at the 8 corners of a trilinear hexahedron.
+// Corners where a degenerated brick collapses give a zero determinant.
+pub fn hexa_corner_jacobians(coor: &[f32], hex: &[i32; 8]) -> [f64; 8] {
+    // for each corner: the three neighbours along the local xi, eta, zeta edges
+    const EDGES: [[usize; 3]; 8] = [
+        [1, 3, 4],
+        [2, 0, 5],
+        [3, 1, 6],
+        [0, 2, 7],
+        [7, 5, 0],
+        [4, 6, 1],
+        [5, 7, 2],
+        [6, 4, 3],
+    ];
+    let mut jac = [0f64; 8];
+    for (corner, edges) in EDGES.iter().enumerate() {
+        let p = point(coor, hex[corner]);
+        jac[corner] = triple_product(
+            sub(point(coor, hex[edges[0]]), p),
+            sub(point(coor, hex[edges[1]]), p),
+            sub(point(coor, hex[edges[2]]), p),
+        );
+    }
+    jac
+}
+
+fn hexa_center_jacobian(coor: &[f32], hex: &[i32; 8]) -> f64 {
+    const XI: [f64; 8] = [-1.0, 1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0];
+    const ETA: [f64; 8] = [-1.0, -1.0, 1.0, 1.0, -1.0, -1.0, 1.0, 1.0];
+    const ZETA: [f64; 8] = [-1.0, -1.0, -1.0, -1.0, 1.0, 1.0, 1.0, 1.0];
+    let mut d_xi = [0f64; 3];
+    let mut d_eta = [0f64; 3];
+    let mut d_zeta = [0f64; 3];
+    for i in 0..8 {
+        let p = point(coor, hex[i]);
+        for k in 0..3 {
+            d_xi[k] += XI[i] * p[k];
+            d_eta[k] += ETA[i] * p[k];
+            d_zeta[k] += ZETA[i] * p[k];
+        }
+    }
+    triple_product(d_xi, d_eta, d_zeta)
+}
+
+// ****************************************
+// Reorder inverted solids to the VTK convention.
+// Returns one flag per cell: 1 when the cell is still inverted
+// (zero volume or tangled hexahedron that no reordering can fix)
+// ****************************************
+pub fn fix_solid_orientation(cells: &mut [SolidCell], coor: &[f32]) -> Vec<i32> {
+    let mut inverted = vec![0i32; cells.len()];
+    for (icell, cell) in cells.iter_mut().enumerate() {
+        match cell {
+            SolidCell::Tetra(tet) => {
+                let vol = tetra_signed_volume(coor, tet);
+                if vol < 0.0 {
+                    tet.swap(1, 2);
+                } else if vol == 0.0 {
+                    inverted[icell] = 1;
+                }
+            }
+            SolidCell::Hexa(hex) => {
+                let center = hexa_center_jacobian(coor, hex);
+                if center == 0.0 {
+                    inverted[icell] = 1;
+                    continue;
+                }
+                let jac = hexa_corner_jacobians(coor, hex);
+                let positive = jac.iter().any(|&j| j > 0.0);
+                let negative = jac.iter().any(|&j| j < 0.0);
+                if positive && negative {
+                    // tangled element: some corners inverted, others not
+                    inverted[icell] = 1;
+                } else if center < 0.0 {
+                    // mirror the brick: swap bottom and top faces
+                    *hex = [hex[4], hex[5], hex[6], hex[7], hex[0], hex[1], hex[2], hex[3]];
+                }
+            }
+        }
+    }
+    inverted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // unit cube corners in the Radioss brick order: 1-4 bottom, 5-8 top
+    const CUBE: [[f64; 3]; 8] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0],
+        [0.0, 1.0, 1.0],
+    ];
+
+    // cube corner of each brick node for the shapes the solver stores as bricks
+    const HEXA: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+    const PENTA: [usize; 8] = [0, 1, 2, 2, 4, 5, 6, 6];
+    const PYRAMID: [usize; 8] = [0, 1, 2, 3, 4, 4, 4, 4];
+    const TETRA: [usize; 8] = [0, 1, 2, 2, 4, 4, 4, 4];
+
+    fn coordinates(points: &[[f64; 3]]) -> Vec<f32> {
+        points.iter().flatten().map(|&x| x as f32).collect()
+    }
+
+    fn unique_nodes(nodes: &[i32]) -> Vec<i32> {
+        let mut unique = nodes.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        unique
+    }
+
+    // six times the volume of the cell, from its tetrahedra
+    fn volume(coor: &[f32], cell: &SolidCell) -> f64 {
+        cell.tetrahedra().iter().map(|tet| tetra_signed_volume(coor, tet)).sum()
+    }
+
+    // rotation, scaling along the axes and translation, mirrored on request
+    fn placement() -> impl Strategy<Value = ([f64; 3], [f64; 3], [f64; 3], bool)> {
+        (
+            prop::array::uniform3(-3.2f64..3.2),
+            prop::array::uniform3(0.05f64..20.0),
+            prop::array::uniform3(-1000.0f64..1000.0),
+            any::<bool>(),
+        )
+    }
+
+    fn place(point: [f64; 3], (angles, scales, offset, mirror): &([f64; 3], [f64; 3], [f64; 3], bool)) -> [f64; 3] {
+        let mut p = [point[0] * scales[0], point[1] * scales[1], point[2] * scales[2]];
+        if *mirror {
+            p[0] = -p[0];
+        }
+        for (axis, &angle) in angles.iter().enumerate() {
+            let (i, j) = ((axis + 1) % 3, (axis + 2) % 3);
+            let (sin, cos) = angle.sin_cos();
+            (p[i], p[j]) = (cos * p[i] - sin * p[j], sin * p[i] + cos * p[j]);
+        }
+        [p[0] + offset[0], p[1] + offset[1], p[2] + offset[2]]
+    }
+
+    // brick of <shape> placed in space, its numbering starting at another
+    // corner of the bottom face, with nodes numbered in any order
+    fn placed_brick(
+        shape: &[usize; 8],
+        shift: usize,
+        numbering: &[i32],
+        placement: &([f64; 3], [f64; 3], [f64; 3], bool),
+    ) -> ([i32; 8], Vec<f32>) {
+        let mut brick = [0i32; 8];
+        for i in 0..4 {
+            brick[i] = numbering[shape[(i + shift) % 4]];
+            brick[4 + i] = numbering[shape[4 + (i + shift) % 4]];
+        }
+        let mut points = [[0.0; 3]; 8];
+        for (corner, &node) in numbering.iter().enumerate() {
+            points[node as usize] = place(CUBE[corner], placement);
+        }
+        (brick, coordinates(&points))
+    }
+
+    proptest! {
+        #[test]
+        fn bricks_become_positive_cells_of_their_shape(
+            shape in prop::sample::select(vec![HEXA, PENTA, PYRAMID, TETRA]),
+            shift in 0usize..4,
+            numbering in Just((0..8).collect::<Vec<i32>>()).prop_shuffle(),
+            placement in placement(),
+        ) {
+            let (brick, coor) = placed_brick(&shape, shift, &numbering, &placement);
+            let distinct = unique_nodes(&brick).len();
+            let mut cells = vec![SolidCell::from_brick(&brick)];
+
+            if distinct == 4 {
+                prop_assert_eq!(cells[0].vtk_type(), VTK_TETRA);
+                prop_assert_eq!(cells[0].nodes().len(), 4);
+            } else {
+                prop_assert_eq!(cells[0].vtk_type(), VTK_HEXAHEDRON);
+                prop_assert_eq!(cells[0].nodes().len(), 8);
+            }
+            prop_assert_eq!(unique_nodes(cells[0].nodes()), unique_nodes(&brick));
+
+            // mirrored bricks are reordered, none is left inverted
+            prop_assert_eq!(fix_solid_orientation(&mut cells, &coor), vec![0]);
+            prop_assert_eq!(unique_nodes(cells[0].nodes()), unique_nodes(&brick));
+            prop_assert!(volume(&coor, &cells[0]) > 0.0);
+            if let SolidCell::Hexa(hex) = &cells[0] {
+                prop_assert!(hexa_corner_jacobians(&coor, hex).iter().all(|&jacobian| jacobian >= 0.0));
+            }
+        }
+
+        #[test]
+        fn any_brick_keeps_its_nodes(brick in prop::array::uniform8(0i32..8)) {
+            let cell = SolidCell::from_brick(&brick);
+            let distinct = unique_nodes(&brick);
+            match cell {
+                SolidCell::Tetra(tet) => {
+                    prop_assert_eq!(distinct.len(), 4);
+                    prop_assert_eq!(&tet[..], &distinct[..]);
+                }
+                SolidCell::Hexa(hex) => {
+                    prop_assert_ne!(distinct.len(), 4);
+                    prop_assert_eq!(hex, brick);
+                }
+            }
+            prop_assert_eq!(unique_count(&brick), distinct.len());
+        }
+
+        #[test]
+        fn bricks_of_fewer_than_four_nodes_are_flagged(brick in prop::array::uniform8(0i32..3)) {
+            let coor = coordinates(&CUBE);
+            let mut cells = vec![SolidCell::from_brick(&brick)];
+            prop_assert_eq!(cells[0].vtk_type(), VTK_HEXAHEDRON);
+            prop_assert_eq!(fix_solid_orientation(&mut cells, &coor), vec![1]);
+        }
+
+        #[test]
+        fn facets_become_triangles_or_quads(
+            numbering in Just((0..4).collect::<Vec<i32>>()).prop_shuffle(),
+            triangle in any::<bool>(),
+        ) {
+            let mut facet = numbering.clone();
+            if triangle {
+                facet[3] = facet[2];
+            }
+            let cell = ShellCell::from_facet(&facet);
+            if triangle {
+                prop_assert_eq!(cell.vtk_type(), VTK_TRIANGLE);
+                prop_assert_eq!(unique_nodes(cell.nodes()), unique_nodes(&facet[..3]));
+            } else {
+                prop_assert_eq!(cell.vtk_type(), VTK_QUAD);
+                prop_assert_eq!(cell.nodes(), &facet[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn mirrored_hexahedron_is_reordered() {
+        let coor = coordinates(&CUBE);
+        let mut cells = vec![SolidCell::from_brick(&[4, 5, 6, 7, 0, 1, 2, 3])];
+        assert_eq!(fix_solid_orientation(&mut cells, &coor), [0]);
+        assert_eq!(cells[0].nodes(), [0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn tangled_hexahedron_is_flagged() {
+        let coor = coordinates(&CUBE);
+        // top face twisted: nodes 5 and 6 swapped
+        let mut cells = vec![SolidCell::from_brick(&[0, 1, 2, 3, 5, 4, 6, 7])];
+        assert_eq!(fix_solid_orientation(&mut cells, &coor), [1]);
+    }
+}
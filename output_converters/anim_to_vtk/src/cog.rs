@@ -0,0 +1,196 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Center of gravity and inertia of each part and of the whole model,
+// per state, from the nodal masses
+// ****************************************
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use report::codes;
+
+use crate::anim::AnimFile;
+use crate::part_sums::part_nodes;
+
+struct MassProperties {
+    mass: f64,
+    cog: [f64; 3],
+    // inertia about the center of gravity: xx, yy, zz, xy, yz, zx
+    inertia: [f64; 6],
+}
+
+fn mass_properties(anim: &AnimFile, nodes: impl Iterator<Item = usize> + Clone) -> MassProperties {
+    // unit nodal masses when the file has none
+    let node_mass = |n: usize| anim.n_mass.get(n).map_or(1.0, |&m| m as f64);
+    let position = |n: usize| [anim.coor[3 * n] as f64, anim.coor[3 * n + 1] as f64, anim.coor[3 * n + 2] as f64];
+
+    let mut mass = 0f64;
+    let mut cog = [0f64; 3];
+    for n in nodes.clone() {
+        let m = node_mass(n);
+        let p = position(n);
+        mass += m;
+        for (c, pk) in cog.iter_mut().zip(p) {
+            *c += m * pk;
+        }
+    }
+    if mass != 0.0 {
+        for c in &mut cog {
+            *c /= mass;
+        }
+    }
+
+    let mut inertia = [0f64; 6];
+    for n in nodes {
+        let m = node_mass(n);
+        let p = position(n);
+        let d = [p[0] - cog[0], p[1] - cog[1], p[2] - cog[2]];
+        inertia[0] += m * (d[1] * d[1] + d[2] * d[2]);
+        inertia[1] += m * (d[2] * d[2] + d[0] * d[0]);
+        inertia[2] += m * (d[0] * d[0] + d[1] * d[1]);
+        inertia[3] -= m * d[0] * d[1];
+        inertia[4] -= m * d[1] * d[2];
+        inertia[5] -= m * d[2] * d[0];
+    }
+    MassProperties { mass, cog, inertia }
+}
+
+// ****************************************
+// Velocities come from differencing with the previous state,
+// they are left empty on the first one
+// ****************************************
+pub struct CogTracker {
+    writer: BufWriter<File>,
+    previous: Vec<(String, f64, [f64; 3])>, // part, time, center of gravity
+    state: usize,
+}
+
+impl CogTracker {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "state,time,part,mass,x,y,z,vx,vy,vz,ixx,iyy,izz,ixy,iyz,izx")?;
+        Ok(CogTracker { writer, previous: Vec::new(), state: 0 })
+    }
+
+    pub fn add_state(&mut self, anim: &AnimFile) -> std::io::Result<()> {
+        if anim.n_mass.is_empty() {
+            report::warning(codes::NO_MASS, "no nodal masses, using unit masses");
+        }
+        let time = anim.time as f64;
+        let mut rows = vec![("all".to_string(), mass_properties(anim, 0..anim.nb_nodes))];
+        for (part_id, nodes) in part_nodes(anim) {
+            rows.push((part_id.to_string(), mass_properties(anim, nodes.iter().map(|&n| n as usize))));
+        }
+
+        let mut current = Vec::with_capacity(rows.len());
+        for (part, props) in rows {
+            let velocity = self
+                .previous
+                .iter()
+                .find(|(name, prev_time, _)| *name == part && *prev_time != time)
+                .map(|(_, prev_time, prev_cog)| {
+                    let dt = time - prev_time;
+                    [
+                        (props.cog[0] - prev_cog[0]) / dt,
+                        (props.cog[1] - prev_cog[1]) / dt,
+                        (props.cog[2] - prev_cog[2]) / dt,
+                    ]
+                });
+            let velocity = match velocity {
+                Some(v) => format!("{},{},{}", v[0], v[1], v[2]),
+                None => ",,".to_string(),
+            };
+            let inertia = props.inertia;
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                self.state,
+                anim.time,
+                part,
+                props.mass,
+                props.cog[0],
+                props.cog[1],
+                props.cog[2],
+                velocity,
+                inertia[0],
+                inertia[1],
+                inertia[2],
+                inertia[3],
+                inertia[4],
+                inertia[5]
+            )?;
+            current.push((part, time, props.cog));
+        }
+        self.previous = current;
+        self.state += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{quad_parts, TempDir};
+
+    #[test]
+    fn two_unit_masses() {
+        let anim = AnimFile { nb_nodes: 2, coor: vec![0.0, 0.0, 0.0, 2.0, 0.0, 0.0], n_mass: vec![1.0, 1.0], ..Default::default() };
+        let props = mass_properties(&anim, 0..2);
+        assert_eq!(props.mass, 2.0);
+        assert_eq!(props.cog, [1.0, 0.0, 0.0]);
+        assert_eq!(props.inertia, [0.0, 2.0, 2.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn velocities_come_from_the_previous_state() {
+        let dir = TempDir::new("cog");
+        let path = dir.file("cog.csv");
+        let mut tracker = CogTracker::create(&path).unwrap();
+        // one unit shell of part 10 and unit nodal masses, moved by 1 along x in 0.5
+        let mut anim = quad_parts(&[("10 Plate", 1)]);
+        anim.n_mass = vec![1.0; 4];
+        tracker.add_state(&anim).unwrap();
+        anim.time = 0.5;
+        for x in anim.coor.iter_mut().step_by(3) {
+            *x += 1.0;
+        }
+        tracker.add_state(&anim).unwrap();
+        tracker.finish().unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            [
+                "0,0,all,4,0.5,0.5,0,,,,1,1,2,0,0,0",
+                "0,0,10,4,0.5,0.5,0,,,,1,1,2,0,0,0",
+                "1,0.5,all,4,1.5,0.5,0,2,0,0,1,1,2,0,0,0",
+                "1,0.5,10,4,1.5,0.5,0,2,0,0,1,1,2,0,0,0",
+            ]
+        );
+    }
+}
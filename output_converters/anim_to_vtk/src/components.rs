@@ -0,0 +1,139 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Connected component labeling through shared nodes
+// ****************************************
+
+use crate::anim::AnimFile;
+
+// union-find over node indices
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> Self {
+        DisjointSet {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let ra = self.find(a);
+        let rb = self.find(b);
+        if ra != rb {
+            self.parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+}
+
+// ****************************************
+// Label the active cells of every block (1D, 2D, 3D, SPH) by connected
+// component, numbered from 0 in cell order. Eroded cells do not connect
+// anything and get -1. Returns the labels per block and the number of
+// components.
+// ****************************************
+pub fn label_components(anim: &AnimFile) -> ([Vec<i32>; 4], usize) {
+    let blocks = [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph];
+    let mut set = DisjointSet::new(anim.nb_nodes);
+    for block in blocks {
+        for iel in 0..block.count {
+            if !block.is_active(iel) {
+                continue;
+            }
+            let nodes = block.nodes(iel);
+            for &node in &nodes[1..] {
+                set.union(nodes[0] as usize, node as usize);
+            }
+        }
+    }
+
+    let mut component_of_root = vec![-1i32; anim.nb_nodes];
+    let mut nb_components = 0usize;
+    let mut labels: [Vec<i32>; 4] = Default::default();
+    for (iblock, block) in blocks.iter().enumerate() {
+        labels[iblock] = (0..block.count)
+            .map(|iel| {
+                if !block.is_active(iel) {
+                    return -1;
+                }
+                let root = set.find(block.nodes(iel)[0] as usize);
+                if component_of_root[root] < 0 {
+                    component_of_root[root] = nb_components as i32;
+                    nb_components += 1;
+                }
+                component_of_root[root]
+            })
+            .collect();
+    }
+    (labels, nb_components)
+}
+
+// adds the COMPONENT_ID cell array, returns the number of components
+pub fn add_component_ids(anim: &mut AnimFile) -> usize {
+    let ([labels_1d, labels_2d, labels_3d, labels_sph], nb_components) = label_components(anim);
+    anim.elts_1d.int_arrays.push(("COMPONENT_ID".to_string(), labels_1d));
+    anim.elts_2d.int_arrays.push(("COMPONENT_ID".to_string(), labels_2d));
+    anim.elts_3d.int_arrays.push(("COMPONENT_ID".to_string(), labels_3d));
+    anim.elts_sph.int_arrays.push(("COMPONENT_ID".to_string(), labels_sph));
+    nb_components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::quad_parts;
+
+    #[test]
+    fn parts_without_shared_node_are_separate_components() {
+        let anim = quad_parts(&[("10 Front", 2), ("20 Rear", 3)]);
+        let ([labels_1d, labels_2d, labels_3d, labels_sph], nb_components) = label_components(&anim);
+        assert_eq!(nb_components, 2);
+        assert_eq!(labels_2d, [0, 0, 1, 1, 1]);
+        assert!(labels_1d.is_empty() && labels_3d.is_empty() && labels_sph.is_empty());
+    }
+
+    #[test]
+    fn eroded_cells_split_their_component() {
+        let mut anim = quad_parts(&[("10 Front", 2), ("20 Rear", 3)]);
+        anim.elts_2d.del_elt[3] = 0;
+        let (labels, nb_components) = label_components(&anim);
+        assert_eq!(nb_components, 3);
+        assert_eq!(labels[1], [0, 0, 1, -1, 2]);
+    }
+
+    #[test]
+    fn component_ids_are_added_to_every_block() {
+        let mut anim = quad_parts(&[("10 Front", 1), ("20 Rear", 1)]);
+        assert_eq!(add_component_ids(&mut anim), 2);
+        assert_eq!(anim.elts_2d.int_arrays, [("COMPONENT_ID".to_string(), vec![0, 1])]);
+        assert_eq!(anim.elts_3d.int_arrays, [("COMPONENT_ID".to_string(), Vec::new())]);
+    }
+}
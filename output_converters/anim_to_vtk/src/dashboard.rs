@@ -0,0 +1,411 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Static HTML summary of a run: eroded elements and per part maxima of
+// the elemental scalars versus time, drawn as inline SVG, and the
+// outcome of every input with links to its outputs. The page has no
+// script nor external resource, so it opens from a file share
+// ****************************************
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use report::Report;
+
+use crate::anim::{atoi_prefix, AnimFile, AnimReadError};
+use crate::vtk::replace_underscore;
+
+// parts drawn per field, those reaching the highest maxima
+const MAX_PARTS: usize = 10;
+
+const COLORS: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf",
+];
+
+const WIDTH: f64 = 720.0;
+const HEIGHT: f64 = 300.0;
+const MARGIN_LEFT: f64 = 80.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+
+const BLOCK_NAMES: [&str; 4] = ["1D", "2D", "3D", "SPH"];
+
+// one curve: legend and (time, value) points
+type Series = (String, Vec<(f64, f64)>);
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// percent-encoding of everything but the unreserved characters and '/'
+fn url_escape(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    escaped
+}
+
+// path of an output relative to a directory, '/' separated, so that the
+// run directory can be moved or shared with the file referencing it
+pub fn relative_path(dir: &Path, output: &str) -> String {
+    let target = match Path::new(output).canonicalize() {
+        Ok(target) => target,
+        Err(_) => return output.to_string(),
+    };
+    let from: Vec<Component> = dir.components().collect();
+    let to: Vec<Component> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return target.to_string_lossy().into_owned();
+    }
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component);
+    }
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+// link to an output, relative to the directory of the page
+fn link_target(page_dir: &Path, output: &str) -> String {
+    url_escape(&relative_path(page_dir, output))
+}
+
+// short tick labels, scientific notation out of [1e-3, 1e5)
+fn tick_label(value: f64) -> String {
+    let magnitude = value.abs();
+    if magnitude == 0.0 || (1e-3..1e5).contains(&magnitude) {
+        let text = format!("{:.4}", value);
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        format!("{:.3e}", value)
+    }
+}
+
+// bounds of the values, widened when they are all equal
+fn axis_range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)));
+    if min > max {
+        (0.0, 1.0)
+    } else if min == max {
+        let pad = if min == 0.0 { 1.0 } else { min.abs() * 0.1 };
+        (min - pad, max + pad)
+    } else {
+        (min, max)
+    }
+}
+
+// SVG line chart of the series versus time, with a legend
+fn chart(title: &str, y_label: &str, series: &[Series]) -> String {
+    let (x_min, x_max) = axis_range(series.iter().flat_map(|(_, points)| points.iter().map(|p| p.0)));
+    let (y_min, y_max) = axis_range(series.iter().flat_map(|(_, points)| points.iter().map(|p| p.1)));
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let x = |t: f64| MARGIN_LEFT + (t - x_min) / (x_max - x_min) * plot_width;
+    let y = |v: f64| MARGIN_TOP + (y_max - v) / (y_max - y_min) * plot_height;
+
+    let mut svg = format!("<h3>{}</h3>\n", html_escape(title));
+    let _ = writeln!(svg, "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", WIDTH, HEIGHT, WIDTH, HEIGHT);
+    let _ = writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#999\"/>",
+        MARGIN_LEFT, MARGIN_TOP, plot_width, plot_height
+    );
+    for k in 0..=4 {
+        let t = x_min + (x_max - x_min) * k as f64 / 4.0;
+        let v = y_min + (y_max - y_min) * k as f64 / 4.0;
+        let _ = writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            x(t),
+            HEIGHT - MARGIN_BOTTOM + 16.0,
+            tick_label(t)
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+            MARGIN_LEFT - 6.0,
+            y(v) + 4.0,
+            tick_label(v)
+        );
+    }
+    let _ = writeln!(
+        svg,
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">time</text>",
+        MARGIN_LEFT + plot_width / 2.0,
+        HEIGHT - 6.0
+    );
+    let _ = writeln!(
+        svg,
+        "<text transform=\"translate(14 {:.1}) rotate(-90)\" text-anchor=\"middle\">{}</text>",
+        MARGIN_TOP + plot_height / 2.0,
+        html_escape(y_label)
+    );
+    for (index, (_, points)) in series.iter().enumerate() {
+        let color = COLORS[index % COLORS.len()];
+        let mut sorted = points.clone();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let coordinates: Vec<String> = sorted.iter().map(|&(t, v)| format!("{:.1},{:.1}", x(t), y(v))).collect();
+        let _ = writeln!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{}\"/>",
+            color,
+            coordinates.join(" ")
+        );
+        for &(t, v) in &sorted {
+            let _ = writeln!(svg, "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2.5\" fill=\"{}\"/>", x(t), y(v), color);
+        }
+    }
+    svg.push_str("</svg>\n<p class=\"legend\">");
+    for (index, (name, _)) in series.iter().enumerate() {
+        let _ = write!(
+            svg,
+            "<span style=\"color: {}\">&#9632;</span> {} ",
+            COLORS[index % COLORS.len()],
+            html_escape(name)
+        );
+    }
+    svg.push_str("</p>\n");
+    svg
+}
+
+// ****************************************
+// Every converted state adds its time, its number of eroded elements
+// per type and, per elemental scalar, the maximum over the active
+// elements of each part; the page is written once the run is over
+// ****************************************
+pub struct Dashboard {
+    path: String,
+    page_dir: PathBuf,
+    times: BTreeMap<String, f32>,           // time of every converted input
+    eroded: Vec<(f64, [usize; 4])>,         // (time, eroded elements per type)
+    has_block: [bool; 4],                   // element types present in some state
+    maxima: BTreeMap<String, BTreeMap<i32, Vec<(f64, f64)>>>, // field -> part -> (time, max)
+}
+
+impl Dashboard {
+    // created empty at once, so that a wrong path fails before the conversion
+    pub fn create(path: &str) -> io::Result<Self> {
+        File::create(path)?;
+        let parent = Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let page_dir = parent.canonicalize()?;
+        Ok(Dashboard {
+            path: path.to_string(),
+            page_dir,
+            times: BTreeMap::new(),
+            eroded: Vec::new(),
+            has_block: [false; 4],
+            maxima: BTreeMap::new(),
+        })
+    }
+
+    pub fn add_state(&mut self, input: &str, anim: &AnimFile) -> Result<(), AnimReadError> {
+        let time = anim.time as f64;
+        self.times.insert(input.to_string(), anim.time);
+        let blocks = [("1DELEM_", &anim.elts_1d), ("2DELEM_", &anim.elts_2d), ("3DELEM_", &anim.elts_3d), ("SPHELEM_", &anim.elts_sph)];
+        let mut eroded = [0; 4];
+        for (iblock, (prefix, block)) in blocks.into_iter().enumerate() {
+            if block.count == 0 {
+                continue;
+            }
+            self.has_block[iblock] = true;
+            eroded[iblock] = (0..block.count).filter(|&iel| !block.is_active(iel)).count();
+
+            let part_of = block.part_indices();
+            let part_ids: Vec<i32> = part_of
+                .iter()
+                .map(|&ipart| block.part_text.get(ipart).map_or(0, |text| atoi_prefix(text)))
+                .collect();
+            for (iefun, text) in block.efunc_text.iter().enumerate() {
+                let values = anim.elemental_scalar(block, iefun)?;
+                let mut part_max: BTreeMap<i32, f64> = BTreeMap::new();
+                for iel in 0..block.count {
+                    let value = values[iel] as f64;
+                    if block.is_active(iel) && value.is_finite() {
+                        let max = part_max.entry(part_ids[iel]).or_insert(value);
+                        *max = max.max(value);
+                    }
+                }
+                let field = self.maxima.entry(format!("{}{}", prefix, replace_underscore(text))).or_default();
+                for (part_id, max) in part_max {
+                    field.entry(part_id).or_default().push((time, max));
+                }
+            }
+        }
+        self.eroded.push((time, eroded));
+        Ok(())
+    }
+
+    fn eroded_series(&self) -> Vec<Series> {
+        let mut series = vec![(
+            "all".to_string(),
+            self.eroded.iter().map(|(time, eroded)| (*time, eroded.iter().sum::<usize>() as f64)).collect(),
+        )];
+        for (iblock, name) in BLOCK_NAMES.iter().enumerate() {
+            if self.has_block[iblock] {
+                let points = self.eroded.iter().map(|(time, eroded)| (*time, eroded[iblock] as f64)).collect();
+                series.push((name.to_string(), points));
+            }
+        }
+        series
+    }
+
+    pub fn finish(self, report: &Report) -> io::Result<()> {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>anim_to_vtk run summary</title>\n<style>\n\
+             body { font-family: sans-serif; margin: 2em; }\n\
+             svg { font-size: 11px; }\n\
+             table { border-collapse: collapse; }\n\
+             th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }\n\
+             .failed { color: #c00; }\n\
+             </style>\n</head>\n<body>\n<h1>anim_to_vtk run summary</h1>\n",
+        );
+        let _ = writeln!(html, "<p>{} files converted, {} failed.</p>", report.succeeded(), report.failed());
+
+        if !self.eroded.is_empty() {
+            html.push_str("<h2>Eroded elements</h2>\n");
+            html.push_str(&chart("Eroded elements per type", "eroded elements", &self.eroded_series()));
+
+            html.push_str("<h2>Maximum of the elemental scalars per part</h2>\n");
+            if self.maxima.is_empty() {
+                html.push_str("<p>No elemental scalar in the converted files.</p>\n");
+            }
+            for (name, parts) in &self.maxima {
+                let peak = |points: &Vec<(f64, f64)>| points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+                let mut ranked: Vec<(&i32, &Vec<(f64, f64)>)> = parts.iter().collect();
+                ranked.sort_by(|a, b| peak(b.1).total_cmp(&peak(a.1)));
+                let series: Vec<Series> = ranked
+                    .iter()
+                    .take(MAX_PARTS)
+                    .map(|(part_id, points)| (format!("part {}", part_id), points.to_vec()))
+                    .collect();
+                let title = if parts.len() > MAX_PARTS {
+                    format!("{} ({} of {} parts, highest maxima)", name, MAX_PARTS, parts.len())
+                } else {
+                    name.clone()
+                };
+                html.push_str(&chart(&title, name, &series));
+            }
+        }
+
+        html.push_str("<h2>Files</h2>\n<table>\n<tr><th>input</th><th>time</th><th>status</th><th>outputs</th></tr>\n");
+        for (input, outputs, error) in report.outcomes() {
+            let time = self.times.get(input).map_or(String::new(), |time| time.to_string());
+            let _ = write!(html, "<tr><td>{}</td><td>{}</td>", html_escape(input), time);
+            match error {
+                None => {
+                    let links: Vec<String> = outputs
+                        .iter()
+                        .map(|output| {
+                            format!("<a href=\"{}\">{}</a>", link_target(&self.page_dir, output), html_escape(output))
+                        })
+                        .collect();
+                    let _ = writeln!(html, "<td>ok</td><td>{}</td></tr>", links.join("<br>"));
+                }
+                Some(error) => {
+                    let _ = writeln!(html, "<td class=\"failed\">failed</td><td class=\"failed\">{}</td></tr>", html_escape(error));
+                }
+            }
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        fs::write(&self.path, html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::test_files::{quad_parts, TempDir};
+
+    #[test]
+    fn maxima_per_part_and_eroded_elements() {
+        let dir = TempDir::new("dashboard");
+        let path = dir.file("run.html");
+        let mut dashboard = Dashboard::create(&path).unwrap();
+
+        let mut anim = quad_parts(&[("10 Front", 2), ("20 Rear", 1)]);
+        anim.elts_2d.efunc_text = vec!["Von Mises".to_string()];
+        anim.elts_2d.efunc = vec![100.0, 300.0, 50.0];
+        dashboard.add_state("runA001", &anim).unwrap();
+        // the eroded element leaves the maximum of its part
+        anim.time = 0.5;
+        anim.elts_2d.efunc = vec![200.0, 900.0, 80.0];
+        anim.elts_2d.del_elt[1] = 0;
+        dashboard.add_state("runA002", &anim).unwrap();
+
+        let parts = &dashboard.maxima["2DELEM_Von_Mises"];
+        assert_eq!(parts[&10], [(0.0, 300.0), (0.5, 200.0)]);
+        assert_eq!(parts[&20], [(0.0, 50.0), (0.5, 80.0)]);
+        assert_eq!(dashboard.eroded, [(0.0, [0, 0, 0, 0]), (0.5, [0, 1, 0, 0])]);
+        assert_eq!(dashboard.eroded_series().iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["all", "2D"]);
+
+        let output = dir.file("runA001 & co.vtk");
+        fs::write(&output, "").unwrap();
+        let mut report = Report::new("anim_to_vtk");
+        report.success("runA001", &[output], Duration::ZERO);
+        report.success("runA002", &[], Duration::ZERO);
+        report.failure("runA003", "Can't open <runA003>");
+        dashboard.finish(&report).unwrap();
+
+        let html = fs::read_to_string(&path).unwrap();
+        assert!(html.contains("<p>2 files converted, 1 failed.</p>"));
+        assert!(html.contains("<h3>2DELEM_Von_Mises</h3>"));
+        assert!(html.contains("<span style=\"color: #1f77b4\">&#9632;</span> part 10 "));
+        assert!(html.contains("<tr><td>runA002</td><td>0.5</td><td>ok</td>"));
+        // links relative to the page, escaped
+        assert!(html.contains("<a href=\"runA001%20%26%20co.vtk\">"));
+        assert!(html.contains("<td class=\"failed\">Can&#39;t open &lt;runA003&gt;</td>"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn tick_labels_stay_short() {
+        assert_eq!(tick_label(0.0), "0");
+        assert_eq!(tick_label(0.125), "0.125");
+        assert_eq!(tick_label(250.0), "250");
+        assert_eq!(tick_label(2.5e-5), "2.500e-5");
+        assert_eq!(tick_label(-1.5e6), "-1.500e6");
+    }
+}
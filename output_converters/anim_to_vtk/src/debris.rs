@@ -0,0 +1,234 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Debris tracking across a sequence of states
+// ****************************************
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::anim::AnimFile;
+use crate::components::label_components;
+
+// element key unique across dimensions: (block index, ELEMENT_ID)
+type ElementKey = (u8, i32);
+
+struct Fragment {
+    elements: Vec<ElementKey>,
+    mass: f64,
+    centroid: [f64; 3],
+}
+
+// ****************************************
+// Components detached from the main body (the largest component) are
+// fragments. Fragments keep their id from one state to the next when
+// they share elements with a fragment of the previous state.
+// ****************************************
+pub struct DebrisTracker {
+    writer: BufWriter<File>,
+    previous: Vec<(usize, Vec<ElementKey>)>, // fragment id, sorted elements
+    next_id: usize,
+    state: usize,
+}
+
+fn collect_fragments(anim: &AnimFile) -> Vec<Fragment> {
+    let (labels, nb_components) = label_components(anim);
+    let blocks = [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph];
+
+    let mut fragments: Vec<Fragment> = (0..nb_components)
+        .map(|_| Fragment { elements: Vec::new(), mass: 0.0, centroid: [0.0; 3] })
+        .collect();
+    let mut weights = vec![0f64; nb_components];
+    for (iblock, block) in blocks.iter().enumerate() {
+        for (iel, &label) in labels[iblock].iter().enumerate() {
+            if label < 0 {
+                continue;
+            }
+            let fragment = &mut fragments[label as usize];
+            let id = block.el_num.get(iel).copied().unwrap_or(iel as i32);
+            fragment.elements.push((iblock as u8, id));
+
+            let mass = block.e_mass.get(iel).copied().unwrap_or(0.0) as f64;
+            fragment.mass += mass;
+            // mass weighted centroid, element count weighted without masses
+            let weight = if block.e_mass.is_empty() { 1.0 } else { mass };
+            let nodes = block.nodes(iel);
+            for k in 0..3 {
+                let mean = nodes.iter().map(|&n| anim.coor[3 * n as usize + k] as f64).sum::<f64>()
+                    / nodes.len() as f64;
+                fragment.centroid[k] += weight * mean;
+            }
+            weights[label as usize] += weight;
+        }
+    }
+    for (fragment, &weight) in fragments.iter_mut().zip(&weights) {
+        if weight > 0.0 {
+            for k in 0..3 {
+                fragment.centroid[k] /= weight;
+            }
+        }
+        fragment.elements.sort_unstable();
+    }
+
+    // the largest component is the main body
+    if let Some(main_body) = (0..fragments.len()).max_by_key(|&i| fragments[i].elements.len()) {
+        fragments.remove(main_body);
+    }
+    fragments
+}
+
+fn overlap(a: &[ElementKey], b: &[ElementKey]) -> usize {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+impl DebrisTracker {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "state,time,fragment,nb_elements,mass,x,y,z")?;
+        Ok(DebrisTracker {
+            writer,
+            previous: Vec::new(),
+            next_id: 0,
+            state: 0,
+        })
+    }
+
+    pub fn add_state(&mut self, anim: &AnimFile) -> std::io::Result<()> {
+        let fragments = collect_fragments(anim);
+
+        // greedy matching on the number of shared elements
+        let mut element_owner: HashMap<ElementKey, usize> = HashMap::new();
+        for (iprev, (_, elements)) in self.previous.iter().enumerate() {
+            for &key in elements {
+                element_owner.insert(key, iprev);
+            }
+        }
+        let mut candidates: Vec<(usize, usize, usize)> = Vec::new(); // (overlap, fragment, previous)
+        for (ifrag, fragment) in fragments.iter().enumerate() {
+            let mut seen: Vec<usize> = Vec::new();
+            for key in &fragment.elements {
+                if let Some(&iprev) = element_owner.get(key) {
+                    if !seen.contains(&iprev) {
+                        seen.push(iprev);
+                        candidates.push((overlap(&fragment.elements, &self.previous[iprev].1), ifrag, iprev));
+                    }
+                }
+            }
+        }
+        candidates.sort_unstable_by(|a, b| b.cmp(a));
+        let mut ids: Vec<Option<usize>> = vec![None; fragments.len()];
+        let mut taken = vec![false; self.previous.len()];
+        for (_, ifrag, iprev) in candidates {
+            if ids[ifrag].is_none() && !taken[iprev] {
+                ids[ifrag] = Some(self.previous[iprev].0);
+                taken[iprev] = true;
+            }
+        }
+
+        let mut current = Vec::with_capacity(fragments.len());
+        for (fragment, id) in fragments.into_iter().zip(ids) {
+            let id = id.unwrap_or_else(|| {
+                self.next_id += 1;
+                self.next_id - 1
+            });
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{},{}",
+                self.state,
+                anim.time,
+                id,
+                fragment.elements.len(),
+                fragment.mass,
+                fragment.centroid[0],
+                fragment.centroid[1],
+                fragment.centroid[2]
+            )?;
+            current.push((id, fragment.elements));
+        }
+        self.previous = current;
+        self.state += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{quad_parts, TempDir};
+
+    // main body of 3 shells (part 10), fragments of 1 (20) and 2 shells (30)
+    fn state(time: f32) -> AnimFile {
+        let mut anim = quad_parts(&[("10 Body", 3), ("20 Clip", 1), ("30 Panel", 2)]);
+        anim.time = time;
+        anim.elts_2d.e_mass = vec![1.0; 6];
+        anim
+    }
+
+    #[test]
+    fn fragments_keep_their_id_across_states() {
+        let dir = TempDir::new("debris");
+        let path = dir.file("debris.csv");
+        let mut tracker = DebrisTracker::create(&path).unwrap();
+        tracker.add_state(&state(0.0)).unwrap();
+        // first shell of the panel eroded
+        let mut eroded = state(1.0);
+        eroded.elts_2d.del_elt[4] = 0;
+        tracker.add_state(&eroded).unwrap();
+        tracker.finish().unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "state,time,fragment,nb_elements,mass,x,y,z",
+                "0,0,0,1,1,0.5,2.5,0",
+                "0,0,1,2,2,1,4.5,0",
+                "1,1,0,1,1,0.5,2.5,0",
+                "1,1,1,1,1,1.5,4.5,0",
+            ]
+        );
+    }
+
+    #[test]
+    fn overlap_counts_shared_sorted_elements() {
+        assert_eq!(overlap(&[(1, 3), (1, 5), (2, 1)], &[(1, 5), (2, 1), (2, 7)]), 2);
+        assert_eq!(overlap(&[(1, 3)], &[]), 0);
+    }
+}
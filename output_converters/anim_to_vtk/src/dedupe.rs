@@ -0,0 +1,96 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Duplicate cell detection: cells sharing the same node set
+// ****************************************
+
+use std::collections::HashMap;
+
+use crate::anim::{AnimFile, ElementBlock};
+
+// Keep the first cell of each node set. The kept cell records in
+// DUPLICATE_COUNT how many coincident copies were removed.
+pub fn dedupe_block(block: &ElementBlock) -> (ElementBlock, usize) {
+    let mut first_of: HashMap<Vec<i32>, usize> = HashMap::with_capacity(block.count);
+    let mut kept: Vec<usize> = Vec::with_capacity(block.count);
+    let mut duplicates = vec![0i32; block.count];
+    for iel in 0..block.count {
+        let mut key = block.nodes(iel).to_vec();
+        key.sort_unstable();
+        key.dedup();
+        match first_of.get(&key) {
+            Some(&first) => duplicates[first] += 1,
+            None => {
+                first_of.insert(key, iel);
+                kept.push(iel);
+            }
+        }
+    }
+    let removed = block.count - kept.len();
+    let mut deduped = block.gather(&kept);
+    deduped
+        .int_arrays
+        .push(("DUPLICATE_COUNT".to_string(), kept.iter().map(|&iel| duplicates[iel]).collect()));
+    (deduped, removed)
+}
+
+// returns the number of removed cells
+pub fn dedupe_cells(anim: &mut AnimFile) -> usize {
+    let mut removed = 0;
+    for block in [&mut anim.elts_1d, &mut anim.elts_2d, &mut anim.elts_3d, &mut anim.elts_sph] {
+        let (deduped, nb_removed) = dedupe_block(block);
+        *block = deduped;
+        removed += nb_removed;
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{int_cell_array, legacy_vtk, quad_parts};
+    use crate::vtk::WriteOptions;
+
+    #[test]
+    fn duplicates_across_parts_keep_the_first_cell_and_its_part() {
+        let mut anim = quad_parts(&[("10 Front", 2), ("20 Middle", 1), ("30 Rear", 2)]);
+        // the cell of part 20 is the first one of part 10, nodes rotated
+        let first = anim.elts_2d.nodes(0).to_vec();
+        anim.elts_2d.connect[8..12].copy_from_slice(&[first[2], first[3], first[0], first[1]]);
+        assert_eq!(dedupe_cells(&mut anim), 1);
+
+        assert_eq!(anim.elts_2d.el_num, [101, 102, 104, 105]);
+        assert_eq!(anim.elts_2d.int_arrays, [("DUPLICATE_COUNT".to_string(), vec![1, 0, 0, 0])]);
+        let text = legacy_vtk(&anim, &WriteOptions::default());
+        assert_eq!(int_cell_array(&text, "PART_ID"), [10, 10, 30, 30]);
+    }
+
+    #[test]
+    fn collapsed_nodes_are_one_node_set() {
+        let mut anim = quad_parts(&[("10 Front", 2)]);
+        // triangles in the 4-node layout, last node repeated
+        anim.elts_2d.connect = vec![0, 1, 4, 4, 4, 1, 0, 0];
+        let (deduped, removed) = dedupe_block(&anim.elts_2d);
+        assert_eq!(removed, 1);
+        assert_eq!(deduped.count, 1);
+    }
+}
@@ -0,0 +1,511 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Derived fields: elemental scalars and nodal fields computed from the
+// fields of the A-file, added as if the solver wrote them
+// ****************************************
+
+use std::collections::HashMap;
+
+use report::codes;
+
+use crate::anim::{field_name_matches, read_anim_file_with_budget, AnimFile, ElementBlock, Endian, TextEncoding};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Derived {
+    Invariant(Invariant), // computed from the stress tensor of the element alone
+    EpsDot,               // plastic strain rate since the previous state of the batch
+    Magnitude,            // magnitude of the velocity, acceleration and displacement vectors
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Invariant {
+    I1,          // first invariant, trace of the stress
+    J2,          // second invariant of the deviatoric stress
+    Triaxiality, // mean stress over von Mises stress
+    Lode,        // normalized Lode angle parameter, 1 - 6 theta / pi
+    VonMises,    // von Mises stress, sqrt(3 J2)
+    Principal,   // principal stresses, largest first, and maximum shear
+}
+
+// appended to the name of the array the field is derived from
+const RATE: &str = "RATE";
+const MAGNITUDE: &str = "MAGNITUDE";
+
+impl Derived {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "i1" => Some(Derived::Invariant(Invariant::I1)),
+            "j2" => Some(Derived::Invariant(Invariant::J2)),
+            "triaxiality" => Some(Derived::Invariant(Invariant::Triaxiality)),
+            "lode" => Some(Derived::Invariant(Invariant::Lode)),
+            "von-mises" => Some(Derived::Invariant(Invariant::VonMises)),
+            "principal" => Some(Derived::Invariant(Invariant::Principal)),
+            "epsdot" => Some(Derived::EpsDot),
+            "magnitude" => Some(Derived::Magnitude),
+            _ => None,
+        }
+    }
+
+    pub fn is_invariant(self) -> bool {
+        matches!(self, Derived::Invariant(_))
+    }
+}
+
+impl Invariant {
+    // appended to the tensor name, "Stress" gives 3DELEM_Stress_TRIAXIALITY,
+    // one array per suffix
+    fn suffixes(self) -> &'static [&'static str] {
+        match self {
+            Invariant::I1 => &["I1"],
+            Invariant::J2 => &["J2"],
+            Invariant::Triaxiality => &["TRIAXIALITY"],
+            Invariant::Lode => &["LODE_PARAMETER"],
+            Invariant::VonMises => &["VON_MISES"],
+            Invariant::Principal => &["P1", "P2", "P3", "MAX_SHEAR"],
+        }
+    }
+
+    // also computed from the plane stress tensors of the shells
+    fn applies_to_shells(self) -> bool {
+        matches!(self, Invariant::VonMises | Invariant::Principal)
+    }
+}
+
+// ****************************************
+// Stress invariants of a symmetric tensor [xx, yy, zz, xy, xz, yz]:
+// I1 = trace, s = deviator, J2 = s:s / 2, J3 = det(s),
+// triaxiality = (I1 / 3) / sqrt(3 J2),
+// cos(3 theta) = 3 sqrt(3) / 2 * J3 / J2^(3/2), theta in [0, pi/3],
+// Lode parameter = 1 - 6 theta / pi: 1 in axisymmetric tension,
+// 0 in shear, -1 in axisymmetric compression.
+// Without deviatoric stress, triaxiality and Lode parameter are 0.
+// Principal stresses from the Lode angle, largest first:
+// p_k = I1 / 3 + 2 sqrt(J2 / 3) cos(theta - 2 pi k / 3), k = 0, 1, 2,
+// maximum shear = (p_0 - p_2) / 2.
+// Plane stress tensors [xx, yy, xy] of the shells have zz = xz = yz = 0.
+// ****************************************
+fn invariants(invariant: Invariant, t: &[f32]) -> [f32; 4] {
+    let [xx, yy, zz, xy, xz, yz] = match *t {
+        [xx, yy, xy] => [xx, yy, 0.0, xy, 0.0, 0.0],
+        _ => [t[0], t[1], t[2], t[3], t[4], t[5]],
+    }
+    .map(|v| v as f64);
+    let i1 = xx + yy + zz;
+    let mean = i1 / 3.0;
+    let (sx, sy, sz) = (xx - mean, yy - mean, zz - mean);
+    let j2 = 0.5 * (sx * sx + sy * sy + sz * sz) + xy * xy + xz * xz + yz * yz;
+    // Lode angle theta in [0, pi/3]
+    let theta = || {
+        if j2 > 0.0 {
+            let j3 = sx * (sy * sz - yz * yz) - xy * (xy * sz - yz * xz) + xz * (xy * yz - sy * xz);
+            (1.5 * 3f64.sqrt() * j3 / j2.powf(1.5)).clamp(-1.0, 1.0).acos() / 3.0
+        } else {
+            0.0
+        }
+    };
+    let value = match invariant {
+        Invariant::I1 => i1,
+        Invariant::J2 => j2,
+        Invariant::Triaxiality => {
+            let von_mises = (3.0 * j2).sqrt();
+            if von_mises > 0.0 { mean / von_mises } else { 0.0 }
+        }
+        Invariant::Lode => {
+            if j2 > 0.0 { 1.0 - 6.0 * theta() / std::f64::consts::PI } else { 0.0 }
+        }
+        Invariant::VonMises => (3.0 * j2).sqrt(),
+        Invariant::Principal => {
+            let radius = 2.0 * (j2 / 3.0).sqrt();
+            let theta = theta();
+            let [p1, p2, p3] = [0.0, 1.0, 2.0]
+                .map(|k: f64| mean + radius * (theta - 2.0 * std::f64::consts::PI * k / 3.0).cos());
+            return [p1, p2, p3, 0.5 * (p1 - p3)].map(|v| v as f32);
+        }
+    };
+    [value as f32, 0.0, 0.0, 0.0]
+}
+
+// stress tensors of a block: 6 components, or 3 for the shells, named like a stress
+fn stress_tensors(block: &ElementBlock) -> Vec<usize> {
+    if block.tens_stride != 6 && block.tens_stride != 3 {
+        return Vec::new();
+    }
+    (0..block.tens_text.len()).filter(|&itens| block.tens_text[itens].to_lowercase().contains("stress")).collect()
+}
+
+// adds the derived arrays of every stress tensor of the solids and SPH
+// particles, and of the shells for von Mises and principal stresses,
+// returns the number of arrays added; needs the fields in memory
+pub fn add_derived_fields(anim: &mut AnimFile, derived: &[Derived]) -> usize {
+    let mut added = 0;
+    for (block, shells) in [(&mut anim.elts_2d, true), (&mut anim.elts_3d, false), (&mut anim.elts_sph, false)] {
+        for itens in stress_tensors(block) {
+            let stride = block.tens_stride;
+            let len = stride * block.count;
+            let name = block.tens_text[itens].trim().to_string();
+            let fields = derived.iter().filter_map(|field| match *field {
+                Derived::Invariant(invariant) if !shells || invariant.applies_to_shells() => Some(invariant),
+                _ => None,
+            });
+            for field in fields {
+                let tensor = &block.tens_val[itens * len..(itens + 1) * len];
+                let values: Vec<[f32; 4]> = tensor.chunks_exact(stride).map(|t| invariants(field, t)).collect();
+                for (k, suffix) in field.suffixes().iter().enumerate() {
+                    block.efunc_text.push(format!("{} {}", name, suffix));
+                    block.efunc.extend(values.iter().map(|v| v[k]));
+                    added += 1;
+                }
+            }
+        }
+    }
+    added
+}
+
+// ****************************************
+// Fields across the states of a batch, elements matched by user id
+// with the previous converted state:
+// - plastic strain rate (EPS(t_n) - EPS(t_n-1)) / dt, zero in the first
+//   state and for the elements the previous state doesn't have
+// - running sum of an increment field, starting from the element's
+//   own value when the previous state doesn't have it
+// so that every state of the series has the arrays.
+// ****************************************
+
+const CUMULATIVE: &str = "CUMULATIVE";
+
+// elemental scalars named like a plastic strain ("Plastic strain",
+// "Plastic_strain"), the rates and sums added by this module aside
+fn plastic_strains(block: &ElementBlock) -> Vec<usize> {
+    (0..block.efunc_text.len())
+        .filter(|&ifunc| {
+            let name = block.efunc_text[ifunc].trim().to_lowercase().replace('_', " ");
+            name.contains("plastic strain") && !name.ends_with(" rate") && !is_cumulative(&block.efunc_text[ifunc])
+        })
+        .collect()
+}
+
+fn element_id(block: &ElementBlock, iel: usize) -> i32 {
+    block.el_num.get(iel).copied().unwrap_or(iel as i32)
+}
+
+fn blocks(anim: &AnimFile) -> [&ElementBlock; 4] {
+    [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph]
+}
+
+// arrays of a converted state the next one derives fields from: plastic
+// strains and running sums, kept by the batch
+pub struct StateHistory {
+    time: f32,
+    values: HashMap<(usize, String), HashMap<i32, f32>>, // (block, array) -> element id -> value
+}
+
+impl StateHistory {
+    pub fn capture(anim: &AnimFile) -> Self {
+        let mut values = HashMap::new();
+        for (iblock, block) in blocks(anim).into_iter().enumerate() {
+            let sums = (0..block.efunc_text.len()).filter(|&ifunc| is_cumulative(&block.efunc_text[ifunc]));
+            for ifunc in plastic_strains(block).into_iter().chain(sums) {
+                let field = &block.efunc[ifunc * block.count..(ifunc + 1) * block.count];
+                let by_id = (0..block.count).map(|iel| (element_id(block, iel), field[iel])).collect();
+                values.insert((iblock, block.efunc_text[ifunc].trim().to_string()), by_id);
+            }
+        }
+        StateHistory { time: anim.time, values }
+    }
+
+    fn values(&self, iblock: usize, name: &str) -> Option<&HashMap<i32, f32>> {
+        self.values.get(&(iblock, name.to_string()))
+    }
+}
+
+// adds the rate of every plastic strain array, returns the number of arrays
+// added; needs the fields in memory
+pub fn add_rate_fields(anim: &mut AnimFile, previous: Option<&StateHistory>) -> usize {
+    let mut dt = previous.map(|previous| anim.time - previous.time);
+    if let Some(step) = dt.filter(|&step| step <= 0.0) {
+        report::warning(
+            codes::TIME_ORDER,
+            format_args!("time step {} since the previous state, plastic strain rates set to 0", step),
+        );
+        dt = None;
+    }
+    let mut added = 0;
+    for (iblock, block) in [&mut anim.elts_1d, &mut anim.elts_2d, &mut anim.elts_3d, &mut anim.elts_sph]
+        .into_iter()
+        .enumerate()
+    {
+        for ifunc in plastic_strains(block) {
+            let name = block.efunc_text[ifunc].trim().to_string();
+            let before = previous.zip(dt).and_then(|(previous, dt)| previous.values(iblock, &name).map(|values| (values, dt)));
+            let rates: Vec<f32> = (0..block.count)
+                .map(|iel| {
+                    let value = block.efunc[ifunc * block.count + iel];
+                    match before.and_then(|(values, dt)| values.get(&element_id(block, iel)).map(|v| (v, dt))) {
+                        Some((before, dt)) => (value - before) / dt,
+                        None => 0.0,
+                    }
+                })
+                .collect();
+            block.efunc_text.push(format!("{} {}", name, RATE));
+            block.efunc.extend_from_slice(&rates);
+            added += 1;
+        }
+    }
+    added
+}
+
+fn is_cumulative(name: &str) -> bool {
+    name.trim().ends_with(&format!(" {}", CUMULATIVE))
+}
+
+// adds the running sum of the elemental scalars matching the names, returns
+// the number of arrays added; needs the fields in memory
+pub fn add_cumulative_fields(anim: &mut AnimFile, names: &[String], previous: Option<&StateHistory>) -> usize {
+    let mut added = 0;
+    for (iblock, block) in [&mut anim.elts_1d, &mut anim.elts_2d, &mut anim.elts_3d, &mut anim.elts_sph]
+        .into_iter()
+        .enumerate()
+    {
+        let increments: Vec<usize> = (0..block.efunc_text.len())
+            .filter(|&ifunc| names.iter().any(|name| field_name_matches(&block.efunc_text[ifunc], name)))
+            .collect();
+        for ifunc in increments {
+            let name = format!("{} {}", block.efunc_text[ifunc].trim(), CUMULATIVE);
+            let before = previous.and_then(|previous| previous.values(iblock, &name));
+            let sums: Vec<f32> = (0..block.count)
+                .map(|iel| {
+                    let value = block.efunc[ifunc * block.count + iel];
+                    value + before.and_then(|values| values.get(&element_id(block, iel))).copied().unwrap_or(0.0)
+                })
+                .collect();
+            block.efunc_text.push(name);
+            block.efunc.extend_from_slice(&sums);
+            added += 1;
+        }
+    }
+    added
+}
+
+// ****************************************
+// Nodal fields:
+// - displacement from a reference state, usually the first A-file of
+//   the run: current minus reference coordinates, nodes matched by
+//   NODE_ID (by index in files without user ids, which then need the
+//   same number of nodes)
+// - magnitude of every nodal vector named like a velocity, an
+//   acceleration or a displacement, the computed one included
+// ****************************************
+
+pub const DISPLACEMENT: &str = "DISPLACEMENT";
+
+// coordinates of the reference state
+pub struct ReferenceState {
+    coor: Vec<f32>,
+    index: HashMap<i32, usize>, // node user id -> node index, empty without ids
+}
+
+impl ReferenceState {
+    pub fn read(file_name: &str, encoding: TextEncoding, endian: Option<Endian>) -> Result<Self, String> {
+        // the fields are left on disk, only the coordinates are used
+        let anim = read_anim_file_with_budget(file_name, Some(0), encoding, endian).map_err(|e| e.to_string())?;
+        let index = anim.nod_num.iter().enumerate().map(|(inod, &id)| (id, inod)).collect();
+        Ok(ReferenceState { coor: anim.coor, index })
+    }
+
+    // nodes matched by index, without user ids in one of the files
+    fn by_index(&self, anim: &AnimFile) -> bool {
+        self.index.is_empty() || anim.nod_num.len() < anim.nb_nodes
+    }
+
+    // index of the node in the reference
+    fn node(&self, anim: &AnimFile, inod: usize) -> Option<usize> {
+        match anim.nod_num.get(inod) {
+            Some(id) if !self.index.is_empty() => self.index.get(id).copied(),
+            _ => (3 * inod < self.coor.len()).then_some(inod),
+        }
+    }
+}
+
+// adds the DISPLACEMENT vector, returns the number of nodes not in the
+// reference, whose displacement is 0; needs the fields in memory
+pub fn add_displacement(anim: &mut AnimFile, reference: &ReferenceState) -> Result<usize, String> {
+    let nb_reference = reference.coor.len() / 3;
+    if reference.by_index(anim) && nb_reference != anim.nb_nodes {
+        return Err(format!(
+            "{} nodes and {} in the reference state, without node ids to match them for DISPLACEMENT",
+            anim.nb_nodes, nb_reference
+        ));
+    }
+    let mut missing = 0;
+    let mut displacement = vec![0.0; 3 * anim.nb_nodes];
+    for inod in 0..anim.nb_nodes {
+        match reference.node(anim, inod) {
+            Some(iref) => {
+                for k in 0..3 {
+                    displacement[3 * inod + k] = anim.coor[3 * inod + k] - reference.coor[3 * iref + k];
+                }
+            }
+            None => missing += 1,
+        }
+    }
+    anim.vect_text.push(DISPLACEMENT.to_string());
+    anim.vect_val.extend_from_slice(&displacement);
+    Ok(missing)
+}
+
+fn has_magnitude(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["velocity", "acceleration", "displacement"].iter().any(|kind| name.contains(kind))
+}
+
+// adds the magnitude of the velocity, acceleration and displacement
+// vectors as nodal scalars, returns the number of arrays added; needs
+// the fields in memory
+pub fn add_magnitudes(anim: &mut AnimFile) -> usize {
+    let mut added = 0;
+    let len = 3 * anim.nb_nodes;
+    for ivect in (0..anim.vect_text.len()).filter(|&ivect| has_magnitude(&anim.vect_text[ivect])) {
+        let vector = &anim.vect_val[ivect * len..(ivect + 1) * len];
+        let magnitudes: Vec<f32> = vector.chunks_exact(3).map(|v| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()).collect();
+        anim.func_text.push(format!("{} {}", anim.vect_text[ivect].trim(), MAGNITUDE));
+        anim.func.extend_from_slice(&magnitudes);
+        added += 1;
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{AnimBuilder, TempDir};
+
+    fn assert_close(actual: &[f32], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((*a as f64 - e).abs() <= 1e-4 * e.abs().max(1.0), "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn scalar(invariant: Invariant, t: &[f32]) -> f32 {
+        invariants(invariant, t)[0]
+    }
+
+    #[test]
+    fn uniaxial_stress() {
+        let tension = [100.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        assert_close(&[scalar(Invariant::I1, &tension)], &[100.0]);
+        assert_close(&[scalar(Invariant::J2, &tension)], &[10000.0 / 3.0]);
+        assert_close(&[scalar(Invariant::VonMises, &tension)], &[100.0]);
+        assert_close(&[scalar(Invariant::Triaxiality, &tension)], &[1.0 / 3.0]);
+        assert_close(&[scalar(Invariant::Lode, &tension)], &[1.0]);
+        assert_close(&invariants(Invariant::Principal, &tension), &[100.0, 0.0, 0.0, 50.0]);
+
+        // along z, and in compression
+        let compression = [0.0, 0.0, -100.0, 0.0, 0.0, 0.0];
+        assert_close(&[scalar(Invariant::VonMises, &compression)], &[100.0]);
+        assert_close(&[scalar(Invariant::Triaxiality, &compression)], &[-1.0 / 3.0]);
+        assert_close(&[scalar(Invariant::Lode, &compression)], &[-1.0]);
+        assert_close(&invariants(Invariant::Principal, &compression), &[0.0, 0.0, -100.0, 50.0]);
+
+        // plane stress of the shells
+        assert_close(&[scalar(Invariant::VonMises, &[100.0, 0.0, 0.0])], &[100.0]);
+        assert_close(&invariants(Invariant::Principal, &[0.0, 100.0, 0.0]), &[100.0, 0.0, 0.0, 50.0]);
+    }
+
+    #[test]
+    fn pure_shear() {
+        for shear in [[0.0, 0.0, 0.0, 50.0, 0.0, 0.0], [0.0, 0.0, 0.0, 0.0, 0.0, -50.0]] {
+            assert_close(&[scalar(Invariant::I1, &shear)], &[0.0]);
+            assert_close(&[scalar(Invariant::J2, &shear)], &[2500.0]);
+            assert_close(&[scalar(Invariant::VonMises, &shear)], &[50.0 * 3f64.sqrt()]);
+            assert_close(&[scalar(Invariant::Triaxiality, &shear)], &[0.0]);
+            assert_close(&[scalar(Invariant::Lode, &shear)], &[0.0]);
+            assert_close(&invariants(Invariant::Principal, &shear), &[50.0, 0.0, -50.0, 50.0]);
+        }
+        assert_close(&invariants(Invariant::Principal, &[0.0, 0.0, 50.0]), &[50.0, 0.0, -50.0, 50.0]);
+    }
+
+    #[test]
+    fn hydrostatic_stress() {
+        let pressure = [-30.0, -30.0, -30.0, 0.0, 0.0, 0.0];
+        assert_close(&[scalar(Invariant::I1, &pressure)], &[-90.0]);
+        assert_close(&[scalar(Invariant::J2, &pressure)], &[0.0]);
+        assert_close(&[scalar(Invariant::VonMises, &pressure)], &[0.0]);
+        // no deviatoric stress: 0 rather than infinite or undefined
+        assert_close(&[scalar(Invariant::Triaxiality, &pressure)], &[0.0]);
+        assert_close(&[scalar(Invariant::Lode, &pressure)], &[0.0]);
+        assert_close(&invariants(Invariant::Principal, &pressure), &[-30.0, -30.0, -30.0, 0.0]);
+    }
+
+    // the reference <reference> and the state <state>, read from A-files
+    // of their nodes, with user ids unless the ids are empty
+    fn states(name: &str, reference: (&[f32], &[i32]), state: (&[f32], &[i32])) -> (ReferenceState, AnimFile) {
+        let dir = TempDir::new(name);
+        let (first, second) = (dir.file("runA001"), dir.file("runA002"));
+        AnimBuilder::new(0.0).nodes(reference.0).node_ids(reference.1).write(&first);
+        AnimBuilder::new(0.0).nodes(state.0).node_ids(state.1).write(&second);
+        let reference = ReferenceState::read(&first, TextEncoding::default(), None).unwrap();
+        let anim = read_anim_file_with_budget(&second, None, TextEncoding::default(), None).unwrap();
+        (reference, anim)
+    }
+
+    #[test]
+    fn magnitude_of_the_vectors_named_like_a_velocity() {
+        let mut anim = AnimFile { nb_nodes: 2, ..Default::default() };
+        anim.vect_text = vec!["Velocity".to_string(), "Normal".to_string()];
+        anim.vect_val = vec![3.0, 4.0, 0.0, 0.0, 0.0, -2.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+        assert_eq!(add_magnitudes(&mut anim), 1);
+        assert_eq!(anim.func_text, ["Velocity MAGNITUDE"]);
+        assert_close(&anim.func, &[5.0, 2.0]);
+    }
+
+    #[test]
+    fn displacement_of_the_nodes_matched_by_id() {
+        let reference = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        let state = [1.5, 0.0, 0.0, 0.0, 0.25, 0.0, 9.0, 9.0, 9.0];
+        let (reference, mut anim) = states("displacement_ids", (&reference, &[10, 20]), (&state, &[20, 10, 30]));
+        assert_eq!(add_displacement(&mut anim, &reference), Ok(1));
+        assert_eq!(anim.vect_text, [DISPLACEMENT]);
+        // node 30 is not in the reference
+        assert_close(&anim.vect_val, &[0.5, 0.0, 0.0, 0.0, 0.25, 0.0, 0.0, 0.0, 0.0]);
+
+        assert_eq!(add_magnitudes(&mut anim), 1);
+        assert_close(&anim.func, &[0.5, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn displacement_without_ids_needs_the_same_nodes() {
+        let reference = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        let (first, mut anim) = states("displacement_index", (&reference, &[]), (&[0.0, 0.0, 2.0, 1.0, 1.0, 0.0], &[]));
+        assert_eq!(add_displacement(&mut anim, &first), Ok(0));
+        assert_close(&anim.vect_val, &[0.0, 0.0, 2.0, 0.0, 1.0, 0.0]);
+
+        // ids in the state only: matched by index too
+        let (first, mut anim) = states("displacement_count", (&reference, &[]), (&[0.0; 9], &[1, 2, 3]));
+        assert_eq!(
+            add_displacement(&mut anim, &first),
+            Err("3 nodes and 2 in the reference state, without node ids to match them for DISPLACEMENT".to_string())
+        );
+        assert!(anim.vect_text.is_empty());
+    }
+}
@@ -0,0 +1,559 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// EnSight Gold output (--format ensight). Every state gives a geometry
+// file, <input>.geo, and one file per variable, <input>.<variable>:
+// nodal scalars and vectors, and the elemental scalars and symmetric
+// tensors of each element type, defined on the parts of that type only.
+// The active elements of a Radioss part make an EnSight part, numbered
+// from 1 in cell order (beams, shells, solids, SPH); eroded elements are
+// left out, so that the geometry changes from state to state.
+// ASCII, or C Binary with --binary.
+// The case files are written at the end of the run: the states whose
+// input names share their prefix and the width of their trailing number
+// (runA001, runA002, ...) form one time series, <prefix>.case (runA.case).
+// ****************************************
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::anim::AnimFile;
+use crate::cells;
+use crate::mesh_writer::{array_name_matches, write_mesh, Buffered, FieldKind, MeshBuffer, MeshOutput};
+use crate::vtk::WriteOptions;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum VariableKind {
+    NodalScalar,
+    NodalVector,
+    ElementScalar,
+    ElementTensor,
+}
+
+impl VariableKind {
+    fn case_keyword(self) -> &'static str {
+        match self {
+            VariableKind::NodalScalar => "scalar per node",
+            VariableKind::NodalVector => "vector per node",
+            VariableKind::ElementScalar => "scalar per element",
+            VariableKind::ElementTensor => "tensor symm per element",
+        }
+    }
+}
+
+// variable of a state, its EnSight name is the description and the file
+// extension
+#[derive(Clone, PartialEq)]
+pub struct Variable {
+    pub kind: VariableKind,
+    pub name: String,
+}
+
+// ****************************************
+// Parts of a state
+// ****************************************
+struct Section {
+    element_type: &'static str,
+    elements: Vec<usize>,   // elements of the block
+    connectivity: Vec<i32>, // nodes numbered in the part from 1
+}
+
+struct Part<'a> {
+    block: usize,           // 0 beams, 1 shells, 2 solids, 3 SPH
+    description: &'a str,
+    nodes: Vec<usize>,      // nodes of the part, in part numbering order
+    sections: Vec<Section>, // elements grouped by EnSight element type
+}
+
+fn element_type(cell_type: i32) -> &'static str {
+    match cell_type {
+        cells::VTK_VERTEX => "point",
+        cells::VTK_LINE => "bar2",
+        cells::VTK_TRIANGLE => "tria3",
+        cells::VTK_QUAD => "quad4",
+        cells::VTK_TETRA => "tetra4",
+        _ => "hexa8",
+    }
+}
+
+// the active elements of every part of the blocks
+fn state_parts(mesh: &MeshBuffer) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    // nodes in the part numbering, zero outside the part
+    let mut local = vec![0i32; mesh.coor.len() / 3];
+    for iblock in 0..4 {
+        for (description, elements) in mesh.parts(iblock) {
+            let mut part = Part { block: iblock, description, nodes: Vec::new(), sections: Vec::new() };
+            for iel in elements {
+                let (cell_type, nodes) = mesh.cell(iblock, iel);
+                let element_type = element_type(cell_type);
+                let isection = match part.sections.iter().position(|section| section.element_type == element_type) {
+                    Some(isection) => isection,
+                    None => {
+                        part.sections.push(Section { element_type, elements: Vec::new(), connectivity: Vec::new() });
+                        part.sections.len() - 1
+                    }
+                };
+                let section = &mut part.sections[isection];
+                section.elements.push(iel);
+                // linear triangles repeat their last node
+                for &node in &nodes[..nodes_per_element(element_type)] {
+                    let node = node as usize;
+                    if local[node] == 0 {
+                        part.nodes.push(node);
+                        local[node] = part.nodes.len() as i32;
+                    }
+                    section.connectivity.push(local[node]);
+                }
+            }
+            for &node in &part.nodes {
+                local[node] = 0;
+            }
+            parts.push(part);
+        }
+    }
+    parts
+}
+
+// ****************************************
+// EnsightFile - ASCII or C Binary content of a geometry or variable file
+// ****************************************
+struct EnsightFile {
+    out: BufWriter<File>,
+    binary: bool,
+}
+
+// C "%12.5e"
+fn scientific(value: f32) -> String {
+    let text = format!("{:.5e}", value);
+    match text.split_once('e') {
+        Some((mantissa, exponent)) => {
+            let exponent: i32 = exponent.parse().unwrap_or(0);
+            let sign = if exponent < 0 { '-' } else { '+' };
+            format!("{:>12}", format!("{}e{}{:02}", mantissa, sign, exponent.abs()))
+        }
+        None => format!("{:>12}", text),
+    }
+}
+
+impl EnsightFile {
+    fn create(path: &str, binary: bool) -> io::Result<Self> {
+        Ok(EnsightFile { out: BufWriter::new(File::create(path)?), binary })
+    }
+
+    // lines of at most 79 characters, 80 bytes in binary
+    fn text(&mut self, line: &str) -> io::Result<()> {
+        let line: String = line.chars().filter(char::is_ascii).take(79).collect();
+        if self.binary {
+            let mut record = [0u8; 80];
+            record[..line.len()].copy_from_slice(line.as_bytes());
+            self.out.write_all(&record)
+        } else {
+            writeln!(self.out, "{}", line)
+        }
+    }
+
+    fn int(&mut self, value: i32) -> io::Result<()> {
+        if self.binary {
+            self.out.write_all(&value.to_le_bytes())
+        } else {
+            writeln!(self.out, "{:>10}", value)
+        }
+    }
+
+    fn ints(&mut self, values: impl Iterator<Item = i32>) -> io::Result<()> {
+        for value in values {
+            self.int(value)?;
+        }
+        Ok(())
+    }
+
+    // elements of `width` nodes, one per line in ASCII
+    fn connectivity(&mut self, values: &[i32], width: usize) -> io::Result<()> {
+        if self.binary {
+            return self.ints(values.iter().copied());
+        }
+        for element in values.chunks_exact(width) {
+            for value in element {
+                write!(self.out, "{:>10}", value)?;
+            }
+            self.out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn floats(&mut self, values: impl Iterator<Item = f32>) -> io::Result<()> {
+        for value in values {
+            if self.binary {
+                self.out.write_all(&value.to_le_bytes())?;
+            } else {
+                writeln!(self.out, "{}", scientific(value))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn nodes_per_element(element_type: &str) -> usize {
+    match element_type {
+        "point" => 1,
+        "bar2" => 2,
+        "tria3" => 3,
+        "quad4" | "tetra4" => 4,
+        _ => 8,
+    }
+}
+
+fn write_geometry(path: &str, title: &str, mesh: &MeshBuffer, parts: &[Part], binary: bool) -> io::Result<()> {
+    let node_ids = !mesh.node_ids.is_empty();
+    let element_ids = (0..4).all(|iblock| mesh.element_ids(iblock).is_some());
+
+    let mut file = EnsightFile::create(path, binary)?;
+    if binary {
+        file.text("C Binary")?;
+    }
+    file.text(title)?;
+    file.text(&format!("TIME {}", mesh.time as f32))?;
+    file.text(if node_ids { "node id given" } else { "node id assign" })?;
+    file.text(if element_ids { "element id given" } else { "element id assign" })?;
+    for (ipart, part) in parts.iter().enumerate() {
+        file.text("part")?;
+        file.int(ipart as i32 + 1)?;
+        file.text(part.description)?;
+        file.text("coordinates")?;
+        file.int(part.nodes.len() as i32)?;
+        if node_ids {
+            file.ints(part.nodes.iter().map(|&node| mesh.node_ids[node]))?;
+        }
+        for k in 0..3 {
+            file.floats(part.nodes.iter().map(|&node| mesh.coor[3 * node + k]))?;
+        }
+        for section in &part.sections {
+            file.text(section.element_type)?;
+            file.int(section.elements.len() as i32)?;
+            if element_ids {
+                let ids = mesh.element_ids(part.block).unwrap_or_default();
+                file.ints(section.elements.iter().map(|&iel| ids[iel]))?;
+            }
+            file.connectivity(&section.connectivity, nodes_per_element(section.element_type))?;
+        }
+    }
+    file.finish()
+}
+
+fn write_nodal(path: &str, name: &str, parts: &[Part], values: &[f32], width: usize, binary: bool) -> io::Result<()> {
+    let mut file = EnsightFile::create(path, binary)?;
+    file.text(name)?;
+    for (ipart, part) in parts.iter().enumerate() {
+        file.text("part")?;
+        file.int(ipart as i32 + 1)?;
+        file.text("coordinates")?;
+        for k in 0..width {
+            file.floats(part.nodes.iter().map(|&node| values[width * node + k]))?;
+        }
+    }
+    file.finish()
+}
+
+// components of the elements, written on the parts of the blocks with the field
+fn write_elemental(
+    path: &str,
+    name: &str,
+    parts: &[Part],
+    blocks: &[Option<Vec<f32>>],
+    components: usize,
+    value: impl Fn(&[f32], usize, usize) -> f32,
+    binary: bool,
+) -> io::Result<()> {
+    let mut file = EnsightFile::create(path, binary)?;
+    file.text(name)?;
+    for (ipart, part) in parts.iter().enumerate() {
+        let Some(values) = &blocks[part.block] else {
+            continue;
+        };
+        file.text("part")?;
+        file.int(ipart as i32 + 1)?;
+        for section in &part.sections {
+            file.text(section.element_type)?;
+            for k in 0..components {
+                file.floats(section.elements.iter().map(|&iel| value(values, iel, k)))?;
+            }
+        }
+    }
+    file.finish()
+}
+
+// symmetric tensor components in the EnSight order, 11 22 33 12 13 23,
+// out of the 3x3 components
+const SYMMETRIC: [usize; 6] = [0, 4, 8, 1, 2, 5];
+
+// error of a file, with its name
+fn in_file(path: &str) -> impl FnOnce(io::Error) -> io::Error + '_ {
+    move |e| io::Error::new(e.kind(), format!("{}: {}", path, e))
+}
+
+// ****************************************
+// EnsightOutput - geometry and variable files of a state, from the
+// mesh write_mesh gives
+// ****************************************
+struct EnsightOutput {
+    file_name: String,
+    title: String,
+    fields: Vec<String>, // arrays to write, all when empty
+    binary: bool,
+    outputs: Vec<String>,
+    variables: Vec<Variable>,
+}
+
+// EnSight name of an array: letters, digits and `_` only, the others
+// (blanks, `(`, `)`, `.`, `+`, `-`, ...) becoming `_`, with a `V` in
+// front of a leading digit (`2DELEM_Stress_(upper)` gives
+// `V2DELEM_Stress__upper_`)
+fn variable_name(name: &str) -> String {
+    let mut variable: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    if !variable.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        variable.insert(0, 'V');
+    }
+    variable
+}
+
+impl EnsightOutput {
+    // file and EnSight name of a variable, added to the outputs; names
+    // that become the same are told apart by a number
+    fn variable(&mut self, kind: VariableKind, name: &str) -> (String, String) {
+        let base = variable_name(name);
+        let mut name = base.clone();
+        let mut count = 1;
+        while self.variables.iter().any(|variable| variable.name == name) {
+            count += 1;
+            name = format!("{}_{}", base, count);
+        }
+        let path = format!("{}.{}", self.file_name, name);
+        self.outputs.push(path.clone());
+        self.variables.push(Variable { kind, name: name.clone() });
+        (path, name)
+    }
+}
+
+impl MeshOutput for EnsightOutput {
+    fn keeps(&self, name: &str) -> bool {
+        self.fields.is_empty() || self.fields.iter().any(|field| array_name_matches(name, field))
+    }
+
+    fn write(&mut self, mesh: &MeshBuffer) -> io::Result<()> {
+        let parts = state_parts(mesh);
+        let binary = self.binary;
+        let geometry = format!("{}.geo", self.file_name);
+        self.outputs.push(geometry.clone());
+        write_geometry(&geometry, &self.title, mesh, &parts, binary).map_err(in_file(&geometry))?;
+
+        for (name, kind, values) in &mesh.point_fields {
+            let (kind, width) = match kind {
+                FieldKind::Scalar => (VariableKind::NodalScalar, 1),
+                FieldKind::Vector => (VariableKind::NodalVector, 3),
+                FieldKind::Tensor | FieldKind::Color => continue,
+            };
+            let (path, name) = self.variable(kind, name);
+            write_nodal(&path, &name, &parts, values, width, binary).map_err(in_file(&path))?;
+        }
+        // 1D torseurs come as one scalar per component
+        for (name, kind, blocks) in &mesh.cell_fields {
+            let (path, written) = match kind {
+                FieldKind::Scalar => {
+                    let (path, name) = self.variable(VariableKind::ElementScalar, name);
+                    let written = write_elemental(&path, &name, &parts, blocks, 1, |values, iel, _| values[iel], binary);
+                    (path, written)
+                }
+                FieldKind::Tensor => {
+                    let (path, name) = self.variable(VariableKind::ElementTensor, name);
+                    let component = |values: &[f32], iel: usize, k: usize| values[9 * iel + SYMMETRIC[k]];
+                    let written = write_elemental(&path, &name, &parts, blocks, 6, component, binary);
+                    (path, written)
+                }
+                FieldKind::Vector | FieldKind::Color => continue,
+            };
+            written.map_err(in_file(&path))?;
+        }
+        Ok(())
+    }
+}
+
+// writes the files of a state, returns them (geometry first) and the
+// variables they hold
+pub fn write_state(
+    anim: &AnimFile,
+    file_name: &str,
+    fields: &[String],
+    options: &WriteOptions,
+) -> Result<(Vec<String>, Vec<Variable>), String> {
+    let mut writer = Buffered::new(EnsightOutput {
+        file_name: file_name.to_string(),
+        title: anim.radioss_run_text.trim().to_string(),
+        fields: fields.to_vec(),
+        binary: options.binary,
+        outputs: Vec::new(),
+        variables: Vec::new(),
+    });
+    let result = write_mesh(anim, options, &mut writer);
+    let EnsightOutput { outputs, variables, .. } = writer.output;
+    match result {
+        Ok(()) => Ok((outputs, variables)),
+        Err(e) => {
+            for output in &outputs {
+                let _ = fs::remove_file(output);
+            }
+            Err(format!("can't write {}", e))
+        }
+    }
+}
+
+// ****************************************
+// Case files of the run, one per time series of input names
+// ****************************************
+struct Series {
+    base: String,             // input name without its trailing number
+    width: usize,             // digits of the trailing number
+    steps: Vec<(u64, f32)>,   // file number and time of the states
+    variables: Vec<Variable>, // variables of every state of the series
+}
+
+#[derive(Default)]
+pub struct Case {
+    series: Vec<Series>,
+}
+
+impl Case {
+    pub fn add_state(&mut self, file_name: &str, time: f32, variables: &[Variable]) {
+        let base = file_name.trim_end_matches(|c: char| c.is_ascii_digit());
+        let (base, number) = match file_name[base.len()..].parse::<u64>() {
+            Ok(number) => (base, number),
+            Err(_) => (file_name, 0),
+        };
+        let width = file_name.len() - base.len();
+        match self.series.iter_mut().find(|series| series.base == base && series.width == width) {
+            Some(series) => {
+                if series.steps.iter().all(|&(step, _)| step != number) {
+                    series.steps.push((number, time));
+                    series.variables.retain(|variable| variables.contains(variable));
+                }
+            }
+            None => self.series.push(Series {
+                base: base.to_string(),
+                width,
+                steps: vec![(number, time)],
+                variables: variables.to_vec(),
+            }),
+        }
+    }
+
+    // written to temporary files then renamed, as the report
+    pub fn finish(self) -> io::Result<()> {
+        for series in &self.series {
+            let name = Path::new(&series.base).file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+            let pattern = format!("{}{}", name, "*".repeat(series.width));
+
+            let mut case = String::from("FORMAT\ntype: ensight gold\n\nGEOMETRY\n");
+            case.push_str(&format!("model: 1 {}.geo\n", pattern));
+            if !series.variables.is_empty() {
+                case.push_str("\nVARIABLE\n");
+                for variable in &series.variables {
+                    let keyword = variable.kind.case_keyword();
+                    case.push_str(&format!("{}: 1 {} {}.{}\n", keyword, variable.name, pattern, variable.name));
+                }
+            }
+            case.push_str(&format!("\nTIME\ntime set: 1\nnumber of steps: {}\n", series.steps.len()));
+            case.push_str("filename numbers:\n");
+            for (number, _) in &series.steps {
+                case.push_str(&format!("{}\n", number));
+            }
+            case.push_str("time values:\n");
+            for (_, time) in &series.steps {
+                case.push_str(&format!("{}\n", time));
+            }
+
+            let path = format!("{}.case", series.base);
+            let temporary = format!("{}.tmp", path);
+            fs::write(&temporary, case)?;
+            fs::rename(&temporary, &path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::TempDir;
+
+    fn output() -> EnsightOutput {
+        EnsightOutput {
+            file_name: "runA001".to_string(),
+            title: String::new(),
+            fields: Vec::new(),
+            binary: false,
+            outputs: Vec::new(),
+            variables: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn variable_names_are_sanitized() {
+        assert_eq!(variable_name("Stress_(upper)"), "Stress__upper_");
+        assert_eq!(variable_name("2DELEM_Stress_(upper)"), "V2DELEM_Stress__upper_");
+        assert_eq!(variable_name("Von Mises"), "Von_Mises");
+        assert_eq!(variable_name("1DELEM_ForceF1"), "V1DELEM_ForceF1");
+        assert_eq!(variable_name("DISPLACEMENT"), "DISPLACEMENT");
+    }
+
+    #[test]
+    fn variable_files_take_the_sanitized_name() {
+        let mut output = output();
+        let (path, name) = output.variable(VariableKind::ElementTensor, "2DELEM_Stress_(upper)");
+        assert_eq!(name, "V2DELEM_Stress__upper_");
+        assert_eq!(path, "runA001.V2DELEM_Stress__upper_");
+        // same EnSight name for another array
+        let (path, name) = output.variable(VariableKind::ElementTensor, "2DELEM_Stress_[upper]");
+        assert_eq!(name, "V2DELEM_Stress__upper__2");
+        assert_eq!(path, "runA001.V2DELEM_Stress__upper__2");
+        assert_eq!(output.outputs.len(), 2);
+        assert_eq!(output.variables[0].name, "V2DELEM_Stress__upper_");
+    }
+
+    #[test]
+    fn case_entries_take_the_sanitized_name() {
+        let dir = TempDir::new("ensight_case");
+        let file_name = dir.file("runA001");
+        let mut output = output();
+        output.file_name = file_name.clone();
+        output.variable(VariableKind::ElementTensor, "2DELEM_Stress_(upper)");
+        let mut case = Case::default();
+        case.add_state(&file_name, 0.0, &output.variables);
+        case.finish().unwrap();
+        let text = fs::read_to_string(dir.file("runA.case")).unwrap();
+        assert!(text.contains("tensor symm per element: 1 V2DELEM_Stress__upper_ runA***.V2DELEM_Stress__upper_\n"), "{}", text);
+    }
+}
@@ -0,0 +1,145 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Intermediate frames: between two converted states, extra outputs
+// are interpolated linearly in time. Nodes and elements are matched
+// by id; the frames keep the mesh and erosion status of the earlier
+// state, and its values where the later state has no match.
+// ****************************************
+
+use std::collections::HashMap;
+
+use report::codes;
+
+use crate::anim::{AnimFile, ElementBlock};
+use crate::vtk::{write_vtk_file, WriteOptions};
+
+pub struct FrameInterpolator {
+    frames: usize, // frames between two states
+    options: WriteOptions,
+    previous: Option<(String, AnimFile)>, // last converted input and its state
+}
+
+impl FrameInterpolator {
+    pub fn new(frames: usize, options: &WriteOptions) -> Self {
+        FrameInterpolator { frames, options: options.clone(), previous: None }
+    }
+
+    // writes the frames between the previous state and this one, returns
+    // the time and file of each frame written; keeps this state for the next ones
+    pub fn add_state(&mut self, file_name: &str, anim: AnimFile) -> Vec<(f32, String)> {
+        let mut written = Vec::new();
+        if let Some((previous_name, previous)) = &self.previous {
+            report_line!("Interpolating {} frames between {} and {}", self.frames, previous_name, file_name);
+            let node_rows = matches(&ids(&previous.nod_num, previous.nb_nodes), &ids(&anim.nod_num, anim.nb_nodes));
+            let element_rows: Vec<Vec<Option<usize>>> = blocks(previous)
+                .into_iter()
+                .zip(blocks(&anim))
+                .map(|(before, after)| matches(&ids(&before.el_num, before.count), &ids(&after.el_num, after.count)))
+                .collect();
+            for iframe in 1..=self.frames {
+                let s = iframe as f32 / (self.frames + 1) as f32;
+                let frame = interpolate(previous, &anim, &node_rows, &element_rows, s);
+                let output_file_name = format!("{}_frame{}.vtk", previous_name, iframe);
+                match write_vtk_file(&frame, &self.options, &output_file_name) {
+                    Ok(()) => written.push((frame.time, output_file_name)),
+                    Err(e) => report::error(codes::WRITE_FAILED, e),
+                }
+            }
+        }
+        self.previous = Some((file_name.to_string(), anim));
+        written
+    }
+}
+
+fn blocks(anim: &AnimFile) -> [&ElementBlock; 4] {
+    [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph]
+}
+
+// user ids, the indices without numbering
+fn ids(numbering: &[i32], count: usize) -> Vec<i32> {
+    (0..count).map(|i| numbering.get(i).copied().unwrap_or(i as i32)).collect()
+}
+
+// row of each id of the earlier state in the later one
+fn matches(before: &[i32], after: &[i32]) -> Vec<Option<usize>> {
+    let rows: HashMap<i32, usize> = after.iter().enumerate().map(|(row, &id)| (id, row)).collect();
+    before.iter().map(|id| rows.get(id).copied()).collect()
+}
+
+// blends every array of <ncomp> values per row towards its match in the later state
+fn blend(values: &mut [f32], after: &[f32], rows: &[Option<usize>], ncomp: usize, s: f32) {
+    for (values, row) in values.chunks_exact_mut(ncomp).zip(rows) {
+        if let Some(row) = row {
+            for (value, &target) in values.iter_mut().zip(&after[ncomp * row..ncomp * (row + 1)]) {
+                *value += s * (target - *value);
+            }
+        }
+    }
+}
+
+// blends the fields of the earlier state named as a field of the later one
+fn blend_fields(
+    names: &[String],
+    values: &mut [f32],
+    after_names: &[String],
+    after_values: &[f32],
+    rows: &[Option<usize>],
+    ncomp: usize,
+    s: f32,
+) {
+    let size = ncomp * rows.len();
+    let after_size = if after_names.is_empty() { 0 } else { after_values.len() / after_names.len() };
+    for (ifield, name) in names.iter().enumerate() {
+        if let Some(jfield) = after_names.iter().position(|after_name| after_name.trim() == name.trim()) {
+            blend(
+                &mut values[ifield * size..(ifield + 1) * size],
+                &after_values[jfield * after_size..(jfield + 1) * after_size],
+                rows,
+                ncomp,
+                s,
+            );
+        }
+    }
+}
+
+// state at the fraction s of the way from before to after
+fn interpolate(
+    before: &AnimFile,
+    after: &AnimFile,
+    node_rows: &[Option<usize>],
+    element_rows: &[Vec<Option<usize>>],
+    s: f32,
+) -> AnimFile {
+    let mut frame = before.clone();
+    frame.time += s * (after.time - before.time);
+    blend(&mut frame.coor, &after.coor, node_rows, 3, s);
+    blend_fields(&before.func_text, &mut frame.func, &after.func_text, &after.func, node_rows, 1, s);
+    blend_fields(&before.vect_text, &mut frame.vect_val, &after.vect_text, &after.vect_val, node_rows, 3, s);
+    let frame_blocks = [&mut frame.elts_1d, &mut frame.elts_2d, &mut frame.elts_3d, &mut frame.elts_sph];
+    for ((block, after_block), rows) in frame_blocks.into_iter().zip(blocks(after)).zip(element_rows) {
+        blend_fields(&block.efunc_text, &mut block.efunc, &after_block.efunc_text, &after_block.efunc, rows, 1, s);
+        let stride = block.tens_stride;
+        blend_fields(&block.tens_text, &mut block.tens_val, &after_block.tens_text, &after_block.tens_val, rows, stride, s);
+    }
+    frame
+}
@@ -0,0 +1,104 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Shell area and solid volume of each part and of the whole model,
+// per state, from the coordinates of the active elements
+// ****************************************
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::anim::{atoi_prefix, AnimFile, ElementBlock};
+use crate::cells::{tetra_signed_volume, SolidCell};
+
+fn point(coor: &[f32], node: i32) -> [f64; 3] {
+    let i = node as usize * 3;
+    [coor[i] as f64, coor[i + 1] as f64, coor[i + 2] as f64]
+}
+
+// half the norm of the cross product of the diagonals: exact for planar
+// quads and triangles (repeated last node), the projected area of warped ones
+fn facet_area(coor: &[f32], nodes: &[i32]) -> f64 {
+    let p = [point(coor, nodes[0]), point(coor, nodes[1]), point(coor, nodes[2]), point(coor, nodes[3])];
+    let d1 = [p[2][0] - p[0][0], p[2][1] - p[0][1], p[2][2] - p[0][2]];
+    let d2 = [p[3][0] - p[1][0], p[3][1] - p[1][1], p[3][2] - p[1][2]];
+    let cross = [d1[1] * d2[2] - d1[2] * d2[1], d1[2] * d2[0] - d1[0] * d2[2], d1[0] * d2[1] - d1[1] * d2[0]];
+    0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt()
+}
+
+// hexahedra are split into 6 tetrahedra around their 0-6 diagonal,
+// collapsed bricks give flat tetrahedra that add nothing
+fn brick_volume(coor: &[f32], nodes: &[i32]) -> f64 {
+    let tets = SolidCell::from_brick(nodes).tetrahedra();
+    let six_volume: f64 = tets.iter().map(|tet| tetra_signed_volume(coor, tet)).sum();
+    six_volume.abs() / 6.0
+}
+
+// adds the measure of every active element of the block to its part,
+// in slot 0 (area) or 1 (volume)
+fn add_block(parts: &mut BTreeMap<i32, [f64; 2]>, block: &ElementBlock, slot: usize, measure: impl Fn(&[i32]) -> f64) {
+    let part_of = block.part_indices();
+    for (iel, &ipart) in part_of.iter().enumerate() {
+        let part_id = block.part_text.get(ipart).map_or(0, |text| atoi_prefix(text));
+        let entry = parts.entry(part_id).or_default();
+        if block.is_active(iel) {
+            entry[slot] += measure(block.nodes(iel));
+        }
+    }
+}
+
+// ****************************************
+// Eroded elements are left out, so the values drop as the model erodes.
+// Parts appear as soon as they have elements, even when all are eroded
+// ****************************************
+pub struct GeometryReport {
+    writer: BufWriter<File>,
+    state: usize,
+}
+
+impl GeometryReport {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "state,time,part,area,volume")?;
+        Ok(GeometryReport { writer, state: 0 })
+    }
+
+    pub fn add_state(&mut self, anim: &AnimFile) -> std::io::Result<()> {
+        let mut parts = BTreeMap::new();
+        add_block(&mut parts, &anim.elts_2d, 0, |nodes| facet_area(&anim.coor, nodes));
+        add_block(&mut parts, &anim.elts_3d, 1, |nodes| brick_volume(&anim.coor, nodes));
+
+        let area: f64 = parts.values().map(|measures| measures[0]).sum();
+        let volume: f64 = parts.values().map(|measures| measures[1]).sum();
+        writeln!(self.writer, "{},{},all,{},{}", self.state, anim.time, area, volume)?;
+        for (part_id, [area, volume]) in parts {
+            writeln!(self.writer, "{},{},{},{},{}", self.state, anim.time, part_id, area, volume)?;
+        }
+        self.state += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
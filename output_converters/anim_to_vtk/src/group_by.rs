@@ -0,0 +1,138 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Split an animation file into one output per material
+// ****************************************
+
+use std::mem;
+
+use report::{codes, Diagnostic};
+
+use crate::anim::{AnimFile, ElementBlock};
+use crate::vtk::{remove_files, write_vtk_file, WriteOptions};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Material,
+}
+
+impl GroupBy {
+    pub fn parse(value: &str) -> Option<GroupBy> {
+        match value.to_ascii_lowercase().as_str() {
+            "material" => Some(GroupBy::Material),
+            _ => None,
+        }
+    }
+}
+
+// ****************************************
+// Each output keeps all the nodes and the elements of the parts made
+// of one material, named <input>_material<k>.vtk (k from 1, in the
+// order of the material table). Materials without elements give no
+// output, elements of parts without a material are not written.
+// Returns the output files; on failure, none is left.
+// ****************************************
+pub fn write_material_outputs(anim: &mut AnimFile, file_name: &str, options: &WriteOptions) -> Result<Vec<String>, Diagnostic> {
+    let names = anim.material_names().to_vec();
+    if names.is_empty() {
+        return Err(Diagnostic::new(codes::CONVERSION_FAILED, "No material table"));
+    }
+
+    let blocks = [
+        mem::take(&mut anim.elts_1d),
+        mem::take(&mut anim.elts_2d),
+        mem::take(&mut anim.elts_3d),
+        mem::take(&mut anim.elts_sph),
+    ];
+    let written = write_materials(anim, &blocks, &names, file_name, options);
+    // the state is given back whole, for the per-state outputs
+    let [elts_1d, elts_2d, elts_3d, elts_sph] = blocks;
+    anim.elts_1d = elts_1d;
+    anim.elts_2d = elts_2d;
+    anim.elts_3d = elts_3d;
+    anim.elts_sph = elts_sph;
+    written
+}
+
+fn write_materials(
+    anim: &mut AnimFile,
+    blocks: &[ElementBlock; 4],
+    names: &[String],
+    file_name: &str,
+    options: &WriteOptions,
+) -> Result<Vec<String>, Diagnostic> {
+    let materials: Vec<Vec<i32>> = blocks.iter().map(|block| block.material_indices(names.len())).collect();
+    let unassigned: usize = materials.iter().map(|imats| imats.iter().filter(|&&imat| imat < 0).count()).sum();
+    if unassigned > 0 {
+        report::warning(codes::NO_MATERIAL, format_args!("{} elements without material are not written", unassigned));
+    }
+
+    let mut outputs = Vec::with_capacity(names.len());
+    for (imat, name) in names.iter().enumerate() {
+        if !materials.iter().any(|imats| imats.contains(&(imat as i32))) {
+            continue;
+        }
+        let select = |block: &ElementBlock, imats: &[i32]| {
+            let kept: Vec<usize> = (0..block.count).filter(|&iel| imats[iel] == imat as i32).collect();
+            block.gather(&kept)
+        };
+        anim.elts_1d = select(&blocks[0], &materials[0]);
+        anim.elts_2d = select(&blocks[1], &materials[1]);
+        anim.elts_3d = select(&blocks[2], &materials[2]);
+        anim.elts_sph = select(&blocks[3], &materials[3]);
+
+        let output_file_name = format!("{}_material{}.vtk", file_name, imat + 1);
+        report_line!("Converting {} ({}) to {}", file_name, name, output_file_name);
+        if let Err(e) = write_vtk_file(anim, options, &output_file_name) {
+            remove_files(&outputs);
+            return Err(Diagnostic::new(codes::WRITE_FAILED, e));
+        }
+        outputs.push(output_file_name);
+    }
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::Hierarchy;
+    use crate::test_files::{int_cell_array, quad_parts, TempDir};
+
+    #[test]
+    fn material_outputs_keep_the_part_ids() {
+        let dir = TempDir::new("group_by");
+        let mut anim = quad_parts(&[("10 Front", 2), ("20 Middle", 1), ("30 Rear", 2)]);
+        anim.hierarchy = Some(Hierarchy { material_texts: vec!["Steel".to_string(), "Glass".to_string()], ..Default::default() });
+        anim.elts_2d.part_material = vec![0, 0, 1];
+        let base = dir.file("runA001");
+        let outputs = write_material_outputs(&mut anim, &base, &WriteOptions::default()).unwrap();
+
+        assert_eq!(outputs, [format!("{}_material1.vtk", base), format!("{}_material2.vtk", base)]);
+        let steel = std::fs::read_to_string(&outputs[0]).unwrap();
+        assert_eq!(int_cell_array(&steel, "PART_ID"), [10, 10, 20]);
+        let glass = std::fs::read_to_string(&outputs[1]).unwrap();
+        assert_eq!(int_cell_array(&glass, "PART_ID"), [30, 30]);
+        assert_eq!(int_cell_array(&glass, "MATERIAL_INDEX"), [1, 1]);
+        // the state is given back whole
+        assert_eq!(anim.elts_2d.count, 5);
+    }
+}
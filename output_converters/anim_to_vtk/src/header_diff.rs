@@ -0,0 +1,281 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// anim_header_diff <A-file> <A-file>: differences between the headers
+// of two A-files, read without their per node and per element sections
+// (counts, flags, part tables and field lists), to see before a long
+// conversion that two runs did not write the same output requests.
+// The anim_to_vtk binary runs it when called under that name.
+// ****************************************
+
+use std::fmt::Display;
+use std::process;
+
+use clap::Parser;
+
+use crate::anim::{atoi_prefix, read_anim_headers, AnimFile, ElementBlock, TextEncoding};
+use crate::layout::LAYOUTS;
+
+pub const PROGRAM_NAME: &str = "anim_header_diff";
+
+// exit codes, as diff
+const EXIT_SAME: i32 = 0;
+const EXIT_DIFFERENT: i32 = 1;
+pub const EXIT_TROUBLE: i32 = 2;
+
+const ABSENT: &str = "-";
+const PRESENT: &str = "present";
+
+// what differs, with its value in the first and in the second file
+#[derive(Default)]
+struct Differences {
+    rows: Vec<[String; 3]>,
+}
+
+impl Differences {
+    fn value<T: PartialEq + Display>(&mut self, what: &str, first: T, second: T) {
+        if first != second {
+            self.rows.push([what.to_string(), first.to_string(), second.to_string()]);
+        }
+    }
+
+    // names found in one list only, in the order of the first then the second
+    fn names(&mut self, what: &str, first: &[String], second: &[String]) {
+        let has = |names: &[String], name: &str| names.iter().any(|other| other.trim() == name);
+        for name in first.iter().map(|name| name.trim()).filter(|name| !has(second, name)) {
+            self.value(&format!("{} \"{}\"", what, name), PRESENT, ABSENT);
+        }
+        for name in second.iter().map(|name| name.trim()).filter(|name| !has(first, name)) {
+            self.value(&format!("{} \"{}\"", what, name), ABSENT, PRESENT);
+        }
+    }
+
+    fn print(&self, first: &str, second: &str) {
+        let header = [String::new(), first.to_string(), second.to_string()];
+        let mut widths = [0; 3];
+        for row in std::iter::once(&header).chain(&self.rows) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for row in std::iter::once(&header).chain(&self.rows) {
+            let line = format!("{:<w0$}  {:<w1$}  {}", row[0], row[1], row[2], w0 = widths[0], w1 = widths[1]);
+            println!("{}", line.trim_end());
+        }
+    }
+}
+
+// part of a part table, by its id
+pub struct Part {
+    pub id: i32,
+    pub title: String,
+    pub elements: i32,
+    pub material: String,
+}
+
+pub fn parts(anim: &AnimFile, block: &ElementBlock) -> Vec<Part> {
+    let materials = anim.material_names();
+    let mut start = 0;
+    let mut parts = Vec::with_capacity(block.part_text.len());
+    for (ipart, text) in block.part_text.iter().enumerate() {
+        let end = block.def_part.get(ipart).copied().unwrap_or(start);
+        let id_len = text.trim_start().find(char::is_whitespace).unwrap_or(text.trim_start().len());
+        let material = match block.part_material.get(ipart) {
+            Some(&imat) if imat >= 0 && (imat as usize) < materials.len() => materials[imat as usize].trim().to_string(),
+            Some(&imat) => imat.to_string(),
+            None => ABSENT.to_string(),
+        };
+        parts.push(Part {
+            id: atoi_prefix(text),
+            title: text.trim_start()[id_len..].trim().to_string(),
+            elements: end - start,
+            material,
+        });
+        start = end;
+    }
+    parts
+}
+
+fn compare_parts(differences: &mut Differences, label: &str, first: &[Part], second: &[Part]) {
+    for part in first {
+        let what = format!("{} part {}", label, part.id);
+        match second.iter().find(|other| other.id == part.id) {
+            Some(other) => {
+                differences.value(&format!("{} title", what), &part.title, &other.title);
+                differences.value(&format!("{} elements", what), part.elements, other.elements);
+                differences.value(&format!("{} material", what), &part.material, &other.material);
+            }
+            None => differences.value(&what, format!("\"{}\"", part.title), ABSENT.to_string()),
+        }
+    }
+    for part in second.iter().filter(|part| !first.iter().any(|other| other.id == part.id)) {
+        differences.value(&format!("{} part {}", label, part.id), ABSENT.to_string(), format!("\"{}\"", part.title));
+    }
+}
+
+pub fn blocks(anim: &AnimFile) -> [(&'static str, &ElementBlock); 4] {
+    [("1D", &anim.elts_1d), ("2D", &anim.elts_2d), ("3D", &anim.elts_3d), ("SPH", &anim.elts_sph)]
+}
+
+fn compare(first: &AnimFile, second: &AnimFile) -> Differences {
+    let mut differences = Differences::default();
+    differences.value("format", first.layout_name, second.layout_name);
+    differences.value("byte order", first.endian.name(), second.endian.name());
+    differences.value("time", first.time, second.time);
+
+    // flags, named after the layout of the first file
+    let layout = LAYOUTS.iter().find(|layout| layout.name == first.layout_name);
+    for flag in 0..first.flag_a.len().max(second.flag_a.len()) {
+        let name = layout.and_then(|layout| layout.flag_name(flag)).unwrap_or("unknown");
+        let value = |anim: &AnimFile| anim.flag_a.get(flag).copied().unwrap_or(0);
+        differences.value(&format!("flag {} ({})", flag, name), value(first), value(second));
+    }
+
+    // counts
+    differences.value("nodes", first.nb_nodes, second.nb_nodes);
+    for ((label, a), (_, b)) in blocks(first).into_iter().zip(blocks(second)) {
+        differences.value(&format!("{} elements", label), a.count, b.count);
+        differences.value(&format!("{} parts", label), a.part_text.len(), b.part_text.len());
+    }
+    let hierarchy_counts = |anim: &AnimFile| {
+        anim.hierarchy.as_ref().map_or([0; 3], |hierarchy| {
+            [hierarchy.subsets.len(), hierarchy.material_texts.len(), hierarchy.properties_texts.len()]
+        })
+    };
+    let hierarchy_labels = ["subsets", "materials", "properties"];
+    for (what, (a, b)) in hierarchy_labels.iter().zip(hierarchy_counts(first).into_iter().zip(hierarchy_counts(second))) {
+        differences.value(what, a, b);
+    }
+    let th_counts = |anim: &AnimFile| {
+        anim.th.as_ref().map_or([0; 4], |th| [th.nodes.len(), th.elts_1d.len(), th.elts_2d.len(), th.elts_3d.len()])
+    };
+    let th_labels = ["time history nodes", "time history 1D elements", "time history 2D elements", "time history 3D elements"];
+    for (what, (a, b)) in th_labels.iter().zip(th_counts(first).into_iter().zip(th_counts(second))) {
+        differences.value(what, a, b);
+    }
+
+    // part tables
+    for ((label, a), (_, b)) in blocks(first).into_iter().zip(blocks(second)) {
+        compare_parts(&mut differences, label, &parts(first, a), &parts(second, b));
+    }
+    if let (Some(a), Some(b)) = (&first.hierarchy, &second.hierarchy) {
+        differences.names("material", &a.material_texts, &b.material_texts);
+    }
+
+    // fields
+    differences.names("nodal scalar", &first.func_text, &second.func_text);
+    differences.names("nodal vector", &first.vect_text, &second.vect_text);
+    for ((label, a), (_, b)) in blocks(first).into_iter().zip(blocks(second)) {
+        differences.names(&format!("{} elemental scalar", label), &a.efunc_text, &b.efunc_text);
+        let tensor = if label == "1D" { "torseur" } else { "tensor" };
+        differences.names(&format!("{} {}", label, tensor), &a.tens_text, &b.tens_text);
+    }
+    differences
+}
+
+// ****************************************
+// anim_header_diff entry point: exits 0 when the headers are the same,
+// 1 when they differ, 2 when a file can't be read
+// ****************************************
+#[derive(Parser)]
+#[command(
+    name = PROGRAM_NAME,
+    about = "Print the differences between the headers of two A-files: format, time, flags, numbers of nodes, \
+             elements, parts and materials, part tables (title, elements and material of each part id) and field \
+             lists. The nodes and elements themselves are not read.",
+    after_help = "Exit code 0 when the headers are the same, 1 when they differ, 2 when a file can't be read."
+)]
+pub struct HeaderDiffArgs {
+    #[arg(value_name = "A-FILE 1")]
+    first: String,
+    #[arg(value_name = "A-FILE 2")]
+    second: String,
+}
+
+// called as anim_header_diff
+pub fn main(args: &[String]) -> ! {
+    run(crate::parse_or_exit(args, EXIT_TROUBLE))
+}
+
+pub fn run(args: HeaderDiffArgs) -> ! {
+    let read = |file_name: &str| {
+        read_anim_headers(file_name, TextEncoding::default(), None).unwrap_or_else(|e| {
+            report::for_input(file_name, || report::error(e.code(), e));
+            process::exit(EXIT_TROUBLE);
+        })
+    };
+    let (first, second) = (read(&args.first), read(&args.second));
+    let differences = compare(&first, &second);
+    if differences.rows.is_empty() {
+        println!("Headers are the same");
+        process::exit(EXIT_SAME);
+    }
+    differences.print(&args.first, &args.second);
+    println!("{} differences", differences.rows.len());
+    process::exit(EXIT_DIFFERENT);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{quad_parts, AnimBuilder, TempDir};
+
+    #[test]
+    fn headers_differing_in_one_field() {
+        let dir = TempDir::new("header_diff");
+        let (first, second, later) = (dir.file("runA001"), dir.file("runA002"), dir.file("runA003"));
+        let coor = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        AnimBuilder::new(0.5).nodes(&coor).scalar("Temperature", &[1.0, 2.0]).write(&first);
+        AnimBuilder::new(0.5).nodes(&coor).scalar("Pressure", &[1.0, 2.0]).write(&second);
+        AnimBuilder::new(1.0).nodes(&coor).scalar("Temperature", &[3.0, 4.0]).write(&later);
+        let read = |path: &str| read_anim_headers(path, TextEncoding::default(), None).unwrap();
+
+        assert!(compare(&read(&first), &read(&first)).rows.is_empty());
+        // the values themselves are not compared
+        let differences = compare(&read(&first), &read(&later));
+        assert_eq!(differences.rows, [["time".to_string(), "0.5".to_string(), "1".to_string()]]);
+        let differences = compare(&read(&first), &read(&second));
+        assert_eq!(
+            differences.rows,
+            [
+                ["nodal scalar \"Temperature\"".to_string(), PRESENT.to_string(), ABSENT.to_string()],
+                ["nodal scalar \"Pressure\"".to_string(), ABSENT.to_string(), PRESENT.to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn part_tables_are_compared_by_id() {
+        let first = quad_parts(&[("10 Front", 2), ("20 Rear", 1)]);
+        let second = quad_parts(&[("20 Back", 1), ("10 Front", 3)]);
+        let rows: Vec<String> = compare(&first, &second).rows.iter().map(|row| row.join(" | ")).collect();
+        assert_eq!(
+            rows,
+            [
+                "nodes | 10 | 12",
+                "2D elements | 3 | 4",
+                "2D part 10 elements | 2 | 3",
+                "2D part 20 title | Rear | Back",
+            ]
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Declarative layout of the A-file formats: the meaning of every
+// flag_a bit, and the optional blocks following the 2D section,
+// each announced by a flag, in file order
+// ****************************************
+
+use crate::anim::FASTMAGI10;
+
+#[derive(Clone, Copy)]
+pub enum Block {
+    Geometry3d,
+    Geometry1d,
+    Hierarchy,
+    ThLists,
+    Sph,
+}
+
+impl Block {
+    pub fn name(self) -> &'static str {
+        match self {
+            Block::Geometry3d => "3D elements",
+            Block::Geometry1d => "1D elements",
+            Block::Hierarchy => "part hierarchy",
+            Block::ThLists => "time history lists",
+            Block::Sph => "SPH particles",
+        }
+    }
+}
+
+pub struct FormatLayout {
+    pub magic: i32,
+    pub name: &'static str,
+    // flags changing the content of the sections
+    pub modifiers: &'static [(usize, &'static str)],
+    // flags announcing an optional block, in file order
+    pub blocks: &'static [(usize, Block)],
+}
+
+pub const LAYOUTS: &[FormatLayout] = &[FormatLayout {
+    magic: FASTMAGI10,
+    name: "FASTMAGI10",
+    modifiers: &[
+        (0, "masses"),
+        (1, "user numbering"),
+        (4, "part hierarchy"),
+        (6, "skews"),
+    ],
+    blocks: &[
+        (2, Block::Geometry3d),
+        (3, Block::Geometry1d),
+        (4, Block::Hierarchy),
+        (5, Block::ThLists),
+        (7, Block::Sph),
+    ],
+}];
+
+pub fn format_layout(magic: i32) -> Option<&'static FormatLayout> {
+    LAYOUTS.iter().find(|layout| layout.magic == magic)
+}
+
+impl FormatLayout {
+    pub fn is_known_flag(&self, flag: usize) -> bool {
+        self.modifiers.iter().any(|&(f, _)| f == flag) || self.blocks.iter().any(|&(f, _)| f == flag)
+    }
+
+    // what a flag changes or announces, None when unknown
+    pub fn flag_name(&self, flag: usize) -> Option<&'static str> {
+        let modifier = self.modifiers.iter().find(|&&(f, _)| f == flag).map(|&(_, name)| name);
+        modifier.or_else(|| self.blocks.iter().find(|&&(f, _)| f == flag).map(|&(_, block)| block.name()))
+    }
+
+    // set flags this layout does not describe
+    pub fn unknown_flags(&self, flag_a: &[i32]) -> Vec<usize> {
+        (0..flag_a.len())
+            .filter(|&flag| flag_a[flag] != 0 && !self.is_known_flag(flag))
+            .collect()
+    }
+}
@@ -0,0 +1,337 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Level of detail outputs: vertex clustering decimation. Nodes are
+// merged per bin of a regular grid, cells are merged when they end
+// on the same merged nodes, and dropped when they collapse.
+// ****************************************
+
+use std::collections::HashMap;
+use std::fs;
+
+use report::{codes, Diagnostic};
+
+use crate::anim::{AnimFile, ElementBlock};
+use crate::vtk::{remove_files, write_vtk_file, WriteOptions};
+
+pub const LOD_CELL_COUNT: &str = "LOD_CELL_COUNT";
+
+// cell ratio between two successive levels
+const LEVEL_RATIO: f64 = 0.25;
+
+// bisection steps on the bin size
+const BIN_ITERATIONS: usize = 30;
+
+// distinct merged nodes a cell needs to stay a cell (1D, 2D, 3D, SPH)
+const MIN_NODES: [usize; 4] = [2, 3, 4, 1];
+
+fn blocks_mut(anim: &mut AnimFile) -> [&mut ElementBlock; 4] {
+    [&mut anim.elts_1d, &mut anim.elts_2d, &mut anim.elts_3d, &mut anim.elts_sph]
+}
+
+// cluster of every node for bins of size <h>, and the number of clusters
+fn cluster_nodes(coor: &[f32], origin: [f64; 3], h: f64) -> (Vec<usize>, usize) {
+    let mut bins: HashMap<[i64; 3], usize> = HashMap::new();
+    let clusters = coor
+        .chunks_exact(3)
+        .map(|p| {
+            let bin = [0, 1, 2].map(|k| ((p[k] as f64 - origin[k]) / h).floor() as i64);
+            let next = bins.len();
+            *bins.entry(bin).or_insert(next)
+        })
+        .collect();
+    (clusters, bins.len())
+}
+
+// groups of cells merged together, in the order of their first cell.
+// A collapsed cell joins the first group of its part that shares one of
+// its merged nodes, so that its fields and mass are not lost.
+fn merge_cells(block: &ElementBlock, cluster_of: &[usize], min_nodes: usize) -> Vec<Vec<usize>> {
+    let part_of = block.part_indices();
+    let merged_nodes = |iel: usize| {
+        let mut nodes: Vec<usize> = block.nodes(iel).iter().map(|&node| cluster_of[node as usize]).collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    };
+    let mut group_of: HashMap<(usize, Vec<usize>), usize> = HashMap::new();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut collapsed = Vec::new();
+    for (iel, &ipart) in part_of.iter().enumerate() {
+        let key = merged_nodes(iel);
+        if key.len() < min_nodes {
+            collapsed.push(iel);
+            continue;
+        }
+        let next = groups.len();
+        let igroup = *group_of.entry((ipart, key)).or_insert(next);
+        if igroup == next {
+            groups.push(Vec::new());
+        }
+        groups[igroup].push(iel);
+    }
+
+    let mut group_at: HashMap<(usize, usize), usize> = HashMap::new();
+    for (igroup, group) in groups.iter().enumerate() {
+        for cluster in merged_nodes(group[0]) {
+            group_at.entry((part_of[group[0]], cluster)).or_insert(igroup);
+        }
+    }
+    for iel in collapsed {
+        let target = merged_nodes(iel).into_iter().find_map(|cluster| group_at.get(&(part_of[iel], cluster)));
+        if let Some(&igroup) = target {
+            groups[igroup].push(iel);
+        }
+    }
+    groups
+}
+
+fn count_merged_cells(anim: &AnimFile, cluster_of: &[usize]) -> usize {
+    [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph]
+        .iter()
+        .zip(MIN_NODES)
+        .map(|(block, min_nodes)| merge_cells(block, cluster_of, min_nodes).len())
+        .sum()
+}
+
+// mean of <stride> values per item over each group, weighted
+fn merge_values(values: &[f32], stride: usize, groups: &[Vec<usize>], weights: &[f64]) -> Vec<f32> {
+    let mut merged = Vec::with_capacity(groups.len() * stride);
+    for group in groups {
+        let total: f64 = group.iter().map(|&i| weights[i]).sum();
+        for k in 0..stride {
+            let sum: f64 = group.iter().map(|&i| weights[i] * values[i * stride + k] as f64).sum();
+            merged.push((sum / total) as f32);
+        }
+    }
+    merged
+}
+
+// every field of <count> items with <stride> values per item
+fn merge_fields(values: &[f32], count: usize, stride: usize, groups: &[Vec<usize>], weights: &[f64]) -> Vec<f32> {
+    if count == 0 {
+        return Vec::new();
+    }
+    values
+        .chunks_exact(count * stride)
+        .flat_map(|field| merge_values(field, stride, groups, weights))
+        .collect()
+}
+
+// ****************************************
+// Merged cells keep the connectivity, part, ids and integer arrays
+// of their first cell. Their fields are the mean over the merged
+// active cells (all of them when none is active) weighted by the
+// LOD_CELL_COUNT of the previous level, their mass is the sum.
+// ****************************************
+fn merge_block(block: &ElementBlock, cluster_of: &[usize], min_nodes: usize) -> ElementBlock {
+    let groups = merge_cells(block, cluster_of, min_nodes);
+    let first: Vec<usize> = groups.iter().map(|group| group[0]).collect();
+    let mut merged = block.gather(&first);
+
+    let cell_counts: Vec<i32> = match block.int_arrays.iter().find(|(name, _)| name == LOD_CELL_COUNT) {
+        Some((_, counts)) => counts.clone(),
+        None => vec![1; block.count],
+    };
+    let weights: Vec<f64> = cell_counts.iter().map(|&count| count as f64).collect();
+    let field_groups: Vec<Vec<usize>> = groups
+        .iter()
+        .map(|group| {
+            let active: Vec<usize> = group.iter().copied().filter(|&iel| block.is_active(iel)).collect();
+            if active.is_empty() { group.clone() } else { active }
+        })
+        .collect();
+
+    for node in merged.connect.iter_mut() {
+        *node = cluster_of[*node as usize] as i32;
+    }
+    merged.efunc = merge_fields(&block.efunc, block.count, 1, &field_groups, &weights);
+    merged.tens_val = merge_fields(&block.tens_val, block.count, block.tens_stride, &field_groups, &weights);
+    if !block.e_mass.is_empty() {
+        merged.e_mass = groups.iter().map(|group| group.iter().map(|&iel| block.e_mass[iel]).sum()).collect();
+    }
+    if !block.del_elt.is_empty() {
+        merged.del_elt = groups
+            .iter()
+            .map(|group| if group.iter().any(|&iel| block.is_active(iel)) { 1 } else { block.del_elt[group[0]] })
+            .collect();
+    }
+    let merged_counts = groups.iter().map(|group| group.iter().map(|&iel| cell_counts[iel]).sum()).collect();
+    merged.int_arrays.retain(|(name, _)| name != LOD_CELL_COUNT);
+    merged.int_arrays.push((LOD_CELL_COUNT.to_string(), merged_counts));
+    merged
+}
+
+// ****************************************
+// Merge the nodes and cells of <anim> on the grid whose bin size
+// gives at most <target> cells, as close to it as the bisection goes
+// ****************************************
+fn decimate(anim: &mut AnimFile, target: usize) {
+    let mut lower = [f64::MAX; 3];
+    let mut upper = [f64::MIN; 3];
+    for p in anim.coor.chunks_exact(3) {
+        for k in 0..3 {
+            lower[k] = lower[k].min(p[k] as f64);
+            upper[k] = upper[k].max(p[k] as f64);
+        }
+    }
+    let diagonal = (0..3).map(|k| (upper[k] - lower[k]).powi(2)).sum::<f64>().sqrt().max(f64::MIN_POSITIVE);
+
+    // the cell count decreases with the bin size, bisection in log scale
+    let (mut fine, mut coarse) = (diagonal * 1e-6, diagonal);
+    for _ in 0..BIN_ITERATIONS {
+        let h = (fine * coarse).sqrt();
+        let (cluster_of, _) = cluster_nodes(&anim.coor, lower, h);
+        if count_merged_cells(anim, &cluster_of) > target {
+            fine = h;
+        } else {
+            coarse = h;
+        }
+    }
+    let (cluster_of, nb_clusters) = cluster_nodes(&anim.coor, lower, coarse);
+
+    // merged nodes: mean position and fields, summed mass, first id
+    let members: Vec<Vec<usize>> = {
+        let mut members = vec![Vec::new(); nb_clusters];
+        for (inod, &cluster) in cluster_of.iter().enumerate() {
+            members[cluster].push(inod);
+        }
+        members
+    };
+    let unit = vec![1.0; anim.nb_nodes];
+    anim.coor = merge_values(&anim.coor, 3, &members, &unit);
+    anim.func = merge_fields(&anim.func, anim.nb_nodes, 1, &members, &unit);
+    anim.vect_val = merge_fields(&anim.vect_val, anim.nb_nodes, 3, &members, &unit);
+    if !anim.n_mass.is_empty() {
+        anim.n_mass = members.iter().map(|nodes| nodes.iter().map(|&inod| anim.n_mass[inod]).sum()).collect();
+    }
+    if !anim.nod_num.is_empty() {
+        anim.nod_num = members.iter().map(|nodes| anim.nod_num[nodes[0]]).collect();
+    }
+    anim.nb_nodes = nb_clusters;
+
+    for (block, min_nodes) in blocks_mut(anim).into_iter().zip(MIN_NODES) {
+        *block = merge_block(block, &cluster_of, min_nodes);
+    }
+}
+
+// ****************************************
+// Write levels 1 to <levels>-1 as <input>_lod<k>.vtk, each with about
+// a quarter of the cells of the previous one, and the index
+// <input>_lod.json of all the levels, level 0 being <full_output>.
+// The state is left as is, the levels decimate a copy of it.
+// Returns the written files; on failure, none is left.
+// ****************************************
+pub fn write_lod_outputs(
+    full: &AnimFile,
+    file_name: &str,
+    full_output: &str,
+    options: &WriteOptions,
+    levels: usize,
+) -> Result<Vec<String>, Diagnostic> {
+    let mut anim = full.clone();
+    let mut entries = vec![(full_output.to_string(), anim.nb_nodes, anim.nb_elements())];
+    let mut outputs = Vec::with_capacity(levels);
+    for level in 1..levels {
+        let target = (anim.nb_elements() as f64 * LEVEL_RATIO) as usize;
+        decimate(&mut anim, target);
+        let output_file_name = format!("{}_lod{}.vtk", file_name, level);
+        report_line!("Converting {} (level {}, {} cells) to {}", file_name, level, anim.nb_elements(), output_file_name);
+        if let Err(e) = write_vtk_file(&anim, options, &output_file_name) {
+            remove_files(&outputs);
+            return Err(Diagnostic::new(codes::WRITE_FAILED, e));
+        }
+        entries.push((output_file_name.clone(), anim.nb_nodes, anim.nb_elements()));
+        outputs.push(output_file_name);
+    }
+
+    let index_file_name = format!("{}_lod.json", file_name);
+    let mut json = format!("{{\n  \"input\": {},\n  \"time\": {},\n  \"levels\": [", report::json_string(file_name), anim.time);
+    for (level, (output, points, cells)) in entries.iter().enumerate() {
+        json.push_str(if level == 0 { "\n" } else { ",\n" });
+        json.push_str(&format!(
+            "    {{\"level\": {}, \"file\": {}, \"points\": {}, \"cells\": {}}}",
+            level,
+            report::json_string(output),
+            points,
+            cells
+        ));
+    }
+    json.push_str("\n  ]\n}\n");
+    if let Err(e) = fs::write(&index_file_name, json) {
+        let _ = fs::remove_file(&index_file_name);
+        remove_files(&outputs);
+        return Err(Diagnostic::new(codes::WRITE_FAILED, format_args!("Can't write {}: {}", index_file_name, e)));
+    }
+    outputs.push(index_file_name);
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{int_cell_array, legacy_vtk, quad_parts};
+
+    // merged cells of each part id, and the cells of the full model they hold
+    fn cells_by_part(anim: &AnimFile) -> Vec<(i32, usize, i32)> {
+        let text = legacy_vtk(anim, &WriteOptions::default());
+        let part_ids = int_cell_array(&text, "PART_ID");
+        let counts = int_cell_array(&text, LOD_CELL_COUNT);
+        let mut parts: Vec<(i32, usize, i32)> = Vec::new();
+        for (id, count) in part_ids.into_iter().zip(counts) {
+            match parts.iter_mut().find(|(part, _, _)| *part == id) {
+                Some((_, cells, held)) => {
+                    *cells += 1;
+                    *held += count;
+                }
+                None => parts.push((id, 1, count)),
+            }
+        }
+        parts
+    }
+
+    // rows of quads a quarter wide, that bins merge along the rows
+    fn narrow_rows() -> AnimFile {
+        let mut anim = quad_parts(&[("10 Front", 8), ("20 Clip", 2), ("30 Rear", 8)]);
+        for p in anim.coor.chunks_exact_mut(3) {
+            p[0] *= 0.25;
+        }
+        anim
+    }
+
+    #[test]
+    fn decimated_cells_keep_their_part() {
+        let mut anim = narrow_rows();
+        decimate(&mut anim, 10);
+        assert_eq!(anim.nb_elements(), 9);
+        assert_eq!(cells_by_part(&anim), [(10, 4, 8), (20, 1, 2), (30, 4, 8)]);
+    }
+
+    #[test]
+    fn collapsed_part_leaves_the_next_ids() {
+        let mut anim = narrow_rows();
+        decimate(&mut anim, 6);
+        // the clip collapses, its part is left without cell
+        assert_eq!(anim.elts_2d.def_part, [3, 3, 6]);
+        assert_eq!(cells_by_part(&anim), [(10, 3, 8), (30, 3, 8)]);
+    }
+}
@@ -0,0 +1,167 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Named lookup tables for scalar arrays (--lut field:colormap:min:max).
+// Legacy VTK keeps no range with a table: its entries span [min, max]
+// in order, the range is written in FieldData as LUT_RANGE_<field>.
+// ****************************************
+
+use crate::mesh_writer::array_name_matches;
+
+pub const LUT_SIZE: usize = 256;
+
+// (position, r, g, b) control points, linear in between
+const JET: &[[f32; 4]] = &[
+    [0.0, 0.0, 0.0, 0.5],
+    [0.125, 0.0, 0.0, 1.0],
+    [0.375, 0.0, 1.0, 1.0],
+    [0.625, 1.0, 1.0, 0.0],
+    [0.875, 1.0, 0.0, 0.0],
+    [1.0, 0.5, 0.0, 0.0],
+];
+const VIRIDIS: &[[f32; 4]] = &[
+    [0.0, 0.267, 0.005, 0.329],
+    [0.25, 0.229, 0.322, 0.546],
+    [0.5, 0.128, 0.567, 0.551],
+    [0.75, 0.369, 0.789, 0.383],
+    [1.0, 0.993, 0.906, 0.144],
+];
+const COOLWARM: &[[f32; 4]] = &[
+    [0.0, 0.230, 0.299, 0.754],
+    [0.5, 0.865, 0.865, 0.865],
+    [1.0, 0.706, 0.016, 0.150],
+];
+const HOT: &[[f32; 4]] = &[
+    [0.0, 0.0416, 0.0, 0.0],
+    [0.365, 1.0, 0.0, 0.0],
+    [0.746, 1.0, 1.0, 0.0],
+    [1.0, 1.0, 1.0, 1.0],
+];
+const GRAY: &[[f32; 4]] = &[[0.0, 0.0, 0.0, 0.0], [1.0, 1.0, 1.0, 1.0]];
+
+const COLORMAPS: &[(&str, &[[f32; 4]])] = &[
+    ("jet", JET),
+    ("viridis", VIRIDIS),
+    ("coolwarm", COOLWARM),
+    ("hot", HOT),
+    ("gray", GRAY),
+];
+
+#[derive(Clone)]
+pub struct Lut {
+    pub field: String,
+    pub min: f32,
+    pub max: f32,
+    points: &'static [[f32; 4]],
+}
+
+impl Lut {
+    // "VonMises:jet:0:800", the field name may itself contain ':'
+    pub fn parse(value: &str) -> Result<Lut, String> {
+        let mut parts = value.rsplitn(4, ':');
+        let (max, min, colormap, field) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(max), Some(min), Some(colormap), Some(field)) if !field.is_empty() => (max, min, colormap, field),
+            _ => return Err(format!("Invalid value '{}' for --lut, expected field:colormap:min:max", value)),
+        };
+        let points = COLORMAPS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(colormap))
+            .map(|&(_, points)| points)
+            .ok_or_else(|| {
+                let names: Vec<&str> = COLORMAPS.iter().map(|&(name, _)| name).collect();
+                format!("Unknown colormap '{}' for --lut, expected one of {}", colormap, names.join(", "))
+            })?;
+        let bound = |text: &str| {
+            text.trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("Invalid bound '{}' for --lut {}", text, value))
+        };
+        let (min, max) = (bound(min)?, bound(max)?);
+        if min >= max {
+            return Err(format!("Empty range {}:{} for --lut {}", min, max, field));
+        }
+        Ok(Lut { field: field.to_string(), min, max, points })
+    }
+
+    pub fn matches(&self, array_name: &str) -> bool {
+        array_name_matches(array_name, &self.field)
+    }
+
+    // LUT_SIZE RGBA entries, opaque, from min to max
+    pub fn entries(&self) -> Vec<[f32; 4]> {
+        (0..LUT_SIZE)
+            .map(|i| {
+                let t = i as f32 / (LUT_SIZE - 1) as f32;
+                let upper = self.points.iter().position(|p| p[0] >= t).unwrap_or(self.points.len() - 1).max(1);
+                let (a, b) = (self.points[upper - 1], self.points[upper]);
+                let w = ((t - a[0]) / (b[0] - a[0])).clamp(0.0, 1.0);
+                [a[1] + w * (b[1] - a[1]), a[2] + w * (b[2] - a[2]), a[3] + w * (b[3] - a[3]), 1.0]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{legacy_vtk, quad_parts};
+    use crate::vtk::WriteOptions;
+
+    #[test]
+    fn field_names_may_hold_colons() {
+        let lut = Lut::parse("Stress:Von Mises:Viridis:-1.5:800").unwrap();
+        assert_eq!((lut.field.as_str(), lut.min, lut.max), ("Stress:Von Mises", -1.5, 800.0));
+        let error = |value| Lut::parse(value).err().unwrap();
+        assert!(error("VonMises:jet:800").starts_with("Invalid value"));
+        assert!(error("VonMises:rainbow:0:800").contains("jet, viridis"));
+        assert!(error("VonMises:jet:800:0").starts_with("Empty range"));
+        assert!(error("VonMises:jet:0:inf").starts_with("Invalid bound"));
+    }
+
+    #[test]
+    fn entries_span_the_colormap() {
+        let entries = Lut::parse("T:gray:0:1").unwrap().entries();
+        assert_eq!(entries.len(), LUT_SIZE);
+        assert_eq!(entries[0], [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(entries[LUT_SIZE - 1], [1.0, 1.0, 1.0, 1.0]);
+        assert!((entries[128][0] - 128.0 / 255.0).abs() < 1e-6);
+
+        let jet = Lut::parse("T:jet:0:1").unwrap().entries();
+        assert_eq!(jet[0], [0.0, 0.0, 0.5, 1.0]);
+        assert_eq!(jet[LUT_SIZE - 1], [0.5, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn matching_scalars_name_their_table() {
+        let mut anim = quad_parts(&[("1 Plate", 1)]);
+        anim.func_text = vec!["Temperature".to_string()];
+        anim.func = vec![0.0, 1.0, 2.0, 3.0];
+        let options = WriteOptions { luts: vec![Lut::parse("temperature:hot:0:3").unwrap()], ..Default::default() };
+        let text = legacy_vtk(&anim, &options);
+        assert!(text.contains("SCALARS Temperature float 1\nLOOKUP_TABLE Temperature_LUT\n"), "{}", text);
+        assert!(text.contains(&format!("LOOKUP_TABLE Temperature_LUT {}\n", LUT_SIZE)));
+        // the range is named as the option spells the field
+        assert!(text.contains("LUT_RANGE_temperature 2 1 float\n0.0\n3.0\n"));
+    }
+}
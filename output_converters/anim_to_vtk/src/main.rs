@@ -25,1255 +25,1144 @@
 // To launch conversion:
 //   anim_to_vtk animationFile > vtkFile
 
+// eprintln! for the messages of the conversion, through the report crate,
+// which keeps them per file while files are converted in parallel (--jobs)
+macro_rules! report_line {
+    () => {
+        report::write_line("")
+    };
+    ($($arg:tt)*) => {
+        report::write_line(format_args!($($arg)*))
+    };
+}
+
+mod anim;
+mod anim_info;
+mod anonymize;
+#[cfg(feature = "archive")]
+mod archive;
+mod batch;
+#[cfg(feature = "catalog")]
+mod catalog;
+mod cells;
+mod cog;
+mod components;
+mod dashboard;
+mod debris;
+mod derive;
+mod dedupe;
+mod ensight;
+mod header_diff;
+mod frames;
+mod geometry_report;
+mod group_by;
+mod layout;
+mod lod;
+mod lut;
+mod mesh_writer;
+mod metrics;
+mod modes;
+mod multiblock;
+mod ndjson;
+mod npz;
+mod obj;
+mod paraview_script;
+mod part_colors;
+mod part_filter;
+mod part_sums;
+mod part_table;
+mod pvd;
+mod quantize;
+mod read_ahead;
+mod resample;
+mod select;
+mod smooth;
+mod sph_density;
+mod sph_project;
+mod sph_split;
+mod subset;
+mod symmetry;
+#[cfg(test)]
+mod test_files;
+mod time_filter;
+mod time_order;
+mod trajectories;
+mod triangulate;
+#[cfg(feature = "usd")]
+mod usd;
+mod validate;
+mod vars;
+mod vtk;
+mod vtu;
+
+use std::collections::HashSet;
 use std::env;
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
 use std::process;
 use std::path::Path;
+use std::time::Instant;
 
-use libc::{c_char, snprintf};
-
-use itoa::Buffer as ItoaBuffer;
-use ryu::Buffer as RyuBuffer;
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 
-const FASTMAGI10: i32 = 0x542c;
+use anim::{read_anim_file_with_budget, read_error_of, AnimFile, Endian, TextEncoding};
+use group_by::GroupBy;
+use report::{codes, Diagnostic};
+use validate::ParseMode;
+use mesh_writer::{write_mesh, MeshWriter};
+use vtk::WriteOptions;
 
 // ****************************************
-// read big-endian data from file
+// apply the requested mesh transformations before output
 // ****************************************
-fn read_i32<R: Read>(reader: &mut R) -> i32 {
-    let mut buf = [0u8; 4];
-    reader.read_exact(&mut buf).expect("Error in reading file");
-    i32::from_be_bytes(buf)
+fn prepare_anim(anim: &mut AnimFile, cli: &CliOptions, previous: Option<&derive::StateHistory>) -> Result<(), Diagnostic> {
+    // before anything moves or drops nodes, matched by index without user ids
+    if let Some(reference) = &cli.reference {
+        let missing = derive::add_displacement(anim, reference).map_err(|e| Diagnostic::new(codes::CONVERSION_FAILED, e))?;
+        if missing > 0 {
+            report::warning(
+                codes::MISSING_NODES,
+                format_args!("{} of {} nodes not in the reference state, DISPLACEMENT 0", missing, anim.nb_nodes),
+            );
+        }
+    }
+    // first, so that the other steps only see the extracted region
+    if !cli.parts.is_empty() {
+        let (nb_parts, nb_nodes, nb_elements) = part_filter::filter_parts(anim, &cli.parts);
+        report_line!("  kept {} parts: {} nodes and {} elements", nb_parts, nb_nodes, nb_elements);
+    }
+    if cli.node_set.is_some() || cli.element_set.is_some() {
+        let (nb_nodes, nb_elements) = subset::subset(anim, cli.node_set.as_ref(), cli.element_set.as_ref())
+            .map_err(|e| Diagnostic::new(codes::CONVERSION_FAILED, e))?;
+        report_line!("  kept {} nodes and {} elements of the sets", nb_nodes, nb_elements);
+    }
+    if cli.remove_eroded {
+        let removed = subset::remove_eroded(anim);
+        if removed > 0 {
+            report_line!("  removed {} eroded elements", removed);
+        }
+    }
+    if cli.dedupe_cells {
+        let removed = dedupe::dedupe_cells(anim);
+        if removed > 0 {
+            report_line!("  removed {} duplicated cells", removed);
+        }
+    }
+    if cli.triangulate || cli.triangulate_solids {
+        triangulate::triangulate(anim, cli.triangulate_solids);
+    }
+    if cli.components {
+        let nb_components = components::add_component_ids(anim);
+        report_line!("  {} connected components", nb_components);
+    }
+    for (name, iterations) in &cli.smooth_fields {
+        if !smooth::smooth_field(anim, name, *iterations) {
+            report::warning(codes::NO_MATCH, format_args!("no nodal field named {} to smooth", name));
+        }
+    }
+    if let Some(radius) = cli.sph_density {
+        sph_density::add_neighbor_counts(anim, radius);
+    }
+    if cli.derived.iter().any(|derived| derived.is_invariant()) && derive::add_derived_fields(anim, &cli.derived) == 0 {
+        report::warning(codes::NO_MATCH, "no stress tensor to derive fields from");
+    }
+    if cli.derived.contains(&derive::Derived::Magnitude) && derive::add_magnitudes(anim) == 0 {
+        report::warning(codes::NO_MATCH, "no velocity, acceleration or displacement vector to derive a magnitude from");
+    }
+    if cli.derives_rates() && derive::add_rate_fields(anim, previous) == 0 {
+        report::warning(codes::NO_MATCH, "no plastic strain array to derive a rate from");
+    }
+    if !cli.accumulate.is_empty() && derive::add_cumulative_fields(anim, &cli.accumulate, previous) == 0 {
+        report::warning(
+            codes::NO_MATCH,
+            format_args!("no elemental scalar named {} to accumulate", cli.accumulate.join(", ")),
+        );
+    }
+    // after the derived fields, so that they can be selected on too
+    if let Some(selection) = &cli.select_cells {
+        let (nb_kept, nb_cells) = select::select_cells(anim, selection).map_err(|e| Diagnostic::new(e.code(), e))?;
+        report_line!("  selected {} of {} cells", nb_kept, nb_cells);
+    }
+    // after the steps matching the user ids, names and coordinates
+    if let Some(anonymizer) = &cli.anonymizer {
+        anonymize::anonymize(anim, anonymizer);
+    }
+    // last, so that derived fields are rounded too
+    if let Some(step) = cli.quantize {
+        quantize::quantize(anim, step);
+    }
+    if let Some(vars) = &cli.write.vars {
+        for pattern in vars.unmatched(anim) {
+            report::warning(codes::NO_MATCH, format_args!("no variable matches {}", pattern));
+        }
+    }
+    Ok(())
 }
 
-fn read_f32<R: Read>(reader: &mut R) -> f32 {
-    let mut buf = [0u8; 4];
-    reader.read_exact(&mut buf).expect("Error in reading file");
-    f32::from_be_bytes(buf)
+// ****************************************
+// read an A-File within the memory budget and check it. The conversion
+// takes two passes: the first one reads the geometry and leaves the
+// field sections on disk, the writers read them back one at a time.
+// --max-memory keeps the fields that fit in memory; options that
+// modify fields need them all.
+// ****************************************
+fn load_anim(file_name: &str, cli: &CliOptions) -> Result<AnimFile, Diagnostic> {
+    let max_memory = if cli.needs_all_fields() { None } else { Some(cli.max_memory.unwrap_or(0)) };
+    let mut anim = read_anim_file_with_budget(file_name, max_memory, cli.text_encoding, cli.endian)
+        .map_err(|e| Diagnostic::new(e.code(), e))?;
+    validate::validate(&mut anim, cli.parse_mode)?;
+    let deferred = anim.deferred_sections();
+    if deferred > 0 && cli.max_memory.is_some() {
+        report_line!("  {} field sections over the memory budget, read from disk while writing", deferred);
+    }
+    Ok(anim)
 }
 
-fn read_i32_vec<R: Read>(reader: &mut R, count: usize) -> Vec<i32> {
-    let mut bytes = vec![0u8; count * 4];
-    reader
-        .read_exact(&mut bytes)
-        .expect("Error in reading file");
-    let mut result = Vec::with_capacity(count);
-    for chunk in bytes.chunks_exact(4) {
-        result.push(i32::from_be_bytes([
-            chunk[0], chunk[1], chunk[2], chunk[3],
-        ]));
-    }
-    result
+fn load_prepared_anim(
+    file_name: &str,
+    cli: &CliOptions,
+    previous: Option<&derive::StateHistory>,
+) -> Result<AnimFile, Diagnostic> {
+    let mut anim = load_anim(file_name, cli)?;
+    // first, on the fields as read by the first pass of the filter
+    if let Some(filter) = &cli.time_filter {
+        filter.apply(&mut anim, file_name);
+    }
+    prepare_anim(&mut anim, cli, previous)?;
+    if let Some(check) = &cli.check_symmetry {
+        symmetry::check_symmetry(&anim, check).map_err(|e| Diagnostic::new(e.code(), e))?;
+    }
+    Ok(anim)
 }
 
-fn read_f32_vec<R: Read>(reader: &mut R, count: usize) -> Vec<f32> {
-    let mut bytes = vec![0u8; count * 4];
-    reader
-        .read_exact(&mut bytes)
-        .expect("Error in reading file");
-    let mut result = Vec::with_capacity(count);
-    for chunk in bytes.chunks_exact(4) {
-        result.push(f32::from_be_bytes([
-            chunk[0], chunk[1], chunk[2], chunk[3],
-        ]));
-    }
-    result
+// ****************************************
+// convert an A-File through a mesh writer (legacy VTK, ASCII or BINARY)
+// writing output_file_name
+// ****************************************
+fn read_radioss_anim(
+    file_name: &str,
+    cli: &CliOptions,
+    previous: Option<&derive::StateHistory>,
+    out: &mut dyn MeshWriter,
+    output_file_name: &str,
+) -> Result<AnimFile, Diagnostic> {
+    let anim = load_prepared_anim(file_name, cli, previous)?;
+    write_mesh(&anim, &cli.write, out).map_err(|e| output_error(&e, output_file_name))?;
+    Ok(anim)
 }
 
-fn read_u16_vec<R: Read>(reader: &mut R, count: usize) -> Vec<u16> {
-    let mut bytes = vec![0u8; count * 2];
-    reader
-        .read_exact(&mut bytes)
-        .expect("Error in reading file");
-    let mut result = Vec::with_capacity(count);
-    for chunk in bytes.chunks_exact(2) {
-        result.push(u16::from_be_bytes([chunk[0], chunk[1]]));
+// an output stopped by a field section read back from the input fails on the input
+fn output_error(e: &std::io::Error, output_file_name: &str) -> Diagnostic {
+    match read_error_of(e) {
+        Some(read_error) => Diagnostic::new(read_error.code(), read_error),
+        None => Diagnostic::new(codes::WRITE_FAILED, format_args!("can't write {}: {}", output_file_name, e)),
     }
-    result
 }
 
-fn read_bytes<R: Read>(reader: &mut R, count: usize) -> Vec<u8> {
-    let mut buf = vec![0u8; count];
-    reader.read_exact(&mut buf).expect("Error in reading file");
-    buf
+// ****************************************
+// read and check every input file in full, write nothing, and print
+// the read throughput: tells read-bound conversions from write-bound ones
+// ****************************************
+fn parse_only(
+    input_files: &[&String],
+    parse_mode: ParseMode,
+    text_encoding: TextEncoding,
+    endian: Option<Endian>,
+) -> report::Report {
+    let mut report = report::Report::new("anim_to_vtk");
+    let mut total_bytes = 0u64;
+    let mut total_seconds = 0f64;
+    for &file_name in input_files {
+        if !Path::new(file_name.as_str()).exists() {
+            report::error(codes::CANT_OPEN, format_args!("Input file {} does not exist", file_name));
+            report.failure(file_name, "input file does not exist");
+            continue;
+        }
+        let start = Instant::now();
+        let checked = report::for_input(file_name, || {
+            let checked = read_anim_file_with_budget(file_name, None, text_encoding, endian)
+                .map_err(|e| Diagnostic::new(e.code(), e))
+                .and_then(|mut anim| validate::validate(&mut anim, parse_mode));
+            if let Err(e) = &checked {
+                e.print();
+            }
+            checked
+        });
+        let duration = start.elapsed();
+        if let Err(e) = checked {
+            report.failure(file_name, e);
+            continue;
+        }
+        let bytes = std::fs::metadata(file_name.as_str()).map_or(0, |metadata| metadata.len());
+        let seconds = duration.as_secs_f64();
+        report_line!(
+            "Parsed {}: {:.1} MB in {:.3} s, {:.1} MB/s",
+            file_name,
+            bytes as f64 / 1e6,
+            seconds,
+            bytes as f64 / 1e6 / seconds.max(1e-9)
+        );
+        total_bytes += bytes;
+        total_seconds += seconds;
+        report.success(file_name, &[], duration);
+    }
+    if report.succeeded() > 1 {
+        report_line!(
+            "Parsed {} files: {:.1} MB in {:.3} s, {:.1} MB/s",
+            report.succeeded(),
+            total_bytes as f64 / 1e6,
+            total_seconds,
+            total_bytes as f64 / 1e6 / total_seconds.max(1e-9)
+        );
+    }
+    report
 }
 
-fn read_text<R: Read>(reader: &mut R, count: usize) -> String {
-    let buf = read_bytes(reader, count);
-    let s = std::str::from_utf8(&buf).unwrap_or("");
-    s.trim_end_matches('\0').to_string()
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+    Vtk, // legacy VTK unstructured grid
+    Vti, // VTK XML image data resampled on a regular grid
+    Npz, // NumPy arrays
+    Ndjson, // one JSON record per node and element
+    Obj, // Wavefront OBJ surface
+    Ensight, // EnSight Gold case
 }
 
+// input names, after the options of the conversion
+const INPUT_NAMES: &str = "Output files will have .vtk extension added automatically.\n\
+    Input files must have no extension and end with an uppercase letter followed by 3-4 digits.";
+
+// options that write other datasets than the legacy VTK grid of each input
+const SINGLE_GRID: [&str; 5] = ["modes", "group_by", "multiblock", "no_pad", "partition_by_type"];
+
 // ****************************************
-// replace ' ' with '_'
+// Command line: convert, the default, or one of the tools of the binary
 // ****************************************
-fn replace_underscore(s: &str) -> String {
-    s.replace(' ', "_")
+#[derive(Parser)]
+#[command(
+    name = "anim_to_vtk",
+    version,
+    about = "Convert OpenRadioss animation files (A-files) to legacy VTK, ASCII or BINARY, and other formats",
+    args_conflicts_with_subcommands = true,
+    after_help = INPUT_NAMES
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    convert: CliOptions,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Convert A-files, the default without a subcommand", arg_required_else_help = true, after_help = INPUT_NAMES)]
+    Convert(Box<CliOptions>),
+    #[command(about = "Print what A-files hold, as anim_info")]
+    Info(anim_info::InfoArgs),
+    #[command(about = "Print the differences between the headers of two A-files, as anim_header_diff")]
+    HeaderDiff(header_diff::HeaderDiffArgs),
 }
 
 // ****************************************
-// VtkWriter - abstraction for VTK output in binary or ASCII format
+// Command line options
 // ****************************************
-struct VtkWriter<W: Write> {
-    writer: BufWriter<W>,
+#[derive(Args)]
+struct CliOptions {
+    #[arg(short, long, help = "Output in binary VTK format (default is ASCII)")]
     binary: bool,
+    #[arg(short, long, help = "Match C++ ASCII float formatting (default uses fast shortest)")]
     legacy: bool,
-    scratch: Vec<u8>,
-    itoa_buf: ItoaBuffer,
-    ryu_buf: RyuBuffer,
+    #[arg(long, help = "Reorder inverted solids to the VTK convention, flag unfixable ones as INVERTED")]
+    fix_orientation: bool,
+    #[arg(skip)]
+    write: WriteOptions, // the options above and those of the arrays written, gathered
+    #[arg(
+        long,
+        value_enum,
+        ignore_case = true,
+        default_value = "vtk",
+        help = "Output format; vti resamples the solids on a regular grid, <input>.vti; npz writes NumPy arrays and \
+                their schema, <input>.npz; ndjson one JSON line per node and element, <input>.ndjson; obj the outer \
+                surface and the beams, <input>.obj; ensight EnSight Gold geometry and variable files, <input>.geo and \
+                <input>.<variable>, with a case file per series of inputs, <prefix>.case"
+    )]
+    format: OutputFormat,
+    #[arg(long, value_name = "DIR", help = "Write the outputs to <DIR>, created if needed (default the directory of each input)")]
+    output_dir: Option<String>, // directory of the outputs, that of each input without
+    #[arg(
+        short,
+        long = "output",
+        value_name = "TEMPLATE",
+        value_parser = output_template,
+        help = "Base name of the outputs of each input, from {name} (runA001), {run} (run) and {state} (001), \
+                e.g. \"{run}_{state}\" (default {name}); extensions and suffixes are appended"
+    )]
+    output_template: Option<String>, // base name of the outputs, from {name}, {run} and {state} of the input
+    #[arg(long, value_name = "H", value_parser = positive_length, required_if_eq("format", "vti"), help = "Grid spacing of --format vti")]
+    spacing: Option<f64>, // grid spacing of --format vti
+    #[arg(
+        long,
+        value_name = "SPEC",
+        value_parser = sph_project::SphProjection::parse,
+        conflicts_with_all = SINGLE_GRID,
+        help = "Also project SPH scalars on a grid, <input>_sph.vti (\"spacing=<h>[;radius=<r>][;fields=<names>]\")"
+    )]
+    sph_project: Option<sph_project::SphProjection>, // SPH scalars projected on a grid
+    #[arg(
+        long,
+        conflicts_with_all = SINGLE_GRID,
+        help = "Write the SPH particles to <input>_sph.vtp, as points with a RADIUS, instead of the cells of the grid"
+    )]
+    split_sph: bool,
+    #[arg(
+        long,
+        value_name = "NAMES",
+        value_delimiter = ',',
+        help = "Fields written by --format vti, npz, ndjson and ensight (comma separated, default all)"
+    )]
+    fields: Vec<String>, // fields written by --format vti, npz, ndjson and ensight (all when empty)
+    #[arg(
+        long,
+        value_name = "PATTERNS",
+        value_parser = vars::VarSelection::parse,
+        help = "Result variables written, comma separated names or regular expressions matching the whole name, \
+                ignoring case (e.g. \"VON MISES,PRESSURE,DISPLACEMENT.*\"), repeatable; default all"
+    )]
+    vars: Vec<vars::VarSelection>,
+    #[arg(long, help = "Write the CRC32, offset and size of every array of the VTK output in <output>.crc32", conflicts_with_all = SINGLE_GRID)]
+    checksums: bool,
+    #[arg(long, help = "Write the min and max of every scalar array and vector magnitude in FieldData (RANGE_<name>)")]
+    ranges: bool,
+    #[arg(
+        long = "lut",
+        value_name = "FIELD:COLORMAP:MIN:MAX",
+        value_parser = lut::Lut::parse,
+        help = "Color a scalar array with a named lookup table (jet, viridis, coolwarm, hot, gray), repeatable"
+    )]
+    luts: Vec<lut::Lut>,
+    #[arg(long, help = "Write PART_COLOR, an RGB cell array with a color per part that depends on PART_ID only")]
+    part_colors: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_parser = part_colors::PartColors::read_palette,
+        help = "Colors of --part-colors, one \"r g b\" (0-255) palette entry or \"<part id>: r g b\" per line"
+    )]
+    part_palette: Option<part_colors::PartColors>,
+    #[arg(
+        long,
+        value_name = "NAMES",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        help = "Write each mode shape vector as a separate deformed output: the nodal vectors whose name contains MODE, \
+                or those listed (comma separated, e.g. --modes=EIGEN1,EIGEN2)"
+    )]
+    modes: Option<Vec<String>>, // expand mode shapes into separate outputs, the vectors named like MODE without names
+    #[arg(long, value_name = "S", default_value_t = 1.0, help = "Amplitude of the mode shapes")]
+    mode_scale: f32,    // amplitude applied to the mode shapes
+    #[arg(long, value_name = "N", default_value_t = 0, help = "Write <N> phase steps per mode as a harmonic time series")]
+    mode_phases: usize, // phase steps per mode (0: one static output per mode)
+    #[arg(
+        long,
+        value_name = "material",
+        value_parser = group_by,
+        conflicts_with = "modes",
+        help = "Write one output per material, <input>_material<k>.vtk"
+    )]
+    group_by: Option<GroupBy>, // split the outputs by material
+    #[arg(
+        long,
+        conflicts_with_all = ["modes", "group_by"],
+        help = "Write <input>.vtm, one VTK XML file per part in <input>_parts/ under the subset hierarchy"
+    )]
+    multiblock: bool,             // one file per part under the subset tree (.vtm)
+    #[arg(
+        long,
+        conflicts_with_all = ["modes", "group_by"],
+        help = "Write the elemental arrays on the cells of their type only: <input>.vtm, one VTK XML file per element \
+                type in <input>_types/, or per part with --multiblock"
+    )]
+    no_pad: bool,                 // elemental arrays on the cells of their type only (.vtm)
+    #[arg(
+        long,
+        conflicts_with_all = ["modes", "group_by", "multiblock", "no_pad"],
+        help = "Write <input>.pvtu, one VTK XML piece per element type in <input>_pieces/"
+    )]
+    partition_by_type: bool,      // one piece per element type (.pvtu)
+    #[arg(
+        long = "lod",
+        value_name = "N",
+        value_parser = at_least_one,
+        default_value_t = 1,
+        conflicts_with_all = SINGLE_GRID,
+        help = "Also write <N>-1 decimated levels, <input>_lod<k>.vtk, and their index <input>_lod.json"
+    )]
+    lod_levels: usize,        // full mesh and decimated levels written per input
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_parser = subset::read_id_file,
+        help = "Extract the nodes listed by NODE_ID in <FILE>, with the elements made of them only"
+    )]
+    node_set: Option<HashSet<i32>>,    // user ids of the nodes to extract
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_parser = subset::read_id_file,
+        help = "Extract the elements listed by ELEMENT_ID in <FILE>, with their nodes"
+    )]
+    element_set: Option<HashSet<i32>>, // user ids of the elements to extract
+    #[arg(
+        long,
+        value_name = "LIST",
+        help = "Extract the parts listed by id or name glob (comma separated, e.g. 12,\"door*\"), with their nodes"
+    )]
+    include_parts: Vec<String>,
+    #[arg(long, value_name = "LIST", help = "Leave out the parts listed by id or name glob")]
+    exclude_parts: Vec<String>,
+    #[arg(skip)]
+    parts: part_filter::PartFilter,    // parts to extract, by id or name, from the two above
+    #[arg(long, help = "Leave the eroded elements (EROSION_STATUS 0) out of the output cells")]
+    remove_eroded: bool,          // leave the eroded elements out of the output
+    #[arg(
+        long,
+        value_name = "EXPR",
+        value_parser = select::CellSelection::parse,
+        help = "Keep the cells where <EXPR> holds over the cell arrays, with their nodes (e.g. \"PART_ID in (10,12) \
+                && Von_Mises > 200\", with ==, !=, <, <=, >, >=, &&, || and !)"
+    )]
+    select_cells: Option<select::CellSelection>, // expression over the cell arrays, cells kept where it holds
+    #[arg(long, help = "Split quads into triangles")]
+    triangulate: bool,        // split quads into triangles
+    #[arg(long, help = "Split quads into triangles and hexahedra into tetrahedra")]
+    triangulate_solids: bool, // also split hexahedra into tetrahedra
+    #[arg(long, help = "Remove duplicated cells (same node set), count them in DUPLICATE_COUNT")]
+    dedupe_cells: bool,       // remove cells with identical node sets
+    #[arg(long, help = "Label connected components of active cells in COMPONENT_ID")]
+    components: bool,         // label connected components (COMPONENT_ID)
+    #[arg(
+        long,
+        value_name = "RADIUS",
+        value_parser = positive_length,
+        help = "Count SPH neighbors within <RADIUS> in SPH_NEIGHBOR_COUNT"
+    )]
+    sph_density: Option<f64>, // neighbor search radius for SPH_NEIGHBOR_COUNT
+    #[arg(
+        long = "derive",
+        value_name = "NAMES",
+        value_delimiter = ',',
+        value_parser = derived,
+        help = "Add arrays derived from the stress tensors (comma separated: i1, j2, triaxiality, lode, von-mises, \
+                principal), the plastic strain rate between consecutive inputs (epsdot), or the magnitude of the \
+                velocity, acceleration and displacement vectors (magnitude)"
+    )]
+    derived: Vec<derive::Derived>, // fields derived from the stress tensors
+    #[arg(
+        long,
+        value_name = "A-FILE",
+        help = "Add a DISPLACEMENT nodal vector, the coordinates minus those of the reference A-file"
+    )]
+    displacement_from: Option<String>, // reference A-file of the DISPLACEMENT vector
+    #[arg(skip)]
+    reference: Option<derive::ReferenceState>, // its coordinates, read before the run
+    #[arg(
+        long,
+        value_name = "NAMES",
+        value_delimiter = ',',
+        help = "Add the running sum over the inputs of the listed elemental scalars (comma separated)"
+    )]
+    accumulate: Vec<String>,      // elemental increments summed over the states
+    #[arg(
+        long,
+        value_name = "STEP",
+        value_parser = positive_length,
+        help = "Round coordinates and fields to multiples of <STEP> (e.g. 1e-4)"
+    )]
+    quantize: Option<f64>,    // rounding step of coordinates and fields
+    #[arg(
+        long,
+        help = "Replace titles and part, subset, material and property names by generic ones, renumber node, element \
+                and part ids"
+    )]
+    anonymize: bool,          // generic names, renumbered ids
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "anonymize",
+        help = "Key of the --anonymize renumbering, the same ids at every run (default random)"
+    )]
+    anonymize_seed: Option<u64>, // key of the renumbering and of the transform, random without
+    #[arg(long, requires = "anonymize", help = "With --anonymize, also move the model by a random rotation and translation")]
+    anonymize_transform: bool,   // random rigid transform of the model too
+    #[arg(skip)]
+    anonymizer: Option<anonymize::Anonymizer>, // built from the three above, before the run
+    #[arg(
+        long = "smooth-field",
+        value_name = "NAME[:ITERATIONS]",
+        value_parser = smooth_field,
+        help = "Laplacian smoothing of a nodal field (repeatable, default 1 iteration)"
+    )]
+    smooth_fields: Vec<(String, usize)>, // nodal fields to smooth, with their iteration count
+    #[arg(
+        long,
+        value_name = "sgolay|mean:WINDOW[:NAMES]",
+        value_parser = time_filter::TimeFilter::parse,
+        help = "Filter fields over a window of input states, matched by node and element ids (e.g. sgolay:7)"
+    )]
+    time_filter: Option<time_filter::TimeFilter>, // fields filtered across the input states
+    #[arg(long, value_name = "CSV", help = "Track detached fragments across the input files, write their mass and position")]
+    track_debris: Option<String>, // CSV of fragments tracked across the input states
+    #[arg(
+        long = "trajectories",
+        value_name = "IDS.TXT",
+        value_parser = trajectory_nodes,
+        help = "Write the positions of the listed nodes over the states, as CSV and polylines (.vtp)"
+    )]
+    trajectory_nodes: Option<HashSet<i32>>, // user ids of the nodes whose paths are written
+    #[arg(
+        long,
+        value_name = "NAME",
+        default_value = "trajectories",
+        requires = "trajectory_nodes",
+        help = "Files of --trajectories, <NAME>.csv and <NAME>.vtp"
+    )]
+    trajectory_output: String,    // .csv and .vtp of the paths, without extension
+    #[arg(
+        long = "sum-vectors-by-part",
+        value_name = "NAMES",
+        value_delimiter = ',',
+        help = "Sum the listed nodal vectors (comma separated) over each part, per state"
+    )]
+    sum_vectors: Vec<String>,     // nodal vectors summed per part
+    #[arg(long, value_name = "CSV", default_value = "part_sums.csv", requires = "sum_vectors", help = "File for --sum-vectors-by-part")]
+    sum_output: String,           // CSV of the per part sums
+    #[arg(long = "cog", value_name = "CSV", help = "Write center of gravity, velocity and inertia per part and state")]
+    cog_output: Option<String>,   // CSV of centers of gravity per part and state
+    #[arg(long, value_name = "CSV", help = "Write the shell area and solid volume of the active elements per part and state")]
+    geometry_report: Option<String>, // CSV of shell area and solid volume per part and state
+    #[arg(long, value_name = "CSV", help = "Write the id, name, element type, material and property of every part")]
+    part_table: Option<String>,   // CSV of the parts: id, name, type, material, property
+    #[arg(long, value_name = "DB", value_parser = catalog_path, help = "Record converted states and field ranges in an SQLite database")]
+    catalog: Option<String>,      // SQLite index of the converted states
+    #[arg(long, value_name = "FILE", help = "Keep conversion counters and durations in a Prometheus text file")]
+    metrics: Option<String>,      // Prometheus text file of conversion counters
+    #[arg(long, value_name = "FILE", help = "Write the outcome of every input file as JSON")]
+    report_json: Option<String>,  // JSON outcome of every input file
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Also write the warnings and errors, with their code (W001, E201...), as JSON lines"
+    )]
+    diagnostics: Option<String>,  // JSON lines of the coded warnings and errors
+    #[arg(
+        long,
+        value_name = "FILE.HTML",
+        help = "Write a static HTML summary of the run: eroded elements and per part maxima versus time, links to the \
+                outputs"
+    )]
+    dashboard: Option<String>,    // HTML summary of the run
+    #[arg(
+        long,
+        value_name = "FILE.PVD",
+        conflicts_with = "modes",
+        help = "Write a ParaView collection of the converted states with their times, to load them as a time series"
+    )]
+    pvd: Option<String>,          // ParaView collection of the converted states
+    #[arg(long, value_name = "FILE.JSON", help = "Write the frames of the --format obj sequence with their files and times")]
+    obj_manifest: Option<String>, // frames of the --format obj sequence
+    #[arg(
+        long,
+        value_name = "FILE.PY",
+        conflicts_with_all = SINGLE_GRID,
+        help = "Write a ParaView Python script opening the converted states, colored and warped by the displacement"
+    )]
+    paraview_script: Option<String>, // ParaView Python script opening the converted states
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "paraview_script",
+        help = "Field the ParaView script colors by (default: the first elemental scalar)"
+    )]
+    color_by: Option<String>,     // field the ParaView script colors by
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        conflicts_with_all = SINGLE_GRID,
+        help = "Write <N> frames interpolated linearly between two converted states, matched by node and element ids"
+    )]
+    interpolate_frames: usize,    // frames interpolated between two converted states
+    #[arg(
+        long,
+        value_name = "FILE.TAR.ZST",
+        value_parser = archive_path,
+        help = "Pack the outputs of the run, with a MANIFEST of their CRC32, into a compressed archive"
+    )]
+    archive: Option<String>,      // .tar.zst of the outputs of the run
+    #[arg(
+        long,
+        value_name = "FILE.USDA",
+        value_parser = usd_path,
+        help = "Write an OpenUSD stage of the skin of the converted states, one time sample per state"
+    )]
+    usd: Option<String>,          // OpenUSD stage of the skin of the converted states
+    #[arg(
+        long,
+        value_name = "NAMES",
+        value_delimiter = ',',
+        requires = "usd",
+        help = "One or two scalars written as primvars of the USD stage (comma separated)"
+    )]
+    usd_primvars: Vec<String>,    // scalars of the stage, at most two
+    #[arg(
+        long,
+        help = "Write no output for the states without any element (pre-initialization states), with a notice"
+    )]
+    skip_empty: bool,             // states without any element give no output
+    #[arg(
+        long,
+        help = "Fail on any anomaly of an input file (unknown flags, trailing bytes, empty part tables, inconsistencies)"
+    )]
+    strict: bool,
+    #[arg(
+        long,
+        conflicts_with = "strict",
+        help = "Repair inconsistencies (part tables not matching the element count, nodes out of range) with a warning; \
+                default: warn on unknown flags, trailing bytes and empty part tables, fail on inconsistencies"
+    )]
+    lenient: bool,
+    #[arg(skip)]
+    parse_mode: ParseMode,        // --strict / --lenient handling of anomalies
+    #[arg(
+        long,
+        value_name = "SPEC",
+        value_parser = symmetry::SymmetryCheck::parse,
+        help = "Pair nodes across a mirror plane and report the asymmetry of the geometry and nodal fields \
+                (\"plane=y0[;tol=1e-3][;fields=<names>]\")"
+    )]
+    check_symmetry: Option<symmetry::SymmetryCheck>, // mirror plane the states are checked against
+    #[arg(
+        long,
+        value_name = "utf8|latin1|shiftjis",
+        value_parser = text_encoding,
+        default_value = "utf8",
+        help = "Encoding of the titles, part, material and field names"
+    )]
+    text_encoding: TextEncoding,  // decoding of the text records
+    #[arg(
+        long,
+        value_name = "big|little",
+        value_parser = endian,
+        help = "Byte order of the A-files, detected from their magic number by default"
+    )]
+    endian: Option<Endian>,       // byte order of the A-files, detected without
+    #[arg(long, help = "Print every section of the input files with its byte range and first values, no conversion")]
+    dump_layout: bool,            // print the section layout instead of converting
+    #[arg(long, help = "Read and check the input files in full, write nothing, print the read throughput in MB/s")]
+    parse_only: bool,             // read and check the inputs, write nothing
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = memory_size,
+        help = "Keep at most <SIZE> (e.g. 512M, 8G) of field data in memory, read other fields from disk while \
+                writing (default none kept)"
+    )]
+    max_memory: Option<u64>,      // bytes of field data kept in memory
+    #[arg(long, help = "Convert the input files in the order of their simulation time instead of the command line order")]
+    sort_by_time: bool,           // convert in the order of the header times
+    #[arg(long, help = "Of the input files with the same simulation time (restarts), convert only the last one listed")]
+    dedupe_times: bool,           // keep the last listed file of each time
+    #[arg(
+        long,
+        value_name = "START:END",
+        value_parser = time_order::TimeRange::parse,
+        help = "Convert only the input files with a header time in [START, END] (e.g. \"0.02:0.08\", \":0.08\")"
+    )]
+    time_range: Option<time_order::TimeRange>, // window of header times of the converted files
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = at_least_one,
+        default_value_t = 1,
+        help = "Convert only the first input file of every <N> (after --sort-by-time and --time-range)"
+    )]
+    every: usize,                 // convert one file out of every, 1 for all
+    #[arg(
+        long,
+        value_name = "I/N",
+        value_parser = shard,
+        help = "Convert only the I-th of N contiguous slices of the input files (I from 0)"
+    )]
+    shard: Option<(usize, usize)>, // (index, count): convert only this part of the input list
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = at_least_one,
+        default_value_t = 1,
+        help = "Convert N input files at the same time, messages and results still in input order"
+    )]
+    jobs: usize,                   // files converted at the same time
+    #[arg(value_name = "A-FILE")]
+    input_files: Vec<String>,
 }
 
-impl<W: Write> VtkWriter<W> {
-    fn new(writer: W, binary: bool, legacy: bool) -> Self {
-        VtkWriter {
-            writer: BufWriter::new(writer),
-            binary,
-            legacy,
-            scratch: Vec::with_capacity(256),
-            itoa_buf: ItoaBuffer::new(),
-            ryu_buf: RyuBuffer::new(),
-        }
+impl CliOptions {
+    // transformations that rewrite the fields need all of them in memory
+    fn needs_all_fields(&self) -> bool {
+        self.dedupe_cells
+            || self.triangulate
+            || self.triangulate_solids
+            || !self.smooth_fields.is_empty()
+            || self.time_filter.is_some()
+            || !self.derived.is_empty()
+            || self.displacement_from.is_some()
+            || !self.accumulate.is_empty()
+            || self.quantize.is_some()
+            || self.anonymize
+            || self.group_by.is_some()
+            || self.parse_mode == ParseMode::Lenient
+            || self.node_set.is_some()
+            || !self.parts.is_empty()
+            || self.remove_eroded
+            || self.select_cells.is_some()
+            || self.interpolate_frames > 0
+            || self.writes_blocks()
+            || self.element_set.is_some()
+            || self.lod_levels > 1
     }
 
-    fn write_legacy_float_ascii(&mut self, val: f64) {
-        let mut buf = [0u8; 64];
-        let fmt = b"%.6g\0";
-        let written = unsafe {
-            snprintf(
-                buf.as_mut_ptr() as *mut c_char,
-                buf.len(),
-                fmt.as_ptr() as *const c_char,
-                val,
-            )
-        };
-        let len = if written < 0 { 0 } else { written as usize };
-        self.writer.write_all(&buf[..len]).unwrap();
+    // plastic strain rates, computed from the previous state of the batch
+    fn derives_rates(&self) -> bool {
+        self.derived.contains(&derive::Derived::EpsDot)
     }
 
-    fn write_i32(&mut self, val: i32) {
-        if self.binary {
-            self.writer.write_all(&val.to_be_bytes()).unwrap();
-        } else {
-            self.scratch.clear();
-            let s = self.itoa_buf.format(val);
-            self.scratch.extend_from_slice(s.as_bytes());
-            self.scratch.push(b'\n');
-            self.writer.write_all(&self.scratch).unwrap();
-        }
+    // fields computed from the previous state of the batch
+    fn keeps_history(&self) -> bool {
+        self.derives_rates() || !self.accumulate.is_empty()
     }
 
-    fn write_f32(&mut self, val: f32) {
-        if self.binary {
-            self.writer.write_all(&val.to_be_bytes()).unwrap();
-        } else if self.legacy {
-            self.write_legacy_float_ascii(val as f64);
-            self.writer.write_all(b"\n").unwrap();
-        } else {
-            self.scratch.clear();
-            let s = self.ryu_buf.format(val);
-            self.scratch.extend_from_slice(s.as_bytes());
-            self.scratch.push(b'\n');
-            self.writer.write_all(&self.scratch).unwrap();
-        }
-    }
-
-    // Bulk write f32 values from a slice - more efficient than individual writes
-    fn write_f32_slice(&mut self, values: &[f32]) {
-        if self.binary {
-            for &val in values {
-                self.writer.write_all(&val.to_be_bytes()).unwrap();
-            }
-        } else if self.legacy {
-            for &val in values {
-                self.write_legacy_float_ascii(val as f64);
-                self.writer.write_all(b"\n").unwrap();
-            }
-        } else {
-            for &val in values {
-                self.scratch.clear();
-                let s = self.ryu_buf.format(val);
-                self.scratch.extend_from_slice(s.as_bytes());
-                self.scratch.push(b'\n');
-                self.writer.write_all(&self.scratch).unwrap();
-            }
-        }
+    // one legacy VTK unstructured grid per input, the output that
+    // interpolated frames and the ParaView script go with
+    fn writes_single_grid(&self) -> bool {
+        self.format == OutputFormat::Vtk && self.modes.is_none() && self.group_by.is_none() && !self.writes_blocks()
     }
 
-    fn write_f64(&mut self, val: f64) {
-        if self.binary {
-            self.writer.write_all(&val.to_be_bytes()).unwrap();
-        } else if self.legacy {
-            self.write_legacy_float_ascii(val);
-            self.writer.write_all(b"\n").unwrap();
-        } else {
-            self.scratch.clear();
-            let s = self.ryu_buf.format(val);
-            self.scratch.extend_from_slice(s.as_bytes());
-            self.scratch.push(b'\n');
-            self.writer.write_all(&self.scratch).unwrap();
-        }
+    // VTK XML unstructured grids under a .vtm or .pvtu instead of the legacy VTK grid
+    fn writes_blocks(&self) -> bool {
+        self.multiblock || self.no_pad || self.partition_by_type
     }
 
-    fn write_f32_triple(&mut self, a: f32, b: f32, c: f32) {
-        if self.binary {
-            self.writer.write_all(&a.to_be_bytes()).unwrap();
-            self.writer.write_all(&b.to_be_bytes()).unwrap();
-            self.writer.write_all(&c.to_be_bytes()).unwrap();
-        } else if self.legacy {
-            self.write_legacy_float_ascii(a as f64);
-            self.writer.write_all(b" ").unwrap();
-            self.write_legacy_float_ascii(b as f64);
-            self.writer.write_all(b" ").unwrap();
-            self.write_legacy_float_ascii(c as f64);
-            self.writer.write_all(b"\n").unwrap();
-        } else {
-            self.scratch.clear();
-            let sa = self.ryu_buf.format(a);
-            self.scratch.extend_from_slice(sa.as_bytes());
-            self.scratch.push(b' ');
-            let sb = self.ryu_buf.format(b);
-            self.scratch.extend_from_slice(sb.as_bytes());
-            self.scratch.push(b' ');
-            let sc = self.ryu_buf.format(c);
-            self.scratch.extend_from_slice(sc.as_bytes());
-            self.scratch.push(b'\n');
-            self.writer.write_all(&self.scratch).unwrap();
+    // path the outputs of an input are named after, the input itself
+    // without --output-dir and --output
+    fn output_base(&self, file_name: &str) -> String {
+        if self.output_dir.is_none() && self.output_template.is_none() {
+            return file_name.to_string();
         }
+        let path = Path::new(file_name);
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or(file_name);
+        let base = match &self.output_template {
+            Some(template) => expand_output_template(template, name),
+            None => name.to_string(),
+        };
+        let dir = match &self.output_dir {
+            Some(dir) => Path::new(dir),
+            None => path.parent().unwrap_or(Path::new("")),
+        };
+        dir.join(base).to_string_lossy().into_owned()
     }
 
-    fn write_zeros_f32(&mut self, count: usize) {
-        if self.binary {
-            let zero_bytes = 0f32.to_be_bytes();
-            for _ in 0..count {
-                self.writer.write_all(&zero_bytes).unwrap();
-            }
-        } else {
-            for _ in 0..count {
-                self.writer.write_all(b"0\n").unwrap();
+    // options of another output format than the one they write, refused
+    // as clap refuses conflicting options
+    fn check_formats(&self) -> Result<(), clap::Error> {
+        use OutputFormat::*;
+        let checks: [(bool, &str, &[OutputFormat]); 15] = [
+            (self.modes.is_some(), "--modes", &[Vtk]),
+            (self.group_by.is_some(), "--group-by", &[Vtk]),
+            (self.lod_levels > 1, "--lod", &[Vtk]),
+            (self.multiblock, "--multiblock", &[Vtk]),
+            (self.no_pad, "--no-pad", &[Vtk]),
+            (self.partition_by_type, "--partition-by-type", &[Vtk]),
+            (self.paraview_script.is_some(), "--paraview-script", &[Vtk]),
+            (self.interpolate_frames > 0, "--interpolate-frames", &[Vtk]),
+            (self.checksums, "--checksums", &[Vtk]),
+            (self.sph_project.is_some(), "--sph-project", &[Vtk, Npz, Ndjson]),
+            (self.split_sph, "--split-sph", &[Vtk, Npz, Ndjson]),
+            (self.part_colors, "--part-colors", &[Vtk, Npz, Ndjson]),
+            (self.part_palette.is_some(), "--part-palette", &[Vtk, Npz, Ndjson]),
+            (self.pvd.is_some(), "--pvd", &[Vtk, Vti]),
+            (self.obj_manifest.is_some(), "--obj-manifest", &[Obj]),
+        ];
+        match checks.iter().find(|(given, _, formats)| *given && !formats.contains(&self.format)) {
+            Some((_, flag, _)) => {
+                let format = self.format.to_possible_value().map_or(String::new(), |value| value.get_name().to_string());
+                let message = format!("the argument '{}' cannot be used with '--format {}'", flag, format);
+                Err(Cli::command().error(ErrorKind::ArgumentConflict, message))
             }
+            None => Ok(()),
         }
     }
 
-    fn write_zero_tensor(&mut self) {
-        if self.binary {
-            self.write_zeros_f32(9);
-        } else if self.legacy {
-            for _ in 0..3 {
-                self.write_legacy_float_ascii(0.0);
-                self.writer.write_all(b" ").unwrap();
-                self.write_legacy_float_ascii(0.0);
-                self.writer.write_all(b" ").unwrap();
-                self.write_legacy_float_ascii(0.0);
-                self.writer.write_all(b"\n").unwrap();
+    // the options as parsed gathered into those of the writers, the part
+    // filter and the parse mode
+    fn resolve(mut self) -> Result<CliOptions, clap::Error> {
+        self.check_formats()?;
+        self.write.binary = self.binary;
+        self.write.legacy = self.legacy;
+        self.write.fix_orientation = self.fix_orientation;
+        self.write.checksums = self.checksums;
+        self.write.ranges = self.ranges;
+        self.write.split_sph = self.split_sph;
+        self.write.luts = std::mem::take(&mut self.luts);
+        for selection in std::mem::take(&mut self.vars) {
+            match self.write.vars.as_mut() {
+                Some(vars) => vars.extend(selection),
+                None => self.write.vars = Some(selection),
             }
+        }
+        self.write.part_colors = match self.part_palette.take() {
+            Some(palette) => Some(palette),
+            None if self.part_colors => Some(part_colors::PartColors::default()),
+            None => None,
+        };
+        for list in &self.include_parts {
+            part_filter::PartFilter::add(&mut self.parts.include, list);
+        }
+        for list in &self.exclude_parts {
+            part_filter::PartFilter::add(&mut self.parts.exclude, list);
+        }
+        self.parse_mode = if self.strict {
+            ParseMode::Strict
+        } else if self.lenient {
+            ParseMode::Lenient
         } else {
-            for _ in 0..3 {
-                self.writer.write_all(b"0 0 0\n").unwrap();
+            ParseMode::Default
+        };
+        for names in [&mut self.fields, &mut self.accumulate, &mut self.sum_vectors, &mut self.usd_primvars] {
+            names.retain(|name| !name.is_empty());
+        }
+        if let Some(names) = &mut self.modes {
+            names.retain(|name| !name.is_empty());
+        }
+        let mut derived = Vec::new();
+        for name in std::mem::take(&mut self.derived) {
+            if !derived.contains(&name) {
+                derived.push(name);
             }
         }
+        self.derived = derived;
+        if self.usd_primvars.len() > 2 {
+            let message = "--usd-primvars takes at most two scalars";
+            return Err(Cli::command().error(ErrorKind::TooManyValues, message));
+        }
+        Ok(self)
     }
+}
 
-    fn write_header(&mut self, text: &str) {
-        self.writer.write_all(text.as_bytes()).unwrap();
-        self.writer.write_all(b"\n").unwrap();
-    }
 
-    fn newline(&mut self) {
-        self.writer.write_all(b"\n").unwrap();
-    }
+const OUTPUT_PLACEHOLDERS: [&str; 3] = ["{name}", "{run}", "{state}"];
 
-    fn flush(&mut self) {
-        self.writer.flush().unwrap();
-    }
+// "runA001": {name} runA001, {run} run, {state} 001
+fn expand_output_template(template: &str, name: &str) -> String {
+    let run = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let state = &name[run.len()..];
+    let run = run.strip_suffix(|c: char| c.is_ascii_uppercase()).unwrap_or(run);
+    template.replace("{name}", name).replace("{run}", run).replace("{state}", state)
+}
 
-    fn write_i32_line(&mut self, values: &[i32]) {
-        if self.binary {
-            for &v in values {
-                self.writer.write_all(&v.to_be_bytes()).unwrap();
-            }
-        } else {
-            self.scratch.clear();
-            for (i, &v) in values.iter().enumerate() {
-                if i > 0 {
-                    self.scratch.push(b' ');
-                }
-                let s = self.itoa_buf.format(v);
-                self.scratch.extend_from_slice(s.as_bytes());
+// placeholders other than those of expand_output_template
+fn check_output_template(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let placeholder = rest[start..].find('}').map(|end| &rest[start..=start + end]);
+        match placeholder {
+            Some(placeholder) if OUTPUT_PLACEHOLDERS.contains(&placeholder) => rest = &rest[start + placeholder.len()..],
+            _ => {
+                let expected = OUTPUT_PLACEHOLDERS.join(", ");
+                return Err(format!("unknown placeholder in '{}', expected {}", template, expected));
             }
-            self.scratch.push(b'\n');
-            self.writer.write_all(&self.scratch).unwrap();
         }
     }
+    if template.is_empty() || template.contains(['/', '\\']) {
+        return Err(format!("'{}' is not a file name, use --output-dir for the directory", template));
+    }
+    Ok(())
 }
 
 // ****************************************
-// Small fixed-size dedup helpers
+// values of the options, checked while parsing
 // ****************************************
-fn unique_count(nodes: &[i32]) -> usize {
-    let mut uniq = [0i32; 8];
-    let mut count = 0usize;
-    for &n in nodes {
-        let mut seen = false;
-        for i in 0..count {
-            if uniq[i] == n {
-                seen = true;
-                break;
-            }
-        }
-        if !seen {
-            uniq[count] = n;
-            count += 1;
-        }
+fn positive_length(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(length) if length > 0.0 && length.is_finite() => Ok(length),
+        _ => Err("expected a positive length".to_string()),
     }
-    count
 }
 
-fn unique_sorted_4(nodes: &[i32]) -> Option<[i32; 4]> {
-    let mut uniq = [0i32; 8];
-    let mut count = 0usize;
-    for &n in nodes {
-        let mut seen = false;
-        for i in 0..count {
-            if uniq[i] == n {
-                seen = true;
-                break;
-            }
-        }
-        if !seen {
-            uniq[count] = n;
-            count += 1;
-        }
-    }
-    if count == 4 {
-        let mut arr = [uniq[0], uniq[1], uniq[2], uniq[3]];
-        arr.sort_unstable();
-        Some(arr)
-    } else {
-        None
+fn at_least_one(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err("expected a positive number".to_string()),
     }
 }
 
-// ****************************************
-// Helper function: resolve part ID for an element
-// Advances part_index at part boundaries and parses part ID from text
-// ****************************************
-fn resolve_part_id(
-    iel: usize,           // Element index
-    part_index: &mut usize, // Current part index (mutated at boundaries)
-    def_part: &[i32],     // Element indices where parts begin
-    p_text: &[String],    // Part ID strings (to be parsed as integers)
-) -> i32 {
-    if *part_index < def_part.len() && iel == def_part[*part_index] as usize {
-        *part_index += 1;
-    }
-    if *part_index < p_text.len() {
-        atoi_prefix(&p_text[*part_index])
-    } else {
-        0
-    }
+fn group_by(value: &str) -> Result<GroupBy, String> {
+    GroupBy::parse(value).ok_or_else(|| "expected material".to_string())
 }
 
-// Match C/C++ atoi behavior: parse leading integer prefix, ignore trailing text.
-fn atoi_prefix(text: &str) -> i32 {
-    let bytes = text.as_bytes();
-    let mut idx = 0;
-    while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
-        idx += 1;
-    }
-    let mut sign: i32 = 1;
-    if idx < bytes.len() {
-        if bytes[idx] == b'-' {
-            sign = -1;
-            idx += 1;
-        } else if bytes[idx] == b'+' {
-            idx += 1;
-        }
-    }
-    let mut value: i32 = 0;
-    let mut seen_digit = false;
-    while idx < bytes.len() && bytes[idx].is_ascii_digit() {
-        seen_digit = true;
-        value = value.saturating_mul(10)
-            .saturating_add((bytes[idx] - b'0') as i32);
-        idx += 1;
-    }
-    if seen_digit { sign.saturating_mul(value) } else { 0 }
+fn derived(name: &str) -> Result<derive::Derived, String> {
+    derive::Derived::parse(name)
+        .ok_or_else(|| "expected i1, j2, triaxiality, lode, von-mises, principal, epsdot or magnitude".to_string())
 }
 
-// ****************************************
-// Helper function: write per-cell i32 values from multiple slices
-// ****************************************
-fn write_cell_i32_values<W: Write>(
-    writer: &mut VtkWriter<W>,
-    slices: &[&[i32]],
-) {
-    for slice in slices {
-        for &val in *slice {
-            writer.write_i32(val);
-        }
+// "name" or "name:iterations"
+fn smooth_field(value: &str) -> Result<(String, usize), String> {
+    match value.rsplit_once(':') {
+        Some((name, iterations)) => match iterations.parse() {
+            Ok(iterations) => Ok((name.to_string(), iterations)),
+            Err(_) => Err(format!("invalid iteration count '{}'", iterations)),
+        },
+        None => Ok((value.to_string(), 1)),
     }
-    writer.newline();
 }
 
-// ****************************************
-// Helper function: write elemental scalar field with zero-padding
-// ****************************************
-fn write_elemental_scalar<W: Write>(
-    writer: &mut VtkWriter<W>,
-    name: &str,
-    counts: &[usize],       // [nb_1d, nb_2d, nb_3d, nb_sph]
-    active_idx: usize,      // which element type has actual values
-    values: &[f32],         // actual values for active element type
-) {
-    writer.write_header(&format!("SCALARS {} float 1", name));
-    writer.write_header("LOOKUP_TABLE default");
-    
-    for (idx, &count) in counts.iter().enumerate() {
-        if idx == active_idx {
-            // Use bulk write for the entire slice - more efficient
-            writer.write_f32_slice(&values[0..count]);
-        } else {
-            writer.write_zeros_f32(count);
-        }
+fn trajectory_nodes(path: &str) -> Result<HashSet<i32>, String> {
+    let ids = subset::read_id_file(path)?;
+    if ids.is_empty() {
+        return Err("needs at least one node id".to_string());
     }
-    writer.newline();
+    Ok(ids)
 }
 
-// ****************************************
-// Helper function: write elemental scalar from strided data
-// For data like torseur values where each element has multiple components
-// ****************************************
-fn write_elemental_scalar_strided<W: Write>(
-    writer: &mut VtkWriter<W>,
-    name: &str,
-    counts: &[usize],       // [nb_1d, nb_2d, nb_3d, nb_sph]
-    active_idx: usize,      // which element type has actual values
-    data: &[f32],           // source data array
-    stride: usize,          // stride between elements (e.g., 9 for torseur)
-    offset: usize,          // offset within stride for this component
-    count: usize,           // number of elements
-) {
-    writer.write_header(&format!("SCALARS {} float 1", name));
-    writer.write_header("LOOKUP_TABLE default");
-    
-    for (idx, &elem_count) in counts.iter().enumerate() {
-        if idx == active_idx {
-            // Write strided values
-            for iel in 0..count {
-                writer.write_f32(data[iel * stride + offset]);
-            }
-        } else {
-            writer.write_zeros_f32(elem_count);
-        }
+// "i/N" with 0 <= i < N
+fn shard(value: &str) -> Result<(usize, usize), String> {
+    let shard = value
+        .split_once('/')
+        .and_then(|(index, count)| Some((index.trim().parse().ok()?, count.trim().parse().ok()?)));
+    match shard {
+        Some((index, count)) if index < count => Ok((index, count)),
+        _ => Err("expected i/N with 0 <= i < N".to_string()),
     }
-    writer.newline();
 }
 
-// ****************************************
-// Helper function: write symmetric tensor (6-component: 3D/SPH)
-// ****************************************
-fn write_symmetric_tensor_6<W: Write>(
-    writer: &mut VtkWriter<W>,
-    name: &str,
-    counts: &[usize],
-    active_idx: usize,
-    values: &[f32],         // [xx, yy, zz, xy, xz, yz] for each element
-) {
-    writer.write_header(&format!("TENSORS {} float", name));
-    
-    for (idx, &count) in counts.iter().enumerate() {
-        if idx == active_idx {
-            for i in 0..count {
-                let base = i * 6;
-                let xx = values[base];
-                let yy = values[base + 1];
-                let zz = values[base + 2];
-                let xy = values[base + 3];
-                let xz = values[base + 4];
-                let yz = values[base + 5];
-                
-                writer.write_f32_triple(xx, xy, xz);
-                writer.write_f32_triple(xy, yy, yz);
-                writer.write_f32_triple(xz, yz, zz);
-            }
-        } else {
-            for _ in 0..count {
-                writer.write_zero_tensor();
-            }
-        }
+// "8G", "512M", "64K" or a number of bytes
+fn memory_size(value: &str) -> Result<u64, String> {
+    let upper = value.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches('B');
+    let (number, unit) = match digits.chars().last() {
+        Some('K') => (&digits[..digits.len() - 1], 1u64 << 10),
+        Some('M') => (&digits[..digits.len() - 1], 1u64 << 20),
+        Some('G') => (&digits[..digits.len() - 1], 1u64 << 30),
+        Some('T') => (&digits[..digits.len() - 1], 1u64 << 40),
+        _ => (digits, 1u64),
+    };
+    // f64 parsing also takes inf and nan, that would saturate
+    match number.parse::<f64>() {
+        Ok(number) if number.is_finite() && number >= 0.0 => Ok((number * unit as f64) as u64),
+        _ => Err("expected a size such as 512M or 8G".to_string()),
     }
-    writer.newline();
 }
 
-// ****************************************
-// Helper function: write symmetric tensor (3-component: 2D)
-// ****************************************
-fn write_symmetric_tensor_3<W: Write>(
-    writer: &mut VtkWriter<W>,
-    name: &str,
-    counts: &[usize],
-    active_idx: usize,
-    values: &[f32],         // [xx, yy, xy] for each element
-) {
-    writer.write_header(&format!("TENSORS {} float", name));
-    
-    for (idx, &count) in counts.iter().enumerate() {
-        if idx == active_idx {
-            for i in 0..count {
-                let base = i * 3;
-                let xx = values[base];
-                let yy = values[base + 1];
-                let xy = values[base + 2];
-                
-                writer.write_f32_triple(xx, xy, 0.0);
-                writer.write_f32_triple(xy, yy, 0.0);
-                writer.write_f32_triple(0.0, 0.0, 0.0);
-            }
-        } else {
-            for _ in 0..count {
-                writer.write_zero_tensor();
-            }
-        }
+fn text_encoding(value: &str) -> Result<TextEncoding, String> {
+    match value {
+        "utf8" => Ok(TextEncoding::Utf8),
+        "latin1" => Ok(TextEncoding::Latin1),
+        #[cfg(feature = "encodings")]
+        "shiftjis" => Ok(TextEncoding::ShiftJis),
+        #[cfg(not(feature = "encodings"))]
+        "shiftjis" => Err("shiftjis needs a build with the encodings feature (cargo build --features encodings)".to_string()),
+        _ => Err("expected utf8, latin1 or shiftjis".to_string()),
     }
-    writer.newline();
 }
 
-// ****************************************
-// convert an A-File to vtk format (ASCII or BINARY)
-// ****************************************
-fn read_radioss_anim<W: Write>(file_name: &str, binary_format: bool, legacy_format: bool, writer: W) {
-    let input_file = File::open(file_name).unwrap_or_else(|_| {
-        eprintln!("Can't open input file {}", file_name);
-        process::exit(1);
-    });
-    let mut inf = BufReader::new(input_file);
-
-    let mut vtk = VtkWriter::new(writer, binary_format, legacy_format);
-
-    let magic = read_i32(&mut inf);
-
-    match magic {
-        FASTMAGI10 => {
-            let a_time = read_f32(&mut inf);
-            let _time_text = read_text(&mut inf, 81);
-            let _mod_anim_text = read_text(&mut inf, 81);
-            let _radioss_run_text = read_text(&mut inf, 81);
-
-            let flag_a = read_i32_vec(&mut inf, 10);
-
-            // ********************
-            // 2D GEOMETRY
-            // ********************
-            let nb_nodes = read_i32(&mut inf) as usize;
-            let nb_facets = read_i32(&mut inf) as usize;
-            let nb_parts = read_i32(&mut inf) as usize;
-            let nb_func = read_i32(&mut inf) as usize;
-            let nb_efunc = read_i32(&mut inf) as usize;
-            let nb_vect = read_i32(&mut inf) as usize;
-            let nb_tens = read_i32(&mut inf) as usize;
-            let nb_skew = read_i32(&mut inf) as usize;
-
-            if nb_skew > 0 {
-                let _skew_short = read_u16_vec(&mut inf, nb_skew * 6);
-                // skew values are read but only used internally, not in VTK output
-            }
-
-            let coor_a = read_f32_vec(&mut inf, 3 * nb_nodes);
-
-            let mut connect_a: Vec<i32> = Vec::new();
-            let mut del_elt_a: Vec<u8> = Vec::new();
-            if nb_facets > 0 {
-                connect_a = read_i32_vec(&mut inf, nb_facets * 4);
-                del_elt_a = read_bytes(&mut inf, nb_facets);
-            }
-
-            let mut def_part_a: Vec<i32> = Vec::new();
-            let mut p_text_a: Vec<String> = Vec::new();
-            if nb_parts > 0 {
-                def_part_a = read_i32_vec(&mut inf, nb_parts);
-                p_text_a = (0..nb_parts)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-            }
-
-            let _norm_short_a = read_u16_vec(&mut inf, 3 * nb_nodes);
-
-            let mut f_text_a: Vec<String> = Vec::new();
-            let mut func_a: Vec<f32> = Vec::new();
-            let mut efunc_a: Vec<f32> = Vec::new();
-            if nb_func + nb_efunc > 0 {
-                f_text_a = (0..nb_func + nb_efunc)
-                    .map(|_| read_text(&mut inf, 81))
-                    .collect();
-                if nb_func > 0 {
-                    func_a = read_f32_vec(&mut inf, nb_nodes * nb_func);
-                }
-                if nb_efunc > 0 {
-                    efunc_a = read_f32_vec(&mut inf, nb_facets * nb_efunc);
-                }
-            }
-
-            let mut v_text_a: Vec<String> = Vec::new();
-            if nb_vect > 0 {
-                v_text_a = (0..nb_vect)
-                    .map(|_| read_text(&mut inf, 81))
-                    .collect();
-            }
-            let vect_val_a = read_f32_vec(&mut inf, 3 * nb_nodes * nb_vect);
-
-            let mut t_text_a: Vec<String> = Vec::new();
-            let mut tens_val_a: Vec<f32> = Vec::new();
-            if nb_tens > 0 {
-                t_text_a = (0..nb_tens)
-                    .map(|_| read_text(&mut inf, 81))
-                    .collect();
-                tens_val_a = read_f32_vec(&mut inf, nb_facets * 3 * nb_tens);
-            }
-
-            if flag_a[0] == 1 {
-                let _e_mass_a = read_f32_vec(&mut inf, nb_facets);
-                let _n_mass_a = read_f32_vec(&mut inf, nb_nodes);
-            }
-
-            let mut nod_num_a: Vec<i32> = Vec::new();
-            let mut el_num_a: Vec<i32> = Vec::new();
-            if flag_a[1] != 0 {
-                nod_num_a = read_i32_vec(&mut inf, nb_nodes);
-                el_num_a = read_i32_vec(&mut inf, nb_facets);
-            }
-
-            if flag_a[4] != 0 {
-                let _part2subset_2d = read_i32_vec(&mut inf, nb_parts);
-                let _part_material_2d = read_i32_vec(&mut inf, nb_parts);
-                let _part_properties_2d = read_i32_vec(&mut inf, nb_parts);
-            }
-
-            // ********************
-            // 3D GEOMETRY
-            // ********************
-            let mut nb_elts_3d: usize = 0;
-            let mut nb_efunc_3d: usize = 0;
-            let mut nb_tens_3d: usize = 0;
-            let mut connect_3d: Vec<i32> = Vec::new();
-            let mut del_elt_3d: Vec<u8> = Vec::new();
-            let mut def_part_3d: Vec<i32> = Vec::new();
-            let mut p_text_3d: Vec<String> = Vec::new();
-            let mut f_text_3d: Vec<String> = Vec::new();
-            let mut efunc_3d: Vec<f32> = Vec::new();
-            let mut t_text_3d: Vec<String> = Vec::new();
-            let mut tens_val_3d: Vec<f32> = Vec::new();
-            let mut el_num_3d: Vec<i32> = Vec::new();
-
-            if flag_a[2] != 0 {
-                nb_elts_3d = read_i32(&mut inf) as usize;
-                let nb_parts_3d = read_i32(&mut inf) as usize;
-                nb_efunc_3d = read_i32(&mut inf) as usize;
-                nb_tens_3d = read_i32(&mut inf) as usize;
-
-                connect_3d = read_i32_vec(&mut inf, nb_elts_3d * 8);
-                del_elt_3d = read_bytes(&mut inf, nb_elts_3d);
-
-                def_part_3d = read_i32_vec(&mut inf, nb_parts_3d);
-                p_text_3d = (0..nb_parts_3d)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-
-                if nb_efunc_3d > 0 {
-                    f_text_3d = (0..nb_efunc_3d)
-                        .map(|_| read_text(&mut inf, 81))
-                        .collect();
-                    efunc_3d = read_f32_vec(&mut inf, nb_efunc_3d * nb_elts_3d);
-                }
-
-                if nb_tens_3d > 0 {
-                    t_text_3d = (0..nb_tens_3d)
-                        .map(|_| read_text(&mut inf, 81))
-                        .collect();
-                    tens_val_3d = read_f32_vec(&mut inf, nb_elts_3d * 6 * nb_tens_3d);
-                }
-
-                if flag_a[0] == 1 {
-                    let _e_mass_3d = read_f32_vec(&mut inf, nb_elts_3d);
-                }
-                if flag_a[1] == 1 {
-                    el_num_3d = read_i32_vec(&mut inf, nb_elts_3d);
-                }
-                if flag_a[4] != 0 {
-                    let _part2subset_3d = read_i32_vec(&mut inf, nb_parts_3d);
-                    let _part_material_3d = read_i32_vec(&mut inf, nb_parts_3d);
-                    let _part_properties_3d = read_i32_vec(&mut inf, nb_parts_3d);
-                }
-            }
-
-            // ********************
-            // 1D GEOMETRY
-            // ********************
-            let mut nb_elts_1d: usize = 0;
-            let mut nb_efunc_1d: usize = 0;
-            let mut nb_tors_1d: usize = 0;
-            let mut connect_1d: Vec<i32> = Vec::new();
-            let mut del_elt_1d: Vec<u8> = Vec::new();
-            let mut def_part_1d: Vec<i32> = Vec::new();
-            let mut p_text_1d: Vec<String> = Vec::new();
-            let mut f_text_1d: Vec<String> = Vec::new();
-            let mut efunc_1d: Vec<f32> = Vec::new();
-            let mut t_text_1d: Vec<String> = Vec::new();
-            let mut tors_val_1d: Vec<f32> = Vec::new();
-            let mut el_num_1d: Vec<i32> = Vec::new();
-
-            if flag_a[3] != 0 {
-                nb_elts_1d = read_i32(&mut inf) as usize;
-                let nb_parts_1d = read_i32(&mut inf) as usize;
-                nb_efunc_1d = read_i32(&mut inf) as usize;
-                nb_tors_1d = read_i32(&mut inf) as usize;
-                let is_skew_1d = read_i32(&mut inf);
-
-                connect_1d = read_i32_vec(&mut inf, nb_elts_1d * 2);
-                del_elt_1d = read_bytes(&mut inf, nb_elts_1d);
-
-                def_part_1d = read_i32_vec(&mut inf, nb_parts_1d);
-                p_text_1d = (0..nb_parts_1d)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-
-                if nb_efunc_1d > 0 {
-                    f_text_1d = (0..nb_efunc_1d)
-                        .map(|_| read_text(&mut inf, 81))
-                        .collect();
-                    efunc_1d = read_f32_vec(&mut inf, nb_efunc_1d * nb_elts_1d);
-                }
-
-                if nb_tors_1d > 0 {
-                    t_text_1d = (0..nb_tors_1d)
-                        .map(|_| read_text(&mut inf, 81))
-                        .collect();
-                    tors_val_1d = read_f32_vec(&mut inf, nb_elts_1d * 9 * nb_tors_1d);
-                }
-
-                if is_skew_1d != 0 {
-                    let _elt2_skew_1d = read_i32_vec(&mut inf, nb_elts_1d);
-                }
-                if flag_a[0] == 1 {
-                    let _e_mass_1d = read_f32_vec(&mut inf, nb_elts_1d);
-                }
-                if flag_a[1] == 1 {
-                    el_num_1d = read_i32_vec(&mut inf, nb_elts_1d);
-                }
-                if flag_a[4] != 0 {
-                    let _part2subset_1d = read_i32_vec(&mut inf, nb_parts_1d);
-                    let _part_material_1d = read_i32_vec(&mut inf, nb_parts_1d);
-                    let _part_properties_1d = read_i32_vec(&mut inf, nb_parts_1d);
-                }
-            }
-
-            // hierarchy
-            if flag_a[4] != 0 {
-                let nb_subsets = read_i32(&mut inf) as usize;
-                for _ in 0..nb_subsets {
-                    let _subset_text = read_text(&mut inf, 50);
-                    let _num_parent = read_i32(&mut inf);
-                    let nb_subset_son = read_i32(&mut inf) as usize;
-                    if nb_subset_son > 0 {
-                        let _subset_son = read_i32_vec(&mut inf, nb_subset_son);
-                    }
-                    let nb_sub_part_2d = read_i32(&mut inf) as usize;
-                    if nb_sub_part_2d > 0 {
-                        let _sub_part_2d = read_i32_vec(&mut inf, nb_sub_part_2d);
-                    }
-                    let nb_sub_part_3d = read_i32(&mut inf) as usize;
-                    if nb_sub_part_3d > 0 {
-                        let _sub_part_3d = read_i32_vec(&mut inf, nb_sub_part_3d);
-                    }
-                    let nb_sub_part_1d = read_i32(&mut inf) as usize;
-                    if nb_sub_part_1d > 0 {
-                        let _sub_part_1d = read_i32_vec(&mut inf, nb_sub_part_1d);
-                    }
-                }
-
-                let nb_materials = read_i32(&mut inf) as usize;
-                let nb_properties = read_i32(&mut inf) as usize;
-                let _material_texts: Vec<String> = (0..nb_materials)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-                let _material_types = read_i32_vec(&mut inf, nb_materials);
-                let _properties_texts: Vec<String> = (0..nb_properties)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-                let _properties_types = read_i32_vec(&mut inf, nb_properties);
-            }
-
-            // ********************
-            // NODES/ELTS FOR Time History
-            // ********************
-            if flag_a[5] != 0 {
-                let nb_nodes_th = read_i32(&mut inf) as usize;
-                let nb_elts_2d_th = read_i32(&mut inf) as usize;
-                let nb_elts_3d_th = read_i32(&mut inf) as usize;
-                let nb_elts_1d_th = read_i32(&mut inf) as usize;
-
-                let _nodes_2th = read_i32_vec(&mut inf, nb_nodes_th);
-                let _n2th_texts: Vec<String> = (0..nb_nodes_th)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-                let _elt_2d_th = read_i32_vec(&mut inf, nb_elts_2d_th);
-                let _elt_2d_th_texts: Vec<String> = (0..nb_elts_2d_th)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-                let _elt_3d_th = read_i32_vec(&mut inf, nb_elts_3d_th);
-                let _elt_3d_th_texts: Vec<String> = (0..nb_elts_3d_th)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-                let _elt_1d_th = read_i32_vec(&mut inf, nb_elts_1d_th);
-                let _elt_1d_th_texts: Vec<String> = (0..nb_elts_1d_th)
-                    .map(|_| read_text(&mut inf, 50))
-                    .collect();
-            }
-
-            // ********************
-            // READ SPH PART
-            // ********************
-            let mut nb_elts_sph: usize = 0;
-            let mut nb_efunc_sph: usize = 0;
-            let mut nb_tens_sph: usize = 0;
-            let mut connec_sph: Vec<i32> = Vec::new();
-            let mut del_elt_sph: Vec<u8> = Vec::new();
-            let mut def_part_sph: Vec<i32> = Vec::new();
-            let mut p_text_sph: Vec<String> = Vec::new();
-            let mut scal_text_sph: Vec<String> = Vec::new();
-            let mut efunc_sph: Vec<f32> = Vec::new();
-            let mut tens_text_sph: Vec<String> = Vec::new();
-            let mut tens_val_sph: Vec<f32> = Vec::new();
-            let mut nod_num_sph: Vec<i32> = Vec::new();
-
-            if flag_a[7] != 0 {
-                nb_elts_sph = read_i32(&mut inf) as usize;
-                let nb_parts_sph = read_i32(&mut inf) as usize;
-                nb_efunc_sph = read_i32(&mut inf) as usize;
-                nb_tens_sph = read_i32(&mut inf) as usize;
-
-                if nb_elts_sph > 0 {
-                    connec_sph = read_i32_vec(&mut inf, nb_elts_sph);
-                    del_elt_sph = read_bytes(&mut inf, nb_elts_sph);
-                }
-                if nb_parts_sph > 0 {
-                    def_part_sph = read_i32_vec(&mut inf, nb_parts_sph);
-                    p_text_sph = (0..nb_parts_sph)
-                        .map(|_| read_text(&mut inf, 50))
-                        .collect();
-                }
-                if nb_efunc_sph > 0 {
-                    scal_text_sph = (0..nb_efunc_sph)
-                        .map(|_| read_text(&mut inf, 81))
-                        .collect();
-                    efunc_sph = read_f32_vec(&mut inf, nb_efunc_sph * nb_elts_sph);
-                }
-                if nb_tens_sph > 0 {
-                    tens_text_sph = (0..nb_tens_sph)
-                        .map(|_| read_text(&mut inf, 81))
-                        .collect();
-                    tens_val_sph = read_f32_vec(&mut inf, nb_elts_sph * nb_tens_sph * 6);
-                }
-                if flag_a[0] == 1 {
-                    let _e_mass_sph = read_f32_vec(&mut inf, nb_elts_sph);
-                }
-                if flag_a[1] == 1 {
-                    nod_num_sph = read_i32_vec(&mut inf, nb_elts_sph);
-                }
-                if flag_a[4] != 0 {
-                    let _num_parent_sph = read_i32_vec(&mut inf, nb_parts_sph);
-                    let _mat_part_sph = read_i32_vec(&mut inf, nb_parts_sph);
-                    let _prop_part_sph = read_i32_vec(&mut inf, nb_parts_sph);
-                }
-            }
-
-            // ********************
-            // VTK output
-            // ********************
-            vtk.write_header("# vtk DataFile Version 3.0");
-            vtk.write_header("vtk output");
-            if binary_format {
-                vtk.write_header("BINARY");
-            } else {
-                vtk.write_header("ASCII");
-            }
-            vtk.write_header("DATASET UNSTRUCTURED_GRID");
-
-            vtk.write_header("FIELD FieldData 2");
-            vtk.write_header("TIME 1 1 double");
-            vtk.write_f64(a_time as f64);
-            if binary_format {
-                vtk.newline();
-            }
-            vtk.write_header("CYCLE 1 1 int");
-            vtk.write_i32(0);
-            if binary_format {
-                vtk.newline();
-            }
-
-            // nodes
-            vtk.write_header(&format!("POINTS {} float", nb_nodes));
-            for inod in 0..nb_nodes {
-                vtk.write_f32_triple(
-                    coor_a[3 * inod],
-                    coor_a[3 * inod + 1],
-                    coor_a[3 * inod + 2],
-                );
-            }
-            vtk.newline();
-
-            // detect tetrahedra in 3D cells
-            let mut is_3d_cell_tetrahedron: Vec<bool> = Vec::with_capacity(nb_elts_3d);
-            let mut tetra_nodes: Vec<[i32; 4]> = Vec::with_capacity(nb_elts_3d);
-            let mut tetrahedron_count: usize = 0;
-            for icon in 0..nb_elts_3d {
-                let nodes = &connect_3d[icon * 8..icon * 8 + 8];
-                if let Some(tet) = unique_sorted_4(nodes) {
-                    is_3d_cell_tetrahedron.push(true);
-                    tetra_nodes.push(tet);
-                    tetrahedron_count += 1;
-                } else {
-                    is_3d_cell_tetrahedron.push(false);
-                    tetra_nodes.push([0; 4]);
-                }
-            }
-
-            // detect triangles in 2D cells
-            let mut is_2d_triangle: Vec<bool> = Vec::with_capacity(nb_facets);
-            let mut _triangle_count: usize = 0;
-            for icon in 0..nb_facets {
-                let nodes = &connect_a[icon * 4..icon * 4 + 4];
-                if unique_count(nodes) == 3 {
-                    is_2d_triangle.push(true);
-                    _triangle_count += 1;
-                } else {
-                    is_2d_triangle.push(false);
-                }
-            }
-
-            let total_cells = nb_elts_1d + nb_facets + nb_elts_3d + nb_elts_sph;
-            if total_cells > 0 {
-                let cells_size = nb_elts_1d * 3
-                    + nb_facets * 5
-                    + tetrahedron_count * 5
-                    + (nb_elts_3d - tetrahedron_count) * 9
-                    + nb_elts_sph * 2;
-                vtk.write_header(&format!("CELLS {} {}", total_cells, cells_size));
-
-                if binary_format {
-                    // 1D elements
-                    for icon in 0..nb_elts_1d {
-                        vtk.write_i32(2);
-                        vtk.write_i32(connect_1d[icon * 2]);
-                        vtk.write_i32(connect_1d[icon * 2 + 1]);
-                    }
-                    // 2D elements
-                    for icon in 0..nb_facets {
-                        vtk.write_i32(4);
-                        vtk.write_i32(connect_a[icon * 4]);
-                        vtk.write_i32(connect_a[icon * 4 + 1]);
-                        vtk.write_i32(connect_a[icon * 4 + 2]);
-                        vtk.write_i32(connect_a[icon * 4 + 3]);
-                    }
-                    // 3D elements
-                    for icon in 0..nb_elts_3d {
-                        if is_3d_cell_tetrahedron[icon] {
-                            let tet = tetra_nodes[icon];
-                            vtk.write_i32(4);
-                            vtk.write_i32(tet[0]);
-                            vtk.write_i32(tet[1]);
-                            vtk.write_i32(tet[2]);
-                            vtk.write_i32(tet[3]);
-                        } else {
-                            vtk.write_i32(8);
-                            for i in 0..8 {
-                                vtk.write_i32(connect_3d[icon * 8 + i]);
-                            }
-                        }
-                    }
-                    // SPH elements
-                    for icon in 0..nb_elts_sph {
-                        vtk.write_i32(1);
-                        vtk.write_i32(connec_sph[icon]);
-                    }
-                } else {
-                    // 1D elements
-                    for icon in 0..nb_elts_1d {
-                        let vals = [
-                            2,
-                            connect_1d[icon * 2],
-                            connect_1d[icon * 2 + 1],
-                        ];
-                        vtk.write_i32_line(&vals);
-                    }
-                    // 2D elements
-                    for icon in 0..nb_facets {
-                        let vals = [
-                            4,
-                            connect_a[icon * 4],
-                            connect_a[icon * 4 + 1],
-                            connect_a[icon * 4 + 2],
-                            connect_a[icon * 4 + 3],
-                        ];
-                        vtk.write_i32_line(&vals);
-                    }
-                    // 3D elements
-                    for icon in 0..nb_elts_3d {
-                        if is_3d_cell_tetrahedron[icon] {
-                            let tet = tetra_nodes[icon];
-                            let vals = [4, tet[0], tet[1], tet[2], tet[3]];
-                            vtk.write_i32_line(&vals);
-                        } else {
-                            let vals = [
-                                8,
-                                connect_3d[icon * 8],
-                                connect_3d[icon * 8 + 1],
-                                connect_3d[icon * 8 + 2],
-                                connect_3d[icon * 8 + 3],
-                                connect_3d[icon * 8 + 4],
-                                connect_3d[icon * 8 + 5],
-                                connect_3d[icon * 8 + 6],
-                                connect_3d[icon * 8 + 7],
-                            ];
-                            vtk.write_i32_line(&vals);
-                        }
-                    }
-                    // SPH elements
-                    for icon in 0..nb_elts_sph {
-                        let vals = [1, connec_sph[icon]];
-                        vtk.write_i32_line(&vals);
-                    }
-                }
-            }
-            vtk.newline();
-
-            // element types
-            if total_cells > 0 {
-                vtk.write_header(&format!("CELL_TYPES {}", total_cells));
-                for _ in 0..nb_elts_1d {
-                    vtk.write_i32(3);
-                }
-                for icon in 0..nb_facets {
-                    if is_2d_triangle[icon] {
-                        vtk.write_i32(5);
-                    } else {
-                        vtk.write_i32(9);
-                    }
-                }
-                for icon in 0..nb_elts_3d {
-                    if is_3d_cell_tetrahedron[icon] {
-                        vtk.write_i32(10);
-                    } else {
-                        vtk.write_i32(12);
-                    }
-                }
-                for _ in 0..nb_elts_sph {
-                    vtk.write_i32(1);
-                }
-            }
-            vtk.newline();
-
-            // nodal scalars & vectors
-            vtk.write_header(&format!("POINT_DATA {}", nb_nodes));
-
-            // node id
-            vtk.write_header("SCALARS NODE_ID int 1");
-            vtk.write_header("LOOKUP_TABLE default");
-            for inod in 0..nb_nodes {
-                vtk.write_i32(nod_num_a[inod]);
-            }
-            vtk.newline();
-
-            for ifun in 0..nb_func {
-                let name = replace_underscore(&f_text_a[ifun]);
-                vtk.write_header(&format!("SCALARS {} float 1", name));
-                vtk.write_header("LOOKUP_TABLE default");
-                for inod in 0..nb_nodes {
-                    vtk.write_f32(func_a[ifun * nb_nodes + inod]);
-                }
-                vtk.newline();
-            }
-
-            for ivect in 0..nb_vect {
-                let name = replace_underscore(&v_text_a[ivect]);
-                vtk.write_header(&format!("VECTORS {} float", name));
-                for inod in 0..nb_nodes {
-                    vtk.write_f32_triple(
-                        vect_val_a[3 * inod + ivect * 3 * nb_nodes],
-                        vect_val_a[3 * inod + 1 + ivect * 3 * nb_nodes],
-                        vect_val_a[3 * inod + 2 + ivect * 3 * nb_nodes],
-                    );
-                }
-                vtk.newline();
-            }
-
-            vtk.write_header(&format!("CELL_DATA {}", total_cells));
-
-            // element id
-            vtk.write_header("SCALARS ELEMENT_ID int 1");
-            vtk.write_header("LOOKUP_TABLE default");
-            write_cell_i32_values(&mut vtk, &[&el_num_1d, &el_num_a, &el_num_3d, &nod_num_sph]);
-
-            // part id
-            vtk.write_header("SCALARS PART_ID int 1");
-            vtk.write_header("LOOKUP_TABLE default");
-
-            let mut part_1d_index: usize = 0;
-            let mut part_2d_index: usize = 0;
-            let mut part_3d_index: usize = 0;
-            let mut part_0d_index: usize = 0;
-
-            for iel in 0..nb_elts_1d {
-                let part_id = resolve_part_id(iel, &mut part_1d_index, &def_part_1d, &p_text_1d);
-                vtk.write_i32(part_id);
-            }
-            for iel in 0..nb_facets {
-                let part_id = resolve_part_id(iel, &mut part_2d_index, &def_part_a, &p_text_a);
-                vtk.write_i32(part_id);
-            }
-            for iel in 0..nb_elts_3d {
-                let part_id = resolve_part_id(iel, &mut part_3d_index, &def_part_3d, &p_text_3d);
-                vtk.write_i32(part_id);
-            }
-            for iel in 0..nb_elts_sph {
-                let part_id = resolve_part_id(iel, &mut part_0d_index, &def_part_sph, &p_text_sph);
-                vtk.write_i32(part_id);
-            }
-            vtk.newline();
-
-            // element erosion status (0:off, 1:on)
-            vtk.write_header("SCALARS EROSION_STATUS int 1");
-            vtk.write_header("LOOKUP_TABLE default");
-            let to_erosion_status = |v: u8| if v == 1 { 1 } else { 0 };
-            for iel in 0..nb_elts_1d {
-                vtk.write_i32(to_erosion_status(del_elt_1d[iel]));
-            }
-            for iel in 0..nb_facets {
-                vtk.write_i32(to_erosion_status(del_elt_a[iel]));
-            }
-            for iel in 0..nb_elts_3d {
-                vtk.write_i32(to_erosion_status(del_elt_3d[iel]));
-            }
-            for iel in 0..nb_elts_sph {
-                vtk.write_i32(to_erosion_status(del_elt_sph[iel]));
-            }
-            vtk.newline();
-
-            // 1D elemental scalars
-            let counts = [nb_elts_1d, nb_facets, nb_elts_3d, nb_elts_sph];
-            for iefun in 0..nb_efunc_1d {
-                let name = replace_underscore(&f_text_1d[iefun]);
-                // Direct slice access - no Vec allocation needed
-                let start = iefun * nb_elts_1d;
-                let end = start + nb_elts_1d;
-                write_elemental_scalar(&mut vtk, &format!("1DELEM_{}", name), &counts, 0, &efunc_1d[start..end]);
-            }
-
-            // 1D torseur values
-            let tors_suffixes = ["F1", "F2", "F3", "M1", "M2", "M3", "M4", "M5", "M6"];
-            for iefun in 0..nb_tors_1d {
-                let name = replace_underscore(&t_text_1d[iefun]);
-                let base_offset = 9 * iefun * nb_elts_1d;
-                for j in 0..9usize {
-                    // Use strided access - avoids Vec allocation
-                    write_elemental_scalar_strided(
-                        &mut vtk,
-                        &format!("1DELEM_{}{}", name, tors_suffixes[j]),
-                        &counts,
-                        0,
-                        &tors_val_1d[base_offset..],
-                        9,  // stride
-                        j,  // offset within stride
-                        nb_elts_1d,
-                    );
-                }
-            }
+fn endian(value: &str) -> Result<Endian, String> {
+    Endian::parse(value).ok_or_else(|| "expected big or little".to_string())
+}
 
-            // 2D elemental scalars
-            for iefun in 0..nb_efunc {
-                let name = replace_underscore(&f_text_a[iefun + nb_func]);
-                // Direct slice access - no Vec allocation needed
-                let start = iefun * nb_facets;
-                let end = start + nb_facets;
-                write_elemental_scalar(&mut vtk, &format!("2DELEM_{}", name), &counts, 1, &efunc_a[start..end]);
-            }
+fn output_template(template: &str) -> Result<String, String> {
+    check_output_template(template)?;
+    Ok(template.to_string())
+}
 
-            // 2D tensors
-            for ietens in 0..nb_tens {
-                let name = replace_underscore(&t_text_a[ietens]);
-                // Direct slice access - tensor values are already contiguous in memory
-                let start = ietens * 3 * nb_facets;
-                let end = start + 3 * nb_facets;
-                write_symmetric_tensor_3(&mut vtk, &format!("2DELEM_{}", name), &counts, 1, &tens_val_a[start..end]);
-            }
+// outputs of the optional features, refused by the builds without them
+fn feature_path(path: &str, feature: &str, enabled: bool) -> Result<String, String> {
+    if !enabled {
+        return Err(format!("needs a build with the {} feature (cargo build --features {})", feature, feature));
+    }
+    Ok(path.to_string())
+}
 
-            // 3D elemental scalars
-            for iefun in 0..nb_efunc_3d {
-                let name = replace_underscore(&f_text_3d[iefun]);
-                // Direct slice access - no Vec allocation needed
-                let start = iefun * nb_elts_3d;
-                let end = start + nb_elts_3d;
-                write_elemental_scalar(&mut vtk, &format!("3DELEM_{}", name), &counts, 2, &efunc_3d[start..end]);
-            }
+fn catalog_path(path: &str) -> Result<String, String> {
+    feature_path(path, "catalog", cfg!(feature = "catalog"))
+}
 
-            // 3D tensors
-            for ietens in 0..nb_tens_3d {
-                let name = replace_underscore(&t_text_3d[ietens]);
-                // Direct slice access - tensor values are already contiguous in memory
-                let start = ietens * 6 * nb_elts_3d;
-                let end = start + 6 * nb_elts_3d;
-                write_symmetric_tensor_6(&mut vtk, &format!("3DELEM_{}", name), &counts, 2, &tens_val_3d[start..end]);
-            }
+fn archive_path(path: &str) -> Result<String, String> {
+    feature_path(path, "archive", cfg!(feature = "archive"))
+}
 
-            // SPH scalars and tensors
-            if flag_a[7] != 0 {
-                for iefun in 0..nb_efunc_sph {
-                    let name = replace_underscore(&scal_text_sph[iefun]);
-                    // Direct slice access - no Vec allocation needed
-                    let start = iefun * nb_elts_sph;
-                    let end = start + nb_elts_sph;
-                    write_elemental_scalar(&mut vtk, &format!("SPHELEM_{}", name), &counts, 3, &efunc_sph[start..end]);
-                }
-
-                for ietens in 0..nb_tens_sph {
-                    let name = replace_underscore(&tens_text_sph[ietens]);
-                    // Direct slice access - tensor values are already contiguous in memory
-                    let start = ietens * 6 * nb_elts_sph;
-                    let end = start + 6 * nb_elts_sph;
-                    write_symmetric_tensor_6(&mut vtk, &format!("SPHELEM_{}", name), &counts, 3, &tens_val_sph[start..end]);
-                }
-            }
+fn usd_path(path: &str) -> Result<String, String> {
+    feature_path(path, "usd", cfg!(feature = "usd"))
+}
 
-            vtk.flush();
-        }
+// the arguments of <T>, or its usage error, --help or --version, then the exit
+fn parse_or_exit<T: Parser>(args: &[String], usage_error: i32) -> T {
+    T::try_parse_from(args).unwrap_or_else(|e| {
+        let _ = e.print();
+        process::exit(if e.use_stderr() { usage_error } else { report::EXIT_SUCCESS })
+    })
+}
 
-        _ => {
-            eprintln!("Error in Anim Files version");
-            process::exit(1);
-        }
-    }
+// the options of convert, that of the subcommand or the default; info and
+// header-diff run here and exit
+fn parse_args(args: &[String]) -> CliOptions {
+    let usage_error = match args.get(1).map(String::as_str) {
+        Some("header-diff") => header_diff::EXIT_TROUBLE,
+        _ => report::EXIT_FAILURE,
+    };
+    let cli: Cli = parse_or_exit(args, usage_error);
+    let options = match cli.command {
+        None => cli.convert,
+        Some(Command::Convert(options)) => *options,
+        Some(Command::Info(info_args)) => anim_info::run(info_args),
+        Some(Command::HeaderDiff(diff_args)) => header_diff::run(diff_args),
+    };
+    options.resolve().unwrap_or_else(|e| {
+        let _ = e.print();
+        process::exit(report::EXIT_FAILURE)
+    })
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+    // the same binary, installed as anim_header_diff or anim_info (anim_info_linux64_gf...)
+    let program = Path::new(&args[0]).file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
+    if program.starts_with(header_diff::PROGRAM_NAME) {
+        header_diff::main(&args);
+    }
+    if program.starts_with(anim_info::PROGRAM_NAME) {
+        anim_info::main(&args);
+    }
     if args.len() < 2 {
-        eprintln!("Usage: {} <filename1> [filename2 ...] [--binary]", args[0]);
-        eprintln!("  --binary : Output in binary VTK format (default is ASCII)");
-        eprintln!("  --legacy : Match C++ ASCII float formatting (default uses fast shortest)");
-        eprintln!("  Output files will have .vtk extension added automatically");
-        eprintln!("  Input files must have no extension and end with an uppercase letter followed by 3-4 digits");
-        process::exit(1);
+        let _ = Cli::command().print_help();
+        process::exit(report::EXIT_FAILURE);
     }
-    
-    // Check if --binary flag is present
-    let binary_format = args.iter().any(|arg| arg == "--binary" || arg == "-b");
-    let legacy_format = args.iter().any(|arg| arg == "--legacy" || arg == "-l");
-    
-    // Collect all input files (skip program name and --binary flag)
-    let mut input_files: Vec<&String> = args[1..]
-        .iter()
-        .filter(|arg| *arg != "--binary" && *arg != "-b" && *arg != "--legacy" && *arg != "-l")
-        .collect();
+
+    let mut cli = parse_args(&args);
+    if let Some(path) = &cli.diagnostics {
+        if let Err(e) = report::open_diagnostics(path) {
+            report::fail(format_args!("Can't create diagnostics file {}: {}", path, e));
+        }
+    }
+    // once, after the options it is read with
+    if let Some(path) = &cli.displacement_from {
+        match derive::ReferenceState::read(path, cli.text_encoding, cli.endian) {
+            Ok(reference) => cli.reference = Some(reference),
+            Err(e) => report::fail(format_args!("Can't read the reference A-file {}: {}", path, e)),
+        }
+    }
+    if cli.anonymize {
+        cli.anonymizer = Some(anonymize::Anonymizer::new(cli.anonymize_seed, cli.anonymize_transform));
+    }
+    let mut input_files: Vec<&String> = cli.input_files.iter().collect();
 
     // Filter out files with extensions and enforce L###/L#### suffix pattern (L = uppercase letter)
     let mut invalid_files: Vec<&String> = Vec::new();
@@ -1311,60 +1200,153 @@ fn main() {
         true
     });
 
-    if !invalid_files.is_empty() {
-        eprintln!("Warning: Skipping invalid input files:");
-        for file in &invalid_files {
-            eprintln!("  - {}", file);
-        }
+    for file in &invalid_files {
+        report::warning(codes::INVALID_INPUT_NAME, format_args!("Skipping invalid input file {}", file));
     }
     
     if input_files.is_empty() {
-        eprintln!("Error: No valid input files specified");
-        process::exit(1);
+        report::fail("No valid input files specified")
     }
-    
-    // Process each input file
-    let mut failed_files = Vec::new();
-    let mut successful_files = 0;
-    
-    if binary_format && legacy_format {
-        eprintln!("Warning: --legacy has no effect with --binary");
-    }
-
-    for file_name in input_files {
-        // Always append .vtk extension to create output filename
-        let output_file_name = format!("{}.vtk", file_name);
-        
-        // Verify input file exists before creating output file
-        if !std::path::Path::new(file_name.as_str()).exists() {
-            eprintln!("Error: Input file {} does not exist", file_name);
-            failed_files.push(file_name.clone());
-            continue;
+    // one name for all the inputs would have each output overwrite the previous one
+    if let Some(template) = &cli.output_template {
+        if input_files.len() > 1 && !template.contains("{name}") && !template.contains("{state}") {
+            report::fail(format_args!("--output '{}' needs {{name}} or {{state}} with several inputs", template));
+        }
+    }
+    if let Some(dir) = &cli.output_dir {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            report::fail(format_args!("Can't create output directory {}: {}", dir, e));
         }
-        
-        let output_file = match File::create(&output_file_name) {
-            Ok(f) => f,
-            Err(e) => {
-                eprintln!("Error: Can't create output file {}: {}", output_file_name, e);
-                failed_files.push(file_name.clone());
-                continue;
+    }
+
+    if cli.dump_layout {
+        let mut failed = false;
+        for file_name in &input_files {
+            if let Err(e) = anim::dump_layout(file_name, cli.text_encoding, cli.endian) {
+                report::for_input(file_name, || report::error(e.code(), e));
+                failed = true;
             }
-        };
-        
-        eprintln!("Converting {} to {}", file_name, output_file_name);
-        read_radioss_anim(file_name, binary_format, legacy_format, output_file);
-        successful_files += 1;
+        }
+        if failed {
+            process::exit(report::EXIT_FAILURE);
+        }
+        return;
     }
-    
+
+    // before sharding, so that each shard gets successive times
+    if cli.sort_by_time || cli.dedupe_times || cli.time_range.is_some() {
+        let mut timed_files = time_order::header_times(&input_files, cli.endian);
+        if cli.dedupe_times {
+            time_order::dedupe_times(&mut timed_files);
+        }
+        if cli.sort_by_time {
+            time_order::sort_by_time(&mut timed_files);
+        }
+        if let Some(range) = &cli.time_range {
+            time_order::select_time_range(&mut timed_files, range);
+        }
+        input_files = timed_files.into_iter().map(|(file_name, _)| file_name).collect();
+    }
+
+    // after the time order, so that the files kept are evenly spaced in time
+    if cli.every > 1 {
+        let nb_files = input_files.len();
+        input_files = input_files.into_iter().step_by(cli.every).collect();
+        report_line!("Every {} files: {} of {} files", cli.every, input_files.len(), nb_files);
+    }
+
+    // contiguous slices keep successive states together in one shard
+    if let Some((index, count)) = cli.shard {
+        let nb_files = input_files.len();
+        let first = index * nb_files / count;
+        let last = (index + 1) * nb_files / count;
+        input_files = input_files[first..last].to_vec();
+        report_line!("Shard {}/{}: {} of {} files", index, count, input_files.len(), nb_files);
+    }
+
+    if cli.parse_only {
+        let report = parse_only(&input_files, cli.parse_mode, cli.text_encoding, cli.endian);
+        report.print_summary();
+        process::exit(report.exit_code());
+    }
+
+    // Process each input file
+    let mut progress = report::Progress::new(input_files.len());
+
+    if cli.binary && cli.legacy {
+        report::warning(codes::NO_EFFECT, "--legacy has no effect with --binary");
+    }
+    if cli.jobs > 1 && cli.keeps_history() {
+        report::warning(codes::NO_EFFECT, "--derive epsdot and --accumulate need the states in order, converting one file at a time");
+        cli.jobs = 1;
+    }
+    if cli.max_memory.is_some() && cli.needs_all_fields() {
+        report::warning(codes::NO_EFFECT, "--max-memory has no effect with options that modify the fields");
+    }
+
+    // first pass over the input files, once their list is final
+    if let Some(mut filter) = cli.time_filter.take() {
+        filter.prepare(&input_files, |file_name| report::capture(|| load_anim(file_name, &cli)).0.ok());
+        cli.time_filter = Some(filter);
+    }
+
+    let mut batch = batch::Batch::new(&cli);
+    if cli.jobs > 1 && input_files.len() > 1 {
+        batch::convert_parallel(&input_files, &cli, cli.jobs, &progress, &mut batch);
+    } else {
+        for file_name in input_files {
+            progress.next();
+            let outcome = batch::convert_file(file_name, &cli, batch.history(), &progress);
+            batch.record(outcome);
+        }
+    }
+
     // Report results
-    if !failed_files.is_empty() {
-        eprintln!("\nConversion summary: {} succeeded, {} failed", successful_files, failed_files.len());
-        eprintln!("Failed files:");
-        for file in &failed_files {
-            eprintln!("  - {}", file);
+    let report = batch.finish();
+    report.print_summary();
+    if report.exit_code() != report::EXIT_SUCCESS {
+        process::exit(report.exit_code());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_sizes() {
+        assert_eq!(memory_size("512M"), Ok(512 << 20));
+        assert_eq!(memory_size("1.5gb"), Ok(3 << 29));
+        assert_eq!(memory_size("4096"), Ok(4096));
+        for value in ["-1G", "nan", "inf", "-inf", "infinityT", "G", "8X"] {
+            assert_eq!(memory_size(value), Err("expected a size such as 512M or 8G".to_string()), "{}", value);
         }
-        process::exit(1);
-    } else if successful_files > 1 {
-        eprintln!("\nConversion complete: {} files converted successfully", successful_files);
+    }
+
+    #[test]
+    fn quantize_steps() {
+        let cli = Cli::try_parse_from(["anim_to_vtk", "--quantize=1e-4", "runA001"]).unwrap();
+        assert_eq!(cli.convert.quantize, Some(1e-4));
+        for value in ["nan", "inf", "-inf", "-1", "0"] {
+            let option = format!("--quantize={}", value);
+            assert!(Cli::try_parse_from(["anim_to_vtk", option.as_str(), "runA001"]).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn sph_density_radii() {
+        let cli = Cli::try_parse_from(["anim_to_vtk", "--sph-density=0.5", "runA001"]).unwrap();
+        assert_eq!(cli.convert.sph_density, Some(0.5));
+        for value in ["nan", "inf", "-1", "0"] {
+            let option = format!("--sph-density={}", value);
+            assert!(Cli::try_parse_from(["anim_to_vtk", option.as_str(), "runA001"]).is_err(), "{}", value);
+        }
+    }
+
+    // accepted as before the command line moved to clap, --legacy warns
+    #[test]
+    fn binary_with_legacy() {
+        let cli = Cli::try_parse_from(["anim_to_vtk", "-b", "-l", "runA001"]).unwrap();
+        assert!(cli.convert.binary && cli.convert.legacy);
     }
 }
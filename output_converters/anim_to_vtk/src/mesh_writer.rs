@@ -0,0 +1,692 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Format independent mesh output: write_mesh walks an animation file
+// in a fixed order (geometry, points, cells, point fields, cell fields)
+// and hands each piece to a MeshWriter. Cell classification, orientation
+// fix-up and the padding of block arrays over all cells are done here,
+// so that an output format only encodes what it receives.
+// Cells are ordered 1D, 2D, 3D, SPH in every chunk and cell field.
+// ****************************************
+
+use std::collections::HashMap;
+use std::io;
+
+use crate::anim::{atoi_prefix, field_name_matches, resolve_part_id, AnimFile, ElementBlock};
+use crate::cells::{self, ShellCell, SolidCell};
+use crate::vtk::{replace_underscore, WriteOptions};
+
+pub struct MeshHeader<'a> {
+    pub time: f64,
+    pub nb_points: usize,
+    pub nb_cells: usize,
+    pub connectivity_size: usize, // node references of all cells
+    pub string_tables: Vec<(&'a str, &'a [String])>, // named string lists (MATERIAL_NAME)
+    pub ranges: Vec<(String, f64, f64)>, // min and max of the arrays, empty unless asked for
+    pub mode_phase: Option<f64>, // phase of a mode shape step, legacy VTK only
+    pub part_texts: [&'a [String]; 4], // part texts of each block, PART_ID being their leading number
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Scalar,
+    Vector, // 3 components per value
+    Tensor, // 3x3 components per value
+    Color,  // r, g, b unsigned chars per value
+}
+
+// values of a point field
+pub enum PointValues<'a> {
+    Int(&'a [i32]),
+    Float(&'a [f32]),
+}
+
+// values of a cell field over the cells of one block
+pub enum CellValues<'a> {
+    Int(&'a [i32]),
+    Float(&'a [f32]),
+    // one component out of <stride> per cell (torseur values)
+    Strided { values: &'a [f32], stride: usize, offset: usize },
+    // symmetric tensors, [xx, yy, zz, xy, xz, yz] per cell
+    Tensor6(&'a [f32]),
+    // plane symmetric tensors, [xx, yy, xy] per cell
+    Tensor3(&'a [f32]),
+    // r, g, b per cell
+    Rgb(&'a [u8]),
+    // block without this field
+    Zeros(usize),
+}
+
+// Every step returns the error of the output, the first one ends the
+// walk of write_mesh
+pub trait MeshWriter {
+    fn begin_geometry(&mut self, header: &MeshHeader) -> io::Result<()>;
+    // x, y, z of successive points
+    fn write_points_chunk(&mut self, coor: &[f32]) -> io::Result<()>;
+    // VTK cell type and node list of successive cells, a block of
+    // cells possibly in several chunks
+    fn write_cells_chunk(&mut self, cells: &mut dyn Iterator<Item = (i32, &[i32])>) -> io::Result<()>;
+    fn write_point_field(&mut self, name: &str, kind: FieldKind, values: PointValues) -> io::Result<()>;
+    // one entry per element block, in cell order
+    fn write_cell_field(&mut self, name: &str, kind: FieldKind, blocks: &[CellValues]) -> io::Result<()>;
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+// ****************************************
+// RangeCollector - MeshWriter that only keeps the min and max of the
+// arrays, as written: scalars with their zero padding, vectors by
+// magnitude. Tensors and colors have no range.
+// ****************************************
+#[derive(Default)]
+struct RangeCollector {
+    ranges: Vec<(String, f64, f64)>,
+}
+
+impl RangeCollector {
+    fn add(&mut self, name: &str, values: impl Iterator<Item = f64>) {
+        let range = values.fold(None, |range: Option<(f64, f64)>, v| match range {
+            Some((min, max)) => Some((min.min(v), max.max(v))),
+            None => Some((v, v)),
+        });
+        if let Some((min, max)) = range {
+            self.ranges.push((name.to_string(), min, max));
+        }
+    }
+}
+
+impl MeshWriter for RangeCollector {
+    fn begin_geometry(&mut self, _header: &MeshHeader) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_points_chunk(&mut self, _coor: &[f32]) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_cells_chunk(&mut self, _cells: &mut dyn Iterator<Item = (i32, &[i32])>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_point_field(&mut self, name: &str, kind: FieldKind, values: PointValues) -> io::Result<()> {
+        match (kind, values) {
+            (FieldKind::Vector, PointValues::Float(values)) => self.add(
+                name,
+                values.chunks_exact(3).map(|v| (v[0] as f64).hypot(v[1] as f64).hypot(v[2] as f64)),
+            ),
+            (_, PointValues::Float(values)) => self.add(name, values.iter().map(|&v| v as f64)),
+            (_, PointValues::Int(values)) => self.add(name, values.iter().map(|&v| v as f64)),
+        }
+        Ok(())
+    }
+
+    fn write_cell_field(&mut self, name: &str, kind: FieldKind, blocks: &[CellValues]) -> io::Result<()> {
+        if kind == FieldKind::Tensor || kind == FieldKind::Color {
+            return Ok(());
+        }
+        let values = blocks.iter().flat_map(|values| -> Box<dyn Iterator<Item = f64> + '_> {
+            match *values {
+                CellValues::Int(values) => Box::new(values.iter().map(|&v| v as f64)),
+                CellValues::Float(values) => Box::new(values.iter().map(|&v| v as f64)),
+                CellValues::Strided { values, stride, offset } => {
+                    Box::new(values.iter().skip(offset).step_by(stride).map(|&v| v as f64))
+                }
+                CellValues::Zeros(count) => Box::new(std::iter::repeat_n(0.0, count)),
+                CellValues::Tensor6(_) | CellValues::Tensor3(_) | CellValues::Rgb(_) => Box::new(std::iter::empty()),
+            }
+        });
+        self.add(name, values);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// min and max of every array write_mesh gives, by a pass without output
+fn field_ranges(anim: &AnimFile, options: &WriteOptions) -> io::Result<Vec<(String, f64, f64)>> {
+    let mut collector = RangeCollector::default();
+    write_mesh(anim, &WriteOptions { ranges: false, ..options.clone() }, &mut collector)?;
+    Ok(collector.ranges)
+}
+
+// a cell field gathered over all the cells
+pub enum CellArray {
+    Int(Vec<i32>),
+    Float(Vec<f32>), // tensors as 3x3 components per cell, row by row
+    Rgb(Vec<u8>),
+}
+
+// for the formats that write an array at once
+pub fn collect_cell_field(kind: FieldKind, blocks: &[CellValues], nb_cells: usize) -> CellArray {
+    if kind == FieldKind::Color {
+        let mut data = Vec::with_capacity(3 * nb_cells);
+        for values in blocks {
+            match *values {
+                CellValues::Rgb(values) => data.extend_from_slice(values),
+                CellValues::Zeros(count) => data.resize(data.len() + 3 * count, 0),
+                _ => {}
+            }
+        }
+        return CellArray::Rgb(data);
+    }
+    if blocks.iter().any(|values| matches!(values, CellValues::Int(_))) {
+        let mut data = Vec::with_capacity(nb_cells);
+        for values in blocks {
+            match *values {
+                CellValues::Int(values) => data.extend_from_slice(values),
+                CellValues::Zeros(count) => data.resize(data.len() + count, 0),
+                _ => {}
+            }
+        }
+        return CellArray::Int(data);
+    }
+    let width = if kind == FieldKind::Tensor { 9 } else { 1 };
+    let mut data = Vec::with_capacity(width * nb_cells);
+    for values in blocks {
+        match *values {
+            CellValues::Float(values) => data.extend_from_slice(values),
+            CellValues::Strided { values, stride, offset } => {
+                data.extend(values.iter().skip(offset).step_by(stride));
+            }
+            CellValues::Tensor6(values) => {
+                for t in values.chunks_exact(6) {
+                    // [xx, yy, zz, xy, xz, yz]
+                    data.extend_from_slice(&[t[0], t[3], t[4], t[3], t[1], t[5], t[4], t[5], t[2]]);
+                }
+            }
+            CellValues::Tensor3(values) => {
+                for t in values.chunks_exact(3) {
+                    // [xx, yy, xy]
+                    data.extend_from_slice(&[t[0], t[2], 0.0, t[2], t[1], 0.0, 0.0, 0.0, 0.0]);
+                }
+            }
+            CellValues::Zeros(count) => data.resize(data.len() + width * count, 0.0),
+            CellValues::Int(_) | CellValues::Rgb(_) => {}
+        }
+    }
+    CellArray::Float(data)
+}
+
+// Arrays named on the command line match the written name with or
+// without its element prefix (2DELEM_Von_Mises or Von_Mises)
+pub fn array_name_matches(array_name: &str, name: &str) -> bool {
+    field_name_matches(array_name, name)
+        || ["1DELEM_", "2DELEM_", "3DELEM_", "SPHELEM_"]
+            .iter()
+            .any(|prefix| array_name.strip_prefix(prefix).is_some_and(|rest| field_name_matches(rest, name)))
+}
+
+// names of the 1D torseur components, after the field name
+pub const TORSEUR_SUFFIXES: [&str; 9] = ["F1", "F2", "F3", "M1", "M2", "M3", "M4", "M5", "M6"];
+
+// number of cells of a block of cell values
+pub fn cell_count(values: &CellValues) -> usize {
+    match *values {
+        CellValues::Int(values) => values.len(),
+        CellValues::Float(values) => values.len(),
+        CellValues::Strided { values, stride, .. } => values.len() / stride,
+        CellValues::Tensor6(values) => values.len() / 6,
+        CellValues::Tensor3(values) => values.len() / 3,
+        CellValues::Rgb(values) => values.len() / 3,
+        CellValues::Zeros(count) => count,
+    }
+}
+
+// ****************************************
+// Buffered - MeshWriter keeping the whole mesh until finish, for the
+// formats written by part rather than array by array (EnSight, OBJ,
+// image data). Cells and cell fields are kept by block; of the integer
+// arrays, only the ids and the erosion status, which select and group
+// the cells. Colors are left out.
+// ****************************************
+
+// format written from the whole mesh
+pub trait MeshOutput {
+    // whether the float array `name` is kept for write
+    fn keeps(&self, name: &str) -> bool;
+    fn write(&mut self, mesh: &MeshBuffer) -> io::Result<()>;
+}
+
+// values of a cell field by block, None on the blocks without the
+// field; tensors as 3x3 components
+pub type BlockValues = Vec<Option<Vec<f32>>>;
+
+#[derive(Default)]
+pub struct MeshBuffer {
+    pub time: f64,
+    pub coor: Vec<f32>,
+    part_texts: [Vec<String>; 4],
+    cell_types: Vec<i32>,
+    offsets: Vec<usize>, // start of the nodes of each cell, then their end
+    connectivity: Vec<i32>,
+    block_starts: Vec<usize>, // first cell of each block, then the cell count
+    pub node_ids: Vec<i32>,   // empty without user numbering
+    element_ids: Vec<Vec<i32>>,
+    part_ids: Vec<Vec<i32>>,
+    status: Vec<Vec<i32>>,
+    pub point_fields: Vec<(String, FieldKind, Vec<f32>)>,
+    pub cell_fields: Vec<(String, FieldKind, BlockValues)>,
+}
+
+impl MeshBuffer {
+    pub fn block_count(&self, iblock: usize) -> usize {
+        self.block_starts.get(iblock + 1).map_or(0, |end| end - self.block_starts[iblock])
+    }
+
+    // VTK cell type and nodes of element iel of a block
+    pub fn cell(&self, iblock: usize, iel: usize) -> (i32, &[i32]) {
+        let icell = self.block_starts[iblock] + iel;
+        (self.cell_types[icell], &self.connectivity[self.offsets[icell]..self.offsets[icell + 1]])
+    }
+
+    // user ids of the elements of a block, None without user numbering
+    pub fn element_ids(&self, iblock: usize) -> Option<&[i32]> {
+        self.element_ids.get(iblock).map(|ids| &ids[..])
+    }
+
+    // EROSION_STATUS 1, elements without status being active
+    pub fn is_active(&self, iblock: usize, iel: usize) -> bool {
+        self.status.get(iblock).and_then(|status| status.get(iel)).is_none_or(|&status| status == 1)
+    }
+
+    // active elements of a block grouped by PART_ID, parts in the order
+    // of their first element, named by their part text
+    pub fn parts(&self, iblock: usize) -> Vec<(&str, Vec<usize>)> {
+        let ids = self.part_ids.get(iblock).map_or(&[][..], |ids| &ids[..]);
+        let mut parts: Vec<(i32, Vec<usize>)> = Vec::new();
+        let mut index: HashMap<i32, usize> = HashMap::new();
+        for iel in (0..self.block_count(iblock)).filter(|&iel| self.is_active(iblock, iel)) {
+            let id = ids.get(iel).copied().unwrap_or(0);
+            let ipart = *index.entry(id).or_insert_with(|| {
+                parts.push((id, Vec::new()));
+                parts.len() - 1
+            });
+            parts[ipart].1.push(iel);
+        }
+        parts
+            .into_iter()
+            .map(|(id, elements)| {
+                let text = self.part_texts[iblock].iter().find(|text| atoi_prefix(text) == id);
+                (text.map_or("default", |text| text.as_str()), elements)
+            })
+            .collect()
+    }
+}
+
+pub struct Buffered<O> {
+    mesh: MeshBuffer,
+    pub output: O,
+}
+
+impl<O: MeshOutput> Buffered<O> {
+    pub fn new(output: O) -> Self {
+        Buffered { mesh: MeshBuffer::default(), output }
+    }
+}
+
+impl<O: MeshOutput> MeshWriter for Buffered<O> {
+    fn begin_geometry(&mut self, header: &MeshHeader) -> io::Result<()> {
+        let mesh = &mut self.mesh;
+        mesh.time = header.time;
+        mesh.part_texts = header.part_texts.map(|texts| texts.to_vec());
+        mesh.coor.reserve(3 * header.nb_points);
+        mesh.cell_types.reserve(header.nb_cells);
+        mesh.offsets = Vec::with_capacity(header.nb_cells + 1);
+        mesh.offsets.push(0);
+        mesh.connectivity.reserve(header.connectivity_size);
+        Ok(())
+    }
+
+    fn write_points_chunk(&mut self, coor: &[f32]) -> io::Result<()> {
+        self.mesh.coor.extend_from_slice(coor);
+        Ok(())
+    }
+
+    fn write_cells_chunk(&mut self, cells: &mut dyn Iterator<Item = (i32, &[i32])>) -> io::Result<()> {
+        let mesh = &mut self.mesh;
+        for (cell_type, nodes) in cells {
+            mesh.cell_types.push(cell_type);
+            mesh.connectivity.extend_from_slice(nodes);
+            mesh.offsets.push(mesh.connectivity.len());
+        }
+        Ok(())
+    }
+
+    fn write_point_field(&mut self, name: &str, kind: FieldKind, values: PointValues) -> io::Result<()> {
+        match values {
+            PointValues::Int(ids) if name == "NODE_ID" => self.mesh.node_ids = ids.to_vec(),
+            PointValues::Float(values) if self.output.keeps(name) => {
+                self.mesh.point_fields.push((name.to_string(), kind, values.to_vec()));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn write_cell_field(&mut self, name: &str, kind: FieldKind, blocks: &[CellValues]) -> io::Result<()> {
+        let mesh = &mut self.mesh;
+        // blocks of cells, from the first cell field: a block can come in several cell chunks
+        if mesh.block_starts.is_empty() {
+            mesh.block_starts = std::iter::once(0)
+                .chain(blocks.iter().scan(0, |end, values| {
+                    *end += cell_count(values);
+                    Some(*end)
+                }))
+                .collect();
+        }
+        let ints = || {
+            let int_values = |values: &CellValues| if let CellValues::Int(values) = values { values.to_vec() } else { Vec::new() };
+            blocks.iter().map(int_values).collect()
+        };
+        match name {
+            "ELEMENT_ID" => mesh.element_ids = ints(),
+            "PART_ID" => mesh.part_ids = ints(),
+            "EROSION_STATUS" => mesh.status = ints(),
+            _ if kind == FieldKind::Color || blocks.iter().any(|values| matches!(values, CellValues::Int(_))) => {}
+            _ if self.output.keeps(name) => {
+                let values = blocks
+                    .iter()
+                    .map(|values| match values {
+                        CellValues::Zeros(_) => None,
+                        values => match collect_cell_field(kind, std::slice::from_ref(values), cell_count(values)) {
+                            CellArray::Float(values) => Some(values),
+                            CellArray::Int(_) | CellArray::Rgb(_) => None,
+                        },
+                    })
+                    .collect();
+                mesh.cell_fields.push((name.to_string(), kind, values));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.output.write(&self.mesh)
+    }
+}
+
+// Shells and solids are converted to VTK cells and written by chunks of
+// elements: a cell list of the whole model would hold its connectivity
+// a second time
+const CELL_CHUNK: usize = 1 << 16;
+
+// integer cell array built and released block by block
+fn write_int_cell_field(out: &mut dyn MeshWriter, name: &str, arrays: &[Vec<i32>]) -> io::Result<()> {
+    let values: Vec<CellValues> = arrays.iter().map(|values| CellValues::Int(values)).collect();
+    out.write_cell_field(name, FieldKind::Scalar, &values)
+}
+
+// elemental field of one block, zero on the others
+fn padded<'a>(counts: &[usize], active: usize, values: CellValues<'a>) -> Vec<CellValues<'a>> {
+    let mut padded: Vec<CellValues> = counts.iter().map(|&count| CellValues::Zeros(count)).collect();
+    padded[active] = values;
+    padded
+}
+
+// ****************************************
+// write an A-File content through a MeshWriter
+// ****************************************
+pub fn write_mesh(anim: &AnimFile, options: &WriteOptions, out: &mut dyn MeshWriter) -> io::Result<()> {
+    let nb_nodes = anim.nb_nodes;
+    let beams = &anim.elts_1d;
+    let shells = &anim.elts_2d;
+    let solids = &anim.elts_3d;
+    // without cells nor arrays when the particles go to their own file
+    let no_sph = ElementBlock::default();
+    let sph = if options.split_sph { &no_sph } else { &anim.elts_sph };
+    let blocks = [beams, shells, solids, sph];
+    let counts = [beams.count, shells.count, solids.count, sph.count];
+
+    // cell sizes first, the header gives the connectivity size; the
+    // fix-up of the orientation reorders nodes but keeps their number
+    let connectivity_size = beams.count * 2
+        + (0..shells.count).map(|icon| ShellCell::from_facet(shells.nodes(icon)).nodes().len()).sum::<usize>()
+        + solids.connect.chunks_exact(8).map(|brick| SolidCell::from_brick(brick).nodes().len()).sum::<usize>()
+        + sph.count;
+
+    let material_names = anim.material_names();
+    let mut string_tables = Vec::new();
+    // names indexed by the MATERIAL_INDEX cell array
+    if !material_names.is_empty() {
+        string_tables.push(("MATERIAL_NAME", material_names));
+    }
+    out.begin_geometry(&MeshHeader {
+        time: anim.time as f64,
+        nb_points: nb_nodes,
+        nb_cells: counts.iter().sum(),
+        connectivity_size,
+        string_tables,
+        ranges: if options.ranges { field_ranges(anim, options)? } else { Vec::new() },
+        mode_phase: options.mode_phase,
+        part_texts: blocks.map(|block| &block.part_text[..]),
+    })?;
+
+    // nodes
+    out.write_points_chunk(&anim.coor[..3 * nb_nodes])?;
+
+    // cells: tetrahedra detected in 3D cells, triangles in 2D cells
+    out.write_cells_chunk(&mut (0..beams.count).map(|icon| (cells::VTK_LINE, beams.nodes(icon))))?;
+    for first in (0..shells.count).step_by(CELL_CHUNK) {
+        let chunk: Vec<ShellCell> =
+            (first..shells.count.min(first + CELL_CHUNK)).map(|icon| ShellCell::from_facet(shells.nodes(icon))).collect();
+        out.write_cells_chunk(&mut chunk.iter().map(|cell| (cell.vtk_type(), cell.nodes())))?;
+    }
+    let mut inverted_3d = Vec::new();
+    for bricks in solids.connect.chunks(8 * CELL_CHUNK) {
+        let mut chunk = cells::solid_cells(bricks);
+        if options.fix_orientation {
+            inverted_3d.extend(cells::fix_solid_orientation(&mut chunk, &anim.coor));
+        }
+        out.write_cells_chunk(&mut chunk.iter().map(|cell| (cell.vtk_type(), cell.nodes())))?;
+    }
+    out.write_cells_chunk(&mut (0..sph.count).map(|icon| (cells::VTK_VERTEX, sph.nodes(icon))))?;
+
+    // nodal scalars & vectors, user ids when the file has them (flag_a[1])
+    if anim.nod_num.len() >= nb_nodes {
+        out.write_point_field("NODE_ID", FieldKind::Scalar, PointValues::Int(&anim.nod_num[..nb_nodes]))?;
+    }
+
+    for (ifun, text) in anim.func_text.iter().enumerate() {
+        let name = replace_underscore(text);
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.nodal_scalar(ifun)?;
+        out.write_point_field(&name, FieldKind::Scalar, PointValues::Float(&values))?;
+    }
+
+    for (ivect, text) in anim.vect_text.iter().enumerate() {
+        let name = replace_underscore(text);
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.nodal_vector(ivect)?;
+        out.write_point_field(&name, FieldKind::Vector, PointValues::Float(&values[..3 * nb_nodes]))?;
+    }
+
+    // element id
+    if blocks.iter().all(|block| block.el_num.len() == block.count) {
+        let el_num: Vec<CellValues> = blocks.iter().map(|block| CellValues::Int(&block.el_num)).collect();
+        out.write_cell_field("ELEMENT_ID", FieldKind::Scalar, &el_num)?;
+    }
+
+    // part id, and color of the part from its id
+    {
+        let part_ids: Vec<Vec<i32>> = blocks
+            .iter()
+            .map(|block| {
+                let mut part_index: usize = 0;
+                (0..block.count)
+                    .map(|iel| resolve_part_id(iel, &mut part_index, &block.def_part, &block.part_text))
+                    .collect()
+            })
+            .collect();
+        write_int_cell_field(out, "PART_ID", &part_ids)?;
+        if let Some(part_colors) = &options.part_colors {
+            let colors: Vec<Vec<u8>> = part_ids.iter().map(|ids| part_colors.colors(ids)).collect();
+            let values: Vec<CellValues> = colors.iter().map(|rgb| CellValues::Rgb(rgb)).collect();
+            out.write_cell_field("PART_COLOR", FieldKind::Color, &values)?;
+        }
+    }
+
+    // material of the part, index in MATERIAL_NAME (-1: none)
+    if !material_names.is_empty() {
+        let indices: Vec<Vec<i32>> = blocks.iter().map(|block| block.material_indices(material_names.len())).collect();
+        write_int_cell_field(out, "MATERIAL_INDEX", &indices)?;
+    }
+
+    // element erosion status (0:off, 1:on)
+    let status: Vec<Vec<i32>> = blocks
+        .iter()
+        .map(|block| block.del_elt.iter().map(|&del| if del == 1 { 1 } else { 0 }).collect())
+        .collect();
+    write_int_cell_field(out, "EROSION_STATUS", &status)?;
+
+    // raw del_elt codes, only when some are neither 0 nor 1: their meaning
+    // (failure mode, deactivation reason) is not documented for FASTMAGI10,
+    // so they are passed through instead of being collapsed into EROSION_STATUS
+    if blocks.iter().any(|block| block.del_elt.iter().any(|&del| del > 1)) {
+        let codes: Vec<Vec<i32>> = blocks
+            .iter()
+            .map(|block| block.del_elt.iter().map(|&del| del as i32).collect())
+            .collect();
+        write_int_cell_field(out, "STATUS_CODE", &codes)?;
+    }
+
+    // solids left inverted after orientation fix-up (0:ok, 1:inverted)
+    if options.fix_orientation {
+        let values = [
+            CellValues::Zeros(beams.count),
+            CellValues::Zeros(shells.count),
+            CellValues::Int(&inverted_3d),
+            CellValues::Zeros(sph.count),
+        ];
+        out.write_cell_field("INVERTED", FieldKind::Scalar, &values)?;
+    }
+
+    // integer cell arrays added by conversion steps, zero on blocks without them
+    let mut int_array_names: Vec<&str> = Vec::new();
+    for block in blocks {
+        for (name, _) in &block.int_arrays {
+            if !int_array_names.contains(&name.as_str()) {
+                int_array_names.push(name);
+            }
+        }
+    }
+    for name in int_array_names {
+        let values: Vec<CellValues> = blocks
+            .iter()
+            .map(|block| match block.int_arrays.iter().find(|(array_name, _)| array_name == name) {
+                Some((_, values)) => CellValues::Int(values),
+                None => CellValues::Zeros(block.count),
+            })
+            .collect();
+        out.write_cell_field(name, FieldKind::Scalar, &values)?;
+    }
+
+    // 1D elemental scalars
+    for (iefun, text) in beams.efunc_text.iter().enumerate() {
+        let name = format!("1DELEM_{}", replace_underscore(text));
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.elemental_scalar(beams, iefun)?;
+        out.write_cell_field(&name, FieldKind::Scalar, &padded(&counts, 0, CellValues::Float(&values[..beams.count])))?;
+    }
+
+    // 1D torseur values
+    for (iefun, text) in beams.tens_text.iter().enumerate() {
+        let names: Vec<String> =
+            TORSEUR_SUFFIXES.iter().map(|suffix| format!("1DELEM_{}{}", replace_underscore(text), suffix)).collect();
+        if !names.iter().any(|name| options.selects(text, name)) {
+            continue;
+        }
+        let values = anim.elemental_tensor(beams, iefun)?;
+        for (j, name) in names.iter().enumerate().filter(|(_, name)| options.selects(text, name)) {
+            let component = CellValues::Strided { values: &values[..9 * beams.count], stride: 9, offset: j };
+            out.write_cell_field(name, FieldKind::Scalar, &padded(&counts, 0, component))?;
+        }
+    }
+
+    // 2D elemental scalars
+    for (iefun, text) in shells.efunc_text.iter().enumerate() {
+        let name = format!("2DELEM_{}", replace_underscore(text));
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.elemental_scalar(shells, iefun)?;
+        out.write_cell_field(&name, FieldKind::Scalar, &padded(&counts, 1, CellValues::Float(&values[..shells.count])))?;
+    }
+
+    // 2D tensors
+    for (ietens, text) in shells.tens_text.iter().enumerate() {
+        let name = format!("2DELEM_{}", replace_underscore(text));
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.elemental_tensor(shells, ietens)?;
+        out.write_cell_field(&name, FieldKind::Tensor, &padded(&counts, 1, CellValues::Tensor3(&values[..3 * shells.count])))?;
+    }
+
+    // 3D elemental scalars
+    for (iefun, text) in solids.efunc_text.iter().enumerate() {
+        let name = format!("3DELEM_{}", replace_underscore(text));
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.elemental_scalar(solids, iefun)?;
+        out.write_cell_field(&name, FieldKind::Scalar, &padded(&counts, 2, CellValues::Float(&values[..solids.count])))?;
+    }
+
+    // 3D tensors
+    for (ietens, text) in solids.tens_text.iter().enumerate() {
+        let name = format!("3DELEM_{}", replace_underscore(text));
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.elemental_tensor(solids, ietens)?;
+        out.write_cell_field(&name, FieldKind::Tensor, &padded(&counts, 2, CellValues::Tensor6(&values[..6 * solids.count])))?;
+    }
+
+    // SPH scalars and tensors
+    for (iefun, text) in sph.efunc_text.iter().enumerate() {
+        let name = format!("SPHELEM_{}", replace_underscore(text));
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.elemental_scalar(sph, iefun)?;
+        out.write_cell_field(&name, FieldKind::Scalar, &padded(&counts, 3, CellValues::Float(&values[..sph.count])))?;
+    }
+
+    for (ietens, text) in sph.tens_text.iter().enumerate() {
+        let name = format!("SPHELEM_{}", replace_underscore(text));
+        if !options.selects(text, &name) {
+            continue;
+        }
+        let values = anim.elemental_tensor(sph, ietens)?;
+        out.write_cell_field(&name, FieldKind::Tensor, &padded(&counts, 3, CellValues::Tensor6(&values[..6 * sph.count])))?;
+    }
+
+    out.finish()
+}
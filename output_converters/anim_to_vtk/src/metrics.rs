@@ -0,0 +1,103 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Conversion metrics in the Prometheus text format, rewritten after
+// every file so that a node exporter (textfile collector) or a farm
+// monitor always sees the current counters
+// ****************************************
+
+use std::fs;
+use std::io;
+use std::time::Duration;
+
+// upper bounds of the conversion duration histogram, in seconds
+const DURATION_BUCKETS: [f64; 9] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+pub struct Metrics {
+    path: String,
+    files_converted: u64,
+    files_failed: u64,
+    bytes_read: u64,
+    bytes_written: u64,
+    duration_counts: [u64; DURATION_BUCKETS.len()],
+    duration_sum: f64,
+}
+
+fn file_size(path: &str) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+impl Metrics {
+    pub fn new(path: &str) -> Self {
+        Metrics {
+            path: path.to_string(),
+            files_converted: 0,
+            files_failed: 0,
+            bytes_read: 0,
+            bytes_written: 0,
+            duration_counts: [0; DURATION_BUCKETS.len()],
+            duration_sum: 0.0,
+        }
+    }
+
+    pub fn record_success(&mut self, input: &str, outputs: &[String], duration: Duration) {
+        self.files_converted += 1;
+        self.bytes_read += file_size(input);
+        self.bytes_written += outputs.iter().map(|output| file_size(output)).sum::<u64>();
+        let seconds = duration.as_secs_f64();
+        self.duration_sum += seconds;
+        for (count, &bound) in self.duration_counts.iter_mut().zip(&DURATION_BUCKETS) {
+            if seconds <= bound {
+                *count += 1;
+            }
+        }
+    }
+
+    pub fn set_failures(&mut self, failed: usize) {
+        self.files_failed = failed as u64;
+    }
+
+    // written to a temporary file then renamed, readers never see a partial file
+    pub fn write(&self) -> io::Result<()> {
+        let mut text = String::new();
+        let mut counter = |name: &str, help: &str, value: u64| {
+            text.push_str(&format!("# HELP {} {}\n# TYPE {} counter\n{} {}\n", name, help, name, name, value));
+        };
+        counter("anim_to_vtk_files_converted_total", "A-files converted successfully.", self.files_converted);
+        counter("anim_to_vtk_files_failed_total", "A-files that failed to convert.", self.files_failed);
+        counter("anim_to_vtk_bytes_read_total", "Bytes of A-files read.", self.bytes_read);
+        counter("anim_to_vtk_bytes_written_total", "Bytes of output files written.", self.bytes_written);
+
+        let name = "anim_to_vtk_conversion_duration_seconds";
+        text.push_str(&format!("# HELP {} Duration of one A-file conversion.\n# TYPE {} histogram\n", name, name));
+        for (count, bound) in self.duration_counts.iter().zip(&DURATION_BUCKETS) {
+            text.push_str(&format!("{}_bucket{{le=\"{}\"}} {}\n", name, bound, count));
+        }
+        text.push_str(&format!("{}_bucket{{le=\"+Inf\"}} {}\n", name, self.files_converted));
+        text.push_str(&format!("{}_sum {}\n", name, self.duration_sum));
+        text.push_str(&format!("{}_count {}\n", name, self.files_converted));
+
+        let temporary = format!("{}.tmp", self.path);
+        fs::write(&temporary, text)?;
+        fs::rename(&temporary, &self.path)
+    }
+}
@@ -0,0 +1,136 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Eigen/modal animation files: expand mode shapes into outputs
+// ****************************************
+
+use std::f64::consts::PI;
+
+use report::{codes, Diagnostic};
+
+use crate::anim::{field_name_matches, AnimFile};
+use crate::vtk::{remove_files, write_vtk_file, WriteOptions};
+
+// ****************************************
+// Nodal vectors holding mode shapes: those named in <names>, in their
+// order, or without names those whose name contains MODE ("MODE 1",
+// "Mode_2", ...), as the solver names them. A listed name matching no
+// vector fails; finding none by name is left to the caller.
+// ****************************************
+pub fn mode_vectors(anim: &AnimFile, names: &[String]) -> Result<Vec<usize>, Diagnostic> {
+    if names.is_empty() {
+        return Ok(anim
+            .vect_text
+            .iter()
+            .enumerate()
+            .filter(|(_, text)| text.to_ascii_uppercase().contains("MODE"))
+            .map(|(ivect, _)| ivect)
+            .collect());
+    }
+    let mut modes = Vec::with_capacity(names.len());
+    for name in names {
+        match anim.vect_text.iter().position(|text| field_name_matches(text, name)) {
+            Some(ivect) => modes.push(ivect),
+            None => {
+                let vectors: Vec<&str> = anim.vect_text.iter().map(|text| text.trim()).collect();
+                return Err(Diagnostic::new(
+                    codes::CONVERSION_FAILED,
+                    format_args!("no nodal vector named {} for --modes, the vectors are: {}", name, vectors.join(", ")),
+                ));
+            }
+        }
+    }
+    Ok(modes)
+}
+
+fn write_deformed(
+    anim: &mut AnimFile,
+    base_coor: &[f32],
+    ivect: usize,
+    factor: f64,
+    output_file_name: &str,
+    options: &WriteOptions,
+) -> Result<(), Diagnostic> {
+    let shape = anim.nodal_vector(ivect).map_err(|e| Diagnostic::new(e.code(), e))?.into_owned();
+    for (i, coor) in anim.coor.iter_mut().enumerate() {
+        *coor = (base_coor[i] as f64 + factor * shape[i] as f64) as f32;
+    }
+    write_vtk_file(anim, options, output_file_name).map_err(|e| Diagnostic::new(codes::WRITE_FAILED, e))
+}
+
+// ****************************************
+// Write one deformed output per mode shape of <modes>, or with
+// phases > 0 a harmonic time series of <phases> steps per mode
+// (TIME kept, MODE_PHASE holds the phase as a fraction of the period).
+// Returns the written files; on failure, none is left.
+// ****************************************
+pub fn write_mode_outputs(
+    anim: &mut AnimFile,
+    modes: &[usize],
+    file_name: &str,
+    options: &WriteOptions,
+    scale: f32,
+    phases: usize,
+) -> Result<Vec<String>, Diagnostic> {
+    // the state is given back undeformed, for the per-state outputs
+    let base_coor = anim.coor.clone();
+    let written = write_modes(anim, &base_coor, modes, file_name, options, scale, phases);
+    anim.coor = base_coor;
+    written
+}
+
+fn write_modes(
+    anim: &mut AnimFile,
+    base_coor: &[f32],
+    modes: &[usize],
+    file_name: &str,
+    options: &WriteOptions,
+    scale: f32,
+    phases: usize,
+) -> Result<Vec<String>, Diagnostic> {
+    let mut outputs = Vec::new();
+    for (imode, &ivect) in modes.iter().enumerate() {
+        if phases == 0 {
+            let output_file_name = format!("{}_mode{}.vtk", file_name, imode + 1);
+            report_line!("Converting {} ({}) to {}", file_name, anim.vect_text[ivect], output_file_name);
+            if let Err(e) = write_deformed(anim, base_coor, ivect, scale as f64, &output_file_name, options) {
+                remove_files(&outputs);
+                return Err(e);
+            }
+            outputs.push(output_file_name);
+            continue;
+        }
+        for iphase in 0..phases {
+            let phase = iphase as f64 / phases as f64;
+            let factor = scale as f64 * (2.0 * PI * phase).sin();
+            let output_file_name = format!("{}_mode{}_{:03}.vtk", file_name, imode + 1, iphase);
+            report_line!("Converting {} ({}) to {}", file_name, anim.vect_text[ivect], output_file_name);
+            let options = WriteOptions { mode_phase: Some(phase), ..options.clone() };
+            if let Err(e) = write_deformed(anim, base_coor, ivect, factor, &output_file_name, &options) {
+                remove_files(&outputs);
+                return Err(e);
+            }
+            outputs.push(output_file_name);
+        }
+    }
+    Ok(outputs)
+}
@@ -0,0 +1,413 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Multiblock output (.vtm): one VTK XML unstructured grid per part,
+// holding its elements and the nodes they use, under a block tree that
+// follows the subset hierarchy (flag_a[4]) of the A-file, so that the
+// assembly can be browsed and parts shown or hidden in ParaView.
+// Without hierarchy, parts are grouped by element type.
+// Without padding (--no-pad), a file only holds the elemental arrays of
+// its element type, instead of zeros for the arrays of the other types;
+// alone, --no-pad writes one file per element type.
+// The partition by element type (--partition-by-type) writes one piece
+// per type under a parallel unstructured grid (.pvtu) instead.
+// ****************************************
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::anim::{AnimFile, ElementBlock};
+use crate::mesh_writer::write_mesh;
+use crate::resample::xml_attribute;
+use crate::subset::gather_nodes;
+use crate::vtk::WriteOptions;
+use crate::vtu::VtuWriter;
+
+// element blocks in AnimFile order, with the name of their parts' files and tree groups
+const KINDS: [(&str, &str); 4] = [("beam", "Beams"), ("shell", "Shells"), ("solid", "Solids"), ("sph", "SPH")];
+
+fn blocks(anim: &AnimFile) -> [&ElementBlock; 4] {
+    [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph]
+}
+
+// block without elements, keeping the array names of the block when padded
+fn empty_like(block: &ElementBlock, pad: bool) -> ElementBlock {
+    if !pad {
+        return ElementBlock { nodes_per_elt: block.nodes_per_elt, tens_stride: block.tens_stride, ..Default::default() };
+    }
+    ElementBlock {
+        nodes_per_elt: block.nodes_per_elt,
+        tens_stride: block.tens_stride,
+        efunc_text: block.efunc_text.clone(),
+        tens_text: block.tens_text.clone(),
+        int_arrays: block.int_arrays.iter().map(|(name, _)| (name.clone(), Vec::new())).collect(),
+        ..Default::default()
+    }
+}
+
+// state of the elements of one block, with the nodes they use, renumbered
+fn block_state(anim: &AnimFile, iblock: usize, mut part: ElementBlock, pad: bool) -> AnimFile {
+    let mut nodes: Vec<i32> = part.connect.clone();
+    nodes.sort_unstable();
+    nodes.dedup();
+    for node in part.connect.iter_mut() {
+        *node = nodes.binary_search(node).unwrap_or_default() as i32;
+    }
+    let kept: Vec<usize> = nodes.iter().map(|&node| node as usize).collect();
+    let nb_nodes = anim.nb_nodes;
+
+    let mut element_blocks = blocks(anim).map(|block| empty_like(block, pad));
+    element_blocks[iblock] = part;
+    let [elts_1d, elts_2d, elts_3d, elts_sph] = element_blocks;
+    AnimFile {
+        time: anim.time,
+        time_text: anim.time_text.clone(),
+        mod_anim_text: anim.mod_anim_text.clone(),
+        radioss_run_text: anim.radioss_run_text.clone(),
+        flag_a: anim.flag_a.clone(),
+        nb_nodes: kept.len(),
+        coor: gather_nodes(&anim.coor, &kept, 3, nb_nodes),
+        nod_num: gather_nodes(&anim.nod_num, &kept, 1, nb_nodes),
+        n_mass: gather_nodes(&anim.n_mass, &kept, 1, nb_nodes),
+        func_text: anim.func_text.clone(),
+        func: gather_nodes(&anim.func, &kept, 1, nb_nodes),
+        vect_text: anim.vect_text.clone(),
+        vect_val: gather_nodes(&anim.vect_val, &kept, 3, nb_nodes),
+        elts_1d,
+        elts_2d,
+        elts_3d,
+        elts_sph,
+        hierarchy: anim.hierarchy.clone(),
+        source: anim.source.clone(),
+        layout_name: anim.layout_name,
+        ..Default::default()
+    }
+}
+
+// a part written to its own file
+struct PartFile {
+    name: String, // part text, "<id> <title>"
+    file: String, // relative to the .vtm
+}
+
+// block tree of the .vtm, from the subsets of the hierarchy
+struct Tree<'a> {
+    anim: &'a AnimFile,
+    parts: HashMap<(usize, usize), PartFile>, // (block, part index) -> file
+    placed: HashSet<(usize, usize)>,
+    visited: HashSet<usize>,
+    xml: String,
+}
+
+impl Tree<'_> {
+    fn indent(depth: usize) -> String {
+        "  ".repeat(depth + 2)
+    }
+
+    fn dataset(&mut self, key: (usize, usize), index: usize, depth: usize) -> bool {
+        let Some(part) = self.parts.get(&key) else {
+            return false;
+        };
+        if !self.placed.insert(key) {
+            return false;
+        }
+        self.xml.push_str(&format!(
+            "{}<DataSet index=\"{}\" name=\"{}\" file=\"{}\"/>\n",
+            Self::indent(depth),
+            index,
+            xml_attribute(part.name.trim()),
+            xml_attribute(&part.file)
+        ));
+        true
+    }
+
+    // parts of the subset, then its sons
+    fn subset(&mut self, isubset: usize, index: usize, depth: usize) {
+        let Some(subset) = self.anim.hierarchy.as_ref().and_then(|hierarchy| hierarchy.subsets.get(isubset)) else {
+            return;
+        };
+        if !self.visited.insert(isubset) {
+            return;
+        }
+        self.xml.push_str(&format!("{}<Block index=\"{}\" name=\"{}\">\n", Self::indent(depth), index, xml_attribute(subset.text.trim())));
+        let mut keys: Vec<(usize, usize)> = Vec::new();
+        for (iblock, parts) in [(0, &subset.parts_1d), (1, &subset.parts_2d), (2, &subset.parts_3d)] {
+            keys.extend(parts.iter().filter(|&&ipart| ipart >= 0).map(|&ipart| (iblock, ipart as usize)));
+        }
+        let sph = &self.anim.elts_sph.part2subset;
+        keys.extend((0..sph.len()).filter(|&ipart| sph[ipart] == isubset as i32).map(|ipart| (3, ipart)));
+        let mut child = 0;
+        for key in keys {
+            if self.dataset(key, child, depth + 1) {
+                child += 1;
+            }
+        }
+        for &son in &subset.sons {
+            if son >= 0 && !self.visited.contains(&(son as usize)) {
+                self.subset(son as usize, child, depth + 1);
+                child += 1;
+            }
+        }
+        self.xml.push_str(&format!("{}</Block>\n", Self::indent(depth)));
+    }
+
+    // parts left out of the subsets, in a block per element type
+    fn groups(&mut self, mut index: usize) {
+        for (iblock, (_, group)) in KINDS.iter().enumerate() {
+            let mut keys: Vec<(usize, usize)> =
+                self.parts.keys().filter(|key| key.0 == iblock && !self.placed.contains(key)).copied().collect();
+            if keys.is_empty() {
+                continue;
+            }
+            keys.sort_unstable();
+            self.xml.push_str(&format!("{}<Block index=\"{}\" name=\"{}\">\n", Self::indent(0), index, group));
+            for (child, key) in keys.into_iter().enumerate() {
+                self.dataset(key, child, 1);
+            }
+            self.xml.push_str(&format!("{}</Block>\n", Self::indent(0)));
+            index += 1;
+        }
+    }
+}
+
+// ****************************************
+// Writes <input>.vtm and the files of its parts in <input>_parts/,
+// named <type>_part<id>.vtu. Parts without elements have no file.
+// Returns the .vtm followed by the part files.
+// ****************************************
+pub fn write_multiblock(anim: &AnimFile, file_name: &str, options: &WriteOptions, pad: bool) -> Result<Vec<String>, String> {
+    let parts_dir = format!("{}_parts", file_name);
+    fs::create_dir_all(&parts_dir).map_err(|e| format!("can't create directory {}: {}", parts_dir, e))?;
+    let base_name = Path::new(file_name).file_name().map_or(file_name.to_string(), |name| name.to_string_lossy().into_owned());
+
+    let mut outputs = vec![format!("{}.vtm", file_name)];
+    let mut parts = HashMap::new();
+    for (iblock, block) in blocks(anim).into_iter().enumerate() {
+        let mut elements: Vec<Vec<usize>> = vec![Vec::new(); block.part_text.len()];
+        for (iel, ipart) in block.part_indices().into_iter().enumerate() {
+            if let Some(elements) = elements.get_mut(ipart) {
+                elements.push(iel);
+            }
+        }
+        let mut names = HashSet::new();
+        for (ipart, elements) in elements.iter().enumerate().filter(|(_, elements)| !elements.is_empty()) {
+            let id = crate::anim::atoi_prefix(&block.part_text[ipart]);
+            let mut name = format!("{}_part{}.vtu", KINDS[iblock].0, id);
+            if !names.insert(name.clone()) {
+                name = format!("{}_part{}_{}.vtu", KINDS[iblock].0, id, ipart + 1);
+            }
+            let path = format!("{}/{}", parts_dir, name);
+            let mut writer = VtuWriter::create(&path, options.binary)?;
+            let part = block.gather_part(ipart, elements);
+            write_mesh(&block_state(anim, iblock, part, pad), options, &mut writer).map_err(|e| format!("can't write {}: {}", path, e))?;
+            outputs.push(path);
+            let file = format!("{}_parts/{}", base_name, name);
+            parts.insert((iblock, ipart), PartFile { name: block.part_text[ipart].clone(), file });
+        }
+    }
+    if parts.is_empty() {
+        return Err("no part with elements to write".to_string());
+    }
+
+    let mut tree = Tree { anim, parts, placed: HashSet::new(), visited: HashSet::new(), xml: String::new() };
+    let subsets = anim.hierarchy.as_ref().map_or(0, |hierarchy| hierarchy.subsets.len());
+    let sons: HashSet<i32> = anim.hierarchy.iter().flat_map(|hierarchy| hierarchy.subsets.iter()).flat_map(|subset| subset.sons.iter().copied()).collect();
+    let mut index = 0;
+    for isubset in (0..subsets).filter(|&isubset| !sons.contains(&(isubset as i32))) {
+        tree.subset(isubset, index, 0);
+        index += 1;
+    }
+    tree.groups(index);
+    write_vtm(&outputs[0], &tree.xml)?;
+    Ok(outputs)
+}
+
+// ****************************************
+// Writes <input>.vtm and one file per element type with elements in
+// <input>_types/, named <type>.vtu, each holding all the elements of
+// its type (eroded ones included, as in the regular output).
+// Returns the .vtm followed by the type files.
+// ****************************************
+pub fn write_element_types(anim: &AnimFile, file_name: &str, options: &WriteOptions) -> Result<Vec<String>, String> {
+    let types_dir = format!("{}_types", file_name);
+    fs::create_dir_all(&types_dir).map_err(|e| format!("can't create directory {}: {}", types_dir, e))?;
+    let base_name = Path::new(file_name).file_name().map_or(file_name.to_string(), |name| name.to_string_lossy().into_owned());
+
+    let mut outputs = vec![format!("{}.vtm", file_name)];
+    let mut xml = String::new();
+    for (iblock, block) in blocks(anim).into_iter().enumerate().filter(|(_, block)| block.count > 0) {
+        let (kind, group) = KINDS[iblock];
+        let path = format!("{}/{}.vtu", types_dir, kind);
+        let mut writer = VtuWriter::create(&path, options.binary)?;
+        write_mesh(&block_state(anim, iblock, block.clone(), false), options, &mut writer).map_err(|e| format!("can't write {}: {}", path, e))?;
+        xml.push_str(&format!(
+            "    <DataSet index=\"{}\" name=\"{}\" file=\"{}\"/>\n",
+            outputs.len() - 1,
+            group,
+            xml_attribute(&format!("{}_types/{}.vtu", base_name, kind))
+        ));
+        outputs.push(path);
+    }
+    if outputs.len() == 1 {
+        return Err("no element to write".to_string());
+    }
+    write_vtm(&outputs[0], &xml)?;
+    Ok(outputs)
+}
+
+// ****************************************
+// Writes <input>.pvtu and one piece per element type with elements in
+// <input>_pieces/, named <type>.vtu, each holding all the elements of
+// its type. The pieces of a parallel file share their arrays, so the
+// elemental arrays of the other types are padded with zeros as in the
+// regular output (--no-pad writes them without).
+// Returns the .pvtu followed by the pieces.
+// ****************************************
+pub fn write_partitioned(anim: &AnimFile, file_name: &str, options: &WriteOptions) -> Result<Vec<String>, String> {
+    let pieces_dir = format!("{}_pieces", file_name);
+    fs::create_dir_all(&pieces_dir).map_err(|e| format!("can't create directory {}: {}", pieces_dir, e))?;
+    let base_name = Path::new(file_name).file_name().map_or(file_name.to_string(), |name| name.to_string_lossy().into_owned());
+
+    let mut outputs = vec![format!("{}.pvtu", file_name)];
+    let mut declarations = String::new();
+    let mut pieces = String::new();
+    for (iblock, block) in blocks(anim).into_iter().enumerate().filter(|(_, block)| block.count > 0) {
+        let kind = KINDS[iblock].0;
+        let path = format!("{}/{}.vtu", pieces_dir, kind);
+        let mut writer = VtuWriter::create(&path, options.binary)?;
+        write_mesh(&block_state(anim, iblock, block.clone(), true), options, &mut writer).map_err(|e| format!("can't write {}: {}", path, e))?;
+        declarations = writer.parallel_declarations();
+        pieces.push_str(&format!("    <Piece Source=\"{}\"/>\n", xml_attribute(&format!("{}_pieces/{}.vtu", base_name, kind))));
+        outputs.push(path);
+    }
+    if outputs.len() == 1 {
+        return Err("no element to write".to_string());
+    }
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\"?>\n");
+    xml.push_str("<VTKFile type=\"PUnstructuredGrid\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt32\">\n");
+    xml.push_str("  <PUnstructuredGrid GhostLevel=\"0\">\n");
+    xml.push_str(&declarations);
+    xml.push_str("    <PPoints>\n      <PDataArray type=\"Float32\" NumberOfComponents=\"3\"/>\n    </PPoints>\n");
+    xml.push_str(&pieces);
+    xml.push_str("  </PUnstructuredGrid>\n");
+    xml.push_str("</VTKFile>\n");
+    fs::write(&outputs[0], xml).map_err(|e| format!("can't write {}: {}", outputs[0], e))?;
+    Ok(outputs)
+}
+
+fn write_vtm(path: &str, blocks: &str) -> Result<(), String> {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\"?>\n");
+    xml.push_str("<VTKFile type=\"vtkMultiBlockDataSet\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt32\">\n");
+    xml.push_str("  <vtkMultiBlockDataSet>\n");
+    xml.push_str(blocks);
+    xml.push_str("  </vtkMultiBlockDataSet>\n");
+    xml.push_str("</VTKFile>\n");
+    fs::write(path, xml).map_err(|e| format!("can't write {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::{Hierarchy, Subset};
+    use crate::test_files::{quad_parts, TempDir};
+
+    fn piece_sizes(path: &str) -> String {
+        let text = fs::read_to_string(path).unwrap();
+        let start = text.find("<Piece ").unwrap();
+        text[start..start + text[start..].find('>').unwrap() + 1].to_string()
+    }
+
+    #[test]
+    fn parts_without_hierarchy_are_grouped_by_type() {
+        let dir = TempDir::new("multiblock");
+        let base = dir.file("runA001");
+        let anim = quad_parts(&[("10 Front", 2), ("20 Empty", 0), ("30 Rear & back", 1)]);
+        let outputs = write_multiblock(&anim, &base, &WriteOptions::default(), true).unwrap();
+
+        assert_eq!(
+            outputs,
+            [format!("{}.vtm", base), format!("{}_parts/shell_part10.vtu", base), format!("{}_parts/shell_part30.vtu", base)]
+        );
+        let vtm = fs::read_to_string(&outputs[0]).unwrap();
+        assert!(vtm.contains(
+            "    <Block index=\"0\" name=\"Shells\">\n      \
+             <DataSet index=\"0\" name=\"10 Front\" file=\"runA001_parts/shell_part10.vtu\"/>\n      \
+             <DataSet index=\"1\" name=\"30 Rear &amp; back\" file=\"runA001_parts/shell_part30.vtu\"/>\n    </Block>\n"
+        ));
+        // each part keeps the nodes its elements use
+        assert_eq!(piece_sizes(&outputs[1]), "<Piece NumberOfPoints=\"6\" NumberOfCells=\"2\">");
+        assert_eq!(piece_sizes(&outputs[2]), "<Piece NumberOfPoints=\"4\" NumberOfCells=\"1\">");
+    }
+
+    #[test]
+    fn subsets_nest_their_parts() {
+        let dir = TempDir::new("multiblock_subsets");
+        let base = dir.file("runA001");
+        let mut anim = quad_parts(&[("10 Front", 1), ("20 Middle", 1), ("30 Rear", 1)]);
+        let subset = |text: &str, sons: Vec<i32>, parts_2d: Vec<i32>| Subset {
+            text: text.to_string(),
+            num_parent: 0,
+            sons,
+            parts_2d,
+            parts_3d: Vec::new(),
+            parts_1d: Vec::new(),
+        };
+        anim.hierarchy = Some(Hierarchy {
+            subsets: vec![subset("Model", vec![1], vec![2]), subset("Body", vec![], vec![0])],
+            ..Default::default()
+        });
+        let outputs = write_multiblock(&anim, &base, &WriteOptions::default(), true).unwrap();
+
+        let vtm = fs::read_to_string(&outputs[0]).unwrap();
+        let blocks: Vec<&str> = vtm.lines().filter(|line| line.contains("<Block") || line.contains("<DataSet")).collect();
+        assert_eq!(
+            blocks,
+            [
+                "    <Block index=\"0\" name=\"Model\">",
+                "      <DataSet index=\"0\" name=\"30 Rear\" file=\"runA001_parts/shell_part30.vtu\"/>",
+                "      <Block index=\"1\" name=\"Body\">",
+                "        <DataSet index=\"0\" name=\"10 Front\" file=\"runA001_parts/shell_part10.vtu\"/>",
+                "    <Block index=\"1\" name=\"Shells\">",
+                "      <DataSet index=\"0\" name=\"20 Middle\" file=\"runA001_parts/shell_part20.vtu\"/>",
+            ]
+        );
+    }
+
+    #[test]
+    fn element_types_and_pieces_hold_every_element() {
+        let dir = TempDir::new("multiblock_types");
+        let base = dir.file("runA001");
+        let anim = quad_parts(&[("10 Front", 2), ("30 Rear", 1)]);
+        let outputs = write_element_types(&anim, &base, &WriteOptions::default()).unwrap();
+        assert_eq!(outputs[1], format!("{}_types/shell.vtu", base));
+        assert_eq!(piece_sizes(&outputs[1]), "<Piece NumberOfPoints=\"10\" NumberOfCells=\"3\">");
+
+        let outputs = write_partitioned(&anim, &base, &WriteOptions::default()).unwrap();
+        let pvtu = fs::read_to_string(&outputs[0]).unwrap();
+        assert!(pvtu.contains("    <Piece Source=\"runA001_pieces/shell.vtu\"/>\n"));
+        assert_eq!(outputs.len(), 2);
+    }
+}
@@ -0,0 +1,272 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Newline delimited JSON export for web viewers: a header record,
+// one record per node, then one record per element, each on its own
+// line. Fields come by array and records by entity, so the selected
+// arrays are kept until finish. Elements only get the arrays of their
+// block (no 3DELEM_ fields on shells).
+// ****************************************
+
+use std::fmt::Write as _;
+use std::io::{self, BufWriter, Write};
+
+use crate::cells;
+use crate::mesh_writer::{
+    array_name_matches, cell_count, collect_cell_field, CellArray, CellValues, FieldKind, MeshHeader, MeshWriter,
+    PointValues,
+};
+
+// arrays that are record keys rather than fields
+const KEY_ARRAYS: [&str; 3] = ["NODE_ID", "ELEMENT_ID", "PART_ID"];
+
+enum Values {
+    Int(Vec<i32>),
+    Float(Vec<f32>, usize), // components per entity
+}
+
+// JSON has no NaN or infinity
+fn push_number(line: &mut String, value: f32) {
+    if value.is_finite() {
+        let _ = write!(line, "{}", value);
+    } else {
+        line.push_str("null");
+    }
+}
+
+// fields of one entity, those not written for its block are left out
+fn push_fields(line: &mut String, fields: &[(String, Values, Vec<bool>)], index: usize, block: usize) {
+    line.push_str(", \"fields\": {");
+    let mut first = true;
+    for (name, values, _) in fields.iter().filter(|(_, _, in_block)| in_block[block]) {
+        if !first {
+            line.push_str(", ");
+        }
+        first = false;
+        line.push_str(&report::json_string(name));
+        line.push_str(": ");
+        match values {
+            Values::Int(values) => {
+                let _ = write!(line, "{}", values[index]);
+            }
+            Values::Float(values, 1) => push_number(line, values[index]),
+            Values::Float(values, ncomp) => {
+                line.push('[');
+                for (c, &value) in values[index * ncomp..(index + 1) * ncomp].iter().enumerate() {
+                    if c > 0 {
+                        line.push_str(", ");
+                    }
+                    push_number(line, value);
+                }
+                line.push(']');
+            }
+        }
+    }
+    line.push('}');
+}
+
+pub struct NdjsonWriter<W: Write> {
+    writer: BufWriter<W>,
+    fields: Vec<String>, // arrays to write, all when empty
+    time: f64,
+    coor: Vec<f32>,
+    cells: Vec<(i32, Vec<i32>)>,
+    block_ends: Vec<usize>, // cells up to the end of each block
+    node_ids: Vec<i32>,
+    element_ids: Vec<i32>,
+    part_ids: Vec<i32>,
+    point_fields: Vec<(String, Values, Vec<bool>)>,
+    cell_fields: Vec<(String, Values, Vec<bool>)>, // with the blocks holding the field
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W, fields: &[String]) -> Self {
+        NdjsonWriter {
+            writer: BufWriter::new(writer),
+            fields: fields.to_vec(),
+            time: 0.0,
+            coor: Vec::new(),
+            cells: Vec::new(),
+            block_ends: Vec::new(),
+            node_ids: Vec::new(),
+            element_ids: Vec::new(),
+            part_ids: Vec::new(),
+            point_fields: Vec::new(),
+            cell_fields: Vec::new(),
+        }
+    }
+
+    fn selected(&self, name: &str) -> bool {
+        self.fields.is_empty() || self.fields.iter().any(|field| array_name_matches(name, field))
+    }
+
+    // user id of a node, its index without numbering
+    fn node_id(&self, node: i32) -> i32 {
+        self.node_ids.get(node as usize).copied().unwrap_or(node)
+    }
+
+    fn write_records(&mut self) -> io::Result<()> {
+        let nb_points = self.coor.len() / 3;
+        let mut line = String::new();
+        writeln!(
+            self.writer,
+            "{{\"record\": \"header\", \"time\": {}, \"nodes\": {}, \"elements\": {}}}",
+            self.time,
+            nb_points,
+            self.cells.len()
+        )?;
+        for inod in 0..nb_points {
+            line.clear();
+            let _ = write!(line, "{{\"record\": \"node\", \"id\": {}, \"xyz\": [", self.node_id(inod as i32));
+            for (c, &value) in self.coor[3 * inod..3 * inod + 3].iter().enumerate() {
+                if c > 0 {
+                    line.push_str(", ");
+                }
+                push_number(&mut line, value);
+            }
+            line.push(']');
+            push_fields(&mut line, &self.point_fields, inod, 0);
+            line.push('}');
+            writeln!(self.writer, "{}", line)?;
+        }
+        let mut block = 0;
+        for (icell, (cell_type, nodes)) in self.cells.iter().enumerate() {
+            while icell >= self.block_ends[block] {
+                block += 1;
+            }
+            line.clear();
+            let id = self.element_ids.get(icell).copied().unwrap_or(icell as i32);
+            let part = self.part_ids.get(icell).copied().unwrap_or(0);
+            let _ = write!(line, "{{\"record\": \"element\", \"id\": {}, \"part\": {}, \"cell_type\": {}, \"nodes\": [", id, part, cell_type);
+            for (i, &node) in nodes.iter().enumerate() {
+                if i > 0 {
+                    line.push_str(", ");
+                }
+                let _ = write!(line, "{}", self.node_id(node));
+            }
+            line.push(']');
+            push_fields(&mut line, &self.cell_fields, icell, block);
+            line.push('}');
+            writeln!(self.writer, "{}", line)?;
+        }
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> MeshWriter for NdjsonWriter<W> {
+    fn begin_geometry(&mut self, header: &MeshHeader) -> io::Result<()> {
+        self.time = header.time;
+        self.cells.reserve(header.nb_cells);
+        Ok(())
+    }
+
+    fn write_points_chunk(&mut self, coor: &[f32]) -> io::Result<()> {
+        self.coor.extend_from_slice(coor);
+        Ok(())
+    }
+
+    fn write_cells_chunk(&mut self, cells: &mut dyn Iterator<Item = (i32, &[i32])>) -> io::Result<()> {
+        // triangles come with their last node repeated
+        self.cells.extend(cells.map(|(cell_type, nodes)| {
+            let nodes = if cell_type == cells::VTK_TRIANGLE { &nodes[..3] } else { nodes };
+            (cell_type, nodes.to_vec())
+        }));
+        Ok(())
+    }
+
+    fn write_point_field(&mut self, name: &str, kind: FieldKind, values: PointValues) -> io::Result<()> {
+        if name == "NODE_ID" {
+            if let PointValues::Int(ids) = values {
+                self.node_ids = ids.to_vec();
+            }
+            return Ok(());
+        }
+        if !self.selected(name) {
+            return Ok(());
+        }
+        let values = match values {
+            PointValues::Int(values) => Values::Int(values.to_vec()),
+            PointValues::Float(values) => Values::Float(values.to_vec(), if kind == FieldKind::Vector { 3 } else { 1 }),
+        };
+        self.point_fields.push((name.to_string(), values, vec![true]));
+        Ok(())
+    }
+
+    fn write_cell_field(&mut self, name: &str, kind: FieldKind, blocks: &[CellValues]) -> io::Result<()> {
+        // blocks of cells, from the first cell field: a block can come in several cell chunks
+        if self.block_ends.is_empty() {
+            self.block_ends = blocks
+                .iter()
+                .scan(0, |end, values| {
+                    *end += cell_count(values);
+                    Some(*end)
+                })
+                .collect();
+        }
+        let is_key = KEY_ARRAYS.contains(&name);
+        if !is_key && !self.selected(name) {
+            return Ok(());
+        }
+        let in_block = blocks.iter().map(|values| !matches!(values, CellValues::Zeros(_))).collect();
+        let values = match collect_cell_field(kind, blocks, self.cells.len()) {
+            CellArray::Int(values) => Values::Int(values),
+            CellArray::Float(values) => Values::Float(values, if kind == FieldKind::Tensor { 9 } else { 1 }),
+            CellArray::Rgb(values) => Values::Float(values.into_iter().map(f32::from).collect(), 3),
+        };
+        match (name, values) {
+            ("ELEMENT_ID", Values::Int(ids)) => self.element_ids = ids,
+            ("PART_ID", Values::Int(ids)) => self.part_ids = ids,
+            (_, values) => self.cell_fields.push((name.to_string(), values, in_block)),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.write_records()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cells::ShellCell;
+
+    #[test]
+    fn triangles_give_their_3_distinct_nodes() {
+        let mut output = Vec::new();
+        let mut writer = NdjsonWriter::new(&mut output, &[]);
+        let header = MeshHeader { time: 0.0, nb_points: 5, nb_cells: 2, connectivity_size: 7, string_tables: Vec::new(), ranges: Vec::new(), mode_phase: None, part_texts: [&[]; 4] };
+        writer.begin_geometry(&header).unwrap();
+        writer.write_points_chunk(&[0.0; 15]).unwrap();
+        let facets = [ShellCell::from_facet(&[0, 1, 4, 4]), ShellCell::from_facet(&[0, 1, 2, 3])];
+        writer.write_cells_chunk(&mut facets.iter().map(|cell| (cell.vtk_type(), cell.nodes()))).unwrap();
+        writer.write_point_field("NODE_ID", FieldKind::Scalar, PointValues::Int(&[1001, 1002, 1003, 1004, 1005])).unwrap();
+        writer.write_cell_field("ELEMENT_ID", FieldKind::Scalar, &[CellValues::Int(&[7, 8])]).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+        let text = String::from_utf8(output).unwrap();
+        let elements: Vec<&str> = text.lines().filter(|line| line.contains("\"element\"")).collect();
+        assert_eq!(elements.len(), 2);
+        assert!(elements[0].contains("\"id\": 7, \"part\": 0, \"cell_type\": 5, \"nodes\": [1001, 1002, 1005], "), "{}", elements[0]);
+        assert!(elements[1].contains("\"cell_type\": 9, \"nodes\": [1001, 1002, 1003, 1004], "), "{}", elements[1]);
+    }
+}
@@ -0,0 +1,314 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// NumPy export (.npz): a zip of .npy arrays, read with numpy.load.
+// Cells are stored as in VTK XML files (connectivity, offsets,
+// cell_types), fields under their VTK array name. A schema.json
+// member describes every array.
+// ****************************************
+
+use std::io::{self, BufWriter, Write};
+
+use crc32fast::Hasher;
+
+use crate::cells;
+use crate::mesh_writer::{
+    array_name_matches, collect_cell_field, CellArray, CellValues, FieldKind, MeshHeader, MeshWriter, PointValues,
+};
+
+// arrays written whatever the field selection
+const ID_ARRAYS: [&str; 3] = ["NODE_ID", "ELEMENT_ID", "PART_ID"];
+
+// values of one array, little endian
+enum Data {
+    F32(Vec<f32>),
+    I32(Vec<i32>),
+    U8(Vec<u8>),
+}
+
+impl Data {
+    fn descr(&self) -> &'static str {
+        match self {
+            Data::F32(_) => "<f4",
+            Data::I32(_) => "<i4",
+            Data::U8(_) => "|u1",
+        }
+    }
+
+    fn dtype(&self) -> &'static str {
+        match self {
+            Data::F32(_) => "float32",
+            Data::I32(_) => "int32",
+            Data::U8(_) => "uint8",
+        }
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            Data::F32(values) => values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            Data::I32(values) => values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            Data::U8(values) => values.clone(),
+        }
+    }
+}
+
+// .npy version 1.0: magic, header length, then a Python dict padded
+// with spaces to a multiple of 64 bytes, ending with a new line
+fn npy(data: &Data, shape: &[usize]) -> Vec<u8> {
+    let dims: Vec<String> = shape.iter().map(|n| n.to_string()).collect();
+    let shape = if dims.len() == 1 { format!("({},)", dims[0]) } else { format!("({})", dims.join(", ")) };
+    let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", data.descr(), shape);
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat(unpadded.next_multiple_of(64) - unpadded));
+    header.push('\n');
+
+    let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(&data.bytes());
+    bytes
+}
+
+// ****************************************
+// ZipWriter - stored (uncompressed) members, no zip64:
+// members and archive are limited to 4 GB
+// ****************************************
+struct ZipWriter<W: Write> {
+    writer: BufWriter<W>,
+    offset: u64,
+    central: Vec<u8>,
+    nb_members: u16,
+}
+
+impl<W: Write> ZipWriter<W> {
+    fn new(writer: W) -> Self {
+        ZipWriter { writer: BufWriter::new(writer), offset: 0, central: Vec::new(), nb_members: 0 }
+    }
+
+    fn add(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        if data.len() as u64 > u32::MAX as u64 || self.offset > u32::MAX as u64 {
+            return Err(io::Error::other(format!("{} goes beyond the 4 GB of a zip without zip64", name)));
+        }
+        let mut hasher = Hasher::new();
+        hasher.update(data);
+        let crc = hasher.finalize();
+        let size = data.len() as u32;
+
+        // version 2.0, no flags, stored, 1980-01-01 00:00
+        let mut common = Vec::with_capacity(26);
+        common.extend_from_slice(&20u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0x21u16.to_le_bytes());
+        common.extend_from_slice(&crc.to_le_bytes());
+        common.extend_from_slice(&size.to_le_bytes());
+        common.extend_from_slice(&size.to_le_bytes());
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        self.writer.write_all(&0x0403_4b50u32.to_le_bytes())?;
+        self.writer.write_all(&common)?;
+        self.writer.write_all(name.as_bytes())?;
+        self.writer.write_all(data)?;
+
+        self.central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        self.central.extend_from_slice(&20u16.to_le_bytes());
+        self.central.extend_from_slice(&common);
+        self.central.extend_from_slice(&[0u8; 6]); // comment length, disk, internal attributes
+        self.central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        self.central.extend_from_slice(&(self.offset as u32).to_le_bytes());
+        self.central.extend_from_slice(name.as_bytes());
+
+        self.offset += 30 + name.len() as u64 + data.len() as u64;
+        self.nb_members += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.offset > u32::MAX as u64 {
+            return Err(io::Error::other("archive beyond the 4 GB of a zip without zip64"));
+        }
+        self.writer.write_all(&self.central)?;
+        self.writer.write_all(&0x0605_4b50u32.to_le_bytes())?;
+        self.writer.write_all(&[0u8; 4])?; // disk numbers
+        self.writer.write_all(&self.nb_members.to_le_bytes())?;
+        self.writer.write_all(&self.nb_members.to_le_bytes())?;
+        self.writer.write_all(&(self.central.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&(self.offset as u32).to_le_bytes())?;
+        self.writer.write_all(&0u16.to_le_bytes())?;
+        self.writer.flush()
+    }
+}
+
+// ****************************************
+// NpzWriter - MeshWriter of .npz archives. Every array is written
+// as soon as it is complete.
+// ****************************************
+pub struct NpzWriter<W: Write> {
+    zip: ZipWriter<W>,
+    fields: Vec<String>, // arrays to write besides the ids, all when empty
+    schema: Vec<String>,
+    time: f64,
+    nb_points: usize,
+    nb_cells: usize,
+    connectivity: Vec<i32>,
+    offsets: Vec<i32>,
+    cell_types: Vec<u8>,
+    cells_written: bool,
+}
+
+impl<W: Write> NpzWriter<W> {
+    pub fn new(writer: W, fields: &[String]) -> Self {
+        NpzWriter {
+            zip: ZipWriter::new(writer),
+            fields: fields.to_vec(),
+            schema: Vec::new(),
+            time: 0.0,
+            nb_points: 0,
+            nb_cells: 0,
+            connectivity: Vec::new(),
+            offsets: vec![0],
+            cell_types: Vec::new(),
+            cells_written: false,
+        }
+    }
+
+    fn selected(&self, name: &str) -> bool {
+        self.fields.is_empty()
+            || ID_ARRAYS.contains(&name)
+            || self.fields.iter().any(|field| array_name_matches(name, field))
+    }
+
+    fn add(&mut self, name: &str, location: &str, data: Data, shape: &[usize]) -> io::Result<()> {
+        // '/' would make a directory of the member
+        let member = format!("{}.npy", name.replace('/', "_"));
+        let dims: Vec<String> = shape.iter().map(|n| n.to_string()).collect();
+        self.schema.push(format!(
+            "    {{\"name\": {}, \"file\": {}, \"location\": \"{}\", \"dtype\": \"{}\", \"shape\": [{}]}}",
+            report::json_string(name),
+            report::json_string(&member),
+            location,
+            data.dtype(),
+            dims.join(", ")
+        ));
+        self.zip.add(&member, &npy(&data, shape))
+    }
+
+    // cells are complete once the first field comes
+    fn flush_cells(&mut self) -> io::Result<()> {
+        if self.cells_written {
+            return Ok(());
+        }
+        self.cells_written = true;
+        let nb_connectivity = self.connectivity.len();
+        let connectivity = Data::I32(std::mem::take(&mut self.connectivity));
+        self.add("connectivity", "cell", connectivity, &[nb_connectivity])?;
+        let offsets = Data::I32(std::mem::take(&mut self.offsets));
+        self.add("offsets", "cell", offsets, &[self.nb_cells + 1])?;
+        let cell_types = Data::U8(std::mem::take(&mut self.cell_types));
+        self.add("cell_types", "cell", cell_types, &[self.nb_cells])
+    }
+}
+
+impl<W: Write> MeshWriter for NpzWriter<W> {
+    fn begin_geometry(&mut self, header: &MeshHeader) -> io::Result<()> {
+        self.time = header.time;
+        self.nb_points = header.nb_points;
+        self.nb_cells = header.nb_cells;
+        self.connectivity.reserve(header.connectivity_size);
+        self.offsets.reserve(header.nb_cells);
+        self.cell_types.reserve(header.nb_cells);
+        Ok(())
+    }
+
+    fn write_points_chunk(&mut self, coor: &[f32]) -> io::Result<()> {
+        self.add("coordinates", "point", Data::F32(coor.to_vec()), &[coor.len() / 3, 3])
+    }
+
+    fn write_cells_chunk(&mut self, cells: &mut dyn Iterator<Item = (i32, &[i32])>) -> io::Result<()> {
+        for (cell_type, nodes) in cells {
+            // triangles come with their last node repeated, VTK_TRIANGLE has 3
+            let nodes = if cell_type == cells::VTK_TRIANGLE { &nodes[..3] } else { nodes };
+            self.connectivity.extend_from_slice(nodes);
+            self.offsets.push(self.connectivity.len() as i32);
+            self.cell_types.push(cell_type as u8);
+        }
+        Ok(())
+    }
+
+    fn write_point_field(&mut self, name: &str, kind: FieldKind, values: PointValues) -> io::Result<()> {
+        self.flush_cells()?;
+        if !self.selected(name) {
+            return Ok(());
+        }
+        let n = self.nb_points;
+        match (kind, values) {
+            (FieldKind::Vector, PointValues::Float(values)) => self.add(name, "point", Data::F32(values.to_vec()), &[n, 3]),
+            (_, PointValues::Float(values)) => self.add(name, "point", Data::F32(values.to_vec()), &[n]),
+            (_, PointValues::Int(values)) => self.add(name, "point", Data::I32(values.to_vec()), &[n]),
+        }
+    }
+
+    fn write_cell_field(&mut self, name: &str, kind: FieldKind, blocks: &[CellValues]) -> io::Result<()> {
+        self.flush_cells()?;
+        if !self.selected(name) {
+            return Ok(());
+        }
+        let n = self.nb_cells;
+        match collect_cell_field(kind, blocks, n) {
+            CellArray::Int(values) => self.add(name, "cell", Data::I32(values), &[n]),
+            CellArray::Float(values) if kind == FieldKind::Tensor => self.add(name, "cell", Data::F32(values), &[n, 3, 3]),
+            CellArray::Float(values) => self.add(name, "cell", Data::F32(values), &[n]),
+            CellArray::Rgb(values) => self.add(name, "cell", Data::U8(values), &[n, 3]),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.flush_cells()?;
+        let schema = format!(
+            "{{\n  \"time\": {},\n  \"points\": {},\n  \"cells\": {},\n  \"arrays\": [\n{}\n  ]\n}}\n",
+            self.time,
+            self.nb_points,
+            self.nb_cells,
+            self.schema.join(",\n")
+        );
+        self.zip.add("schema.json", schema.as_bytes())?;
+        self.zip.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cells::ShellCell;
+
+    #[test]
+    fn triangles_keep_their_3_distinct_nodes() {
+        let mut writer = NpzWriter::new(Vec::new(), &[]);
+        let facets = [ShellCell::from_facet(&[0, 1, 2, 2]), ShellCell::from_facet(&[0, 2, 3, 4])];
+        writer.write_cells_chunk(&mut facets.iter().map(|cell| (cell.vtk_type(), cell.nodes()))).unwrap();
+        assert_eq!(writer.connectivity, [0, 1, 2, 0, 2, 3, 4]);
+        assert_eq!(writer.offsets, [0, 3, 7]);
+        assert_eq!(writer.cell_types, [cells::VTK_TRIANGLE as u8, cells::VTK_QUAD as u8]);
+    }
+}
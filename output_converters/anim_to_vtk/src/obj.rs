@@ -0,0 +1,299 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Wavefront OBJ surface of a state (--format obj), for Blender and the
+// other tools without a VTK reader. Every node is a vertex, so that the
+// states of a run share their vertex numbering; the active shells and the
+// free faces of the active solids are faces, the active beams lines, in
+// one group per part. SPH particles are left out.
+// The sequence manifest (--obj-manifest) lists the files of the states
+// with their frame number and TIME.
+// ****************************************
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use ryu::Buffer as RyuBuffer;
+
+use crate::anim::AnimFile;
+use crate::cells;
+use crate::dashboard::relative_path;
+use crate::mesh_writer::{write_mesh, Buffered, MeshBuffer, MeshOutput};
+use crate::vtk::WriteOptions;
+
+// faces of a brick, outward for the Radioss node order (1-4 bottom, 5-8 top)
+const HEXA_FACES: [[usize; 4]; 6] = [[0, 3, 2, 1], [4, 5, 6, 7], [0, 1, 5, 4], [1, 2, 6, 5], [2, 3, 7, 6], [3, 0, 4, 7]];
+
+// faces of a tetrahedron, outward for a positive volume
+const TETRA_FACES: [[usize; 3]; 4] = [[0, 2, 1], [0, 1, 3], [1, 2, 3], [2, 0, 3]];
+
+// corners without their repeats: collapsed bricks and triangles
+pub fn polygon(nodes: impl IntoIterator<Item = i32>) -> Vec<i32> {
+    let mut corners: Vec<i32> = Vec::with_capacity(4);
+    for node in nodes {
+        if !corners.contains(&node) {
+            corners.push(node);
+        }
+    }
+    corners
+}
+
+// OBJ group names end at the first blank
+fn group_name(part_text: &str) -> String {
+    part_text.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+// faces of a brick, or of the hexahedron written for it, left with 3 corners at least
+pub fn brick_faces(nodes: &[i32]) -> Vec<Vec<i32>> {
+    HEXA_FACES.iter().map(|face| polygon(face.iter().map(|&i| nodes[i]))).filter(|corners| corners.len() >= 3).collect()
+}
+
+// faces of a VTK solid cell
+fn cell_faces(cell_type: i32, nodes: &[i32]) -> Vec<Vec<i32>> {
+    if cell_type == cells::VTK_TETRA {
+        TETRA_FACES.iter().map(|face| face.iter().map(|&i| nodes[i]).collect()).collect()
+    } else {
+        brick_faces(nodes)
+    }
+}
+
+// faces of the elements of every part that no other element shares,
+// with the element they belong to
+pub fn unshared_faces(per_part: &[Vec<usize>], faces: impl Fn(usize) -> Vec<Vec<i32>>) -> Vec<Vec<(usize, Vec<i32>)>> {
+    let mut shared: HashMap<Vec<i32>, usize> = HashMap::new();
+    for &iel in per_part.iter().flatten() {
+        for mut key in faces(iel) {
+            key.sort_unstable();
+            *shared.entry(key).or_insert(0) += 1;
+        }
+    }
+    per_part
+        .iter()
+        .map(|elements| {
+            let mut free = Vec::new();
+            for &iel in elements {
+                for corners in faces(iel) {
+                    let mut key = corners.clone();
+                    key.sort_unstable();
+                    if shared.get(&key) == Some(&1) {
+                        free.push((iel, corners));
+                    }
+                }
+            }
+            free
+        })
+        .collect()
+}
+
+fn write_elements<'a>(out: &mut impl Write, keyword: &str, elements: impl Iterator<Item = &'a [i32]>) -> io::Result<()> {
+    for nodes in elements {
+        out.write_all(keyword.as_bytes())?;
+        for node in nodes {
+            write!(out, " {}", node + 1)?;
+        }
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+// ****************************************
+// ObjOutput - the OBJ file of a state, from the mesh write_mesh gives
+// ****************************************
+struct ObjOutput<'a> {
+    path: &'a str,
+}
+
+impl MeshOutput for ObjOutput<'_> {
+    // geometry only
+    fn keeps(&self, _name: &str) -> bool {
+        false
+    }
+
+    fn write(&mut self, mesh: &MeshBuffer) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(self.path)?);
+        writeln!(out, "# TIME {}", mesh.time as f32)?;
+
+        let mut ryu = RyuBuffer::new();
+        for point in mesh.coor.chunks_exact(3) {
+            out.write_all(b"v")?;
+            for &value in point {
+                out.write_all(b" ")?;
+                out.write_all(ryu.format(value).as_bytes())?;
+            }
+            out.write_all(b"\n")?;
+        }
+
+        for (name, elements) in mesh.parts(0) {
+            writeln!(out, "g {}", group_name(name))?;
+            write_elements(&mut out, "l", elements.iter().map(|&iel| mesh.cell(0, iel).1))?;
+        }
+        for (name, elements) in mesh.parts(1) {
+            let faces: Vec<Vec<i32>> =
+                elements.iter().map(|&iel| polygon(mesh.cell(1, iel).1.iter().copied())).filter(|corners| corners.len() >= 3).collect();
+            if !faces.is_empty() {
+                writeln!(out, "g {}", group_name(name))?;
+                write_elements(&mut out, "f", faces.iter().map(|corners| &corners[..]))?;
+            }
+        }
+        let (names, per_part): (Vec<&str>, Vec<Vec<usize>>) = mesh.parts(2).into_iter().unzip();
+        let free = unshared_faces(&per_part, |iel| {
+            let (cell_type, nodes) = mesh.cell(2, iel);
+            cell_faces(cell_type, nodes)
+        });
+        for (name, faces) in names.iter().zip(&free).filter(|(_, faces)| !faces.is_empty()) {
+            writeln!(out, "g {}", group_name(name))?;
+            write_elements(&mut out, "f", faces.iter().map(|(_, corners)| &corners[..]))?;
+        }
+        out.flush()
+    }
+}
+
+pub fn write_obj(anim: &AnimFile, output_file_name: &str, options: &WriteOptions) -> io::Result<()> {
+    write_mesh(anim, options, &mut Buffered::new(ObjOutput { path: output_file_name }))
+}
+
+// ****************************************
+// Manifest of the OBJ sequence, frames numbered from 1 in run order.
+// Paths are relative to the manifest.
+// ****************************************
+pub struct Manifest {
+    path: String,
+    dir: PathBuf,
+    frames: Vec<(f32, String)>,
+}
+
+impl Manifest {
+    pub fn create(path: &str) -> io::Result<Self> {
+        File::create(path)?;
+        let parent = Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let dir = parent.canonicalize()?;
+        Ok(Manifest { path: path.to_string(), dir, frames: Vec::new() })
+    }
+
+    pub fn add_state(&mut self, time: f32, output: &str) {
+        self.frames.push((time, output.to_string()));
+    }
+
+    // written to a temporary file then renamed, as the report
+    pub fn finish(self) -> io::Result<()> {
+        let mut json = String::from("{\n  \"format\": \"obj\",\n  \"frames\": [");
+        for (index, (time, output)) in self.frames.iter().enumerate() {
+            json.push_str(if index == 0 { "\n" } else { ",\n" });
+            json.push_str(&format!(
+                "    {{\"frame\": {}, \"time\": {}, \"file\": {}}}",
+                index + 1,
+                time,
+                report::json_string(&relative_path(&self.dir, output))
+            ));
+        }
+        json.push_str("\n  ]\n}\n");
+
+        let temporary = format!("{}.tmp", self.path);
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::ElementBlock;
+    use crate::test_files::{quad_parts, TempDir};
+
+    fn lines_starting(text: &str, keyword: &str) -> Vec<String> {
+        text.lines().filter(|line| line.starts_with(keyword)).map(str::to_string).collect()
+    }
+
+    #[test]
+    fn shells_are_faces_grouped_by_part() {
+        let dir = TempDir::new("obj_shells");
+        let path = dir.file("runA001.obj");
+        let mut anim = quad_parts(&[("10 Front door", 2), ("20 Rear", 1)]);
+        anim.time = 0.5;
+        anim.elts_2d.del_elt[1] = 0;
+        write_obj(&anim, &path, &WriteOptions::default()).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("# TIME 0.5\n"));
+        // every node, also those of eroded elements
+        assert_eq!(lines_starting(&text, "v ").len(), 10);
+        assert_eq!(lines_starting(&text, "v ")[4], "v 1.0 1.0 0.0");
+        assert_eq!(lines_starting(&text, "g "), ["g 10_Front_door", "g 20_Rear"]);
+        assert_eq!(lines_starting(&text, "f "), ["f 1 2 5 4", "f 7 8 10 9"]);
+    }
+
+    #[test]
+    fn bricks_give_their_free_faces() {
+        let dir = TempDir::new("obj_bricks");
+        let path = dir.file("runA001.obj");
+        // two bricks sharing the face x = 1
+        let mut coor = Vec::new();
+        for z in [0.0, 1.0] {
+            for (x, y) in [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (0.0, 1.0)] {
+                coor.extend_from_slice(&[x, y, z]);
+            }
+        }
+        let mut anim = quad_parts(&[]);
+        anim.nb_nodes = 12;
+        anim.coor = coor;
+        anim.nod_num = (1..=12).collect();
+        anim.elts_3d = ElementBlock {
+            count: 2,
+            nodes_per_elt: 8,
+            tens_stride: 6,
+            connect: vec![0, 1, 4, 5, 6, 7, 10, 11, 1, 2, 3, 4, 7, 8, 9, 10],
+            del_elt: vec![1; 2],
+            def_part: vec![2],
+            part_text: vec!["3 Block".to_string()],
+            el_num: vec![1, 2],
+            ..Default::default()
+        };
+        write_obj(&anim, &path, &WriteOptions::default()).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(lines_starting(&text, "g "), ["g 3_Block"]);
+        let faces = lines_starting(&text, "f ");
+        assert_eq!(faces.len(), 10);
+        assert!(faces.contains(&"f 1 6 5 2".to_string()), "{:?}", faces);
+        assert!(!faces.iter().any(|face| face.contains(" 2 5 11 8") || face.contains(" 8 11 5 2")));
+    }
+
+    #[test]
+    fn manifest_lists_the_frames() {
+        let dir = TempDir::new("obj_manifest");
+        let path = dir.file("run.json");
+        let mut manifest = Manifest::create(&path).unwrap();
+        // paths are made relative once the states are written
+        for (time, name) in [(0.0, "runA001.obj"), (0.5, "runA002.obj")] {
+            fs::write(dir.file(name), "").unwrap();
+            manifest.add_state(time, &dir.file(name));
+        }
+        manifest.finish().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"format\": \"obj\",\n  \"frames\": [\n    {\"frame\": 1, \"time\": 0, \"file\": \"runA001.obj\"},\n    \
+             {\"frame\": 2, \"time\": 0.5, \"file\": \"runA002.obj\"}\n  ]\n}\n"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// ParaView Python script of the run: opens the --pvd collection, or
+// the converted states as a file series, colors them by a field with
+// a colormap over the whole run, and warps them by the displacement
+// when the states have one. Run with pvpython or "paraview --script".
+// ****************************************
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use report::codes;
+
+use crate::anim::AnimFile;
+use crate::dashboard::relative_path;
+use crate::mesh_writer::array_name_matches;
+use crate::vtk::replace_underscore;
+
+const COLORMAP: &str = "Jet";
+
+pub struct ParaviewScript {
+    path: String,
+    dir: PathBuf,
+    collection: Option<String>,     // --pvd collection, loaded instead of the files
+    color_by: Option<String>,       // field asked for, the first elemental or nodal scalar without
+    files: Vec<String>,             // first output of each state, in time order
+    color: Option<(&'static str, String, bool)>, // association, array, vector
+    displacement: Option<String>,   // nodal vector array
+    described: bool,
+}
+
+impl ParaviewScript {
+    pub fn create(path: &str, collection: Option<&str>, color_by: Option<&str>) -> io::Result<Self> {
+        File::create(path)?;
+        let parent = Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let dir = parent.canonicalize()?;
+        Ok(ParaviewScript {
+            path: path.to_string(),
+            dir,
+            collection: collection.map(String::from),
+            color_by: color_by.map(String::from),
+            files: Vec::new(),
+            color: None,
+            displacement: None,
+            described: false,
+        })
+    }
+
+    // arrays of the first converted state, as written in the VTK outputs
+    pub fn describe(&mut self, anim: &AnimFile) {
+        if self.described {
+            return;
+        }
+        self.described = true;
+        let mut arrays: Vec<(&'static str, String, bool)> = Vec::new();
+        let blocks = [("1DELEM_", &anim.elts_1d), ("2DELEM_", &anim.elts_2d), ("3DELEM_", &anim.elts_3d), ("SPHELEM_", &anim.elts_sph)];
+        for (prefix, block) in blocks {
+            let names = block.efunc_text.iter().map(|text| format!("{}{}", prefix, replace_underscore(text)));
+            arrays.extend(names.map(|name| ("CELLS", name, false)));
+        }
+        arrays.extend(anim.func_text.iter().map(|text| ("POINTS", replace_underscore(text), false)));
+        arrays.extend(anim.vect_text.iter().map(|text| ("POINTS", replace_underscore(text), true)));
+        self.color = match &self.color_by {
+            Some(name) => {
+                let found = arrays.iter().find(|(_, array, _)| array_name_matches(array, name)).cloned();
+                if found.is_none() {
+                    report::warning(
+                        codes::NO_MATCH,
+                        format_args!("no array named {} to color the ParaView script by", name),
+                    );
+                }
+                found
+            }
+            None => arrays.into_iter().find(|(_, _, vector)| !vector),
+        };
+        self.displacement = anim
+            .vect_text
+            .iter()
+            .find(|text| text.to_ascii_lowercase().contains("displacement"))
+            .map(|text| replace_underscore(text));
+    }
+
+    pub fn add_file(&mut self, output: &str) {
+        self.files.push(output.to_string());
+    }
+
+    // written to a temporary file then renamed, as the collection
+    pub fn finish(self) -> io::Result<()> {
+        let mut script = String::new();
+        script.push_str("# ParaView script written by anim_to_vtk: pvpython <script> or paraview --script=<script>\n");
+        script.push_str("import os\n");
+        script.push_str("from paraview.simple import *\n\n");
+        script.push_str("# deformation magnification: the outputs hold the deformed geometry (1)\n");
+        script.push_str("WARP_SCALE = 1.0\n\n");
+        script.push_str("here = os.path.dirname(os.path.abspath(__file__))\n");
+        match &self.collection {
+            Some(collection) => {
+                let collection = relative_path(&self.dir, collection);
+                script.push_str(&format!("files = os.path.join(here, {})\n", python_string(&collection)));
+            }
+            None => {
+                script.push_str("files = [os.path.join(here, name) for name in [\n");
+                for file in &self.files {
+                    script.push_str(&format!("    {},\n", python_string(&relative_path(&self.dir, file))));
+                }
+                script.push_str("]]\n");
+            }
+        }
+        script.push_str("source = OpenDataFile(files)\n");
+        if let Some(displacement) = &self.displacement {
+            script.push_str(&format!(
+                "source = WarpByVector(Input=source, Vectors=['POINTS', {}], ScaleFactor=WARP_SCALE - 1.0)\n",
+                python_string(displacement)
+            ));
+        }
+        script.push_str("view = GetActiveViewOrCreate('RenderView')\n");
+        script.push_str("display = Show(source, view)\n");
+        if let Some((association, array, vector)) = &self.color {
+            let component = if *vector { ", 'Magnitude'" } else { "" };
+            script.push_str(&format!("ColorBy(display, ('{}', {}{}))\n", association, python_string(array), component));
+            script.push_str(&format!("GetColorTransferFunction({}).ApplyPreset('{}', True)\n", python_string(array), COLORMAP));
+            script.push_str("display.RescaleTransferFunctionToDataRangeOverTime()\n");
+            script.push_str("display.SetScalarBarVisibility(view, True)\n");
+        }
+        script.push_str("GetAnimationScene().UpdateAnimationUsingDataTimeSteps()\n");
+        script.push_str("view.ResetCamera()\n");
+        script.push_str("Render()\n");
+
+        let temporary = format!("{}.tmp", self.path);
+        fs::write(&temporary, script)?;
+        fs::rename(&temporary, &self.path)
+    }
+}
+
+// single-quoted Python literal
+fn python_string(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
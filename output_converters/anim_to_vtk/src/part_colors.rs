@@ -0,0 +1,161 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Part colors (--part-colors): an RGB color per PART_ID that depends on
+// the id only, so that a part has the same color in every state, output
+// and tool reading the PART_COLOR array. The id is hashed (MurmurHash3
+// finalizer) into a hue, saturation and value, or into an entry of the
+// palette of --part-palette, which can also pin the color of a part.
+// ****************************************
+
+use std::collections::HashMap;
+use std::fs;
+
+#[derive(Clone, Default)]
+pub struct PartColors {
+    palette: Vec<[u8; 3]>,
+    fixed: HashMap<i32, [u8; 3]>,
+}
+
+// MurmurHash3 32 bits finalizer
+fn hash(id: i32) -> u32 {
+    let mut h = id as u32;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^= h >> 16;
+    h
+}
+
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [u8; 3] {
+    let sector = hue / 60.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    [r, g, b].map(|c| ((c + m) * 255.0).round() as u8)
+}
+
+fn parse_rgb(tokens: &[&str]) -> Option<[u8; 3]> {
+    match tokens {
+        [r, g, b] => Some([r.parse().ok()?, g.parse().ok()?, b.parse().ok()?]),
+        _ => None,
+    }
+}
+
+impl PartColors {
+    // Palette file, one color per line: "r g b" (0-255) adds an entry to
+    // the palette, "<part id>: r g b" gives the color of a part; '#'
+    // starts a comment
+    pub fn read_palette(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+        let mut colors = PartColors::default();
+        for (iline, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || format!("Invalid color '{}' at line {} of {}", line, iline + 1, path);
+            let (id, rgb) = match line.split_once(':') {
+                Some((id, rgb)) => (Some(id.trim().parse::<i32>().map_err(|_| invalid())?), rgb),
+                None => (None, line),
+            };
+            let tokens: Vec<&str> = rgb.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()).collect();
+            let rgb = parse_rgb(&tokens).ok_or_else(invalid)?;
+            match id {
+                Some(id) => {
+                    colors.fixed.insert(id, rgb);
+                }
+                None => colors.palette.push(rgb),
+            }
+        }
+        Ok(colors)
+    }
+
+    pub fn color(&self, part_id: i32) -> [u8; 3] {
+        if let Some(&rgb) = self.fixed.get(&part_id) {
+            return rgb;
+        }
+        let h = hash(part_id);
+        if !self.palette.is_empty() {
+            return self.palette[h as usize % self.palette.len()];
+        }
+        // saturated, light enough colors: dark parts read badly on screenshots
+        let hue = (h & 0xffff) as f32 / 65536.0 * 360.0;
+        let saturation = 0.55 + 0.35 * ((h >> 16) & 0xff) as f32 / 255.0;
+        let value = 0.75 + 0.2 * (h >> 24) as f32 / 255.0;
+        hsv_to_rgb(hue, saturation, value)
+    }
+
+    // r, g, b of each part id
+    pub fn colors(&self, part_ids: &[i32]) -> Vec<u8> {
+        part_ids.iter().flat_map(|&id| self.color(id)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part_filter::{filter_parts, PartFilter};
+    use crate::test_files::{legacy_vtk, quad_parts, TempDir};
+    use crate::vtk::WriteOptions;
+
+    #[test]
+    fn colors_depend_on_the_part_id_only() {
+        let colors = PartColors::default();
+        assert_eq!(colors.colors(&[30, 10]), [colors.color(30), colors.color(10)].concat());
+        assert_ne!(colors.color(10), colors.color(11));
+    }
+
+    #[test]
+    fn palette_files_fix_colors_by_id() {
+        let dir = TempDir::new("palette");
+        let path = dir.file("palette.txt");
+        fs::write(&path, "# fixed\n30: 255 0 0\n0, 0, 255\n").unwrap();
+        let colors = PartColors::read_palette(&path).unwrap();
+        assert_eq!(colors.color(30), [255, 0, 0]);
+        assert_eq!(colors.color(10), [0, 0, 255]);
+        fs::write(&path, "30: 255 0\n").unwrap();
+        assert_eq!(PartColors::read_palette(&path).err().unwrap(), format!("Invalid color '30: 255 0' at line 1 of {}", path));
+    }
+
+    #[test]
+    fn filtered_parts_keep_their_color() {
+        let mut anim = quad_parts(&[("10 Front", 2), ("20 Middle", 2), ("30 Rear", 2)]);
+        let mut filter = PartFilter::default();
+        PartFilter::add(&mut filter.include, "30");
+        filter_parts(&mut anim, &filter);
+        let mut colors = PartColors::default();
+        colors.fixed.insert(30, [255, 0, 0]);
+        let options = WriteOptions { part_colors: Some(colors), ..Default::default() };
+        let text = legacy_vtk(&anim, &options);
+        assert!(text.contains("COLOR_SCALARS PART_COLOR 3\n1.0 0.0 0.0\n1.0 0.0 0.0\n"), "{}", text);
+    }
+}
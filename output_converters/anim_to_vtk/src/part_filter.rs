@@ -0,0 +1,179 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Part filtering: only the elements of the selected parts are kept,
+// with the nodes they use, renumbered. Parts are given by id or by a
+// glob on their name ("*" any text, "?" any character, ignoring case).
+// ****************************************
+
+use report::codes;
+
+use crate::anim::{atoi_prefix, AnimFile};
+use crate::subset;
+
+pub enum PartPattern {
+    Id(i32),
+    Name(String), // glob, in upper case
+}
+
+impl PartPattern {
+    fn parse(item: &str) -> Self {
+        match item.parse() {
+            Ok(id) => PartPattern::Id(id),
+            Err(_) => PartPattern::Name(item.to_uppercase()),
+        }
+    }
+
+    // part text is "<id> <name>"
+    fn matches(&self, part_text: &str) -> bool {
+        match self {
+            PartPattern::Id(id) => atoi_prefix(part_text) == *id,
+            PartPattern::Name(glob) => {
+                let text = part_text.trim_start();
+                let name = text.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '+');
+                glob_matches(glob, &name.trim().to_uppercase())
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            PartPattern::Id(id) => id.to_string(),
+            PartPattern::Name(glob) => glob.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PartFilter {
+    pub include: Vec<PartPattern>, // every part without patterns
+    pub exclude: Vec<PartPattern>,
+}
+
+impl PartFilter {
+    // comma separated ids and name globs, the option can be repeated
+    pub fn add(patterns: &mut Vec<PartPattern>, list: &str) {
+        patterns.extend(list.split(',').map(str::trim).filter(|item| !item.is_empty()).map(PartPattern::parse));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    fn keeps(&self, part_text: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|pattern| pattern.matches(part_text)))
+            && !self.exclude.iter().any(|pattern| pattern.matches(part_text))
+    }
+}
+
+// "*" matches any text, "?" any character
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut ig, mut it) = (0, 0);
+    let mut star: Option<(usize, usize)> = None; // glob index after the last '*', text index it matched up to
+    while it < text.len() {
+        if ig < glob.len() && (glob[ig] == '?' || glob[ig] == text[it]) {
+            ig += 1;
+            it += 1;
+        } else if ig < glob.len() && glob[ig] == '*' {
+            star = Some((ig + 1, it));
+            ig += 1;
+        } else if let Some((after_star, matched)) = star {
+            ig = after_star;
+            it = matched + 1;
+            star = Some((after_star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    glob[ig..].iter().all(|&c| c == '*')
+}
+
+// ****************************************
+// Keep the elements of the parts selected by the filter and the nodes
+// they use. Returns the numbers of kept parts, nodes and elements.
+// ****************************************
+pub fn filter_parts(anim: &mut AnimFile, filter: &PartFilter) -> (usize, usize, usize) {
+    let mut kept = Vec::new();
+    let mut nb_parts = 0;
+    for block in [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph] {
+        let kept_parts: Vec<bool> = block.part_text.iter().map(|text| filter.keeps(text)).collect();
+        nb_parts += kept_parts.iter().filter(|&&kept| kept).count();
+        let part_of = block.part_indices();
+        kept.push((0..block.count).filter(|&iel| kept_parts.get(part_of[iel]).copied().unwrap_or(false)).collect::<Vec<usize>>());
+    }
+    for pattern in filter.include.iter().chain(&filter.exclude) {
+        let found = [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph]
+            .iter()
+            .any(|block| block.part_text.iter().any(|text| pattern.matches(text)));
+        if !found {
+            report::warning(codes::NO_MATCH, format_args!("no part matches {}", pattern.describe()));
+        }
+    }
+    let nb_elements = kept.iter().map(Vec::len).sum();
+    let nb_nodes = subset::keep_elements(anim, &kept, &[]);
+    (nb_parts, nb_nodes, nb_elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{int_cell_array, legacy_vtk, quad_parts};
+    use crate::vtk::WriteOptions;
+
+    fn filter(include: &str, exclude: &str) -> PartFilter {
+        let mut filter = PartFilter::default();
+        PartFilter::add(&mut filter.include, include);
+        PartFilter::add(&mut filter.exclude, exclude);
+        filter
+    }
+
+    #[test]
+    fn names_match_globs_ignoring_case() {
+        let pattern = PartPattern::parse("door*");
+        assert!(pattern.matches("12 Door_left"));
+        assert!(!pattern.matches("12 Roof"));
+        assert!(PartPattern::parse("12").matches("12 Roof"));
+        assert!(PartPattern::parse("r??f").matches("  12   roof "));
+    }
+
+    #[test]
+    fn last_part_alone_keeps_its_part_id() {
+        let mut anim = quad_parts(&[("10 Front", 2), ("20 Middle", 2), ("30 Rear", 2)]);
+        assert_eq!(filter_parts(&mut anim, &filter("30", "")), (1, 6, 2));
+        // parts left without element keep their place in the part table
+        assert_eq!(anim.elts_2d.def_part, [0, 0, 2]);
+        assert_eq!(anim.elts_2d.part_indices(), [2, 2]);
+        let text = legacy_vtk(&anim, &WriteOptions::default());
+        assert_eq!(int_cell_array(&text, "PART_ID"), [30, 30]);
+        assert_eq!(int_cell_array(&text, "ELEMENT_ID"), [105, 106]);
+    }
+
+    #[test]
+    fn excluded_middle_part_leaves_the_others_ids() {
+        let mut anim = quad_parts(&[("10 Front", 1), ("20 Middle", 2), ("30 Rear", 1)]);
+        assert_eq!(filter_parts(&mut anim, &filter("", "middle")), (2, 8, 2));
+        let text = legacy_vtk(&anim, &WriteOptions::default());
+        assert_eq!(int_cell_array(&text, "PART_ID"), [10, 30]);
+    }
+}
@@ -0,0 +1,147 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Nodal vectors summed over the nodes of each part, per state
+// ****************************************
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use report::codes;
+
+use crate::anim::{atoi_prefix, field_name_matches, AnimFile};
+
+// sorted node list of every part, keyed by part ID
+pub fn part_nodes(anim: &AnimFile) -> BTreeMap<i32, Vec<i32>> {
+    let mut parts: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    for block in [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph] {
+        let part_of = block.part_indices();
+        for (iel, &ipart) in part_of.iter().enumerate() {
+            let part_id = block.part_text.get(ipart).map_or(0, |text| atoi_prefix(text));
+            parts.entry(part_id).or_default().extend_from_slice(block.nodes(iel));
+        }
+    }
+    for nodes in parts.values_mut() {
+        nodes.sort_unstable();
+        nodes.dedup();
+    }
+    parts
+}
+
+// ****************************************
+// A node shared by several parts counts once in each of them
+// ****************************************
+pub struct PartSums {
+    writer: BufWriter<File>,
+    vectors: Vec<String>,
+    state: usize,
+}
+
+impl PartSums {
+    pub fn create(path: &str, vectors: Vec<String>) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "state,time,vector,part,x,y,z,magnitude")?;
+        Ok(PartSums { writer, vectors, state: 0 })
+    }
+
+    pub fn add_state(&mut self, anim: &AnimFile) -> std::io::Result<()> {
+        let parts = part_nodes(anim);
+        for name in &self.vectors {
+            let ivect = match anim.vect_text.iter().position(|text| field_name_matches(text, name)) {
+                Some(ivect) => ivect,
+                None => {
+                    report::warning(codes::NO_MATCH, format_args!("no nodal vector named {}", name));
+                    continue;
+                }
+            };
+            let vect_val = anim.nodal_vector(ivect)?;
+            for (part_id, nodes) in &parts {
+                let mut sum = [0f64; 3];
+                for &n in nodes {
+                    for k in 0..3 {
+                        sum[k] += vect_val[3 * n as usize + k] as f64;
+                    }
+                }
+                let magnitude = (sum[0] * sum[0] + sum[1] * sum[1] + sum[2] * sum[2]).sqrt();
+                writeln!(
+                    self.writer,
+                    "{},{},{},{},{},{},{},{}",
+                    self.state,
+                    anim.time,
+                    anim.vect_text[ivect].trim(),
+                    part_id,
+                    sum[0],
+                    sum[1],
+                    sum[2],
+                    magnitude
+                )?;
+            }
+        }
+        self.state += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{quad_parts, TempDir};
+
+    // parts 10 (4 nodes), 20 (6 nodes) and 30 (4 nodes), Force (1, 0, 0)
+    // on the nodes of 10 and 20, (0, 0.75, 1) on those of 30
+    fn state() -> AnimFile {
+        let mut anim = quad_parts(&[("10 Front", 1), ("20 Middle", 2), ("30 Rear", 1)]);
+        anim.vect_text = vec!["Force".to_string()];
+        anim.vect_val = (0..anim.nb_nodes).flat_map(|inod| if inod < 10 { [1.0, 0.0, 0.0] } else { [0.0, 0.75, 1.0] }).collect();
+        anim
+    }
+
+    // rows of the CSV file written in the temporary directory <name>
+    fn sums(name: &str, anim: &AnimFile, vectors: &[&str]) -> Vec<String> {
+        let dir = TempDir::new(name);
+        let path = dir.file("sums.csv");
+        let mut sums = PartSums::create(&path, vectors.iter().map(|name| name.to_string()).collect()).unwrap();
+        sums.add_state(anim).unwrap();
+        sums.finish().unwrap();
+        std::fs::read_to_string(&path).unwrap().lines().skip(1).map(String::from).collect()
+    }
+
+    #[test]
+    fn vectors_are_summed_over_the_nodes_of_each_part() {
+        let rows = sums("part_sums", &state(), &["force", "Velocity"]);
+        assert_eq!(rows, ["0,0,Force,10,4,0,0,4", "0,0,Force,20,6,0,0,6", "0,0,Force,30,0,3,4,5"]);
+    }
+
+    #[test]
+    fn parts_left_without_element_are_skipped() {
+        let mut anim = state();
+        // the shell of part 30 alone
+        anim.elts_2d = anim.elts_2d.gather(&[3]);
+        assert_eq!(part_nodes(&anim), BTreeMap::from([(30, vec![10, 11, 12, 13])]));
+        assert_eq!(sums("part_sums_alone", &anim, &["Force"]), ["0,0,Force,30,0,3,4,5"]);
+    }
+}
@@ -0,0 +1,114 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Part table of the run (--part-table): one CSV row per part of the
+// first converted state, with its id, name, element type, material and
+// property, for the scripts that label the parts of a PART_ID array.
+// Parts are listed in cell order: beams, shells, solids, SPH.
+// ****************************************
+
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+
+use crate::anim::{atoi_prefix, AnimFile, ElementBlock};
+
+const HEADER: &str = "part_id,type,name,elements,material_index,material,property_index,property\n";
+
+pub struct PartTable {
+    path: String,
+    written: bool,
+}
+
+// part text "<id> <title>" without its id
+fn part_name(text: &str) -> &str {
+    let text = text.trim();
+    let rest = text.trim_start_matches(['-', '+']).trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() < text.len() && (rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        rest.trim()
+    } else {
+        text
+    }
+}
+
+fn csv_field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+// index of the part's entry in <texts> and its text, -1 and empty without
+fn table_entry<'a>(index: Option<&i32>, texts: Option<&'a Vec<String>>) -> (i32, &'a str) {
+    match (index, texts) {
+        (Some(&index), Some(texts)) if index >= 0 && (index as usize) < texts.len() => (index, texts[index as usize].trim()),
+        _ => (-1, ""),
+    }
+}
+
+impl PartTable {
+    pub fn create(path: &str) -> io::Result<Self> {
+        fs::write(path, HEADER)?;
+        Ok(PartTable { path: path.to_string(), written: false })
+    }
+
+    fn add_block(&self, csv: &mut String, anim: &AnimFile, kind: &str, block: &ElementBlock) {
+        let mut elements = vec![0usize; block.part_text.len()];
+        for ipart in block.part_indices() {
+            if let Some(count) = elements.get_mut(ipart) {
+                *count += 1;
+            }
+        }
+        let hierarchy = anim.hierarchy.as_ref();
+        for (ipart, text) in block.part_text.iter().enumerate() {
+            let (material_index, material) = table_entry(block.part_material.get(ipart), hierarchy.map(|h| &h.material_texts));
+            let (property_index, property) =
+                table_entry(block.part_properties.get(ipart), hierarchy.map(|h| &h.properties_texts));
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                atoi_prefix(text),
+                kind,
+                csv_field(part_name(text)),
+                elements[ipart],
+                material_index,
+                csv_field(material),
+                property_index,
+                csv_field(property)
+            ));
+        }
+    }
+
+    // written once, from the first state
+    pub fn add_state(&mut self, anim: &AnimFile) -> io::Result<()> {
+        if self.written {
+            return Ok(());
+        }
+        self.written = true;
+        let mut csv = String::from(HEADER);
+        self.add_block(&mut csv, anim, "beam", &anim.elts_1d);
+        self.add_block(&mut csv, anim, "shell", &anim.elts_2d);
+        self.add_block(&mut csv, anim, "solid", &anim.elts_3d);
+        self.add_block(&mut csv, anim, "sph", &anim.elts_sph);
+        fs::write(&self.path, csv)
+    }
+}
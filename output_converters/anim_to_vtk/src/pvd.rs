@@ -0,0 +1,78 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// ParaView collection (.pvd) of the converted states: one DataSet per
+// output of each state with its TIME, so that the whole run opens as
+// a time series. Paths are relative to the collection.
+// ****************************************
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::dashboard::relative_path;
+use crate::resample::xml_attribute;
+
+pub struct Collection {
+    path: String,
+    dir: PathBuf,
+    steps: Vec<(f32, Vec<String>)>, // time, datasets of the state (several parts with --group-by)
+}
+
+impl Collection {
+    pub fn create(path: &str) -> io::Result<Self> {
+        File::create(path)?;
+        let parent = Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let dir = parent.canonicalize()?;
+        Ok(Collection { path: path.to_string(), dir, steps: Vec::new() })
+    }
+
+    pub fn add_state(&mut self, time: f32, datasets: &[String]) {
+        if !datasets.is_empty() {
+            self.steps.push((time, datasets.to_vec()));
+        }
+    }
+
+    // written to a temporary file then renamed, as the report
+    pub fn finish(self) -> io::Result<()> {
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\"?>\n");
+        xml.push_str("<VTKFile type=\"Collection\" version=\"0.1\" byte_order=\"LittleEndian\">\n");
+        xml.push_str("  <Collection>\n");
+        for (time, datasets) in &self.steps {
+            for (part, dataset) in datasets.iter().enumerate() {
+                xml.push_str(&format!(
+                    "    <DataSet timestep=\"{}\" group=\"\" part=\"{}\" file=\"{}\"/>\n",
+                    time,
+                    part,
+                    xml_attribute(&relative_path(&self.dir, dataset))
+                ));
+            }
+        }
+        xml.push_str("  </Collection>\n");
+        xml.push_str("</VTKFile>\n");
+
+        let temporary = format!("{}.tmp", self.path);
+        fs::write(&temporary, xml)?;
+        fs::rename(&temporary, &self.path)
+    }
+}
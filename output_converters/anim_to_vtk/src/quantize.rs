@@ -0,0 +1,82 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Round coordinates and fields to a fixed step, so that outputs of
+// slightly different solver builds become bitwise identical
+// ****************************************
+
+use crate::anim::{AnimFile, ElementBlock};
+
+// adding 0.0 turns -0.0 into 0.0, small negative values would print "-0"
+fn round_values(values: &mut [f32], step: f64) {
+    for value in values.iter_mut() {
+        *value = ((*value as f64 / step).round() * step + 0.0) as f32;
+    }
+}
+
+fn round_block(block: &mut ElementBlock, step: f64) {
+    round_values(&mut block.efunc, step);
+    round_values(&mut block.tens_val, step);
+    round_values(&mut block.e_mass, step);
+}
+
+// <step> is checked positive and finite while parsing --quantize
+pub fn quantize(anim: &mut AnimFile, step: f64) {
+    assert!(step > 0.0 && step.is_finite(), "quantize step must be positive and finite");
+    round_values(&mut anim.coor, step);
+    round_values(&mut anim.func, step);
+    round_values(&mut anim.vect_val, step);
+    round_values(&mut anim.n_mass, step);
+    for block in [&mut anim.elts_1d, &mut anim.elts_2d, &mut anim.elts_3d, &mut anim.elts_sph] {
+        round_block(block, step);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_move_by_at_most_half_a_step() {
+        let step = 1e-3;
+        let original: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.7371).sin() * 50.0).collect();
+        let mut values = original.clone();
+        round_values(&mut values, step);
+        for (value, original) in values.iter().zip(&original) {
+            // half a step, and the f32 rounding of values up to 50
+            assert!(((value - original) as f64).abs() <= 0.5 * step + 1e-5, "{} from {}", value, original);
+        }
+
+        // rounding again changes nothing
+        let rounded = values.clone();
+        round_values(&mut values, step);
+        assert_eq!(values, rounded);
+    }
+
+    #[test]
+    fn small_negative_values_round_to_positive_zero() {
+        let mut anim = AnimFile { coor: vec![-0.0004, 0.0016, -0.0], ..Default::default() };
+        quantize(&mut anim, 0.001);
+        assert_eq!(anim.coor, [0.0, 0.002, 0.0]);
+        assert!(anim.coor.iter().all(|value| value.is_sign_positive()));
+    }
+}
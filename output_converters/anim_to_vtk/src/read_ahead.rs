@@ -0,0 +1,235 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Read-ahead input: a background thread reads the next buffers of the
+// file while the current one is parsed, hiding the latency of network
+// filesystems
+// ****************************************
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::mem;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::{self, JoinHandle};
+
+const BUFFER_SIZE: usize = 1 << 20;
+// buffers waiting to be parsed, on top of the one being parsed
+const BUFFERS_AHEAD: usize = 2;
+// longer forward seeks restart the background thread at the target
+// instead of reading the skipped bytes
+const SEEK_RESTART: u64 = (BUFFER_SIZE * (BUFFERS_AHEAD + 1)) as u64;
+
+type Buffers = Receiver<io::Result<Vec<u8>>>;
+
+pub struct ReadAhead {
+    receiver: Buffers,
+    worker: Option<JoinHandle<File>>, // gives the file back once stopped
+    buffer: Vec<u8>,
+    pos: usize,     // read position in buffer
+    offset: u64,    // file offset of the read position
+    eof: bool,
+}
+
+// reads the file from its current position until its end, the
+// receiver is dropped or a read fails
+fn spawn_reader(mut file: File) -> (Buffers, JoinHandle<File>) {
+    let (sender, receiver) = sync_channel(BUFFERS_AHEAD);
+    let worker = thread::spawn(move || loop {
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        let mut filled = 0;
+        // fill the whole buffer unless the file ends
+        let result = loop {
+            match file.read(&mut buffer[filled..]) {
+                Ok(0) => break Ok(()),
+                Ok(n) => {
+                    filled += n;
+                    if filled == BUFFER_SIZE {
+                        break Ok(());
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        let done = filled < BUFFER_SIZE;
+        buffer.truncate(filled);
+        let message = result.map(|_| buffer);
+        let failed = message.is_err();
+        // the parser stopped reading: nothing left to do
+        if sender.send(message).is_err() || done || failed {
+            return file;
+        }
+    });
+    (receiver, worker)
+}
+
+impl ReadAhead {
+    pub fn new(file: File) -> Self {
+        let (receiver, worker) = spawn_reader(file);
+        ReadAhead {
+            receiver,
+            worker: Some(worker),
+            buffer: Vec::new(),
+            pos: 0,
+            offset: 0,
+            eof: false,
+        }
+    }
+
+    // makes the current buffer non empty, false at end of file
+    fn fill(&mut self) -> io::Result<bool> {
+        while self.pos == self.buffer.len() {
+            if self.eof {
+                return Ok(false);
+            }
+            match self.receiver.recv() {
+                Ok(Ok(buffer)) => {
+                    self.eof = buffer.len() < BUFFER_SIZE;
+                    self.buffer = buffer;
+                    self.pos = 0;
+                }
+                Ok(Err(e)) => {
+                    self.eof = true;
+                    return Err(e);
+                }
+                Err(_) => {
+                    self.eof = true;
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    // stops the background thread and starts another one at <target>,
+    // the buffers read ahead are dropped
+    fn restart(&mut self, target: u64) -> io::Result<()> {
+        let (_, closed) = sync_channel(0);
+        drop(mem::replace(&mut self.receiver, closed));
+        let worker = self.worker.take().ok_or_else(|| io::Error::other("read-ahead input already failed"))?;
+        let mut file = worker.join().map_err(|_| io::Error::other("read-ahead thread panicked"))?;
+        if target > file.metadata()?.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "seek past end of file"));
+        }
+        file.seek(SeekFrom::Start(target))?;
+        let (receiver, worker) = spawn_reader(file);
+        self.receiver = receiver;
+        self.worker = Some(worker);
+        self.buffer = Vec::new();
+        self.pos = 0;
+        self.offset = target;
+        self.eof = false;
+        Ok(())
+    }
+}
+
+impl Read for ReadAhead {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() || !self.fill()? {
+            return Ok(0);
+        }
+        let n = out.len().min(self.buffer.len() - self.pos);
+        out[..n].copy_from_slice(&self.buffer[self.pos..self.pos + n]);
+        self.pos += n;
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+// The file is read sequentially: only forward seeks are supported.
+// Short ones consume the buffers read ahead, long ones seek the file.
+impl Seek for ReadAhead {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(target) => target,
+            SeekFrom::Current(delta) => self.offset.checked_add_signed(delta).unwrap_or(0),
+            SeekFrom::End(_) => {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "seek from end in read-ahead input"));
+            }
+        };
+        if target < self.offset {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "backward seek in read-ahead input"));
+        }
+        let mut remaining = target - self.offset;
+        if remaining > SEEK_RESTART + (self.buffer.len() - self.pos) as u64 {
+            self.restart(target)?;
+            return Ok(self.offset);
+        }
+        while remaining > 0 {
+            if !self.fill()? {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "seek past end of file"));
+            }
+            let n = remaining.min((self.buffer.len() - self.pos) as u64) as usize;
+            self.pos += n;
+            self.offset += n as u64;
+            remaining -= n as u64;
+        }
+        Ok(self.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::TempDir;
+
+    // byte <i> of the file is <i> mod 251, so that no buffer repeats
+    fn counting_file(dir: &TempDir, len: usize) -> File {
+        let name = dir.file("counting.bin");
+        let bytes: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&name, bytes).unwrap();
+        File::open(name).unwrap()
+    }
+
+    fn byte_at(reader: &mut ReadAhead) -> u8 {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    #[test]
+    fn short_and_long_forward_seeks() {
+        let dir = TempDir::new("read_ahead_seek");
+        let len = 6 * BUFFER_SIZE + 123;
+        let mut reader = ReadAhead::new(counting_file(&dir, len));
+        assert_eq!(byte_at(&mut reader), 0);
+        // within the buffers read ahead
+        assert_eq!(reader.seek(SeekFrom::Current(1000)).unwrap(), 1001);
+        assert_eq!(byte_at(&mut reader), (1001 % 251) as u8);
+        // past them: the thread is restarted at the target
+        let target = 5 * BUFFER_SIZE as u64 + 7;
+        assert_eq!(reader.seek(SeekFrom::Start(target)).unwrap(), target);
+        assert_eq!(byte_at(&mut reader), (target % 251) as u8);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest.len() as u64, len as u64 - target - 1);
+        assert!(rest.iter().enumerate().all(|(i, &b)| b == ((target as usize + 1 + i) % 251) as u8));
+    }
+
+    #[test]
+    fn long_seek_past_the_end_fails() {
+        let dir = TempDir::new("read_ahead_eof");
+        let mut reader = ReadAhead::new(counting_file(&dir, 1000));
+        let error = reader.seek(SeekFrom::Start(10 * BUFFER_SIZE as u64)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,476 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Resampling onto a regular grid (VTK XML image data, .vti). Grid
+// points are located in the tetrahedra of the active solids: nodal
+// fields are interpolated linearly in the tetrahedron, elemental
+// fields take the value of the solid. Points outside every solid get
+// zeros and vtkValidPointMask 0, as with the ParaView probe filter.
+// ****************************************
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use report::codes;
+
+use crate::anim::{field_name_matches, AnimFile};
+use crate::cells::SolidCell;
+use crate::mesh_writer::{array_name_matches, write_mesh, Buffered, FieldKind, MeshBuffer, MeshOutput};
+use crate::vtk::WriteOptions;
+
+// grid points beyond which the output is refused (spacing too small)
+const MAX_POINTS: u64 = 1 << 31;
+
+// block of the solids in the mesh
+const SOLIDS: usize = 2;
+
+// inside tolerance on the barycentric coordinates
+const EPSILON: f64 = 1e-9;
+
+// solid and interpolation weights of a grid point
+#[derive(Clone, Copy)]
+struct Sample {
+    element: usize,
+    nodes: [i32; 4],
+    weights: [f64; 4],
+}
+
+pub struct Grid {
+    origin: [f64; 3],
+    spacing: f64,
+    pub dims: [usize; 3],
+}
+
+impl Grid {
+    // grid of the given spacing from lower, up to upper at most
+    pub fn covering(lower: [f64; 3], upper: [f64; 3], spacing: f64) -> Result<Self, String> {
+        let dims = [0, 1, 2].map(|k| ((upper[k] - lower[k]) / spacing).floor() as usize + 1);
+        if dims.iter().map(|&n| n as u64).product::<u64>() > MAX_POINTS {
+            return Err(format!("spacing {} gives a {}x{}x{} grid, too large", spacing, dims[0], dims[1], dims[2]));
+        }
+        Ok(Grid { origin: lower, spacing, dims })
+    }
+
+    pub fn nb_points(&self) -> usize {
+        self.dims[0] * self.dims[1] * self.dims[2]
+    }
+
+    pub fn point(&self, index: [usize; 3]) -> [f64; 3] {
+        [0, 1, 2].map(|k| self.origin[k] + index[k] as f64 * self.spacing)
+    }
+
+    // grid indices in [lower, upper] along axis k
+    pub fn span(&self, k: usize, lower: f64, upper: f64) -> std::ops::Range<usize> {
+        let first = ((lower - self.origin[k]) / self.spacing).ceil().max(0.0) as usize;
+        let last = ((upper - self.origin[k]) / self.spacing).floor();
+        if last < 0.0 {
+            return 0..0;
+        }
+        first..(last as usize + 1).min(self.dims[k])
+    }
+}
+
+fn node_point(coor: &[f32], node: i32) -> [f64; 3] {
+    let i = node as usize * 3;
+    [coor[i] as f64, coor[i + 1] as f64, coor[i + 2] as f64]
+}
+
+// inverse of the edge matrix [b-a, c-a, d-a], None for a flat tetrahedron
+fn inverse(m: [[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let det = m[0][0] * cofactor(1, 2, 1, 2) - m[0][1] * cofactor(1, 2, 0, 2) + m[0][2] * cofactor(1, 2, 0, 1);
+    if det.abs() < f64::MIN_POSITIVE {
+        return None;
+    }
+    Some([
+        [cofactor(1, 2, 1, 2) / det, -cofactor(0, 2, 1, 2) / det, cofactor(0, 1, 1, 2) / det],
+        [-cofactor(1, 2, 0, 2) / det, cofactor(0, 2, 0, 2) / det, -cofactor(0, 1, 0, 2) / det],
+        [cofactor(1, 2, 0, 1) / det, -cofactor(0, 2, 0, 1) / det, cofactor(0, 1, 0, 1) / det],
+    ])
+}
+
+// first active solid tetrahedron containing each grid point
+fn locate(mesh: &MeshBuffer, grid: &Grid) -> Vec<Option<Sample>> {
+    let coor = &mesh.coor;
+    let mut samples: Vec<Option<Sample>> = vec![None; grid.nb_points()];
+    for iel in (0..mesh.block_count(SOLIDS)).filter(|&iel| mesh.is_active(SOLIDS, iel)) {
+        let (cell_type, nodes) = mesh.cell(SOLIDS, iel);
+        let Some(cell) = SolidCell::from_vtk(cell_type, nodes) else {
+            continue;
+        };
+        for tet in cell.tetrahedra() {
+            let p = tet.map(|node| node_point(coor, node));
+            let columns = [1, 2, 3].map(|i| [0, 1, 2].map(|k| p[i][k] - p[0][k]));
+            let edges = [0, 1, 2].map(|r| [columns[0][r], columns[1][r], columns[2][r]]);
+            let Some(inv) = inverse(edges) else {
+                continue;
+            };
+            let spans: Vec<_> = (0..3)
+                .map(|k| {
+                    let lower = p.iter().map(|q| q[k]).fold(f64::MAX, f64::min);
+                    let upper = p.iter().map(|q| q[k]).fold(f64::MIN, f64::max);
+                    grid.span(k, lower, upper)
+                })
+                .collect();
+            for iz in spans[2].clone() {
+                for iy in spans[1].clone() {
+                    for ix in spans[0].clone() {
+                        let ipoint = ix + grid.dims[0] * (iy + grid.dims[1] * iz);
+                        if samples[ipoint].is_some() {
+                            continue;
+                        }
+                        let x = grid.point([ix, iy, iz]);
+                        let d = [0, 1, 2].map(|k| x[k] - p[0][k]);
+                        let l = [0, 1, 2].map(|r| inv[r][0] * d[0] + inv[r][1] * d[1] + inv[r][2] * d[2]);
+                        let weights = [1.0 - l[0] - l[1] - l[2], l[0], l[1], l[2]];
+                        if weights.iter().all(|&w| w >= -EPSILON) {
+                            samples[ipoint] = Some(Sample { element: iel, nodes: tet, weights });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    samples
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(TABLE[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+// field names may hold any character
+pub fn xml_attribute(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// value types of the DataArrays
+pub trait DataValue: Copy + std::fmt::Display {
+    const TYPE: &'static str;
+    fn extend_le(self, bytes: &mut Vec<u8>);
+}
+
+impl DataValue for f32 {
+    const TYPE: &'static str = "Float32";
+    fn extend_le(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl DataValue for i32 {
+    const TYPE: &'static str = "Int32";
+    fn extend_le(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl DataValue for u8 {
+    const TYPE: &'static str = "UInt8";
+    fn extend_le(self, bytes: &mut Vec<u8>) {
+        bytes.push(self);
+    }
+}
+
+// ****************************************
+// VtiWriter - DataArrays of VTK XML image data, inline ASCII or
+// base64 binary (UInt32 byte count, then little endian values)
+// ****************************************
+pub struct VtiWriter<W: Write> {
+    writer: BufWriter<W>,
+    binary: bool,
+}
+
+impl VtiWriter<File> {
+    pub fn create(path: &str, binary: bool) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("can't create output file {}: {}", path, e))?;
+        Ok(VtiWriter::new(file, binary))
+    }
+}
+
+impl<W: Write> VtiWriter<W> {
+    pub fn new(writer: W, binary: bool) -> Self {
+        VtiWriter { writer: BufWriter::new(writer), binary }
+    }
+
+    // file header up to the point data of the single piece
+    pub fn begin(&mut self, grid: &Grid, time: f32) -> io::Result<()> {
+        let dims = grid.dims;
+        let extent = format!("0 {} 0 {} 0 {}", dims[0] - 1, dims[1] - 1, dims[2] - 1);
+        let (origin, spacing) = (grid.origin, grid.spacing);
+        let w = &mut self.writer;
+        writeln!(w, "<?xml version=\"1.0\"?>")?;
+        writeln!(w, "<VTKFile type=\"ImageData\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt32\">")?;
+        writeln!(
+            w,
+            "  <ImageData WholeExtent=\"{}\" Origin=\"{} {} {}\" Spacing=\"{} {} {}\">",
+            extent, origin[0], origin[1], origin[2], spacing, spacing, spacing
+        )?;
+        writeln!(w, "    <FieldData>")?;
+        writeln!(w, "      <DataArray type=\"Float64\" Name=\"TIME\" NumberOfTuples=\"1\" format=\"ascii\">{}</DataArray>", time as f64)?;
+        writeln!(w, "    </FieldData>")?;
+        writeln!(w, "    <Piece Extent=\"{}\">", extent)?;
+        writeln!(w, "      <PointData>")
+    }
+
+    pub fn end(&mut self) -> io::Result<()> {
+        let w = &mut self.writer;
+        writeln!(w, "      </PointData>")?;
+        writeln!(w, "    </Piece>")?;
+        writeln!(w, "  </ImageData>")?;
+        writeln!(w, "</VTKFile>")?;
+        w.flush()
+    }
+
+    pub fn data_array<T: DataValue>(&mut self, name: &str, ncomp: usize, values: &[T]) -> io::Result<()> {
+        data_array(&mut self.writer, self.binary, name, ncomp, values)
+    }
+}
+
+// inline DataArray of a VTK XML file, image data or unstructured grid
+pub fn data_array<T: DataValue>(
+    writer: &mut impl Write,
+    binary: bool,
+    name: &str,
+    ncomp: usize,
+    values: &[T],
+) -> io::Result<()> {
+    let format = if binary { "binary" } else { "ascii" };
+    writeln!(
+        writer,
+        "        <DataArray type=\"{}\" Name=\"{}\" NumberOfComponents=\"{}\" format=\"{}\">",
+        T::TYPE,
+        xml_attribute(name),
+        ncomp,
+        format
+    )?;
+    if binary {
+        let size = std::mem::size_of_val(values);
+        let mut block = Vec::with_capacity(4 + size);
+        block.extend_from_slice(&(size as u32).to_le_bytes());
+        for &value in values {
+            value.extend_le(&mut block);
+        }
+        writeln!(writer, "          {}", base64(&block))?;
+    } else {
+        for tuple in values.chunks(ncomp.max(1)) {
+            let texts: Vec<String> = tuple.iter().map(|v| v.to_string()).collect();
+            writeln!(writer, "          {}", texts.join(" "))?;
+        }
+    }
+    writeln!(writer, "        </DataArray>")
+}
+
+// names given on the command line, all the arrays without names
+fn selected(names: &[String], name: &str) -> bool {
+    names.is_empty() || names.iter().any(|field| array_name_matches(name, field))
+}
+
+fn write_image<W: Write>(vti: &mut VtiWriter<W>, mesh: &MeshBuffer, grid: &Grid, samples: &[Option<Sample>]) -> io::Result<()> {
+    vti.begin(grid, mesh.time as f32)?;
+
+    let mask: Vec<u8> = samples.iter().map(|sample| sample.is_some() as u8).collect();
+    vti.data_array("vtkValidPointMask", 1, &mask)?;
+
+    let interpolate = |values: &[f32], ncomp: usize| -> Vec<f32> {
+        let mut resampled = vec![0f32; ncomp * samples.len()];
+        for (ipoint, sample) in samples.iter().enumerate() {
+            if let Some(sample) = sample {
+                for c in 0..ncomp {
+                    let v: f64 = (0..4)
+                        .map(|i| sample.weights[i] * values[sample.nodes[i] as usize * ncomp + c] as f64)
+                        .sum();
+                    resampled[ipoint * ncomp + c] = v as f32;
+                }
+            }
+        }
+        resampled
+    };
+    for (name, kind, values) in &mesh.point_fields {
+        match kind {
+            FieldKind::Scalar => vti.data_array(name, 1, &interpolate(values, 1))?,
+            FieldKind::Vector => vti.data_array(name, 3, &interpolate(values, 3))?,
+            FieldKind::Tensor | FieldKind::Color => {}
+        }
+    }
+    // scalars of the solids
+    for (name, kind, blocks) in &mesh.cell_fields {
+        if let (FieldKind::Scalar, Some(values)) = (kind, &blocks[SOLIDS]) {
+            let resampled: Vec<f32> =
+                samples.iter().map(|sample| sample.map_or(0.0, |sample| values[sample.element])).collect();
+            vti.data_array(name, 1, &resampled)?;
+        }
+    }
+
+    vti.end()
+}
+
+// ****************************************
+// VtiOutput - the resampled image of a state, from the mesh
+// write_mesh gives
+// ****************************************
+struct VtiOutput<'a> {
+    path: &'a str,
+    spacing: f64,
+    fields: &'a [String],
+    binary: bool,
+    nb_points: usize, // of the grid written
+}
+
+impl MeshOutput for VtiOutput<'_> {
+    fn keeps(&self, name: &str) -> bool {
+        selected(self.fields, name)
+    }
+
+    fn write(&mut self, mesh: &MeshBuffer) -> io::Result<()> {
+        let mut lower = [f64::MAX; 3];
+        let mut upper = [f64::MIN; 3];
+        for iel in (0..mesh.block_count(SOLIDS)).filter(|&iel| mesh.is_active(SOLIDS, iel)) {
+            for &node in mesh.cell(SOLIDS, iel).1 {
+                let p = node_point(&mesh.coor, node);
+                for k in 0..3 {
+                    lower[k] = lower[k].min(p[k]);
+                    upper[k] = upper[k].max(p[k]);
+                }
+            }
+        }
+        if lower[0] > upper[0] {
+            return Err(io::Error::other("no active solid to resample"));
+        }
+        let grid = Grid::covering(lower, upper, self.spacing).map_err(io::Error::other)?;
+        let samples = locate(mesh, &grid);
+        let mut vti = VtiWriter::new(File::create(self.path)?, self.binary);
+        write_image(&mut vti, mesh, &grid, &samples)?;
+        self.nb_points = grid.nb_points();
+        Ok(())
+    }
+}
+
+// ****************************************
+// Resample the nodal scalars and vectors and the solid scalars
+// (those listed in <fields>, all without list) on a grid of the given
+// spacing over the active solids. Returns the number of grid points.
+// ****************************************
+pub fn write_vti(anim: &AnimFile, path: &str, spacing: f64, fields: &[String], options: &WriteOptions) -> Result<usize, String> {
+    for name in fields {
+        let known = anim.func_text.iter().chain(&anim.vect_text).chain(&anim.elts_3d.efunc_text);
+        if !known.clone().any(|text| field_name_matches(text, name)) {
+            report::warning(codes::NO_MATCH, format_args!("no nodal or solid field named {} to resample", name));
+        }
+    }
+
+    let mut writer = Buffered::new(VtiOutput { path, spacing, fields, binary: options.binary, nb_points: 0 });
+    write_mesh(anim, options, &mut writer).map_err(|e| format!("can't write {}: {}", path, e))?;
+    Ok(writer.output.nb_points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::ElementBlock;
+    use crate::test_files::TempDir;
+
+    // unit cubes at x = 0 and x = 2, the nodal scalar X is x
+    fn two_cubes() -> AnimFile {
+        let mut coor = Vec::new();
+        for x0 in [0.0, 2.0] {
+            for z in [0.0, 1.0] {
+                for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                    coor.extend_from_slice(&[x0 + x, y, z]);
+                }
+            }
+        }
+        let mut flag_a = vec![0; 10];
+        flag_a[1] = 1;
+        AnimFile {
+            flag_a,
+            nb_nodes: 16,
+            func_text: vec!["X".to_string()],
+            func: coor.chunks_exact(3).map(|p| p[0]).collect(),
+            coor,
+            elts_3d: ElementBlock {
+                count: 2,
+                nodes_per_elt: 8,
+                tens_stride: 6,
+                connect: (0..16).collect(),
+                del_elt: vec![1; 2],
+                def_part: vec![2],
+                part_text: vec!["1 Blocks".to_string()],
+                el_num: vec![1, 2],
+                efunc_text: vec!["Density".to_string()],
+                efunc: vec![7.0, 9.0],
+                ..Default::default()
+            },
+            elts_1d: ElementBlock { nodes_per_elt: 2, tens_stride: 9, ..Default::default() },
+            elts_2d: ElementBlock { nodes_per_elt: 4, tens_stride: 3, ..Default::default() },
+            elts_sph: ElementBlock { nodes_per_elt: 1, tens_stride: 6, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    // values of the ASCII DataArray <name>
+    fn data_array_values(text: &str, name: &str) -> Vec<f32> {
+        let start = text.find(&format!("Name=\"{}\"", name)).unwrap();
+        let start = start + text[start..].find('\n').unwrap() + 1;
+        let end = start + text[start..].find("</DataArray>").unwrap();
+        text[start..end].split_whitespace().map(|value| value.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn grid_points_interpolate_the_solids() {
+        let dir = TempDir::new("resample");
+        let path = dir.file("runA001.vti");
+        let nb_points = write_vti(&two_cubes(), &path, 0.5, &[], &WriteOptions::default()).unwrap();
+        assert_eq!(nb_points, 7 * 3 * 3);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("<ImageData WholeExtent=\"0 6 0 2 0 2\" Origin=\"0 0 0\" Spacing=\"0.5 0.5 0.5\">"), "{}", text);
+        // along x on each row: cube, gap at 1.5, cube
+        let mask = data_array_values(&text, "vtkValidPointMask");
+        assert_eq!(mask[..7], [1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0]);
+        assert_eq!(mask.iter().filter(|&&valid| valid == 0.0).count(), 9);
+        let x = data_array_values(&text, "X");
+        assert_eq!(x[28..35], [0.0, 0.5, 1.0, 0.0, 2.0, 2.5, 3.0]);
+        let density = data_array_values(&text, "3DELEM_Density");
+        assert_eq!(density[..7], [7.0, 7.0, 7.0, 0.0, 9.0, 9.0, 9.0]);
+    }
+
+    #[test]
+    fn states_without_solids_are_refused() {
+        let dir = TempDir::new("resample_empty");
+        let mut anim = two_cubes();
+        anim.elts_3d.del_elt = vec![0; 2];
+        let error = write_vti(&anim, &dir.file("runA001.vti"), 0.5, &[], &WriteOptions::default()).unwrap_err();
+        assert!(error.contains("no active solid to resample"), "{}", error);
+    }
+}
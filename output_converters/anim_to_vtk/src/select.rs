@@ -0,0 +1,452 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+
+// ****************************************
+// Cell selection: a boolean expression over the cell arrays, e.g.
+// "PART_ID in (10,12) && Von_Mises > 200", keeps the cells where it
+// holds, with the nodes they use. Comparisons on an array the element
+// type does not have are false.
+// ****************************************
+
+use std::collections::HashMap;
+
+use report::codes;
+
+use crate::anim::{field_name_matches, resolve_part_id, AnimFile, AnimReadError, ElementBlock};
+use crate::mesh_writer::array_name_matches;
+use crate::subset;
+use crate::vtk::replace_underscore;
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn holds(self, a: f64, b: f64) -> bool {
+        match self {
+            Comparison::Eq => a == b,
+            Comparison::Ne => a != b,
+            Comparison::Lt => a < b,
+            Comparison::Le => a <= b,
+            Comparison::Gt => a > b,
+            Comparison::Ge => a >= b,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Operand {
+    Value(f64),
+    Array(String),
+}
+
+#[derive(Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Comparison, Operand),
+    In(String, Vec<f64>),
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 13] = ["&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "(", ")", ",", "="];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            // "=" alone compares as "=="
+            tokens.push(Token::Symbol(if *symbol == "=" { "==" } else { symbol }));
+            symbol.len()
+        } else if c.is_alphabetic() || c == '_' || c.is_ascii_digit() || c == '.' || c == '-' || c == '+' {
+            // names may start with a digit (2DELEM_Von_Mises), numbers stop before a letter
+            let name_len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            let len = number_len(rest);
+            if c.is_alphabetic() || c == '_' || (c.is_ascii_digit() && len < name_len) {
+                tokens.push(Token::Name(rest[..name_len].to_string()));
+                name_len
+            } else {
+                let number = rest[..len].parse().map_err(|_| format!("invalid number '{}'", &rest[..len.max(1)]))?;
+                tokens.push(Token::Number(number));
+                len
+            }
+        } else {
+            return Err(format!("unexpected '{}'", c));
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+// sign, digits, decimal point and exponent
+fn number_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut len = 0;
+    while len < bytes.len() {
+        let c = bytes[len];
+        let exponent_sign = (c == b'-' || c == b'+') && len > 0 && matches!(bytes[len - 1], b'e' | b'E');
+        if c.is_ascii_digit() || c == b'.' || c == b'e' || c == b'E' || exponent_sign || (len == 0 && (c == b'-' || c == b'+')) {
+            len += 1;
+        } else {
+            break;
+        }
+    }
+    len
+}
+
+// recursive descent, || binding looser than &&, then !
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(other)) if *other == symbol) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' {}", symbol, self.position()))
+        }
+    }
+
+    fn position(&self) -> String {
+        match self.peek() {
+            Some(Token::Number(number)) => format!("before {}", number),
+            Some(Token::Name(name)) => format!("before {}", name),
+            Some(Token::Symbol(symbol)) => format!("before '{}'", symbol),
+            None => "at the end".to_string(),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.eat("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        if self.eat("(") {
+            let expr = self.or()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.operand()?;
+        if let (Operand::Array(name), Some(Token::Name(keyword))) = (&left, self.peek()) {
+            if keyword.eq_ignore_ascii_case("in") {
+                let name = name.clone();
+                self.pos += 1;
+                return self.list().map(|values| Expr::In(name, values));
+            }
+        }
+        let comparison = match self.peek() {
+            Some(Token::Symbol("==")) => Comparison::Eq,
+            Some(Token::Symbol("!=")) => Comparison::Ne,
+            Some(Token::Symbol("<")) => Comparison::Lt,
+            Some(Token::Symbol("<=")) => Comparison::Le,
+            Some(Token::Symbol(">")) => Comparison::Gt,
+            Some(Token::Symbol(">=")) => Comparison::Ge,
+            _ => return Err(format!("expected a comparison {}", self.position())),
+        };
+        self.pos += 1;
+        Ok(Expr::Compare(left, comparison, self.operand()?))
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        let operand = match self.peek() {
+            Some(Token::Number(number)) => Operand::Value(*number),
+            Some(Token::Name(name)) => Operand::Array(name.clone()),
+            _ => return Err(format!("expected a number or an array name {}", self.position())),
+        };
+        self.pos += 1;
+        Ok(operand)
+    }
+
+    // "(10, 12, 14)"
+    fn list(&mut self) -> Result<Vec<f64>, String> {
+        self.expect("(")?;
+        let mut values = Vec::new();
+        loop {
+            match self.operand()? {
+                Operand::Value(value) => values.push(value),
+                Operand::Array(name) => return Err(format!("expected a number in the list, got {}", name)),
+            }
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(values)
+    }
+}
+
+impl Expr {
+    fn arrays<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Expr::Or(a, b) | Expr::And(a, b) => {
+                a.arrays(names);
+                b.arrays(names);
+            }
+            Expr::Not(a) => a.arrays(names),
+            Expr::Compare(a, _, b) => {
+                for operand in [a, b] {
+                    if let Operand::Array(name) = operand {
+                        names.push(name);
+                    }
+                }
+            }
+            Expr::In(name, _) => names.push(name),
+        }
+    }
+
+    fn holds(&self, columns: &HashMap<&str, Option<Vec<f64>>>, iel: usize) -> bool {
+        let value = |operand: &Operand| match operand {
+            Operand::Value(value) => Some(*value),
+            Operand::Array(name) => columns[name.as_str()].as_ref().map(|column| column[iel]),
+        };
+        match self {
+            Expr::Or(a, b) => a.holds(columns, iel) || b.holds(columns, iel),
+            Expr::And(a, b) => a.holds(columns, iel) && b.holds(columns, iel),
+            Expr::Not(a) => !a.holds(columns, iel),
+            Expr::Compare(a, comparison, b) => match (value(a), value(b)) {
+                (Some(a), Some(b)) => comparison.holds(a, b),
+                _ => false,
+            },
+            Expr::In(name, values) => columns[name.as_str()].as_ref().is_some_and(|column| values.contains(&column[iel])),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CellSelection {
+    text: String,
+    expr: Expr,
+}
+
+impl CellSelection {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0 };
+        let expr = parser.or()?;
+        if parser.peek().is_some() {
+            return Err(format!("unexpected text {}", parser.position()));
+        }
+        Ok(CellSelection { text: text.to_string(), expr })
+    }
+}
+
+// values of a cell array for each element of a block: PART_ID,
+// ELEMENT_ID, EROSION_STATUS, arrays added by conversion steps and
+// elemental scalars, with or without their element prefix
+fn column(anim: &AnimFile, block: &ElementBlock, prefix: &str, name: &str) -> Result<Option<Vec<f64>>, AnimReadError> {
+    if field_name_matches("PART_ID", name) {
+        let mut part_index = 0;
+        let ids = (0..block.count).map(|iel| resolve_part_id(iel, &mut part_index, &block.def_part, &block.part_text));
+        return Ok(Some(ids.map(f64::from).collect()));
+    }
+    if field_name_matches("ELEMENT_ID", name) {
+        return Ok((block.el_num.len() == block.count).then(|| block.el_num.iter().map(|&id| id as f64).collect()));
+    }
+    if field_name_matches("EROSION_STATUS", name) {
+        return Ok(Some((0..block.count).map(|iel| if block.is_active(iel) { 1.0 } else { 0.0 }).collect()));
+    }
+    if let Some((_, values)) = block.int_arrays.iter().find(|(array, _)| field_name_matches(array, name)) {
+        return Ok(Some(values.iter().map(|&value| value as f64).collect()));
+    }
+    match block.efunc_text.iter().position(|text| array_name_matches(&format!("{}{}", prefix, replace_underscore(text)), name)) {
+        Some(iefun) => Ok(Some(anim.elemental_scalar(block, iefun)?[..block.count].iter().map(|&value| value as f64).collect())),
+        None => Ok(None),
+    }
+}
+
+// ****************************************
+// Keep the cells where the selection holds and the nodes they use.
+// Returns the numbers of kept cells and of cells before.
+// ****************************************
+pub fn select_cells(anim: &mut AnimFile, selection: &CellSelection) -> Result<(usize, usize), AnimReadError> {
+    let mut names = Vec::new();
+    selection.expr.arrays(&mut names);
+    let blocks = [("1DELEM_", &anim.elts_1d), ("2DELEM_", &anim.elts_2d), ("3DELEM_", &anim.elts_3d), ("SPHELEM_", &anim.elts_sph)];
+
+    let mut kept = Vec::new();
+    let mut found = vec![false; names.len()];
+    for (prefix, block) in blocks {
+        let mut columns: HashMap<&str, Option<Vec<f64>>> = HashMap::new();
+        for &name in &names {
+            columns.insert(name, column(anim, block, prefix, name)?);
+        }
+        for (in_block, name) in found.iter_mut().zip(&names) {
+            *in_block |= block.count > 0 && columns[name].is_some();
+        }
+        kept.push((0..block.count).filter(|&iel| selection.expr.holds(&columns, iel)).collect::<Vec<usize>>());
+    }
+    for (name, _) in names.iter().zip(found).filter(|(_, found)| !found) {
+        report::warning(
+            codes::NO_MATCH,
+            format_args!("no cell array {} in \"{}\", its comparisons are false", name, selection.text),
+        );
+    }
+
+    let nb_cells = blocks.iter().map(|(_, block)| block.count).sum();
+    let nb_kept = kept.iter().map(Vec::len).sum();
+    subset::keep_elements(anim, &kept, &[]);
+    Ok((nb_kept, nb_cells))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // four shells: ids 1 to 4, parts 10 (1, 2) and 12 (3, 4), element 3
+    // eroded, Von Mises 100, 250, 300 and 50
+    fn shells() -> AnimFile {
+        let mut anim = AnimFile { nb_nodes: 4, coor: vec![0.0; 12], ..Default::default() };
+        anim.elts_2d = ElementBlock {
+            count: 4,
+            nodes_per_elt: 4,
+            connect: [0, 1, 2, 3].repeat(4),
+            del_elt: vec![1, 1, 0, 1],
+            def_part: vec![2, 4],
+            part_text: vec!["10 door".to_string(), "12 roof".to_string()],
+            efunc_text: vec!["Von Mises".to_string()],
+            efunc: vec![100.0, 250.0, 300.0, 50.0],
+            el_num: vec![1, 2, 3, 4],
+            ..Default::default()
+        };
+        anim
+    }
+
+    // ids of the shells kept by <text>
+    fn kept(text: &str) -> Vec<i32> {
+        let selection = CellSelection::parse(text).unwrap_or_else(|e| panic!("{}: {}", text, e));
+        let mut anim = shells();
+        let (nb_kept, nb_cells) = select_cells(&mut anim, &selection).unwrap();
+        assert_eq!((nb_kept, nb_cells), (anim.elts_2d.el_num.len(), 4));
+        anim.elts_2d.el_num
+    }
+
+    fn parse_error(text: &str) -> String {
+        CellSelection::parse(text).err().unwrap_or_else(|| panic!("{} parsed", text))
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(kept("PART_ID == 10 || PART_ID == 12 && Von_Mises > 200"), [1, 2, 3]);
+        assert_eq!(kept("PART_ID == 12 && Von_Mises > 200 || PART_ID == 10"), [1, 2, 3]);
+        assert_eq!(kept("!PART_ID == 10 && Von_Mises < 200"), [4]);
+    }
+
+    #[test]
+    fn parentheses_group_first() {
+        assert_eq!(kept("(PART_ID == 10 || PART_ID == 12) && Von_Mises > 200"), [2, 3]);
+        assert_eq!(kept("!(PART_ID == 10 || Von_Mises > 200)"), [4]);
+        assert_eq!(kept("((EROSION_STATUS == 1)) && (ELEMENT_ID in (1, 3, 4))"), [1, 4]);
+    }
+
+    #[test]
+    fn comparison_operators() {
+        assert_eq!(kept("Von_Mises == 250"), [2]);
+        assert_eq!(kept("Von_Mises = 250"), [2]);
+        assert_eq!(kept("Von_Mises != 250"), [1, 3, 4]);
+        assert_eq!(kept("Von_Mises < 250"), [1, 4]);
+        assert_eq!(kept("Von_Mises <= 250"), [1, 2, 4]);
+        assert_eq!(kept("Von_Mises > 250"), [3]);
+        assert_eq!(kept("Von_Mises >= 250"), [2, 3]);
+        assert_eq!(kept("250 <= Von_Mises"), [2, 3]);
+        assert_eq!(kept("2DELEM_Von_Mises >= 2.5e2"), [2, 3]);
+        assert_eq!(kept("part_id IN (12)"), [3, 4]);
+    }
+
+    #[test]
+    fn comparisons_on_unknown_arrays_are_false() {
+        assert_eq!(kept("Plastic_Strain > 0"), Vec::<i32>::new());
+        assert_eq!(kept("Plastic_Strain in (0)"), Vec::<i32>::new());
+        assert_eq!(kept("!(Plastic_Strain > 0)"), [1, 2, 3, 4]);
+        assert_eq!(kept("Plastic_Strain > 0 || PART_ID == 12"), [3, 4]);
+    }
+
+    #[test]
+    fn cells_of_the_last_part_keep_its_part_id() {
+        let mut anim = crate::test_files::quad_parts(&[("10 Front", 2), ("20 Middle", 2), ("30 Rear", 2)]);
+        let selection = CellSelection::parse("ELEMENT_ID >= 105").unwrap();
+        assert_eq!(select_cells(&mut anim, &selection).unwrap(), (2, 6));
+        let text = crate::test_files::legacy_vtk(&anim, &crate::vtk::WriteOptions::default());
+        assert_eq!(crate::test_files::int_cell_array(&text, "PART_ID"), [30, 30]);
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected() {
+        assert_eq!(parse_error(""), "expected a number or an array name at the end");
+        assert_eq!(parse_error("PART_ID >"), "expected a number or an array name at the end");
+        assert_eq!(parse_error("PART_ID 10"), "expected a comparison before 10");
+        assert_eq!(parse_error("(PART_ID == 10"), "expected ')' at the end");
+        assert_eq!(parse_error("PART_ID == 10)"), "unexpected text before ')'");
+        assert_eq!(parse_error("PART_ID == 10 &&"), "expected a number or an array name at the end");
+        assert_eq!(parse_error("PART_ID in 10"), "expected '(' before 10");
+        assert_eq!(parse_error("PART_ID in (10, door)"), "expected a number in the list, got door");
+        assert_eq!(parse_error("PART_ID in ()"), "expected a number or an array name before ')'");
+        assert_eq!(parse_error("Von_Mises > 1.2.3"), "invalid number '1.2.3'");
+        assert_eq!(parse_error("PART_ID # 10"), "unexpected '#'");
+    }
+}
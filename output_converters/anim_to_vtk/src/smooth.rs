@@ -0,0 +1,145 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Laplacian smoothing of nodal fields over the mesh connectivity
+// ****************************************
+
+use crate::anim::{field_name_matches, AnimFile};
+
+// nodes sharing an active element are neighbors
+fn node_neighbors(anim: &AnimFile) -> Vec<Vec<u32>> {
+    let mut neighbors: Vec<Vec<u32>> = vec![Vec::new(); anim.nb_nodes];
+    for block in [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d] {
+        for iel in 0..block.count {
+            if !block.is_active(iel) {
+                continue;
+            }
+            let nodes = block.nodes(iel);
+            for &a in nodes {
+                for &b in nodes {
+                    if a != b {
+                        neighbors[a as usize].push(b as u32);
+                    }
+                }
+            }
+        }
+    }
+    for list in neighbors.iter_mut() {
+        list.sort_unstable();
+        list.dedup();
+    }
+    neighbors
+}
+
+// Each iteration moves every value half way to the mean of its
+// neighbors. Nodes without neighbors keep their value.
+fn smooth_components(values: &mut [f32], ncomp: usize, neighbors: &[Vec<u32>], iterations: usize) {
+    let mut next = values.to_vec();
+    for _ in 0..iterations {
+        for (inod, list) in neighbors.iter().enumerate() {
+            if list.is_empty() {
+                continue;
+            }
+            for k in 0..ncomp {
+                let mean = list.iter().map(|&n| values[ncomp * n as usize + k] as f64).sum::<f64>()
+                    / list.len() as f64;
+                let value = values[ncomp * inod + k] as f64;
+                next[ncomp * inod + k] = (0.5 * (value + mean)) as f32;
+            }
+        }
+        values.copy_from_slice(&next);
+    }
+}
+
+// ****************************************
+// Smooth the nodal scalar or vector <name>.
+// Returns false when no nodal field has this name.
+// ****************************************
+pub fn smooth_field(anim: &mut AnimFile, name: &str, iterations: usize) -> bool {
+    let nb_nodes = anim.nb_nodes;
+    if let Some(ifunc) = anim.func_text.iter().position(|text| field_name_matches(text, name)) {
+        let neighbors = node_neighbors(anim);
+        smooth_components(&mut anim.func[ifunc * nb_nodes..(ifunc + 1) * nb_nodes], 1, &neighbors, iterations);
+        true
+    } else if let Some(ivect) = anim.vect_text.iter().position(|text| field_name_matches(text, name)) {
+        let neighbors = node_neighbors(anim);
+        smooth_components(
+            &mut anim.vect_val[ivect * 3 * nb_nodes..(ivect + 1) * 3 * nb_nodes],
+            3,
+            &neighbors,
+            iterations,
+        );
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::ElementBlock;
+
+    // nodes 0 - 1 - 2 on a line, joined by two beams
+    fn chain(scalar: &[f32], vector: &[f32]) -> AnimFile {
+        AnimFile {
+            nb_nodes: 3,
+            coor: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, 0.0],
+            func_text: vec!["Temperature".to_string()],
+            func: scalar.to_vec(),
+            vect_text: vec!["Velocity".to_string()],
+            vect_val: vector.to_vec(),
+            elts_1d: ElementBlock {
+                count: 2,
+                nodes_per_elt: 2,
+                tens_stride: 9,
+                connect: vec![0, 1, 1, 2],
+                del_elt: vec![1; 2],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn one_step_moves_half_way_to_the_neighbors() {
+        let mut anim = chain(&[0.0, 0.0, 6.0], &[0.0; 9]);
+        assert!(smooth_field(&mut anim, "TEMPERATURE", 1));
+        assert_eq!(anim.func, [0.0, 1.5, 3.0]);
+    }
+
+    #[test]
+    fn vectors_are_smoothed_per_component() {
+        let mut anim = chain(&[0.0; 3], &[4.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(smooth_field(&mut anim, "velocity", 1));
+        assert_eq!(anim.vect_val, [2.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0]);
+        assert!(!smooth_field(&mut anim, "Pressure", 1));
+    }
+
+    #[test]
+    fn eroded_elements_do_not_join_nodes() {
+        let mut anim = chain(&[0.0, 0.0, 6.0], &[0.0; 9]);
+        anim.elts_1d.del_elt[1] = 0;
+        assert!(smooth_field(&mut anim, "Temperature", 2));
+        assert_eq!(anim.func, [0.0, 0.0, 6.0]);
+    }
+}
@@ -0,0 +1,141 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// SPH neighbor count: number of active particles within a radius,
+// a proxy of the particle density when the solver did not output it
+// ****************************************
+
+use std::collections::HashMap;
+
+use crate::anim::AnimFile;
+
+// Particles are binned on a grid of cell size <radius>, so that only
+// the 27 surrounding cells are searched. Eroded particles count 0.
+// <radius> is checked positive and finite while parsing --sph-density.
+pub fn add_neighbor_counts(anim: &mut AnimFile, radius: f64) {
+    assert!(radius > 0.0 && radius.is_finite(), "SPH neighbor radius must be positive and finite");
+    let sph = &anim.elts_sph;
+    if sph.count == 0 {
+        return;
+    }
+    let positions: Vec<[f64; 3]> = (0..sph.count)
+        .map(|iel| {
+            let n = sph.nodes(iel)[0] as usize;
+            [anim.coor[3 * n] as f64, anim.coor[3 * n + 1] as f64, anim.coor[3 * n + 2] as f64]
+        })
+        .collect();
+    let cell_of = |p: &[f64; 3]| {
+        [
+            (p[0] / radius).floor() as i64,
+            (p[1] / radius).floor() as i64,
+            (p[2] / radius).floor() as i64,
+        ]
+    };
+
+    let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    for (iel, position) in positions.iter().enumerate() {
+        if sph.is_active(iel) {
+            grid.entry(cell_of(position)).or_default().push(iel);
+        }
+    }
+
+    let radius2 = radius * radius;
+    let mut counts = vec![0i32; sph.count];
+    for iel in 0..sph.count {
+        if !sph.is_active(iel) {
+            continue;
+        }
+        let p = positions[iel];
+        let cell = cell_of(&p);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(others) = grid.get(&[cell[0] + dx, cell[1] + dy, cell[2] + dz]) else {
+                        continue;
+                    };
+                    for &other in others {
+                        let q = positions[other];
+                        let d2 = (p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2);
+                        if other != iel && d2 <= radius2 {
+                            counts[iel] += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    anim.elts_sph.int_arrays.push(("SPH_NEIGHBOR_COUNT".to_string(), counts));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim::ElementBlock;
+
+    // particles on a 3 x 3 x 3 lattice of unit spacing, center at 13
+    fn lattice() -> AnimFile {
+        let coor: Vec<f32> = (0..27).flat_map(|i| [(i % 3) as f32, (i / 3 % 3) as f32, (i / 9) as f32]).collect();
+        AnimFile {
+            nb_nodes: 27,
+            coor,
+            elts_sph: ElementBlock {
+                count: 27,
+                nodes_per_elt: 1,
+                tens_stride: 6,
+                connect: (0..27).collect(),
+                del_elt: vec![1; 27],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn neighbor_counts(anim: &AnimFile) -> &[i32] {
+        let (name, counts) = &anim.elts_sph.int_arrays[0];
+        assert_eq!(name, "SPH_NEIGHBOR_COUNT");
+        counts
+    }
+
+    #[test]
+    fn uniform_lattice_counts_its_neighbors() {
+        let mut anim = lattice();
+        add_neighbor_counts(&mut anim, 1.0);
+        let counts = neighbor_counts(&anim);
+        // corner, edge, face and center particles
+        assert_eq!([counts[0], counts[1], counts[4], counts[13]], [3, 4, 5, 6]);
+
+        // face diagonals are within 1.5, not the cube ones
+        let mut anim = lattice();
+        add_neighbor_counts(&mut anim, 1.5);
+        assert_eq!(neighbor_counts(&anim)[13], 18);
+    }
+
+    #[test]
+    fn eroded_particles_are_not_counted() {
+        let mut anim = lattice();
+        anim.elts_sph.del_elt[12] = 0;
+        add_neighbor_counts(&mut anim, 1.0);
+        let counts = neighbor_counts(&anim);
+        assert_eq!(counts[12], 0);
+        assert_eq!(counts[13], 5);
+    }
+}
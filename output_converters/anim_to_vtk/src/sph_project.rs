@@ -0,0 +1,173 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Projection of the SPH particle scalars onto a regular grid (VTK XML
+// image data, .vti). Each grid point takes the kernel weighted mean of
+// the particles within the kernel support (Shepard interpolation), with
+// the cubic spline kernel of smoothing length radius / 2. Points out of
+// reach of every particle get zeros and vtkValidPointMask 0.
+// ****************************************
+
+use report::codes;
+
+use crate::anim::{field_name_matches, AnimFile};
+use crate::resample::{Grid, VtiWriter};
+use crate::vtk::replace_underscore;
+
+#[derive(Clone)]
+pub struct SphProjection {
+    pub spacing: f64,
+    pub radius: Option<f64>, // kernel support, 2 * spacing by default
+    pub fields: Vec<String>, // SPH scalars to project, all of them without names
+}
+
+impl SphProjection {
+    // "spacing=2.0;radius=4.0;fields=Pressure,Density", spacing required
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut spacing = None;
+        let mut projection = SphProjection { spacing: 0.0, radius: None, fields: Vec::new() };
+        let length = |key: &str, value: &str| match value.trim().parse::<f64>() {
+            Ok(length) if length > 0.0 && length.is_finite() => Ok(length),
+            _ => Err(format!("invalid {} '{}', expected a positive length", key, value)),
+        };
+        for item in spec.split(';').map(str::trim).filter(|item| !item.is_empty()) {
+            let Some((key, value)) = item.split_once('=') else {
+                return Err(format!("expected key=value, got '{}'", item));
+            };
+            match key.trim() {
+                "spacing" => spacing = Some(length("spacing", value)?),
+                "radius" => projection.radius = Some(length("radius", value)?),
+                "fields" => {
+                    projection.fields =
+                        value.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect()
+                }
+                key => return Err(format!("unknown key '{}', expected spacing, radius or fields", key)),
+            }
+        }
+        projection.spacing = spacing.ok_or("spacing is required")?;
+        Ok(projection)
+    }
+
+    fn radius(&self) -> f64 {
+        self.radius.unwrap_or(2.0 * self.spacing)
+    }
+}
+
+// cubic spline kernel at q = distance / support, zero from q = 1,
+// unnormalized (the Shepard mean divides the normalization out)
+fn kernel(q: f64) -> f64 {
+    let q = 2.0 * q;
+    if q < 1.0 {
+        1.0 - 1.5 * q * q + 0.75 * q * q * q
+    } else if q < 2.0 {
+        0.25 * (2.0 - q).powi(3)
+    } else {
+        0.0
+    }
+}
+
+// ****************************************
+// Project the selected SPH scalars of the active particles on a grid
+// covering them, kernel support included. Returns the number of grid points.
+// ****************************************
+pub fn write_vti(anim: &AnimFile, path: &str, projection: &SphProjection, binary: bool) -> Result<usize, String> {
+    let sph = &anim.elts_sph;
+    let radius = projection.radius();
+    let particles: Vec<(usize, [f64; 3])> = (0..sph.count)
+        .filter(|&iel| sph.is_active(iel))
+        .map(|iel| {
+            let n = sph.nodes(iel)[0] as usize * 3;
+            (iel, [anim.coor[n] as f64, anim.coor[n + 1] as f64, anim.coor[n + 2] as f64])
+        })
+        .collect();
+    if particles.is_empty() {
+        return Err("no active SPH particle to project".to_string());
+    }
+    let mut lower = [f64::MAX; 3];
+    let mut upper = [f64::MIN; 3];
+    for (_, p) in &particles {
+        for k in 0..3 {
+            lower[k] = lower[k].min(p[k] - radius);
+            upper[k] = upper[k].max(p[k] + radius);
+        }
+    }
+    let grid = Grid::covering(lower, upper, projection.spacing)?;
+
+    for name in &projection.fields {
+        if !sph.efunc_text.iter().any(|text| field_name_matches(text, name)) {
+            report::warning(codes::NO_MATCH, format_args!("no SPH scalar named {} to project", name));
+        }
+    }
+    let selected: Vec<usize> = (0..sph.efunc_text.len())
+        .filter(|&ifun| {
+            let text = &sph.efunc_text[ifun];
+            projection.fields.is_empty() || projection.fields.iter().any(|name| field_name_matches(text, name))
+        })
+        .collect();
+    let values = selected
+        .iter()
+        .map(|&ifun| anim.elemental_scalar(sph, ifun))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // kernel weights and weighted sums of every grid point
+    let mut weights = vec![0f64; grid.nb_points()];
+    let mut sums = vec![vec![0f64; grid.nb_points()]; selected.len()];
+    for (iel, p) in &particles {
+        let spans: Vec<_> = (0..3).map(|k| grid.span(k, p[k] - radius, p[k] + radius)).collect();
+        for iz in spans[2].clone() {
+            for iy in spans[1].clone() {
+                for ix in spans[0].clone() {
+                    let x = grid.point([ix, iy, iz]);
+                    let distance = ((x[0] - p[0]).powi(2) + (x[1] - p[1]).powi(2) + (x[2] - p[2]).powi(2)).sqrt();
+                    let weight = kernel(distance / radius);
+                    if weight <= 0.0 {
+                        continue;
+                    }
+                    let ipoint = ix + grid.dims[0] * (iy + grid.dims[1] * iz);
+                    weights[ipoint] += weight;
+                    for (sum, field) in sums.iter_mut().zip(&values) {
+                        sum[ipoint] += weight * field[*iel] as f64;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut vti = VtiWriter::create(path, binary)?;
+    let mut write = || -> std::io::Result<()> {
+        vti.begin(&grid, anim.time)?;
+        let mask: Vec<u8> = weights.iter().map(|&weight| (weight > 0.0) as u8).collect();
+        vti.data_array("vtkValidPointMask", 1, &mask)?;
+        for (&ifun, sum) in selected.iter().zip(&sums) {
+            let projected: Vec<f32> = sum
+                .iter()
+                .zip(&weights)
+                .map(|(&sum, &weight)| if weight > 0.0 { (sum / weight) as f32 } else { 0.0 })
+                .collect();
+            vti.data_array(&format!("SPHELEM_{}", replace_underscore(&sph.efunc_text[ifun])), 1, &projected)?;
+        }
+        vti.end()
+    };
+    write().map_err(|e| format!("can't write {}: {}", path, e))?;
+    Ok(grid.nb_points())
+}
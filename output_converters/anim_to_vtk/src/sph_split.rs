@@ -0,0 +1,172 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+
+// ****************************************
+// SPH particles on their own: with --split-sph, the SPH cells are left
+// out of the unstructured grid and written to <input>_sph.vtp, a VTK
+// XML polydata file with one vertex per particle. The particle arrays
+// are point data there, with a RADIUS for point gaussian rendering,
+// and the nodal fields of the particle nodes come along.
+// ****************************************
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use report::codes;
+
+use crate::anim::{resolve_part_id, AnimFile, AnimReadError};
+use crate::mesh_writer::{collect_cell_field, CellArray, CellValues, FieldKind};
+use crate::resample::{data_array, DataValue};
+use crate::subset::gather_nodes;
+use crate::vtk::{replace_underscore, WriteOptions};
+
+// radius of each particle: half the diameter written by the solver,
+// else the radius of the sphere of its mass at its density
+fn radii(anim: &AnimFile) -> Result<Option<Vec<f32>>, AnimReadError> {
+    let sph = &anim.elts_sph;
+    let scalar = |key: &str| sph.efunc_text.iter().position(|text| text.to_ascii_uppercase().contains(key));
+    if let Some(idiam) = scalar("DIAM") {
+        return Ok(Some(anim.elemental_scalar(sph, idiam)?[..sph.count].iter().map(|&diameter| diameter / 2.0).collect()));
+    }
+    let idens = match scalar("DENS") {
+        Some(idens) if sph.e_mass.len() >= sph.count => idens,
+        _ => return Ok(None),
+    };
+    let densities = anim.elemental_scalar(sph, idens)?;
+    let radii = sph.e_mass[..sph.count].iter().zip(&densities[..sph.count]).map(|(&mass, &density)| {
+        if density > 0.0 {
+            (3.0 * mass / (4.0 * std::f32::consts::PI * density)).cbrt()
+        } else {
+            0.0
+        }
+    });
+    Ok(Some(radii.collect()))
+}
+
+struct VtpWriter {
+    writer: BufWriter<File>,
+    binary: bool,
+}
+
+impl VtpWriter {
+    fn array<T: DataValue>(&mut self, name: &str, ncomp: usize, values: &[T]) -> io::Result<()> {
+        data_array(&mut self.writer, self.binary, name, ncomp, values)
+    }
+}
+
+// ****************************************
+// write the SPH particles of a state, returns their number
+// ****************************************
+pub fn write_vtp(anim: &AnimFile, path: &str, options: &WriteOptions) -> Result<usize, String> {
+    let sph = &anim.elts_sph;
+    let nodes: Vec<usize> = (0..sph.count).map(|iel| sph.nodes(iel)[0] as usize).collect();
+    let radii = radii(anim).map_err(|e| e.to_string())?;
+    if radii.is_none() && sph.count > 0 {
+        report::warning(codes::NO_SPH_RADIUS, "no SPH diameter, or mass and density, to give the particles a RADIUS");
+    }
+
+    let file = File::create(path).map_err(|e| format!("can't create output file {}: {}", path, e))?;
+    let mut vtp = VtpWriter { writer: BufWriter::new(file), binary: options.binary };
+    let mut write = || -> io::Result<()> {
+        let w = &mut vtp.writer;
+        writeln!(w, "<?xml version=\"1.0\"?>")?;
+        writeln!(w, "<VTKFile type=\"PolyData\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt32\">")?;
+        writeln!(w, "  <PolyData>")?;
+        writeln!(w, "    <FieldData>")?;
+        writeln!(w, "      <DataArray type=\"Float64\" Name=\"TIME\" NumberOfTuples=\"1\" format=\"ascii\">{}</DataArray>", anim.time)?;
+        writeln!(w, "    </FieldData>")?;
+        writeln!(w, "    <Piece NumberOfPoints=\"{}\" NumberOfVerts=\"{}\">", sph.count, sph.count)?;
+        writeln!(w, "      <PointData>")?;
+
+        // particle arrays, named as the cell arrays of the unstructured grid
+        if sph.el_num.len() == sph.count {
+            vtp.array("ELEMENT_ID", 1, &sph.el_num)?;
+        }
+        let mut part_index = 0;
+        let part_ids: Vec<i32> =
+            (0..sph.count).map(|iel| resolve_part_id(iel, &mut part_index, &sph.def_part, &sph.part_text)).collect();
+        vtp.array("PART_ID", 1, &part_ids)?;
+        if let Some(part_colors) = &options.part_colors {
+            vtp.array("PART_COLOR", 3, &part_colors.colors(&part_ids))?;
+        }
+        let status: Vec<i32> = (0..sph.count).map(|iel| sph.is_active(iel) as i32).collect();
+        vtp.array("EROSION_STATUS", 1, &status)?;
+        for (name, values) in &sph.int_arrays {
+            vtp.array(name, 1, values)?;
+        }
+        if let Some(radii) = &radii {
+            vtp.array("RADIUS", 1, radii)?;
+        }
+        for (iefun, text) in sph.efunc_text.iter().enumerate() {
+            let name = format!("SPHELEM_{}", replace_underscore(text));
+            if options.selects(text, &name) {
+                vtp.array(&name, 1, &anim.elemental_scalar(sph, iefun)?[..sph.count])?;
+            }
+        }
+        for (ietens, text) in sph.tens_text.iter().enumerate() {
+            let name = format!("SPHELEM_{}", replace_underscore(text));
+            if !options.selects(text, &name) {
+                continue;
+            }
+            let values = anim.elemental_tensor(sph, ietens)?;
+            let blocks = [CellValues::Tensor6(&values[..6 * sph.count])];
+            if let CellArray::Float(tensors) = collect_cell_field(FieldKind::Tensor, &blocks, sph.count) {
+                vtp.array(&name, 9, &tensors)?;
+            }
+        }
+
+        // nodal fields of the particle nodes
+        if anim.nod_num.len() == anim.nb_nodes {
+            vtp.array("NODE_ID", 1, &gather_nodes(&anim.nod_num, &nodes, 1, anim.nb_nodes))?;
+        }
+        for (ifun, text) in anim.func_text.iter().enumerate() {
+            let name = replace_underscore(text);
+            if options.selects(text, &name) {
+                vtp.array(&name, 1, &gather_nodes(&anim.nodal_scalar(ifun)?, &nodes, 1, anim.nb_nodes))?;
+            }
+        }
+        for (ivect, text) in anim.vect_text.iter().enumerate() {
+            let name = replace_underscore(text);
+            if options.selects(text, &name) {
+                vtp.array(&name, 3, &gather_nodes(&anim.nodal_vector(ivect)?, &nodes, 3, anim.nb_nodes))?;
+            }
+        }
+
+        writeln!(vtp.writer, "      </PointData>")?;
+        writeln!(vtp.writer, "      <Points>")?;
+        vtp.array("Points", 3, &gather_nodes(&anim.coor, &nodes, 3, anim.nb_nodes))?;
+        writeln!(vtp.writer, "      </Points>")?;
+        writeln!(vtp.writer, "      <Verts>")?;
+        let connectivity: Vec<i32> = (0..sph.count as i32).collect();
+        vtp.array("connectivity", 1, &connectivity)?;
+        let offsets: Vec<i32> = (1..=sph.count as i32).collect();
+        vtp.array("offsets", 1, &offsets)?;
+        let w = &mut vtp.writer;
+        writeln!(w, "      </Verts>")?;
+        writeln!(w, "    </Piece>")?;
+        writeln!(w, "  </PolyData>")?;
+        writeln!(w, "</VTKFile>")?;
+        w.flush()
+    };
+    write().map_err(|e| format!("can't write {}: {}", path, e))?;
+    Ok(sph.count)
+}
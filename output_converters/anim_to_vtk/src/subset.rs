@@ -0,0 +1,227 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Extraction of listed nodes and elements, by user id (NODE_ID,
+// ELEMENT_ID), with the nodes renumbered to the kept ones
+// ****************************************
+
+use std::collections::HashSet;
+use std::fs;
+
+use crate::anim::AnimFile;
+
+// ids separated by spaces, commas or new lines, '#' starts a comment
+pub fn read_id_file(path: &str) -> Result<HashSet<i32>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+    let mut ids = HashSet::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for token in line.split(|c: char| c == ',' || c.is_whitespace()).filter(|token| !token.is_empty()) {
+            let id = token.parse().map_err(|_| format!("Invalid id '{}' in {}", token, path))?;
+            ids.insert(id);
+        }
+    }
+    Ok(ids)
+}
+
+// values of the kept nodes, for arrays of <stride> values per node
+pub fn gather_nodes<T: Copy>(values: &[T], kept: &[usize], stride: usize, nb_nodes: usize) -> Vec<T> {
+    if values.is_empty() || nb_nodes == 0 {
+        return Vec::new();
+    }
+    let nb_fields = values.len() / (stride * nb_nodes);
+    let mut gathered = Vec::with_capacity(nb_fields * stride * kept.len());
+    for field in values.chunks_exact(stride * nb_nodes) {
+        for &inod in kept {
+            gathered.extend_from_slice(&field[inod * stride..(inod + 1) * stride]);
+        }
+    }
+    gathered
+}
+
+fn compact(values: &mut Vec<f32>, kept: &[usize], stride: usize, nb_nodes: usize) {
+    *values = gather_nodes(values, kept, stride, nb_nodes);
+}
+
+// ****************************************
+// Keep the listed elements, or without element list the elements
+// whose nodes are all listed, then the listed nodes and the nodes
+// of the kept elements. Returns the numbers of kept nodes and elements.
+// ****************************************
+pub fn subset(
+    anim: &mut AnimFile,
+    node_ids: Option<&HashSet<i32>>,
+    element_ids: Option<&HashSet<i32>>,
+) -> Result<(usize, usize), String> {
+    if anim.nod_num.len() < anim.nb_nodes {
+        return Err("node and element sets need the user numbering (flag_a[1])".to_string());
+    }
+    let listed_node: Vec<bool> = anim.nod_num[..anim.nb_nodes]
+        .iter()
+        .map(|id| node_ids.is_some_and(|ids| ids.contains(id)))
+        .collect();
+
+    let mut kept = Vec::new();
+    for block in [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph] {
+        kept.push(
+            (0..block.count)
+                .filter(|&iel| match element_ids {
+                    Some(ids) => block.el_num.get(iel).is_some_and(|id| ids.contains(id)),
+                    None => block.nodes(iel).iter().all(|&node| listed_node[node as usize]),
+                })
+                .collect(),
+        );
+    }
+    let nb_elements = kept.iter().map(Vec::len).sum();
+    let nb_nodes = keep_elements(anim, &kept, &listed_node);
+    Ok((nb_nodes, nb_elements))
+}
+
+// ****************************************
+// Leave out the eroded elements, keep every node.
+// Returns the number of elements removed.
+// ****************************************
+pub fn remove_eroded(anim: &mut AnimFile) -> usize {
+    let mut kept = Vec::new();
+    let mut removed = 0;
+    for block in [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph] {
+        let active: Vec<usize> = (0..block.count).filter(|&iel| block.is_active(iel)).collect();
+        removed += block.count - active.len();
+        kept.push(active);
+    }
+    if removed > 0 {
+        keep_elements(anim, &kept, &vec![true; anim.nb_nodes]);
+    }
+    removed
+}
+
+// ****************************************
+// Keep the given elements of each block (1D, 2D, 3D, SPH), all their
+// fields, and the nodes they use or marked in extra_nodes, renumbered.
+// Returns the number of kept nodes.
+// ****************************************
+pub fn keep_elements(anim: &mut AnimFile, kept: &[Vec<usize>], extra_nodes: &[bool]) -> usize {
+    let nb_nodes = anim.nb_nodes;
+    let mut used = vec![false; nb_nodes];
+    for (inod, &extra) in extra_nodes.iter().enumerate() {
+        used[inod] = extra;
+    }
+    for (block, kept) in [&mut anim.elts_1d, &mut anim.elts_2d, &mut anim.elts_3d, &mut anim.elts_sph].into_iter().zip(kept) {
+        *block = block.gather(kept);
+        for &node in &block.connect {
+            used[node as usize] = true;
+        }
+    }
+
+    // old node index -> new one
+    let kept_nodes: Vec<usize> = (0..nb_nodes).filter(|&inod| used[inod]).collect();
+    let mut new_index = vec![-1i32; nb_nodes];
+    for (new, &old) in kept_nodes.iter().enumerate() {
+        new_index[old] = new as i32;
+    }
+    for block in [&mut anim.elts_1d, &mut anim.elts_2d, &mut anim.elts_3d, &mut anim.elts_sph] {
+        for node in block.connect.iter_mut() {
+            *node = new_index[*node as usize];
+        }
+    }
+
+    compact(&mut anim.coor, &kept_nodes, 3, nb_nodes);
+    compact(&mut anim.func, &kept_nodes, 1, nb_nodes);
+    compact(&mut anim.vect_val, &kept_nodes, 3, nb_nodes);
+    compact(&mut anim.n_mass, &kept_nodes, 1, nb_nodes);
+    if anim.nod_num.len() >= nb_nodes {
+        anim.nod_num = kept_nodes.iter().map(|&inod| anim.nod_num[inod]).collect();
+    }
+    anim.nb_nodes = kept_nodes.len();
+    kept_nodes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{int_cell_array, legacy_vtk, quad_parts};
+    use crate::vtk::WriteOptions;
+
+    // parts 10, 20 and 30 of 2 quads each: elements 101 to 106, nodes
+    // 1001 to 1006 (part 10), 1007 to 1012 (20) and 1013 to 1018 (30)
+    fn three_parts() -> AnimFile {
+        quad_parts(&[("10 Front", 2), ("20 Middle", 2), ("30 Rear", 2)])
+    }
+
+    fn ids(values: &[i32]) -> HashSet<i32> {
+        values.iter().copied().collect()
+    }
+
+    #[test]
+    fn element_set_of_the_last_part_keeps_its_part_id() {
+        let mut anim = three_parts();
+        assert_eq!(subset(&mut anim, None, Some(&ids(&[105, 106]))), Ok((6, 2)));
+        let text = legacy_vtk(&anim, &WriteOptions::default());
+        assert_eq!(int_cell_array(&text, "PART_ID"), [30, 30]);
+        assert_eq!(int_cell_array(&text, "ELEMENT_ID"), [105, 106]);
+        assert_eq!(anim.nod_num, (1013..=1018).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn node_set_keeps_the_elements_of_listed_nodes_only() {
+        let mut anim = three_parts();
+        // the 4 nodes of element 106 and one of element 105, and a node of part 10
+        let nodes = ids(&[1014, 1015, 1017, 1018, 1013, 1001]);
+        assert_eq!(subset(&mut anim, Some(&nodes), None), Ok((6, 1)));
+        let text = legacy_vtk(&anim, &WriteOptions::default());
+        assert_eq!(int_cell_array(&text, "PART_ID"), [30]);
+        assert_eq!(int_cell_array(&text, "ELEMENT_ID"), [106]);
+        // listed nodes are kept without element
+        assert_eq!(anim.nod_num, [1001, 1013, 1014, 1015, 1017, 1018]);
+        assert_eq!(anim.coor[..3], [0.0, 0.0, 0.0]);
+        assert_eq!(anim.coor[3..6], [0.0, 4.0, 0.0]);
+    }
+
+    #[test]
+    fn sets_need_the_user_numbering() {
+        let mut anim = three_parts();
+        anim.nod_num.clear();
+        assert!(subset(&mut anim, None, Some(&ids(&[101]))).is_err());
+    }
+
+    #[test]
+    fn eroded_leading_parts_leave_the_last_part_id() {
+        let mut anim = three_parts();
+        anim.elts_2d.del_elt = vec![0, 0, 0, 0, 1, 1];
+        assert_eq!(remove_eroded(&mut anim), 4);
+        // every node is kept
+        assert_eq!(anim.nb_nodes, 18);
+        let text = legacy_vtk(&anim, &WriteOptions::default());
+        assert_eq!(int_cell_array(&text, "PART_ID"), [30, 30]);
+        assert_eq!(int_cell_array(&text, "ELEMENT_ID"), [105, 106]);
+    }
+
+    #[test]
+    fn id_files_take_separators_and_comments() {
+        let dir = crate::test_files::TempDir::new("id_file");
+        let path = dir.file("ids.txt");
+        fs::write(&path, "# door\n101, 102 103\n\n104 # roof\n").unwrap();
+        assert_eq!(read_id_file(&path), Ok(ids(&[101, 102, 103, 104])));
+        fs::write(&path, "101 1o2\n").unwrap();
+        assert_eq!(read_id_file(&path), Err(format!("Invalid id '1o2' in {}", path)));
+    }
+}
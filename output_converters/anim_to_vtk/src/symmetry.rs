@@ -0,0 +1,232 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Symmetry check: every node is paired with the node closest to its
+// mirror image across a plane normal to an axis, within a tolerance.
+// Nodes without mirror tell a geometric asymmetry; on the pairs, the
+// nodal scalars and vectors (normal component mirrored) of a symmetric
+// solution are equal. The worst pair of each field is reported.
+// ****************************************
+
+use std::collections::HashMap;
+
+use report::{codes, Table};
+
+use crate::anim::{field_name_matches, AnimFile, AnimReadError};
+
+// unpaired nodes listed by user id
+const LISTED_NODES: usize = 5;
+
+#[derive(Clone)]
+pub struct SymmetryCheck {
+    pub axis: usize,          // normal of the plane, 0 x, 1 y, 2 z
+    pub position: f64,        // coordinate of the plane along the axis
+    pub tolerance: f64,       // distance between a mirror image and its node
+    pub fields: Vec<String>,  // nodal fields compared, all of them without names
+}
+
+impl SymmetryCheck {
+    // "plane=y0;tol=1e-3;fields=Velocity", plane = axis and coordinate
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut plane = None;
+        let mut check = SymmetryCheck { axis: 0, position: 0.0, tolerance: 1e-3, fields: Vec::new() };
+        for item in spec.split(';').map(str::trim).filter(|item| !item.is_empty()) {
+            let Some((key, value)) = item.split_once('=') else {
+                return Err(format!("expected key=value, got '{}'", item));
+            };
+            let value = value.trim();
+            match key.trim() {
+                "plane" => {
+                    let mut chars = value.chars();
+                    let axis = match chars.next().map(|c| c.to_ascii_lowercase()) {
+                        Some('x') => 0,
+                        Some('y') => 1,
+                        Some('z') => 2,
+                        _ => return Err(format!("invalid plane '{}', expected x, y or z and a coordinate (y0)", value)),
+                    };
+                    let position = match chars.as_str() {
+                        "" => 0.0,
+                        coordinate => coordinate
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|position| position.is_finite())
+                            .ok_or_else(|| format!("invalid plane coordinate '{}'", coordinate))?,
+                    };
+                    plane = Some((axis, position));
+                }
+                "tol" => {
+                    check.tolerance = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|tolerance| *tolerance > 0.0 && tolerance.is_finite())
+                        .ok_or_else(|| format!("invalid tol '{}', expected a positive length", value))?
+                }
+                "fields" => {
+                    check.fields =
+                        value.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect()
+                }
+                key => return Err(format!("unknown key '{}', expected plane, tol or fields", key)),
+            }
+        }
+        (check.axis, check.position) = plane.ok_or("plane is required")?;
+        Ok(check)
+    }
+
+    fn mirror(&self, p: [f64; 3]) -> [f64; 3] {
+        let mut image = p;
+        image[self.axis] = 2.0 * self.position - p[self.axis];
+        image
+    }
+
+    fn selected(&self, text: &str) -> bool {
+        self.fields.is_empty() || self.fields.iter().any(|name| field_name_matches(text, name))
+    }
+}
+
+fn distance(p: [f64; 3], q: [f64; 3]) -> f64 {
+    ((p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2)).sqrt()
+}
+
+// node closest to the mirror image of every node within the tolerance,
+// searched in the 27 cells around the image on a grid of that size
+fn pair_nodes(anim: &AnimFile, check: &SymmetryCheck) -> Vec<Option<(usize, f64)>> {
+    let points: Vec<[f64; 3]> = anim.coor.chunks_exact(3).map(|p| [p[0] as f64, p[1] as f64, p[2] as f64]).collect();
+    let cell_of = |p: [f64; 3]| p.map(|x| (x / check.tolerance).floor() as i64);
+    let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    for (node, &p) in points.iter().enumerate() {
+        grid.entry(cell_of(p)).or_default().push(node);
+    }
+    points
+        .iter()
+        .map(|&p| {
+            let image = check.mirror(p);
+            let cell = cell_of(image);
+            let mut closest: Option<(usize, f64)> = None;
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let Some(nodes) = grid.get(&[cell[0] + dx, cell[1] + dy, cell[2] + dz]) else {
+                            continue;
+                        };
+                        for &other in nodes {
+                            let d = distance(image, points[other]);
+                            if d <= check.tolerance && closest.is_none_or(|(_, best)| d < best) {
+                                closest = Some((other, d));
+                            }
+                        }
+                    }
+                }
+            }
+            closest
+        })
+        .collect()
+}
+
+// largest difference over the pairs of a nodal field of <ncomp>
+// components, the normal component of vectors mirrored
+fn worst_pair(
+    values: &[f32],
+    ncomp: usize,
+    axis: usize,
+    pairs: &[Option<(usize, f64)>],
+) -> Option<(f64, usize, usize)> {
+    let mut worst: Option<(f64, usize, usize)> = None;
+    for (node, pair) in pairs.iter().enumerate() {
+        let Some((mirror, _)) = *pair else {
+            continue;
+        };
+        let difference = (0..ncomp)
+            .map(|c| {
+                let sign = if ncomp == 3 && c == axis { -1.0 } else { 1.0 };
+                (values[node * ncomp + c] as f64 - sign * values[mirror * ncomp + c] as f64).powi(2)
+            })
+            .sum::<f64>()
+            .sqrt();
+        if worst.is_none_or(|(largest, _, _)| difference > largest) {
+            worst = Some((difference, node, mirror));
+        }
+    }
+    worst
+}
+
+// ****************************************
+// Check the symmetry of a state and print the report
+// ****************************************
+pub fn check_symmetry(anim: &AnimFile, check: &SymmetryCheck) -> Result<(), AnimReadError> {
+    let pairs = pair_nodes(anim, check);
+    let node_id = |node: usize| anim.nod_num.get(node).copied().unwrap_or(node as i32);
+    let unpaired: Vec<usize> = (0..pairs.len()).filter(|&node| pairs[node].is_none()).collect();
+    report_line!(
+        "  Symmetry about {} = {} (tolerance {}): {} of {} nodes paired",
+        ["x", "y", "z"][check.axis],
+        check.position,
+        check.tolerance,
+        pairs.len() - unpaired.len(),
+        pairs.len()
+    );
+    if !unpaired.is_empty() {
+        let listed: Vec<String> = unpaired.iter().take(LISTED_NODES).map(|&node| node_id(node).to_string()).collect();
+        let more = if unpaired.len() > LISTED_NODES { ", ..." } else { "" };
+        report::warning(
+            codes::UNPAIRED_NODES,
+            format_args!("{} nodes without mirror: {}{}", unpaired.len(), listed.join(", "), more),
+        );
+    }
+
+    let mut table = Table::new(&["field", "max difference", "node", "mirror node"]);
+    let mut add_row = |name: &str, worst: Option<(f64, usize, usize)>| {
+        if let Some((difference, node, mirror)) = worst {
+            table.add_row(
+                vec![name.to_string(), format!("{:.4e}", difference), node_id(node).to_string(), node_id(mirror).to_string()],
+                None,
+            );
+        }
+    };
+    let mut geometry: Option<(f64, usize, usize)> = None;
+    for (node, pair) in pairs.iter().enumerate() {
+        if let Some((mirror, d)) = *pair {
+            if geometry.is_none_or(|(largest, _, _)| d > largest) {
+                geometry = Some((d, node, mirror));
+            }
+        }
+    }
+    add_row("geometry", geometry);
+    for name in &check.fields {
+        if !anim.func_text.iter().chain(&anim.vect_text).any(|text| field_name_matches(text, name)) {
+            report::warning(codes::NO_MATCH, format_args!("no nodal field named {} to check", name));
+        }
+    }
+    for (ifunc, text) in anim.func_text.iter().enumerate() {
+        if check.selected(text) {
+            add_row(text.trim(), worst_pair(&anim.nodal_scalar(ifunc)?, 1, check.axis, &pairs));
+        }
+    }
+    for (ivect, text) in anim.vect_text.iter().enumerate() {
+        if check.selected(text) {
+            add_row(text.trim(), worst_pair(&anim.nodal_vector(ivect)?, 3, check.axis, &pairs));
+        }
+    }
+    if unpaired.len() < pairs.len() {
+        table.print();
+    }
+    Ok(())
+}
@@ -0,0 +1,218 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+
+// ****************************************
+// Files of the tests: FASTMAGI10 A-files built in memory, states of
+// a few shells, and temporary directories removed when dropped, also
+// when a test fails
+// ****************************************
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::anim::{AnimFile, ElementBlock, FASTMAGI10};
+use crate::mesh_writer::write_mesh;
+use crate::vtk::{LegacyVtkWriter, WriteOptions};
+
+// directory <temp>/anim_to_vtk_<name>_<pid>, emptied when created
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("anim_to_vtk_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    // path of a file of the directory, as the inputs and options take it
+    pub fn file(&self, name: &str) -> String {
+        self.0.join(name).to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// A-file without element: nodes, their user ids, nodal scalars and
+// vectors, and bytes after the last section
+#[derive(Default)]
+pub struct AnimBuilder {
+    time: f32,
+    coor: Vec<f32>,
+    node_ids: Vec<i32>,
+    scalars: Vec<(String, Vec<f32>)>,
+    vectors: Vec<(String, Vec<f32>)>,
+    trailing: Vec<u8>,
+}
+
+fn push_text(bytes: &mut Vec<u8>, text: &str) {
+    let mut text = text.as_bytes().to_vec();
+    text.resize(81, 0);
+    bytes.extend_from_slice(&text);
+}
+
+impl AnimBuilder {
+    pub fn new(time: f32) -> Self {
+        AnimBuilder { time, ..Default::default() }
+    }
+
+    // 3 coordinates per node
+    pub fn nodes(mut self, coor: &[f32]) -> Self {
+        self.coor = coor.to_vec();
+        self
+    }
+
+    // user ids of the nodes, none when empty
+    pub fn node_ids(mut self, ids: &[i32]) -> Self {
+        self.node_ids = ids.to_vec();
+        self
+    }
+
+    pub fn scalar(mut self, name: &str, values: &[f32]) -> Self {
+        self.scalars.push((name.to_string(), values.to_vec()));
+        self
+    }
+
+    // 3 values per node
+    pub fn vector(mut self, name: &str, values: &[f32]) -> Self {
+        self.vectors.push((name.to_string(), values.to_vec()));
+        self
+    }
+
+    pub fn trailing(mut self, bytes: &[u8]) -> Self {
+        self.trailing = bytes.to_vec();
+        self
+    }
+
+    // big endian sections, in file order
+    pub fn bytes(&self) -> Vec<u8> {
+        let nb_nodes = self.coor.len() / 3;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&FASTMAGI10.to_be_bytes());
+        bytes.extend_from_slice(&self.time.to_be_bytes());
+        bytes.extend_from_slice(&[0u8; 3 * 81]);
+        for flag in 0..10 {
+            bytes.extend_from_slice(&i32::from(flag == 1 && !self.node_ids.is_empty()).to_be_bytes());
+        }
+        let counts = [nb_nodes, 0, 0, self.scalars.len(), 0, self.vectors.len(), 0, 0];
+        for count in counts {
+            bytes.extend_from_slice(&(count as i32).to_be_bytes());
+        }
+        for value in &self.coor {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes.extend_from_slice(&vec![0u8; 3 * 2 * nb_nodes]);
+        for (name, _) in &self.scalars {
+            push_text(&mut bytes, name);
+        }
+        for value in self.scalars.iter().flat_map(|(_, values)| values) {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        for (name, _) in &self.vectors {
+            push_text(&mut bytes, name);
+        }
+        for value in self.vectors.iter().flat_map(|(_, values)| values) {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        for id in &self.node_ids {
+            bytes.extend_from_slice(&id.to_be_bytes());
+        }
+        bytes.extend_from_slice(&self.trailing);
+        bytes
+    }
+
+    pub fn write(&self, path: &str) {
+        fs::write(path, self.bytes()).unwrap();
+    }
+}
+
+// ****************************************
+// State of unit quads in rows, one row per part of (part text, count)
+// at y = 2 * part index, so that parts share no node. Node user ids
+// start at 1001, element ones at 101, every element is active.
+// ****************************************
+pub fn quad_parts(parts: &[(&str, usize)]) -> AnimFile {
+    let mut coor = Vec::new();
+    let mut connect = Vec::new();
+    let mut def_part = Vec::new();
+    for (ipart, &(_, count)) in parts.iter().enumerate() {
+        let first = (coor.len() / 3) as i32;
+        let y = 2.0 * ipart as f32;
+        if count > 0 {
+            for row in 0..2 {
+                for x in 0..=count {
+                    coor.extend_from_slice(&[x as f32, y + row as f32, 0.0]);
+                }
+            }
+        }
+        let top = first + count as i32 + 1;
+        for x in 0..count as i32 {
+            connect.extend_from_slice(&[first + x, first + x + 1, top + x + 1, top + x]);
+        }
+        def_part.push((connect.len() / 4) as i32);
+    }
+    let nb_nodes = coor.len() / 3;
+    let count = connect.len() / 4;
+    let mut flag_a = vec![0; 10];
+    flag_a[1] = 1;
+    AnimFile {
+        flag_a,
+        nb_nodes,
+        coor,
+        nod_num: (1001..).take(nb_nodes).collect(),
+        elts_1d: ElementBlock { nodes_per_elt: 2, tens_stride: 9, ..Default::default() },
+        elts_2d: ElementBlock {
+            count,
+            nodes_per_elt: 4,
+            tens_stride: 3,
+            connect,
+            del_elt: vec![1; count],
+            def_part,
+            part_text: parts.iter().map(|(text, _)| text.to_string()).collect(),
+            el_num: (101..).take(count).collect(),
+            ..Default::default()
+        },
+        elts_3d: ElementBlock { nodes_per_elt: 8, tens_stride: 6, ..Default::default() },
+        elts_sph: ElementBlock { nodes_per_elt: 1, tens_stride: 6, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+// legacy VTK text of a state
+pub fn legacy_vtk(anim: &AnimFile, options: &WriteOptions) -> String {
+    let mut output = Vec::new();
+    let mut writer = LegacyVtkWriter::new(&mut output, options);
+    write_mesh(anim, options, &mut writer).unwrap();
+    drop(writer);
+    String::from_utf8(output).unwrap()
+}
+
+// values of an integer cell array of a legacy VTK text
+pub fn int_cell_array(text: &str, name: &str) -> Vec<i32> {
+    let header = format!("SCALARS {} int 1\nLOOKUP_TABLE default\n", name);
+    let start = text.find(&header).map(|at| at + header.len()).unwrap_or(text.len());
+    text[start..].lines().map_while(|line| line.parse().ok()).collect()
+}
@@ -0,0 +1,339 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Temporal filtering of fields across the input states. A first pass
+// reads the selected fields of every input file by node or element id;
+// each value is then filtered over a window of states centered on its
+// own, and the conversion writes the filtered values instead.
+// Savitzky-Golay filters fit a quadratic over the window, which keeps
+// peaks better than a moving mean. Both assume equally spaced states.
+// ****************************************
+
+use std::collections::HashMap;
+
+use report::codes;
+
+use crate::anim::{field_name_matches, AnimFile, ElementBlock};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FilterKind {
+    SavitzkyGolay, // quadratic least squares fit
+    Mean,          // moving mean
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Location {
+    NodalScalar,
+    NodalVector,
+    Elemental(usize), // element block: 1D, 2D, 3D, SPH
+}
+
+// values of one field in every state, by id
+#[derive(Clone)]
+struct Series {
+    ncomp: usize,
+    rows: HashMap<i32, usize>, // node or element id -> row
+    states: Vec<Vec<f32>>,     // per state, ncomp values per row, NaN for an id the state doesn't have
+}
+
+#[derive(Clone)]
+pub struct TimeFilter {
+    pub kind: FilterKind,
+    pub half_width: usize,   // the window holds 2 half_width + 1 states
+    pub fields: Vec<String>, // filtered fields, every nodal scalar and vector and elemental scalar without names
+    states: HashMap<String, usize>, // input file -> state
+    series: HashMap<(Location, String), Series>,
+}
+
+impl TimeFilter {
+    // "sgolay:7" or "mean:5", optionally followed by ":<names>"
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut items = spec.splitn(3, ':').map(str::trim);
+        let kind = match items.next().unwrap_or("") {
+            "sgolay" => FilterKind::SavitzkyGolay,
+            "mean" => FilterKind::Mean,
+            kind => return Err(format!("unknown filter '{}', expected sgolay or mean", kind)),
+        };
+        let window = items.next().unwrap_or("");
+        let min_window = if kind == FilterKind::SavitzkyGolay { 5 } else { 3 };
+        let window: usize = window
+            .parse()
+            .ok()
+            .filter(|&window| window >= min_window && window % 2 == 1)
+            .ok_or_else(|| {
+                let name = if kind == FilterKind::SavitzkyGolay { "sgolay" } else { "mean" };
+                format!("invalid window '{}' for {}, expected an odd number of states, at least {}", window, name, min_window)
+            })?;
+        let fields = items
+            .next()
+            .map(|names| names.split(',').map(str::trim).filter(|name| !name.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        Ok(TimeFilter { kind, half_width: window / 2, fields, states: HashMap::new(), series: HashMap::new() })
+    }
+
+    fn selects(&self, name: &str) -> bool {
+        self.fields.is_empty() || self.fields.iter().any(|field| field_name_matches(name, field))
+    }
+
+    // first pass: reads the selected fields of the input files, in order,
+    // and filters them; files that can't be read are no state of the filter
+    pub fn prepare(&mut self, input_files: &[&String], read: impl Fn(&str) -> Option<AnimFile>) {
+        let mut times = Vec::new();
+        for &file_name in input_files {
+            let Some(anim) = read(file_name) else {
+                continue;
+            };
+            let state = times.len();
+            self.states.insert(file_name.clone(), state);
+            times.push(anim.time);
+            self.add_state(&anim, state);
+        }
+        let nb_states = times.len();
+        for series in self.series.values_mut() {
+            let values_per_state = series.ncomp * series.rows.len();
+            for values in series.states.iter_mut() {
+                values.resize(values_per_state, f32::NAN);
+            }
+            series.states.resize(nb_states, vec![f32::NAN; values_per_state]);
+            series.states = filtered(&series.states, self.kind, self.half_width);
+        }
+
+        report_line!(
+            "Time filter: {} fields over {} states, window of {} states",
+            self.series.len(),
+            nb_states,
+            2 * self.half_width + 1
+        );
+        if self.series.is_empty() {
+            report::warning(codes::NO_MATCH, "no field to filter in the input files");
+        }
+        let steps: Vec<f32> = times.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let (min_step, max_step) = steps.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &step| {
+            (min.min(step), max.max(step))
+        });
+        if steps.len() > 1 && (min_step <= 0.0 || max_step > 1.1 * min_step) {
+            report::warning(
+                codes::TIME_ORDER,
+                format_args!(
+                    "time steps between the states go from {} to {}, the filter assumes equally spaced states",
+                    min_step,
+                    max_step
+                ),
+            );
+        }
+    }
+
+    fn add_state(&mut self, anim: &AnimFile, state: usize) {
+        let node_ids: Vec<i32> = (0..anim.nb_nodes).map(|inod| anim.nod_num.get(inod).copied().unwrap_or(inod as i32)).collect();
+        for (ifunc, name) in anim.func_text.iter().enumerate() {
+            if self.selects(name) {
+                let values = &anim.func[ifunc * anim.nb_nodes..(ifunc + 1) * anim.nb_nodes];
+                self.add_values((Location::NodalScalar, name.trim().to_string()), 1, state, &node_ids, values);
+            }
+        }
+        for (ivect, name) in anim.vect_text.iter().enumerate() {
+            if self.selects(name) {
+                let values = &anim.vect_val[ivect * 3 * anim.nb_nodes..(ivect + 1) * 3 * anim.nb_nodes];
+                self.add_values((Location::NodalVector, name.trim().to_string()), 3, state, &node_ids, values);
+            }
+        }
+        for (iblock, block) in blocks(anim).into_iter().enumerate() {
+            let element_ids: Vec<i32> = (0..block.count).map(|iel| block.el_num.get(iel).copied().unwrap_or(iel as i32)).collect();
+            for (ifunc, name) in block.efunc_text.iter().enumerate() {
+                if self.selects(name) {
+                    let values = &block.efunc[ifunc * block.count..(ifunc + 1) * block.count];
+                    self.add_values((Location::Elemental(iblock), name.trim().to_string()), 1, state, &element_ids, values);
+                }
+            }
+        }
+    }
+
+    fn add_values(&mut self, key: (Location, String), ncomp: usize, state: usize, ids: &[i32], values: &[f32]) {
+        let series = self.series.entry(key).or_insert_with(|| Series { ncomp, rows: HashMap::new(), states: Vec::new() });
+        for &id in ids {
+            let next_row = series.rows.len();
+            series.rows.entry(id).or_insert(next_row);
+        }
+        series.states.resize(state + 1, Vec::new());
+        let state_values = &mut series.states[state];
+        state_values.resize(ncomp * series.rows.len(), f32::NAN);
+        for (&id, values) in ids.iter().zip(values.chunks_exact(ncomp)) {
+            let row = series.rows[&id];
+            state_values[ncomp * row..ncomp * (row + 1)].copy_from_slice(values);
+        }
+    }
+
+    // replaces the selected fields of a state of the first pass with their
+    // filtered values, returns the number of fields replaced
+    pub fn apply(&self, anim: &mut AnimFile, file_name: &str) -> usize {
+        let Some(&state) = self.states.get(file_name) else {
+            return 0;
+        };
+        let node_ids: Vec<i32> = (0..anim.nb_nodes).map(|inod| anim.nod_num.get(inod).copied().unwrap_or(inod as i32)).collect();
+        let nb_nodes = anim.nb_nodes;
+        let mut replaced = 0;
+        for (ifunc, name) in anim.func_text.iter().enumerate() {
+            if let Some(series) = self.series.get(&(Location::NodalScalar, name.trim().to_string())) {
+                series.replace(state, &node_ids, &mut anim.func[ifunc * nb_nodes..(ifunc + 1) * nb_nodes]);
+                replaced += 1;
+            }
+        }
+        for (ivect, name) in anim.vect_text.iter().enumerate() {
+            if let Some(series) = self.series.get(&(Location::NodalVector, name.trim().to_string())) {
+                series.replace(state, &node_ids, &mut anim.vect_val[ivect * 3 * nb_nodes..(ivect + 1) * 3 * nb_nodes]);
+                replaced += 1;
+            }
+        }
+        for (iblock, block) in [&mut anim.elts_1d, &mut anim.elts_2d, &mut anim.elts_3d, &mut anim.elts_sph]
+            .into_iter()
+            .enumerate()
+        {
+            let element_ids: Vec<i32> = (0..block.count).map(|iel| block.el_num.get(iel).copied().unwrap_or(iel as i32)).collect();
+            let count = block.count;
+            for (ifunc, name) in block.efunc_text.iter().enumerate() {
+                if let Some(series) = self.series.get(&(Location::Elemental(iblock), name.trim().to_string())) {
+                    series.replace(state, &element_ids, &mut block.efunc[ifunc * count..(ifunc + 1) * count]);
+                    replaced += 1;
+                }
+            }
+        }
+        replaced
+    }
+}
+
+impl Series {
+    fn replace(&self, state: usize, ids: &[i32], values: &mut [f32]) {
+        let filtered = &self.states[state];
+        for (id, values) in ids.iter().zip(values.chunks_exact_mut(self.ncomp)) {
+            if let Some(&row) = self.rows.get(id) {
+                values.copy_from_slice(&filtered[self.ncomp * row..self.ncomp * (row + 1)]);
+            }
+        }
+    }
+}
+
+fn blocks(anim: &AnimFile) -> [&ElementBlock; 4] {
+    [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph]
+}
+
+// weights of the states j = -m..=m of a centered window of half width m
+fn weights(kind: FilterKind, m: usize) -> Vec<f64> {
+    let m = m as f64;
+    (0..=2 * m as usize)
+        .map(|k| {
+            let j = k as f64 - m;
+            match kind {
+                FilterKind::Mean => 1.0 / (2.0 * m + 1.0),
+                FilterKind::SavitzkyGolay => {
+                    (3.0 * (3.0 * m * m + 3.0 * m - 1.0) - 15.0 * j * j)
+                        / ((2.0 * m - 1.0) * (2.0 * m + 1.0) * (2.0 * m + 3.0))
+                }
+            }
+        })
+        .collect()
+}
+
+// Each value is filtered over the widest centered window, up to the half
+// width, within the states and without a state missing its id: the window
+// narrows near the first and last states, which keep their values.
+fn filtered(states: &[Vec<f32>], kind: FilterKind, half_width: usize) -> Vec<Vec<f32>> {
+    let all_weights: Vec<Vec<f64>> = (0..=half_width).map(|m| weights(kind, m)).collect();
+    let mut result = states.to_vec();
+    let nb_states = states.len();
+    let nb_values = states.first().map_or(0, Vec::len);
+    for (state, result) in result.iter_mut().enumerate() {
+        for ivalue in 0..nb_values {
+            if states[state][ivalue].is_nan() {
+                continue;
+            }
+            let mut m = half_width.min(state).min(nb_states - 1 - state);
+            while (1..=m).any(|j| states[state - j][ivalue].is_nan() || states[state + j][ivalue].is_nan()) {
+                m -= 1;
+            }
+            let window = &states[state - m..=state + m];
+            let value: f64 = all_weights[m].iter().zip(window).map(|(weight, values)| weight * values[ivalue] as f64).sum();
+            result[ivalue] = value as f32;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(values: &[f64], expected: &[f64]) {
+        assert_eq!(values.len(), expected.len());
+        for (value, expected) in values.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12, "{:?} != {:?}", values, expected);
+        }
+    }
+
+    fn parse_error(spec: &str) -> String {
+        TimeFilter::parse(spec).err().unwrap_or_else(|| panic!("{} parsed", spec))
+    }
+
+    #[test]
+    fn savitzky_golay_coefficients() {
+        let five = [-3.0, 12.0, 17.0, 12.0, -3.0].map(|c| c / 35.0);
+        let seven = [-2.0, 3.0, 6.0, 7.0, 6.0, 3.0, -2.0].map(|c| c / 21.0);
+        assert_close(&weights(FilterKind::SavitzkyGolay, 2), &five);
+        assert_close(&weights(FilterKind::SavitzkyGolay, 3), &seven);
+        // the narrowed windows of the first and last states keep the value
+        assert_close(&weights(FilterKind::SavitzkyGolay, 1), &[0.0, 1.0, 0.0]);
+        assert_close(&weights(FilterKind::SavitzkyGolay, 0), &[1.0]);
+        assert_close(&weights(FilterKind::Mean, 1), &[1.0 / 3.0; 3]);
+    }
+
+    #[test]
+    fn quadratics_go_through_the_savitzky_golay_filter() {
+        let states: Vec<Vec<f32>> = (0..9).map(|state| vec![(state * state) as f32, 1.0]).collect();
+        let result = filtered(&states, FilterKind::SavitzkyGolay, 3);
+        assert_eq!(result, states);
+
+        let result = filtered(&states, FilterKind::Mean, 1);
+        assert_eq!(result[0], [0.0, 1.0]);
+        assert_eq!(result[4], [(9.0 + 16.0 + 25.0) / 3.0, 1.0]);
+    }
+
+    #[test]
+    fn windows_are_odd_from_5_for_sgolay_and_3_for_mean() {
+        assert_eq!(TimeFilter::parse("sgolay:5").unwrap().half_width, 2);
+        assert_eq!(TimeFilter::parse("mean:3").unwrap().half_width, 1);
+        let filter = TimeFilter::parse("sgolay:7:Velocity, Contact pressure").unwrap();
+        assert_eq!(filter.fields, ["Velocity", "Contact pressure"]);
+
+        assert_eq!(parse_error("sgolay:3"), "invalid window '3' for sgolay, expected an odd number of states, at least 5");
+        assert_eq!(parse_error("sgolay:6"), "invalid window '6' for sgolay, expected an odd number of states, at least 5");
+        assert_eq!(parse_error("mean:1"), "invalid window '1' for mean, expected an odd number of states, at least 3");
+        assert_eq!(parse_error("mean"), "invalid window '' for mean, expected an odd number of states, at least 3");
+        assert_eq!(parse_error("median:5"), "unknown filter 'median', expected sgolay or mean");
+    }
+
+    #[test]
+    fn the_command_line_states_the_minimum_window() {
+        use clap::Parser;
+
+        let error = crate::Cli::try_parse_from(["anim_to_vtk", "--time-filter", "sgolay:3", "runA001"]).err().unwrap();
+        assert!(error.to_string().contains("expected an odd number of states, at least 5"), "{}", error);
+    }
+}
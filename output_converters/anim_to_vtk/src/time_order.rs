@@ -0,0 +1,234 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Order and selection of the input files by simulation time, read
+// from their headers, for file names that do not sort with the states
+// (A99 after A100, restarted runs numbered from 1 again)
+// ****************************************
+
+use report::codes;
+
+use crate::anim::{read_header_time, Endian};
+
+// times closer than this, relative to their magnitude, are the same state
+const TIME_TOLERANCE: f32 = 1e-6;
+
+// (file, header time), None when the header can't be read
+pub type TimedFile<'a> = (&'a String, Option<f32>);
+
+pub fn header_times<'a>(files: &[&'a String], endian: Option<Endian>) -> Vec<TimedFile<'a>> {
+    files
+        .iter()
+        .map(|&file_name| match read_header_time(file_name, endian) {
+            Ok(time) => (file_name, Some(time)),
+            Err(e) => {
+                report::warning(codes::TIME_UNKNOWN, format_args!("{}: {}, time unknown", file_name, e));
+                (file_name, None)
+            }
+        })
+        .collect()
+}
+
+fn same_time(a: f32, b: f32) -> bool {
+    (a - b).abs() <= TIME_TOLERANCE * a.abs().max(b.abs())
+}
+
+// indices of the files with a known time, sorted by time, stable
+fn time_order(files: &[TimedFile]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..files.len()).filter(|&index| files[index].1.is_some()).collect();
+    order.sort_by(|&a, &b| files[a].1.unwrap_or_default().total_cmp(&files[b].1.unwrap_or_default()));
+    order
+}
+
+// stable, so that files with equal times keep their relative order;
+// files whose header can't be read go last and fail when converted
+pub fn sort_by_time(files: &mut Vec<TimedFile>) {
+    let mut previous: Option<TimedFile> = None;
+    for &(file_name, time) in files.iter() {
+        if let Some(time) = time {
+            if let Some((previous_name, Some(previous_time))) = previous {
+                if time < previous_time {
+                    report::warning(
+                        codes::TIME_ORDER,
+                        format_args!(
+                            "{} (time {}) comes after {} (time {}), times are not monotonic",
+                            file_name,
+                            time,
+                            previous_name,
+                            previous_time
+                        ),
+                    );
+                }
+            }
+            previous = Some((file_name, Some(time)));
+        }
+    }
+
+    let order = time_order(files);
+    for pair in order.windows(2) {
+        let (first, second) = (files[pair[0]], files[pair[1]]);
+        if first.1 == second.1 {
+            report::warning(
+                codes::TIME_ORDER,
+                format_args!("{} and {} have the same time {}", first.0, second.0, first.1.unwrap_or_default()),
+            );
+        }
+    }
+    let mut sorted: Vec<TimedFile> = order.iter().map(|&index| files[index]).collect();
+    sorted.extend(files.iter().filter(|file| file.1.is_none()));
+    *files = sorted;
+}
+
+// of the files with (nearly) the same time, keeps the last one listed,
+// the restart that overwrote the state
+pub fn dedupe_times(files: &mut Vec<TimedFile>) {
+    let order = time_order(files);
+    let mut skipped = vec![false; files.len()];
+    let mut group_start = 0;
+    for end in 1..=order.len() {
+        let same = end < order.len()
+            && same_time(files[order[end - 1]].1.unwrap_or_default(), files[order[end]].1.unwrap_or_default());
+        if same {
+            continue;
+        }
+        let group = &order[group_start..end];
+        let kept = group.iter().copied().max().unwrap_or_default();
+        for &index in group {
+            if index != kept {
+                skipped[index] = true;
+                report_line!(
+                    "Skipping {} (time {}): same time as {} (time {})",
+                    files[index].0,
+                    files[index].1.unwrap_or_default(),
+                    files[kept].0,
+                    files[kept].1.unwrap_or_default()
+                );
+            }
+        }
+        group_start = end;
+    }
+    let nb_skipped = skipped.iter().filter(|&&skip| skip).count();
+    if nb_skipped > 0 {
+        report_line!("Skipped {} of {} files with a duplicated time", nb_skipped, files.len());
+    }
+    let mut index = 0;
+    files.retain(|_| {
+        index += 1;
+        !skipped[index - 1]
+    });
+}
+
+// "start:end" window of simulation times, either bound left out for an
+// open end; bounds included, within the tolerance of same_time
+#[derive(Clone, Copy)]
+pub struct TimeRange {
+    start: Option<f32>,
+    end: Option<f32>,
+}
+
+impl TimeRange {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (start, end) = spec.split_once(':').ok_or("expected start:end")?;
+        let bound = |text: &str| -> Result<Option<f32>, String> {
+            let text = text.trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            match text.parse::<f32>() {
+                Ok(time) if time.is_finite() => Ok(Some(time)),
+                _ => Err(format!("'{}' is not a time", text)),
+            }
+        };
+        let range = TimeRange { start: bound(start)?, end: bound(end)? };
+        if let (Some(start), Some(end)) = (range.start, range.end) {
+            if start > end {
+                return Err("start after end".to_string());
+            }
+        }
+        Ok(range)
+    }
+
+    fn contains(&self, time: f32) -> bool {
+        self.start.is_none_or(|start| time >= start || same_time(time, start))
+            && self.end.is_none_or(|end| time <= end || same_time(time, end))
+    }
+}
+
+// files whose header can't be read are kept, they fail when converted
+pub fn select_time_range(files: &mut Vec<TimedFile>, range: &TimeRange) {
+    let nb_files = files.len();
+    files.retain(|&(_, time)| time.is_none_or(|time| range.contains(time)));
+    report_line!("Time range: {} of {} files", files.len(), nb_files);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{AnimBuilder, TempDir};
+
+    fn names<'a>(files: &[TimedFile<'a>]) -> Vec<&'a str> {
+        files.iter().map(|(file_name, _)| file_name.as_str()).collect()
+    }
+
+    #[test]
+    fn states_out_of_order_are_sorted_with_a_warning() {
+        let dir = TempDir::new("time_order");
+        let paths: Vec<String> = ["runA099", "runA100", "runA001", "runA002"].iter().map(|name| dir.file(name)).collect();
+        for (path, time) in paths.iter().zip([0.99, 1.0, 0.5]) {
+            AnimBuilder::new(time).nodes(&[0.0; 3]).write(path);
+        }
+        // the last file is missing
+        let inputs: Vec<&String> = paths.iter().collect();
+        let (mut files, captured) = report::capture(|| header_times(&inputs, None));
+        assert!(captured.lines().contains(&format!("Warning [{}]: {}: ", codes::TIME_UNKNOWN, paths[3])));
+        assert_eq!(files.iter().map(|(_, time)| *time).collect::<Vec<_>>(), [Some(0.99), Some(1.0), Some(0.5), None]);
+
+        let ((), captured) = report::capture(|| sort_by_time(&mut files));
+        assert_eq!(names(&files), [&paths[2], &paths[0], &paths[1], &paths[3]]);
+        assert_eq!(
+            captured.lines(),
+            format!(
+                "Warning [{}]: {} (time 0.5) comes after {} (time 1), times are not monotonic\n",
+                codes::TIME_ORDER,
+                paths[2],
+                paths[1]
+            )
+        );
+    }
+
+    #[test]
+    fn restarts_keep_the_last_file_of_a_time() {
+        let names_times = [("runA001", 0.0), ("runA002", 0.5), ("restartA001", 0.5), ("restartA002", 1.0)];
+        let owned: Vec<String> = names_times.iter().map(|(name, _)| name.to_string()).collect();
+        let mut files: Vec<TimedFile> = owned.iter().zip(names_times).map(|(name, (_, time))| (name, Some(time))).collect();
+
+        let ((), captured) = report::capture(|| sort_by_time(&mut files));
+        assert!(captured.lines().contains("runA002 and restartA001 have the same time 0.5"));
+        report::capture(|| dedupe_times(&mut files));
+        assert_eq!(names(&files), ["runA001", "restartA001", "restartA002"]);
+
+        let range = TimeRange::parse("0.5:").unwrap();
+        report::capture(|| select_time_range(&mut files, &range));
+        assert_eq!(names(&files), ["restartA001", "restartA002"]);
+        assert!(TimeRange::parse("1:0.5").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Trajectories of listed nodes across the states of a batch: one CSV
+// row per node and state as the states come, and at the end a VTK XML
+// polydata (.vtp) with one polyline per node through its positions
+// ****************************************
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+
+use report::codes;
+
+use crate::anim::AnimFile;
+use crate::resample::xml_attribute;
+
+// time and position of a node in every state it is in
+type Path = Vec<(f32, [f32; 3])>;
+
+pub struct TrajectoryTracker {
+    writer: BufWriter<File>,
+    vtp_path: String,
+    ids: Vec<i32>,                       // listed node ids, sorted
+    paths: HashMap<i32, Path>,           // by node id
+    state: usize,
+}
+
+impl TrajectoryTracker {
+    // <output>.csv and <output>.vtp
+    pub fn create(output: &str, ids: &[i32]) -> io::Result<Self> {
+        let vtp_path = format!("{}.vtp", output);
+        File::create(&vtp_path)?;
+        let mut writer = BufWriter::new(File::create(format!("{}.csv", output))?);
+        writeln!(writer, "state,time,node,x,y,z")?;
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        Ok(TrajectoryTracker { writer, vtp_path, ids, paths: HashMap::new(), state: 0 })
+    }
+
+    pub fn add_state(&mut self, anim: &AnimFile) -> io::Result<()> {
+        // user ids, node indices without numbering
+        let index: HashMap<i32, usize> = if anim.nod_num.is_empty() {
+            (0..anim.nb_nodes).map(|node| (node as i32, node)).collect()
+        } else {
+            anim.nod_num.iter().enumerate().map(|(node, &id)| (id, node)).collect()
+        };
+        let mut missing = 0;
+        for &id in &self.ids {
+            let Some(&node) = index.get(&id) else {
+                missing += 1;
+                continue;
+            };
+            let p = [anim.coor[3 * node], anim.coor[3 * node + 1], anim.coor[3 * node + 2]];
+            writeln!(self.writer, "{},{},{},{},{},{}", self.state, anim.time, id, p[0], p[1], p[2])?;
+            self.paths.entry(id).or_default().push((anim.time, p));
+        }
+        if missing > 0 {
+            report::warning(
+                codes::MISSING_NODES,
+                format_args!("{} of the {} trajectory nodes not in this state", missing, self.ids.len()),
+            );
+        }
+        self.state += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()?;
+
+        // one polyline per node found in at least one state
+        let lines: Vec<(i32, &Path)> =
+            self.ids.iter().filter_map(|id| self.paths.get(id).map(|path| (*id, path))).collect();
+        let nb_points: usize = lines.iter().map(|(_, path)| path.len()).sum();
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\"?>\n");
+        xml.push_str("<VTKFile type=\"PolyData\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt32\">\n");
+        xml.push_str("  <PolyData>\n");
+        xml.push_str(&format!("    <Piece NumberOfPoints=\"{}\" NumberOfLines=\"{}\">\n", nb_points, lines.len()));
+        let data_array = |xml: &mut String, kind: &str, name: &str, ncomp: usize, values: Vec<String>| {
+            xml.push_str(&format!(
+                "        <DataArray type=\"{}\" Name=\"{}\" NumberOfComponents=\"{}\" format=\"ascii\">\n",
+                kind,
+                xml_attribute(name),
+                ncomp
+            ));
+            for tuple in values.chunks(ncomp) {
+                xml.push_str(&format!("          {}\n", tuple.join(" ")));
+            }
+            xml.push_str("        </DataArray>\n");
+        };
+        let points = || lines.iter().flat_map(|(id, path)| path.iter().map(move |(time, p)| (*id, *time, *p)));
+
+        xml.push_str("      <Points>\n");
+        data_array(&mut xml, "Float32", "Points", 3, points().flat_map(|(_, _, p)| p.map(|x| x.to_string())).collect());
+        xml.push_str("      </Points>\n");
+        xml.push_str("      <PointData>\n");
+        data_array(&mut xml, "Float32", "TIME", 1, points().map(|(_, time, _)| time.to_string()).collect());
+        data_array(&mut xml, "Int32", "NODE_ID", 1, points().map(|(id, _, _)| id.to_string()).collect());
+        xml.push_str("      </PointData>\n");
+        xml.push_str("      <CellData>\n");
+        data_array(&mut xml, "Int32", "NODE_ID", 1, lines.iter().map(|(id, _)| id.to_string()).collect());
+        xml.push_str("      </CellData>\n");
+        xml.push_str("      <Lines>\n");
+        data_array(&mut xml, "Int32", "connectivity", 1, (0..nb_points).map(|i| i.to_string()).collect());
+        let offsets = lines.iter().scan(0, |end, (_, path)| {
+            *end += path.len();
+            Some(end.to_string())
+        });
+        data_array(&mut xml, "Int32", "offsets", 1, offsets.collect());
+        xml.push_str("      </Lines>\n");
+        xml.push_str("    </Piece>\n");
+        xml.push_str("  </PolyData>\n");
+        xml.push_str("</VTKFile>\n");
+        fs::write(&self.vtp_path, xml)
+    }
+}
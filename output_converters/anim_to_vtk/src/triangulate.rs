@@ -0,0 +1,105 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Simplicial tessellation: quads to triangles, hexahedra to tetrahedra
+// ****************************************
+
+use crate::anim::{AnimFile, ElementBlock};
+use crate::cells::unique_count;
+
+// 6 tetrahedra sharing the 0-6 diagonal of a brick
+pub(crate) const HEXA_TETS: [[usize; 4]; 6] = [
+    [0, 1, 2, 6],
+    [0, 2, 3, 6],
+    [0, 3, 7, 6],
+    [0, 7, 4, 6],
+    [0, 4, 5, 6],
+    [0, 5, 1, 6],
+];
+
+// Rebuild a block from (source element, child connectivity) pairs.
+// Elemental values of the source are copied onto each child, the
+// element mass is shared between the children.
+fn split_block(block: &ElementBlock, children: Vec<(usize, Vec<i32>)>) -> ElementBlock {
+    let sources: Vec<usize> = children.iter().map(|(iel, _)| *iel).collect();
+    let mut split = block.gather(&sources);
+    split.connect = children.into_iter().flat_map(|(_, nodes)| nodes).collect();
+    if !split.e_mass.is_empty() {
+        let mut nb_children = vec![0u32; block.count];
+        for &iel in &sources {
+            nb_children[iel] += 1;
+        }
+        for (mass, &iel) in split.e_mass.iter_mut().zip(&sources) {
+            *mass /= nb_children[iel] as f32;
+        }
+    }
+    split
+}
+
+// Split quads along their 0-2 diagonal, triangles are kept.
+// Triangles stay in the 4-node facet layout (last node repeated).
+pub fn triangulate_facets(facets: &ElementBlock) -> ElementBlock {
+    let mut children = Vec::with_capacity(2 * facets.count);
+    for iel in 0..facets.count {
+        let n = facets.nodes(iel);
+        if unique_count(n) == 4 {
+            children.push((iel, vec![n[0], n[1], n[2], n[2]]));
+            children.push((iel, vec![n[0], n[2], n[3], n[3]]));
+        } else {
+            children.push((iel, n.to_vec()));
+        }
+    }
+    split_block(facets, children)
+}
+
+// Split bricks into tetrahedra around their 0-6 diagonal. Degenerated
+// bricks (wedges, pyramids) only keep the non-flat tetrahedra.
+// Tetrahedra stay in the 8-node brick layout [a b c c d d d d].
+pub fn tetrahedralize_bricks(bricks: &ElementBlock) -> ElementBlock {
+    let mut children = Vec::with_capacity(6 * bricks.count);
+    for iel in 0..bricks.count {
+        let n = bricks.nodes(iel);
+        if unique_count(n) <= 4 {
+            children.push((iel, n.to_vec()));
+            continue;
+        }
+        let before = children.len();
+        for tet in HEXA_TETS.iter() {
+            let t = [n[tet[0]], n[tet[1]], n[tet[2]], n[tet[3]]];
+            if unique_count(&t) == 4 {
+                children.push((iel, vec![t[0], t[1], t[2], t[2], t[3], t[3], t[3], t[3]]));
+            }
+        }
+        if children.len() == before {
+            // fully collapsed brick, nothing to split
+            children.push((iel, n.to_vec()));
+        }
+    }
+    split_block(bricks, children)
+}
+
+pub fn triangulate(anim: &mut AnimFile, solids: bool) {
+    anim.elts_2d = triangulate_facets(&anim.elts_2d);
+    if solids {
+        anim.elts_3d = tetrahedralize_bricks(&anim.elts_3d);
+    }
+}
@@ -0,0 +1,345 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// OpenUSD stage of the run (--usd, usd feature), for Omniverse based
+// reviews: the skin of the model (active shells and free faces of the
+// active solids, as in --format obj) as one Mesh whose points are
+// time-sampled, one time code per converted state. The topology is
+// sampled again only when erosion changes it. One or two scalars
+// (--usd-primvars) follow as primvars: nodal scalars per vertex,
+// elemental scalars per face.
+// Every attribute gets its samples in a temporary file next to the
+// stage, the stage is assembled from them when the run ends.
+// ****************************************
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+
+use ryu::Buffer as RyuBuffer;
+
+use report::codes;
+
+use crate::anim::{field_name_matches, AnimFile, ElementBlock};
+use crate::obj;
+
+// active elements of every part, in part order
+fn elements_per_part(block: &ElementBlock) -> Vec<Vec<usize>> {
+    let last = block.part_text.len().saturating_sub(1);
+    let mut elements = vec![Vec::new(); last + 1];
+    for (iel, ipart) in block.part_indices().into_iter().enumerate() {
+        if block.is_active(iel) {
+            elements[ipart.min(last)].push(iel);
+        }
+    }
+    elements
+}
+
+// faces of the active shells by part, with their element
+fn shell_faces(shells: &ElementBlock) -> Vec<Vec<(usize, Vec<i32>)>> {
+    elements_per_part(shells)
+        .iter()
+        .map(|elements| {
+            elements
+                .iter()
+                .map(|&iel| (iel, obj::polygon(shells.nodes(iel)[..4].iter().copied())))
+                .filter(|(_, corners)| corners.len() >= 3)
+                .collect()
+        })
+        .collect()
+}
+
+// faces of the active bricks that no other active brick shares, by part,
+// with the brick they belong to
+fn free_faces(solids: &ElementBlock) -> Vec<Vec<(usize, Vec<i32>)>> {
+    obj::unshared_faces(&elements_per_part(solids), |iel| obj::brick_faces(solids.nodes(iel)))
+}
+
+enum Interpolation {
+    Vertex,  // nodal scalar
+    Uniform, // elemental scalar of the shells or solids
+}
+
+// one time-sampled attribute: its declaration and samples
+struct Samples {
+    declaration: String,
+    temporary: String,
+    writer: BufWriter<File>,
+}
+
+impl Samples {
+    fn create(path: &str, index: usize, declaration: String) -> io::Result<Self> {
+        let temporary = format!("{}.{}.tmp", path, index);
+        let writer = BufWriter::new(File::create(&temporary)?);
+        Ok(Samples { declaration, temporary, writer })
+    }
+
+    // "<time code>: [v, v, ...]," with each value written by <value>
+    fn add<T>(&mut self, time_code: usize, values: &[T], mut value: impl FnMut(&mut String, &T)) -> io::Result<()> {
+        let mut text = String::new();
+        for (index, item) in values.iter().enumerate() {
+            if index > 0 {
+                text.push_str(", ");
+            }
+            value(&mut text, item);
+        }
+        writeln!(self.writer, "            {}: [{}],", time_code, text)
+    }
+}
+
+struct Primvar {
+    name: String,
+    interpolation: Interpolation,
+    samples: Samples,
+}
+
+pub struct UsdStage {
+    path: String,
+    names: Vec<String>,     // requested primvars, resolved on the first state
+    primvars: Vec<Primvar>,
+    points: Samples,
+    extent: Samples,
+    counts: Samples,
+    indices: Samples,
+    times: Vec<f32>,
+    topology: (Vec<i32>, Vec<i32>), // last sampled face vertex counts and indices
+}
+
+fn push_float(text: &mut String, ryu: &mut RyuBuffer, value: f32) {
+    text.push_str(if value.is_finite() { ryu.format(value) } else { "0" });
+}
+
+fn push_point(text: &mut String, ryu: &mut RyuBuffer, point: &[f32]) {
+    text.push('(');
+    for (index, &value) in point.iter().enumerate() {
+        if index > 0 {
+            text.push_str(", ");
+        }
+        push_float(text, ryu, value);
+    }
+    text.push(')');
+}
+
+// USD identifiers: letters, digits and '_', not starting with a digit
+fn identifier(text: &str) -> String {
+    let name: String = text.trim().chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+impl UsdStage {
+    pub fn create(path: &str, names: &[String]) -> io::Result<Self> {
+        File::create(path)?;
+        Ok(UsdStage {
+            path: path.to_string(),
+            names: names.to_vec(),
+            primvars: Vec::new(),
+            points: Samples::create(path, 0, "point3f[] points".to_string())?,
+            extent: Samples::create(path, 1, "float3[] extent".to_string())?,
+            counts: Samples::create(path, 2, "int[] faceVertexCounts".to_string())?,
+            indices: Samples::create(path, 3, "int[] faceVertexIndices".to_string())?,
+            times: Vec::new(),
+            topology: (Vec::new(), Vec::new()),
+        })
+    }
+
+    // primvars from the fields of the first state
+    fn resolve_primvars(&mut self, anim: &AnimFile) -> io::Result<()> {
+        for (index, name) in std::mem::take(&mut self.names).iter().enumerate() {
+            let find = |texts: &[String]| texts.iter().find(|text| field_name_matches(text, name)).cloned();
+            let elemental = || find(&anim.elts_2d.efunc_text).or_else(|| find(&anim.elts_3d.efunc_text));
+            let (text, interpolation) = match (find(&anim.func_text), elemental()) {
+                (Some(text), _) => (text, Interpolation::Vertex),
+                (None, Some(text)) => (text, Interpolation::Uniform),
+                (None, None) => {
+                    report::warning(
+                        codes::NO_MATCH,
+                        format_args!("no nodal or elemental scalar named {} for the USD primvars", name),
+                    );
+                    continue;
+                }
+            };
+            let declaration = format!("float[] primvars:{}", identifier(&text));
+            let samples = Samples::create(&self.path, 4 + index, declaration)?;
+            self.primvars.push(Primvar { name: name.clone(), interpolation, samples });
+        }
+        Ok(())
+    }
+
+    pub fn add_state(&mut self, anim: &AnimFile) -> io::Result<()> {
+        if self.times.is_empty() {
+            self.resolve_primvars(anim)?;
+        }
+        self.times.push(anim.time);
+        let time_code = self.times.len();
+
+        // faces with the block and element they come from
+        let shells = shell_faces(&anim.elts_2d).into_iter().flatten().map(|face| (0, face));
+        let solids = free_faces(&anim.elts_3d).into_iter().flatten().map(|face| (1, face));
+        let faces: Vec<(usize, (usize, Vec<i32>))> = shells.chain(solids).collect();
+
+        let mut ryu = RyuBuffer::new();
+        self.points.add(time_code, &anim.coor.chunks_exact(3).collect::<Vec<_>>(), |text, point| {
+            push_point(text, &mut ryu, point)
+        })?;
+        let mut bounds = [[f32::MAX; 3], [f32::MIN; 3]];
+        for point in anim.coor.chunks_exact(3) {
+            for axis in 0..3 {
+                bounds[0][axis] = bounds[0][axis].min(point[axis]);
+                bounds[1][axis] = bounds[1][axis].max(point[axis]);
+            }
+        }
+        if anim.coor.is_empty() {
+            bounds = [[0.0; 3]; 2];
+        }
+        self.extent.add(time_code, &bounds, |text, point| push_point(text, &mut ryu, point))?;
+
+        let counts: Vec<i32> = faces.iter().map(|(_, (_, corners))| corners.len() as i32).collect();
+        let indices: Vec<i32> = faces.iter().flat_map(|(_, (_, corners))| corners.iter().copied()).collect();
+        if time_code == 1 || (&counts, &indices) != (&self.topology.0, &self.topology.1) {
+            self.counts.add(time_code, &counts, |text, count| text.push_str(&count.to_string()))?;
+            self.indices.add(time_code, &indices, |text, node| text.push_str(&node.to_string()))?;
+            self.topology = (counts, indices);
+        }
+
+        let blocks = [&anim.elts_2d, &anim.elts_3d];
+        for primvar in &mut self.primvars {
+            let values: Vec<f32> = match primvar.interpolation {
+                Interpolation::Vertex => match anim.func_text.iter().position(|text| field_name_matches(text, &primvar.name)) {
+                    Some(ifunc) => anim.nodal_scalar(ifunc)?.into_owned(),
+                    None => vec![0.0; anim.nb_nodes],
+                },
+                Interpolation::Uniform => {
+                    // faces of a block without the field get 0
+                    let mut fields: Vec<Option<Vec<f32>>> = Vec::new();
+                    for block in blocks {
+                        fields.push(match block.efunc_text.iter().position(|text| field_name_matches(text, &primvar.name)) {
+                            Some(iefun) => Some(anim.elemental_scalar(block, iefun)?.into_owned()),
+                            None => None,
+                        });
+                    }
+                    faces
+                        .iter()
+                        .map(|(iblock, (iel, _))| fields[*iblock].as_ref().map_or(0.0, |values| values[*iel]))
+                        .collect()
+                }
+            };
+            primvar.samples.add(time_code, &values, |text, &value| push_float(text, &mut ryu, value))?;
+        }
+        Ok(())
+    }
+
+    fn append_samples(stage: &mut impl Write, samples: Samples, metadata: &str) -> io::Result<()> {
+        let Samples { declaration, temporary, writer } = samples;
+        writer.into_inner().map_err(|e| e.into_error())?;
+        if !metadata.is_empty() {
+            writeln!(stage, "        {} (\n            {}\n        )", declaration, metadata)?;
+        }
+        writeln!(stage, "        {}.timeSamples = {{", declaration)?;
+        io::copy(&mut File::open(&temporary)?, stage)?;
+        writeln!(stage, "        }}")?;
+        fs::remove_file(&temporary)
+    }
+
+    // written to a temporary file then renamed, as the collection
+    pub fn finish(self) -> io::Result<()> {
+        let temporary = format!("{}.tmp", self.path);
+        let mut stage = BufWriter::new(File::create(&temporary)?);
+        writeln!(stage, "#usda 1.0")?;
+        writeln!(stage, "(")?;
+        writeln!(stage, "    defaultPrim = \"Model\"")?;
+        writeln!(stage, "    startTimeCode = 1")?;
+        writeln!(stage, "    endTimeCode = {}", self.times.len().max(1))?;
+        writeln!(stage, "    timeCodesPerSecond = 24")?;
+        writeln!(stage, "    upAxis = \"Z\"")?;
+        writeln!(stage, ")\n")?;
+        writeln!(stage, "def Xform \"Model\"\n{{")?;
+        writeln!(stage, "    def Mesh \"Skin\"\n    {{")?;
+        writeln!(stage, "        uniform token subdivisionScheme = \"none\"")?;
+        writeln!(stage, "        uniform bool doubleSided = 1")?;
+        Self::append_samples(&mut stage, self.counts, "")?;
+        Self::append_samples(&mut stage, self.indices, "")?;
+        Self::append_samples(&mut stage, self.points, "")?;
+        Self::append_samples(&mut stage, self.extent, "")?;
+        for primvar in self.primvars {
+            let interpolation = match primvar.interpolation {
+                Interpolation::Vertex => "vertex",
+                Interpolation::Uniform => "uniform",
+            };
+            Self::append_samples(&mut stage, primvar.samples, &format!("interpolation = \"{}\"", interpolation))?;
+        }
+        // TIME of every time code
+        writeln!(stage, "        custom double radioss:time.timeSamples = {{")?;
+        for (index, time) in self.times.iter().enumerate() {
+            writeln!(stage, "            {}: {},", index + 1, time)?;
+        }
+        writeln!(stage, "        }}")?;
+        writeln!(stage, "    }}\n}}")?;
+        stage.into_inner().map_err(|e| e.into_error())?;
+        fs::rename(&temporary, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files::{quad_parts, TempDir};
+
+    #[test]
+    fn stage_samples_points_and_eroded_topology() {
+        let dir = TempDir::new("usd");
+        let path = dir.file("run.usda");
+        let names = ["temperature".to_string(), "Stress".to_string(), "Pressure".to_string()];
+        let mut stage = UsdStage::create(&path, &names).unwrap();
+
+        let mut anim = quad_parts(&[("1 Plate", 2)]);
+        anim.func_text = vec!["Temperature".to_string()];
+        anim.func = vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        anim.elts_2d.efunc_text = vec!["Stress".to_string()];
+        anim.elts_2d.efunc = vec![10.0, 20.0];
+        stage.add_state(&anim).unwrap();
+        anim.time = 0.5;
+        anim.coor[1] = -1.0;
+        stage.add_state(&anim).unwrap();
+        anim.time = 1.0;
+        anim.elts_2d.del_elt[0] = 0;
+        stage.add_state(&anim).unwrap();
+        stage.finish().unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("#usda 1.0\n(\n    defaultPrim = \"Model\"\n    startTimeCode = 1\n    endTimeCode = 3\n"));
+        // the topology is sampled again once an element is eroded
+        assert!(text.contains(
+            "int[] faceVertexIndices.timeSamples = {\n            1: [0, 1, 4, 3, 1, 2, 5, 4],\n            3: [1, 2, 5, 4],\n        }"
+        ));
+        assert!(text.contains("2: [(0.0, -1.0, 0.0), (2.0, 1.0, 0.0)],"));
+        assert!(text.contains("float[] primvars:Temperature (\n            interpolation = \"vertex\""));
+        assert!(text.contains("float[] primvars:Stress.timeSamples = {\n            1: [10.0, 20.0],\n"));
+        assert!(text.contains("            3: [20.0],\n"));
+        assert!(!text.contains("Pressure"));
+        assert!(text.contains("radioss:time.timeSamples = {\n            1: 0,\n            2: 0.5,\n            3: 1,\n"));
+        // only the temporary files of the samples are gone
+        assert_eq!(fs::read_dir(dir.file("")).unwrap().count(), 1);
+    }
+}
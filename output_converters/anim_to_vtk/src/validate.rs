@@ -0,0 +1,172 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Consistency checks of a parsed A-file, with a downgrade-to-warning
+// policy selected by --strict / --lenient:
+//  - harmless anomalies leave the data as read (unknown flags, trailing
+//    bytes, empty part tables, no user ids, part titles without id):
+//    warnings by default and with --lenient, errors with --strict
+//  - inconsistencies need the data to be repaired (part tables not
+//    matching the element count, nodes out of range): errors by default
+//    and with --strict, repaired with a warning with --lenient
+// ****************************************
+
+use report::{codes, Diagnostic};
+
+use crate::anim::{AnimFile, ElementBlock};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum ParseMode {
+    #[default]
+    Default,
+    Strict,
+    Lenient,
+}
+
+// elements whose nodes are all valid indices
+fn valid_elements(block: &ElementBlock, nb_nodes: usize) -> Vec<usize> {
+    (0..block.count)
+        .filter(|&iel| block.nodes(iel).iter().all(|&node| node >= 0 && (node as usize) < nb_nodes))
+        .collect()
+}
+
+// cumulative part ends must grow up to the element count
+fn part_table_matches(block: &ElementBlock) -> bool {
+    block.def_part.windows(2).all(|ends| ends[0] <= ends[1])
+        && block.def_part.first().is_none_or(|&end| end >= 0)
+        && block.def_part.last().is_none_or(|&end| end as usize == block.count)
+}
+
+fn repair_part_table(block: &mut ElementBlock) {
+    let count = block.count as i32;
+    let mut previous = 0;
+    for end in block.def_part.iter_mut() {
+        *end = (*end).clamp(previous, count);
+        previous = *end;
+    }
+    if let Some(last) = block.def_part.last_mut() {
+        *last = count;
+    }
+}
+
+// part titles start with the part id, "12 Bumper"
+fn has_part_id(title: &str) -> bool {
+    let title = title.trim_start();
+    title.strip_prefix(['-', '+']).unwrap_or(title).starts_with(|c: char| c.is_ascii_digit())
+}
+
+// ****************************************
+// Check a parsed file: warnings are printed, the first anomaly that
+// is an error in this mode is returned
+// ****************************************
+pub fn validate(anim: &mut AnimFile, mode: ParseMode) -> Result<(), Diagnostic> {
+    let mut harmless = Vec::new(); // (code, message)
+    if !anim.unknown_flags.is_empty() {
+        let message = format!(
+            "flag_a{:?} unknown to the {} layout, skipped {} bytes after the known blocks",
+            anim.unknown_flags, anim.layout_name, anim.trailing_bytes
+        );
+        harmless.push((codes::UNKNOWN_FLAGS, message));
+    } else if anim.trailing_bytes > 0 {
+        let message = format!("{} trailing bytes after the last section, ignored", anim.trailing_bytes);
+        harmless.push((codes::TRAILING_BYTES, message));
+    }
+    let blocks = [&anim.elts_1d, &anim.elts_2d, &anim.elts_3d, &anim.elts_sph];
+    if anim.nod_num.len() < anim.nb_nodes || blocks.iter().any(|block| block.el_num.len() < block.count) {
+        let message = "no node and element user ids (flag_a[1] = 0), NODE_ID and ELEMENT_ID not written";
+        harmless.push((codes::MISSING_ID_ARRAY, message.to_string()));
+    }
+
+    let nb_nodes = anim.nb_nodes;
+    let mut inconsistent = Vec::new(); // (code, anomaly, repair done with --lenient)
+    let blocks = [
+        ("1D", &mut anim.elts_1d),
+        ("2D", &mut anim.elts_2d),
+        ("3D", &mut anim.elts_3d),
+        ("SPH", &mut anim.elts_sph),
+    ];
+    for (label, block) in blocks {
+        if block.count == 0 {
+            continue;
+        }
+        let untitled: Vec<&String> = block.part_text.iter().filter(|title| !has_part_id(title)).collect();
+        if let Some(first) = untitled.first() {
+            let message = format!(
+                "{} of {} {} part titles without a leading id, like \"{}\", PART_ID 0",
+                untitled.len(),
+                block.part_text.len(),
+                label,
+                first.trim()
+            );
+            harmless.push((codes::PART_NAME_UNPARSABLE, message));
+        }
+        if block.def_part.is_empty() {
+            harmless.push((codes::MISSING_PART_TABLE, format!("{} elements without part table, PART_ID 0", label)));
+        } else if !part_table_matches(block) {
+            let message = format!(
+                "{} part table ends at element {} of {}",
+                label,
+                block.def_part.last().copied().unwrap_or(0),
+                block.count
+            );
+            inconsistent.push((codes::PART_TABLE_MISMATCH, message, "part ends clamped to the element count"));
+            if mode == ParseMode::Lenient {
+                repair_part_table(block);
+            }
+        }
+        let valid = valid_elements(block, nb_nodes);
+        if valid.len() < block.count {
+            let message = format!(
+                "{} of {} {} elements refer to nodes out of range",
+                block.count - valid.len(),
+                block.count,
+                label
+            );
+            inconsistent.push((codes::NODES_OUT_OF_RANGE, message, "elements dropped"));
+            if mode == ParseMode::Lenient {
+                *block = block.gather(&valid);
+            }
+        }
+    }
+
+    let inconsistent = inconsistent.into_iter();
+    let (warnings, errors): (Vec<_>, Vec<_>) = match mode {
+        ParseMode::Strict => {
+            (Vec::new(), harmless.into_iter().chain(inconsistent.map(|(code, message, _)| (code, message))).collect())
+        }
+        ParseMode::Default => (harmless, inconsistent.map(|(code, message, _)| (code, message)).collect()),
+        ParseMode::Lenient => (
+            harmless
+                .into_iter()
+                .chain(inconsistent.map(|(code, message, repair)| (code, format!("{}, {}", message, repair))))
+                .collect(),
+            Vec::new(),
+        ),
+    };
+    for (code, warning) in &warnings {
+        report::warning(code, warning);
+    }
+    match errors.into_iter().next() {
+        Some((_, error)) => Err(Diagnostic::new(codes::INCONSISTENT_DATA, error)),
+        None => Ok(()),
+    }
+}
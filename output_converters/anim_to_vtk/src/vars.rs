@@ -0,0 +1,100 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Selection of the result variables written (--vars): a comma separated
+// list of names or regular expressions, matching the whole name and
+// ignoring case, against the A-file title of a field ("VON MISES"), the
+// same with '_' for the blanks ("Von_Mises") or the array name
+// ("2DELEM_Von_Mises"). Nodal scalars and vectors, elemental scalars and
+// tensors are selected; ids, parts, materials and status arrays are
+// always written.
+// ****************************************
+
+use regex_lite::{Regex, RegexBuilder};
+
+use crate::anim::AnimFile;
+use crate::mesh_writer::TORSEUR_SUFFIXES;
+use crate::vtk::replace_underscore;
+
+#[derive(Clone)]
+pub struct VarSelection {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl VarSelection {
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let mut patterns = Vec::new();
+        for item in list.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let regex = RegexBuilder::new(&format!("^(?:{})$", item))
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| format!("invalid variable pattern '{}': {}", item, e))?;
+            patterns.push((item.to_string(), regex));
+        }
+        if patterns.is_empty() {
+            return Err("no variable pattern".to_string());
+        }
+        Ok(VarSelection { patterns })
+    }
+
+    // adds the patterns of another list, the option can be repeated
+    pub fn extend(&mut self, other: VarSelection) {
+        self.patterns.extend(other.patterns);
+    }
+
+    fn pattern_matches(regex: &Regex, text: &str, array_name: &str) -> bool {
+        let text = text.trim();
+        regex.is_match(text) || regex.is_match(&replace_underscore(text)) || regex.is_match(array_name)
+    }
+
+    pub fn matches(&self, text: &str, array_name: &str) -> bool {
+        self.patterns.iter().any(|(_, regex)| Self::pattern_matches(regex, text, array_name))
+    }
+
+    // patterns matching no field of the state
+    pub fn unmatched(&self, anim: &AnimFile) -> Vec<&str> {
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        for text in anim.func_text.iter().chain(&anim.vect_text) {
+            fields.push((text, replace_underscore(text)));
+        }
+        let blocks = [("1DELEM", &anim.elts_1d), ("2DELEM", &anim.elts_2d), ("3DELEM", &anim.elts_3d), ("SPHELEM", &anim.elts_sph)];
+        for (prefix, block) in blocks {
+            for text in &block.efunc_text {
+                fields.push((text, format!("{}_{}", prefix, replace_underscore(text))));
+            }
+            for text in &block.tens_text {
+                let name = format!("{}_{}", prefix, replace_underscore(text));
+                if prefix == "1DELEM" {
+                    // beam torseurs are written component by component
+                    fields.extend(TORSEUR_SUFFIXES.iter().map(|suffix| (text.as_str(), format!("{}{}", name, suffix))));
+                } else {
+                    fields.push((text, name));
+                }
+            }
+        }
+        self.patterns
+            .iter()
+            .filter(|(_, regex)| !fields.iter().any(|(text, name)| Self::pattern_matches(regex, text, name)))
+            .map(|(item, _)| item.as_str())
+            .collect()
+    }
+}
@@ -0,0 +1,894 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Legacy VTK writer
+// ****************************************
+
+use std::fs;
+use std::io::{self, BufWriter, Write};
+
+use crc32fast::Hasher;
+use libc::{c_char, snprintf};
+
+use itoa::Buffer as ItoaBuffer;
+use ryu::Buffer as RyuBuffer;
+
+use report::codes;
+
+use crate::anim::AnimFile;
+use crate::lut::Lut;
+use crate::mesh_writer::{cell_count, write_mesh, CellValues, FieldKind, MeshHeader, MeshWriter, PointValues};
+use crate::part_colors::PartColors;
+use crate::vars::VarSelection;
+
+// ****************************************
+// replace ' ' with '_'
+// ****************************************
+pub fn replace_underscore(s: &str) -> String {
+    s.replace(' ', "_")
+}
+
+// ****************************************
+// Checksummed - counts the bytes written through it and, when
+// enabled, keeps the CRC32 of the file and of the current array
+// ****************************************
+struct Checksummed<W: Write> {
+    inner: W,
+    position: u64,
+    file: Option<Hasher>,
+    array: Option<(u64, Hasher)>, // start offset and CRC32 of the current array
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.position += n as u64;
+        if let Some(hasher) = self.file.as_mut() {
+            hasher.update(&buf[..n]);
+        }
+        if let Some((_, hasher)) = self.array.as_mut() {
+            hasher.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// (name, offset, size, CRC32) of an array as written
+pub type ArrayChecksum = (String, u64, u64, u32);
+
+// ****************************************
+// VtkWriter - abstraction for VTK output in binary or ASCII format
+// ****************************************
+pub struct VtkWriter<W: Write> {
+    writer: Checksummed<BufWriter<W>>,
+    binary: bool,
+    legacy: bool,
+    scratch: Vec<u8>,
+    itoa_buf: ItoaBuffer,
+    ryu_buf: RyuBuffer,
+}
+
+impl<W: Write> VtkWriter<W> {
+    pub fn new(writer: W, binary: bool, legacy: bool) -> Self {
+        VtkWriter {
+            writer: Checksummed { inner: BufWriter::new(writer), position: 0, file: None, array: None },
+            binary,
+            legacy,
+            scratch: Vec::with_capacity(256),
+            itoa_buf: ItoaBuffer::new(),
+            ryu_buf: RyuBuffer::new(),
+        }
+    }
+
+    fn write_legacy_float_ascii(&mut self, val: f64) -> io::Result<()> {
+        let mut buf = [0u8; 64];
+        let fmt = b"%.6g\0";
+        let written = unsafe {
+            snprintf(
+                buf.as_mut_ptr() as *mut c_char,
+                buf.len(),
+                fmt.as_ptr() as *const c_char,
+                val,
+            )
+        };
+        let len = if written < 0 { 0 } else { written as usize };
+        self.writer.write_all(&buf[..len])?;
+        Ok(())
+    }
+
+    fn write_i32(&mut self, val: i32) -> io::Result<()> {
+        if self.binary {
+            self.writer.write_all(&val.to_be_bytes())?;
+        } else {
+            self.scratch.clear();
+            let s = self.itoa_buf.format(val);
+            self.scratch.extend_from_slice(s.as_bytes());
+            self.scratch.push(b'\n');
+            self.writer.write_all(&self.scratch)?;
+        }
+        Ok(())
+    }
+
+    fn write_f32(&mut self, val: f32) -> io::Result<()> {
+        if self.binary {
+            self.writer.write_all(&val.to_be_bytes())?;
+        } else if self.legacy {
+            self.write_legacy_float_ascii(val as f64)?;
+            self.writer.write_all(b"\n")?;
+        } else {
+            self.scratch.clear();
+            let s = self.ryu_buf.format(val);
+            self.scratch.extend_from_slice(s.as_bytes());
+            self.scratch.push(b'\n');
+            self.writer.write_all(&self.scratch)?;
+        }
+        Ok(())
+    }
+
+    // Bulk write f32 values from a slice - more efficient than individual writes
+    fn write_f32_slice(&mut self, values: &[f32]) -> io::Result<()> {
+        if self.binary {
+            for &val in values {
+                self.writer.write_all(&val.to_be_bytes())?;
+            }
+        } else if self.legacy {
+            for &val in values {
+                self.write_legacy_float_ascii(val as f64)?;
+                self.writer.write_all(b"\n")?;
+            }
+        } else {
+            for &val in values {
+                self.scratch.clear();
+                let s = self.ryu_buf.format(val);
+                self.scratch.extend_from_slice(s.as_bytes());
+                self.scratch.push(b'\n');
+                self.writer.write_all(&self.scratch)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_f64(&mut self, val: f64) -> io::Result<()> {
+        if self.binary {
+            self.writer.write_all(&val.to_be_bytes())?;
+        } else if self.legacy {
+            self.write_legacy_float_ascii(val)?;
+            self.writer.write_all(b"\n")?;
+        } else {
+            self.scratch.clear();
+            let s = self.ryu_buf.format(val);
+            self.scratch.extend_from_slice(s.as_bytes());
+            self.scratch.push(b'\n');
+            self.writer.write_all(&self.scratch)?;
+        }
+        Ok(())
+    }
+
+    fn write_f32_triple(&mut self, a: f32, b: f32, c: f32) -> io::Result<()> {
+        if self.binary {
+            self.writer.write_all(&a.to_be_bytes())?;
+            self.writer.write_all(&b.to_be_bytes())?;
+            self.writer.write_all(&c.to_be_bytes())?;
+        } else if self.legacy {
+            self.write_legacy_float_ascii(a as f64)?;
+            self.writer.write_all(b" ")?;
+            self.write_legacy_float_ascii(b as f64)?;
+            self.writer.write_all(b" ")?;
+            self.write_legacy_float_ascii(c as f64)?;
+            self.writer.write_all(b"\n")?;
+        } else {
+            self.scratch.clear();
+            let sa = self.ryu_buf.format(a);
+            self.scratch.extend_from_slice(sa.as_bytes());
+            self.scratch.push(b' ');
+            let sb = self.ryu_buf.format(b);
+            self.scratch.extend_from_slice(sb.as_bytes());
+            self.scratch.push(b' ');
+            let sc = self.ryu_buf.format(c);
+            self.scratch.extend_from_slice(sc.as_bytes());
+            self.scratch.push(b'\n');
+            self.writer.write_all(&self.scratch)?;
+        }
+        Ok(())
+    }
+
+    fn write_zeros_f32(&mut self, count: usize) -> io::Result<()> {
+        if self.binary {
+            let zero_bytes = 0f32.to_be_bytes();
+            for _ in 0..count {
+                self.writer.write_all(&zero_bytes)?;
+            }
+        } else {
+            for _ in 0..count {
+                self.writer.write_all(b"0\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_zero_tensor(&mut self) -> io::Result<()> {
+        if self.binary {
+            self.write_zeros_f32(9)?;
+        } else if self.legacy {
+            for _ in 0..3 {
+                self.write_legacy_float_ascii(0.0)?;
+                self.writer.write_all(b" ")?;
+                self.write_legacy_float_ascii(0.0)?;
+                self.writer.write_all(b" ")?;
+                self.write_legacy_float_ascii(0.0)?;
+                self.writer.write_all(b"\n")?;
+            }
+        } else {
+            for _ in 0..3 {
+                self.writer.write_all(b"0 0 0\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_header(&mut self, text: &str) -> io::Result<()> {
+        self.writer.write_all(text.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn newline(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    // the values written up to end_array make one checksummed array
+    fn begin_array(&mut self) {
+        if self.writer.file.is_some() {
+            self.writer.array = Some((self.writer.position, Hasher::new()));
+        }
+    }
+
+    fn end_array(&mut self, name: &str) -> Option<ArrayChecksum> {
+        let (start, hasher) = self.writer.array.take()?;
+        Some((name.to_string(), start, self.writer.position - start, hasher.finalize()))
+    }
+
+    // FieldData string array: one %-encoded string per line in ASCII,
+    // length-prefixed strings in binary, as the VTK legacy reader expects
+    fn write_string_array(&mut self, name: &str, values: &[String]) -> io::Result<()> {
+        self.write_header(&format!("{} 1 {} string", name, values.len()))?;
+        for value in values {
+            let bytes = value.as_bytes();
+            if self.binary {
+                let len = bytes.len();
+                if len < 1 << 6 {
+                    self.writer.write_all(&[0xC0 | len as u8])?;
+                } else if len < 1 << 14 {
+                    self.writer.write_all(&(0x8000 | len as u16).to_be_bytes())?;
+                } else {
+                    self.writer.write_all(&(0x4000_0000 | len as u32).to_be_bytes())?;
+                }
+                self.writer.write_all(bytes)?;
+            } else {
+                self.scratch.clear();
+                for &b in bytes {
+                    if b <= b' ' || b >= 0x7f || b == b'%' {
+                        self.scratch.extend_from_slice(format!("%{:02X}", b).as_bytes());
+                    } else {
+                        self.scratch.push(b);
+                    }
+                }
+                self.scratch.push(b'\n');
+                self.writer.write_all(&self.scratch)?;
+            }
+        }
+        if self.binary {
+            self.newline()?;
+        }
+        Ok(())
+    }
+
+    // color scalars: unsigned chars in binary, floats in [0, 1] in ASCII
+    fn write_rgb(&mut self, rgb: &[u8]) -> io::Result<()> {
+        if self.binary {
+            self.writer.write_all(rgb)?;
+        } else {
+            for color in rgb.chunks_exact(3) {
+                let [r, g, b] = [color[0], color[1], color[2]].map(|c| c as f32 / 255.0);
+                self.write_f32_triple(r, g, b)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_i32_line(&mut self, values: &[i32]) -> io::Result<()> {
+        if self.binary {
+            for &v in values {
+                self.writer.write_all(&v.to_be_bytes())?;
+            }
+        } else {
+            self.scratch.clear();
+            for (i, &v) in values.iter().enumerate() {
+                if i > 0 {
+                    self.scratch.push(b' ');
+                }
+                let s = self.itoa_buf.format(v);
+                self.scratch.extend_from_slice(s.as_bytes());
+            }
+            self.scratch.push(b'\n');
+            self.writer.write_all(&self.scratch)?;
+        }
+        Ok(())
+    }
+}
+
+// ****************************************
+// WriteOptions - output settings for write_vtk
+// ****************************************
+#[derive(Clone, Default)]
+pub struct WriteOptions {
+    pub binary: bool,          // BINARY instead of ASCII legacy VTK
+    pub legacy: bool,          // C++-compatible ASCII float formatting
+    pub fix_orientation: bool, // reorder inverted solids, write INVERTED flags
+    pub luts: Vec<Lut>,        // named lookup tables of scalar arrays
+    pub ranges: bool,          // min and max of the arrays in FieldData (RANGE_<name>)
+    pub checksums: bool,       // CRC32 of every array in the <output>.crc32 sidecar
+    pub vars: Option<VarSelection>, // result variables written, all without
+    pub part_colors: Option<PartColors>, // PART_COLOR array of the part colors
+    pub split_sph: bool,       // SPH particles left out, written to their own polydata file
+    pub mode_phase: Option<f64>, // phase of a mode shape step, in FieldData (MODE_PHASE)
+}
+
+impl WriteOptions {
+    // whether the field of A-file title `text`, written as `array_name`, is selected
+    pub fn selects(&self, text: &str, array_name: &str) -> bool {
+        self.vars.as_ref().is_none_or(|vars| vars.matches(text, array_name))
+    }
+}
+
+// ****************************************
+// LegacyVtkWriter - MeshWriter for the legacy VTK unstructured grid.
+// CELL_TYPES follows the connectivity, so the cell types are kept
+// until the first field closes the cell section.
+// The writer goes through the sections in order only, and checks that
+// each one holds the number of values its header line announced: a
+// call out of order or a miscounted section would give a file that
+// reads wrong, silently in BINARY, so it fails with an InvalidData
+// error instead.
+// ****************************************
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Section {
+    Start,
+    Points,
+    Cells,
+    PointData,
+    CellData,
+    Finished,
+}
+
+// error of a call out of order or of a miscounted section
+fn inconsistent(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("legacy VTK writer: {}", message))
+}
+
+pub struct LegacyVtkWriter<W: Write> {
+    vtk: VtkWriter<W>,
+    nb_points: usize,
+    nb_cells: usize,
+    connectivity_size: usize,
+    points_written: usize,
+    connectivity_written: usize,
+    cell_types: Vec<u8>, // VTK cell types, all below 256
+    section: Section,
+    luts: Vec<Lut>,
+    lut_used: Vec<bool>,
+    checksum_file: bool,
+    checksums: Vec<ArrayChecksum>,
+}
+
+impl<W: Write> LegacyVtkWriter<W> {
+    pub fn new(writer: W, options: &WriteOptions) -> Self {
+        LegacyVtkWriter {
+            vtk: VtkWriter::new(writer, options.binary, options.legacy),
+            nb_points: 0,
+            nb_cells: 0,
+            connectivity_size: 0,
+            points_written: 0,
+            connectivity_written: 0,
+            cell_types: Vec::new(),
+            section: Section::Start,
+            luts: options.luts.clone(),
+            lut_used: vec![false; options.luts.len()],
+            checksum_file: options.checksums,
+            checksums: Vec::new(),
+        }
+    }
+
+    // checksums of the arrays written, the whole file first as FILE,
+    // empty unless asked for; complete once finished
+    pub fn checksums(&self) -> &[ArrayChecksum] {
+        &self.checksums
+    }
+
+    fn end_array(&mut self, name: &str) {
+        if let Some(checksum) = self.vtk.end_array(name) {
+            self.checksums.push(checksum);
+        }
+    }
+
+    // LOOKUP_TABLE line of a scalar array, its lut if one matches
+    fn lookup_table(&mut self, name: &str) -> io::Result<Option<usize>> {
+        let ilut = self.luts.iter().position(|lut| lut.matches(name));
+        match ilut {
+            Some(ilut) => {
+                self.lut_used[ilut] = true;
+                self.vtk.write_header(&format!("LOOKUP_TABLE {}_LUT", name))?;
+            }
+            None => self.vtk.write_header("LOOKUP_TABLE default")?,
+        }
+        Ok(ilut)
+    }
+
+    // RGBA entries as floats in ASCII, unsigned chars in binary
+    fn write_lut(&mut self, name: &str, ilut: usize) -> io::Result<()> {
+        let entries = self.luts[ilut].entries();
+        self.vtk.write_header(&format!("LOOKUP_TABLE {}_LUT {}", name, entries.len()))?;
+        for rgba in entries {
+            if self.vtk.binary {
+                let bytes = rgba.map(|c| (c * 255.0).round() as u8);
+                self.vtk.writer.write_all(&bytes)?;
+            } else {
+                let line: Vec<String> = rgba.iter().map(|c| format!("{:.4}", c)).collect();
+                self.vtk.write_header(&line.join(" "))?;
+            }
+        }
+        if self.vtk.binary {
+            self.vtk.newline()?;
+        }
+        Ok(())
+    }
+
+    fn enter(&mut self, section: Section) -> io::Result<()> {
+        if self.section == Section::Start || section < self.section {
+            return Err(inconsistent(format!("{:?} section written in the {:?} section", section, self.section)));
+        }
+        if self.section < Section::Cells && section >= Section::Cells {
+            if self.points_written != self.nb_points {
+                return Err(inconsistent(format!("{} points written for POINTS {}", self.points_written, self.nb_points)));
+            }
+            self.end_array("POINTS");
+            self.vtk.newline()?;
+            // also for an empty state: "CELLS 0 0" and "CELL_TYPES 0" make an empty grid
+            self.vtk.write_header(&format!("CELLS {} {}", self.nb_cells, self.connectivity_size))?;
+            self.vtk.begin_array();
+        }
+        if self.section < Section::PointData && section >= Section::PointData {
+            if self.cell_types.len() != self.nb_cells {
+                return Err(inconsistent(format!("{} cells written for CELLS {}", self.cell_types.len(), self.nb_cells)));
+            }
+            if self.connectivity_written != self.connectivity_size {
+                return Err(inconsistent(format!(
+                    "connectivity size {} written for CELLS {} {}",
+                    self.connectivity_written, self.nb_cells, self.connectivity_size
+                )));
+            }
+            self.end_array("CELLS");
+            self.vtk.newline()?;
+            self.vtk.write_header(&format!("CELL_TYPES {}", self.nb_cells))?;
+            self.vtk.begin_array();
+            for icell in 0..self.cell_types.len() {
+                self.vtk.write_i32(self.cell_types[icell] as i32)?;
+            }
+            self.end_array("CELL_TYPES");
+            self.vtk.newline()?;
+            // as VTK's own writer, no attribute section on an empty set
+            if self.nb_points > 0 {
+                self.vtk.write_header(&format!("POINT_DATA {}", self.nb_points))?;
+            }
+        }
+        if self.section < Section::CellData && section >= Section::CellData && self.nb_cells > 0 {
+            self.vtk.write_header(&format!("CELL_DATA {}", self.nb_cells))?;
+        }
+        self.section = self.section.max(section);
+        Ok(())
+    }
+
+    fn write_tensor_rows(&mut self, rows: [[f32; 3]; 3]) -> io::Result<()> {
+        for row in rows {
+            self.vtk.write_f32_triple(row[0], row[1], row[2])?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> MeshWriter for LegacyVtkWriter<W> {
+    fn begin_geometry(&mut self, header: &MeshHeader) -> io::Result<()> {
+        if self.section != Section::Start {
+            return Err(inconsistent(format!("header written in the {:?} section", self.section)));
+        }
+        let vtk = &mut self.vtk;
+        if self.checksum_file {
+            vtk.writer.file = Some(Hasher::new());
+        }
+        vtk.write_header("# vtk DataFile Version 3.0")?;
+        vtk.write_header("vtk output")?;
+        if vtk.binary {
+            vtk.write_header("BINARY")?;
+        } else {
+            vtk.write_header("ASCII")?;
+        }
+        vtk.write_header("DATASET UNSTRUCTURED_GRID")?;
+
+        let nb_arrays = 2 + header.string_tables.len() + self.luts.len() + header.ranges.len() + header.mode_phase.is_some() as usize;
+        vtk.write_header(&format!("FIELD FieldData {}", nb_arrays))?;
+        vtk.write_header("TIME 1 1 double")?;
+        vtk.write_f64(header.time)?;
+        if vtk.binary {
+            vtk.newline()?;
+        }
+        vtk.write_header("CYCLE 1 1 int")?;
+        vtk.write_i32(0)?;
+        if vtk.binary {
+            vtk.newline()?;
+        }
+        for (name, values) in &header.string_tables {
+            vtk.write_string_array(name, values)?;
+        }
+        for lut in &self.luts {
+            vtk.write_header(&format!("LUT_RANGE_{} 2 1 float", replace_underscore(&lut.field)))?;
+            vtk.write_f32(lut.min)?;
+            vtk.write_f32(lut.max)?;
+            if vtk.binary {
+                vtk.newline()?;
+            }
+        }
+        for (name, min, max) in &header.ranges {
+            vtk.write_header(&format!("RANGE_{} 2 1 double", name))?;
+            vtk.write_f64(*min)?;
+            vtk.write_f64(*max)?;
+            if vtk.binary {
+                vtk.newline()?;
+            }
+        }
+        if let Some(phase) = header.mode_phase {
+            vtk.write_header("MODE_PHASE 1 1 double")?;
+            vtk.write_f64(phase)?;
+            if vtk.binary {
+                vtk.newline()?;
+            }
+        }
+
+        vtk.write_header(&format!("POINTS {} float", header.nb_points))?;
+        vtk.begin_array();
+        self.nb_points = header.nb_points;
+        self.nb_cells = header.nb_cells;
+        self.connectivity_size = header.nb_cells + header.connectivity_size;
+        self.cell_types = Vec::with_capacity(header.nb_cells);
+        self.section = Section::Points;
+        Ok(())
+    }
+
+    fn write_points_chunk(&mut self, coor: &[f32]) -> io::Result<()> {
+        self.enter(Section::Points)?;
+        if !coor.len().is_multiple_of(3) {
+            return Err(inconsistent(format!("point chunk of {} coordinates", coor.len())));
+        }
+        self.points_written += coor.len() / 3;
+        for point in coor.chunks_exact(3) {
+            self.vtk.write_f32_triple(point[0], point[1], point[2])?;
+        }
+        Ok(())
+    }
+
+    fn write_cells_chunk(&mut self, cells: &mut dyn Iterator<Item = (i32, &[i32])>) -> io::Result<()> {
+        self.enter(Section::Cells)?;
+        let mut vals: Vec<i32> = Vec::with_capacity(9);
+        for (cell_type, nodes) in cells {
+            self.connectivity_written += 1 + nodes.len();
+            if self.vtk.binary {
+                self.vtk.write_i32(nodes.len() as i32)?;
+                for &node in nodes {
+                    self.vtk.write_i32(node)?;
+                }
+            } else {
+                vals.clear();
+                vals.push(nodes.len() as i32);
+                vals.extend_from_slice(nodes);
+                self.vtk.write_i32_line(&vals)?;
+            }
+            self.cell_types.push(cell_type as u8);
+        }
+        Ok(())
+    }
+
+    fn write_point_field(&mut self, name: &str, kind: FieldKind, values: PointValues) -> io::Result<()> {
+        self.enter(Section::PointData)?;
+        let components = if kind == FieldKind::Vector { 3 } else { 1 };
+        let count = match values {
+            PointValues::Int(values) => values.len(),
+            PointValues::Float(values) => values.len(),
+        };
+        if count != components * self.nb_points {
+            return Err(inconsistent(format!("{} values of point array {} for {} points", count, name, self.nb_points)));
+        }
+        if self.nb_points == 0 {
+            return Ok(());
+        }
+        let mut ilut = None;
+        match (kind, values) {
+            (FieldKind::Vector, PointValues::Float(values)) => {
+                self.vtk.write_header(&format!("VECTORS {} float", name))?;
+                self.vtk.begin_array();
+                for vect in values.chunks_exact(3) {
+                    self.vtk.write_f32_triple(vect[0], vect[1], vect[2])?;
+                }
+            }
+            (_, PointValues::Float(values)) => {
+                self.vtk.write_header(&format!("SCALARS {} float 1", name))?;
+                ilut = self.lookup_table(name)?;
+                self.vtk.begin_array();
+                self.vtk.write_f32_slice(values)?;
+            }
+            (_, PointValues::Int(values)) => {
+                self.vtk.write_header(&format!("SCALARS {} int 1", name))?;
+                ilut = self.lookup_table(name)?;
+                self.vtk.begin_array();
+                for &val in values {
+                    self.vtk.write_i32(val)?;
+                }
+            }
+        }
+        self.end_array(&format!("POINT_DATA/{}", name));
+        if let Some(ilut) = ilut {
+            self.write_lut(name, ilut)?;
+        }
+        self.vtk.newline()?;
+        Ok(())
+    }
+
+    fn write_cell_field(&mut self, name: &str, kind: FieldKind, blocks: &[CellValues]) -> io::Result<()> {
+        self.enter(Section::CellData)?;
+        let count: usize = blocks.iter().map(cell_count).sum();
+        if count != self.nb_cells {
+            return Err(inconsistent(format!("{} cells of cell array {} for {} cells", count, name, self.nb_cells)));
+        }
+        if self.nb_cells == 0 {
+            return Ok(());
+        }
+        let mut ilut = None;
+        if kind == FieldKind::Tensor {
+            self.vtk.write_header(&format!("TENSORS {} float", name))?;
+        } else if kind == FieldKind::Color {
+            self.vtk.write_header(&format!("COLOR_SCALARS {} 3", name))?;
+        } else {
+            let is_int = blocks.iter().any(|values| matches!(values, CellValues::Int(_)));
+            self.vtk.write_header(&format!("SCALARS {} {} 1", name, if is_int { "int" } else { "float" }))?;
+            ilut = self.lookup_table(name)?;
+        }
+        self.vtk.begin_array();
+        for values in blocks {
+            match *values {
+                CellValues::Int(values) => {
+                    for &val in values {
+                        self.vtk.write_i32(val)?;
+                    }
+                }
+                CellValues::Float(values) => self.vtk.write_f32_slice(values)?,
+                CellValues::Strided { values, stride, offset } => {
+                    for iel in 0..values.len() / stride {
+                        self.vtk.write_f32(values[iel * stride + offset])?;
+                    }
+                }
+                CellValues::Tensor6(values) => {
+                    for t in values.chunks_exact(6) {
+                        // [xx, yy, zz, xy, xz, yz]
+                        self.write_tensor_rows([[t[0], t[3], t[4]], [t[3], t[1], t[5]], [t[4], t[5], t[2]]])?;
+                    }
+                }
+                CellValues::Tensor3(values) => {
+                    for t in values.chunks_exact(3) {
+                        // [xx, yy, xy]
+                        self.write_tensor_rows([[t[0], t[2], 0.0], [t[2], t[1], 0.0], [0.0, 0.0, 0.0]])?;
+                    }
+                }
+                CellValues::Rgb(values) => self.vtk.write_rgb(values)?,
+                CellValues::Zeros(count) => {
+                    if kind == FieldKind::Tensor {
+                        for _ in 0..count {
+                            self.vtk.write_zero_tensor()?;
+                        }
+                    } else if kind == FieldKind::Color {
+                        self.vtk.write_rgb(&vec![0; 3 * count])?;
+                    } else {
+                        self.vtk.write_zeros_f32(count)?;
+                    }
+                }
+            }
+        }
+        self.end_array(&format!("CELL_DATA/{}", name));
+        if let Some(ilut) = ilut {
+            self.write_lut(name, ilut)?;
+        }
+        self.vtk.newline()?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.enter(Section::CellData)?;
+        self.section = Section::Finished;
+        self.vtk.flush()?;
+        if let Some(hasher) = self.vtk.writer.file.take() {
+            self.checksums.insert(0, ("FILE".to_string(), 0, self.vtk.writer.position, hasher.finalize()));
+        }
+        for (lut, used) in self.luts.iter().zip(&self.lut_used) {
+            if !used {
+                report::warning(codes::NO_MATCH, format_args!("no scalar array matches --lut {}", lut.field));
+            }
+        }
+        Ok(())
+    }
+}
+
+// ****************************************
+// <output>.crc32 sidecar: one line per array, CRC32 (hex), byte
+// offset and size in the output, and name
+// ****************************************
+pub fn write_checksums(path: &str, checksums: &[ArrayChecksum]) -> io::Result<()> {
+    let mut text = String::from("# crc32  offset  size  array\n");
+    for (name, offset, size, crc) in checksums {
+        text.push_str(&format!("{:08x}  {}  {}  {}\n", crc, offset, size, name));
+    }
+    fs::write(path, text)
+}
+
+// ****************************************
+// write an A-File content in vtk format (ASCII or BINARY)
+// ****************************************
+pub fn write_vtk<W: Write>(anim: &AnimFile, options: &WriteOptions, writer: W) -> io::Result<()> {
+    write_mesh(anim, options, &mut LegacyVtkWriter::new(writer, options))
+}
+
+// same, to a new file removed again if the write fails
+pub fn write_vtk_file(anim: &AnimFile, options: &WriteOptions, path: &str) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| format!("can't create output file {}: {}", path, e))?;
+    write_vtk(anim, options, file).map_err(|e| {
+        let _ = fs::remove_file(path);
+        format!("can't write {}: {}", path, e)
+    })
+}
+
+// outputs of a conversion that failed after writing them
+pub fn remove_files(paths: &[String]) {
+    for path in paths {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> MeshHeader<'static> {
+        MeshHeader { time: 0.5, nb_points: 3, nb_cells: 1, connectivity_size: 3, string_tables: Vec::new(), ranges: Vec::new(), mode_phase: None, part_texts: [&[]; 4] }
+    }
+
+    fn triangle<W: Write>(writer: &mut LegacyVtkWriter<W>) {
+        writer.begin_geometry(&header()).unwrap();
+        writer.write_points_chunk(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]).unwrap();
+        writer.write_cells_chunk(&mut std::iter::once((crate::cells::VTK_TRIANGLE, &[0, 1, 2][..]))).unwrap();
+    }
+
+    #[test]
+    fn sections_in_order_make_a_complete_file() {
+        let mut output = Vec::new();
+        let mut writer = LegacyVtkWriter::new(&mut output, &WriteOptions::default());
+        triangle(&mut writer);
+        writer.write_point_field("NODE_ID", FieldKind::Scalar, PointValues::Int(&[1, 2, 3])).unwrap();
+        writer.write_cell_field("ELEMENT_ID", FieldKind::Scalar, &[CellValues::Int(&[7])]).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains("CELLS 1 4\n3 0 1 2\n\nCELL_TYPES 1\n5\n\nPOINT_DATA 3\n"));
+        assert!(text.ends_with("CELL_DATA 1\nSCALARS ELEMENT_ID int 1\nLOOKUP_TABLE default\n7\n\n"));
+    }
+
+    #[test]
+    fn empty_state_gives_an_empty_grid() {
+        let mut output = Vec::new();
+        let mut writer = LegacyVtkWriter::new(&mut output, &WriteOptions::default());
+        writer.begin_geometry(&MeshHeader { nb_cells: 0, connectivity_size: 0, ..header() }).unwrap();
+        writer.write_points_chunk(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]).unwrap();
+        writer.write_cells_chunk(&mut std::iter::empty()).unwrap();
+        writer.write_point_field("NODE_ID", FieldKind::Scalar, PointValues::Int(&[1, 2, 3])).unwrap();
+        writer.write_cell_field("ELEMENT_ID", FieldKind::Scalar, &[CellValues::Int(&[])]).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains("\nCELLS 0 0\n\nCELL_TYPES 0\n\nPOINT_DATA 3\n"));
+        assert!(!text.contains("CELL_DATA"));
+    }
+
+    #[test]
+    fn full_output_gives_an_error() {
+        let mut output = [0u8; 64];
+        let mut writer = LegacyVtkWriter::new(&mut output[..], &WriteOptions::default());
+        writer.begin_geometry(&header()).unwrap();
+        writer.write_points_chunk(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]).unwrap();
+        writer.write_cells_chunk(&mut std::iter::once((crate::cells::VTK_TRIANGLE, &[0, 1, 2][..]))).unwrap();
+        let error = writer.finish().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    }
+
+    fn assert_inconsistent(error: io::Error, message: &str) {
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), format!("legacy VTK writer: {}", message));
+    }
+
+    #[test]
+    fn point_field_after_cell_field_fails() {
+        let mut writer = LegacyVtkWriter::new(Vec::new(), &WriteOptions { binary: true, ..Default::default() });
+        triangle(&mut writer);
+        writer.write_cell_field("ELEMENT_ID", FieldKind::Scalar, &[CellValues::Int(&[7])]).unwrap();
+        let error = writer.write_point_field("NODE_ID", FieldKind::Scalar, PointValues::Int(&[1, 2, 3])).unwrap_err();
+        assert_inconsistent(error, "PointData section written in the CellData section");
+    }
+
+    #[test]
+    fn second_header_fails() {
+        let mut writer = LegacyVtkWriter::new(Vec::new(), &WriteOptions::default());
+        triangle(&mut writer);
+        let error = writer.begin_geometry(&header()).unwrap_err();
+        assert_inconsistent(error, "header written in the Cells section");
+    }
+
+    #[test]
+    fn missing_points_fail() {
+        let mut writer = LegacyVtkWriter::new(Vec::new(), &WriteOptions::default());
+        writer.begin_geometry(&header()).unwrap();
+        writer.write_points_chunk(&[0.0, 0.0, 0.0]).unwrap();
+        let error = writer.write_cells_chunk(&mut std::iter::once((crate::cells::VTK_TRIANGLE, &[0, 1, 2][..]))).unwrap_err();
+        assert_inconsistent(error, "1 points written for POINTS 3");
+    }
+
+    #[test]
+    fn partial_point_fails() {
+        let mut writer = LegacyVtkWriter::new(Vec::new(), &WriteOptions::default());
+        writer.begin_geometry(&header()).unwrap();
+        let error = writer.write_points_chunk(&[0.0, 0.0]).unwrap_err();
+        assert_inconsistent(error, "point chunk of 2 coordinates");
+    }
+
+    #[test]
+    fn miscounted_arrays_fail() {
+        let mut writer = LegacyVtkWriter::new(Vec::new(), &WriteOptions::default());
+        triangle(&mut writer);
+        let error = writer.write_point_field("NODE_ID", FieldKind::Scalar, PointValues::Int(&[1, 2])).unwrap_err();
+        assert_inconsistent(error, "2 values of point array NODE_ID for 3 points");
+        let error = writer.write_cell_field("PART_ID", FieldKind::Scalar, &[CellValues::Int(&[1]), CellValues::Zeros(1)]).unwrap_err();
+        assert_inconsistent(error, "2 cells of cell array PART_ID for 1 cells");
+    }
+}
@@ -0,0 +1,216 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// VtuWriter - MeshWriter of VTK XML unstructured grids (.vtu), the
+// files a multiblock dataset (.vtm) or a parallel one (.pvtu) can
+// reference. Arrays are inline, ASCII or base64 binary as in the image
+// data files. The cells are kept until the first field, then written
+// with the points.
+// ****************************************
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::mesh_writer::{collect_cell_field, CellArray, CellValues, FieldKind, MeshHeader, MeshWriter, PointValues};
+use crate::resample::{data_array, xml_attribute, DataValue};
+
+#[derive(PartialEq)]
+enum Section {
+    Geometry,
+    PointData,
+    CellData,
+}
+
+pub struct VtuWriter<W: Write> {
+    writer: BufWriter<W>,
+    binary: bool,
+    nb_cells: usize,
+    points: Vec<f32>,
+    connectivity: Vec<i32>,
+    offsets: Vec<i32>,
+    types: Vec<u8>,
+    section: Section,
+    declarations: [String; 2], // PDataArray lines of the point and cell arrays
+}
+
+impl VtuWriter<File> {
+    pub fn create(path: &str, binary: bool) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("can't create output file {}: {}", path, e))?;
+        Ok(VtuWriter {
+            writer: BufWriter::new(file),
+            binary,
+            nb_cells: 0,
+            points: Vec::new(),
+            connectivity: Vec::new(),
+            offsets: Vec::new(),
+            types: Vec::new(),
+            section: Section::Geometry,
+            declarations: Default::default(),
+        })
+    }
+}
+
+impl<W: Write> VtuWriter<W> {
+    // point and cell data of the file, as the <PPointData> and
+    // <PCellData> elements of a parallel file over files like it
+    pub fn parallel_declarations(&self) -> String {
+        format!(
+            "    <PPointData>\n{}    </PPointData>\n    <PCellData>\n{}    </PCellData>\n",
+            self.declarations[0], self.declarations[1]
+        )
+    }
+
+    fn array<T: DataValue>(&mut self, name: &str, ncomp: usize, values: &[T]) -> io::Result<()> {
+        let declarations = &mut self.declarations[(self.section == Section::CellData) as usize];
+        declarations.push_str(&format!(
+            "      <PDataArray type=\"{}\" Name=\"{}\" NumberOfComponents=\"{}\"/>\n",
+            T::TYPE,
+            xml_attribute(name),
+            ncomp
+        ));
+        data_array(&mut self.writer, self.binary, name, ncomp, values)
+    }
+
+    // moves to the section of the next array, writing the points and cells before the first field
+    fn enter(&mut self, section: Section) -> io::Result<()> {
+        if self.section == section {
+            return Ok(());
+        }
+        self.close_section()?;
+        match section {
+            Section::PointData => writeln!(self.writer, "      <PointData>")?,
+            Section::CellData => writeln!(self.writer, "      <CellData>")?,
+            Section::Geometry => {}
+        }
+        self.section = section;
+        Ok(())
+    }
+
+    fn close_section(&mut self) -> io::Result<()> {
+        match self.section {
+            Section::Geometry => {
+                writeln!(self.writer, "      <Points>")?;
+                let points = std::mem::take(&mut self.points);
+                data_array(&mut self.writer, self.binary, "Points", 3, &points)?;
+                writeln!(self.writer, "      </Points>")?;
+                writeln!(self.writer, "      <Cells>")?;
+                data_array(&mut self.writer, self.binary, "connectivity", 1, &std::mem::take(&mut self.connectivity))?;
+                data_array(&mut self.writer, self.binary, "offsets", 1, &std::mem::take(&mut self.offsets))?;
+                data_array(&mut self.writer, self.binary, "types", 1, &std::mem::take(&mut self.types))?;
+                writeln!(self.writer, "      </Cells>")
+            }
+            Section::PointData => writeln!(self.writer, "      </PointData>"),
+            Section::CellData => writeln!(self.writer, "      </CellData>"),
+        }
+    }
+}
+
+fn components(kind: FieldKind) -> usize {
+    match kind {
+        FieldKind::Scalar => 1,
+        FieldKind::Vector | FieldKind::Color => 3,
+        FieldKind::Tensor => 9,
+    }
+}
+
+// string array of the FieldData: the characters of each string as
+// integers, each string ended by 0
+fn string_array(name: &str, strings: &[String]) -> String {
+    let mut codes = Vec::new();
+    for string in strings {
+        codes.extend(string.bytes().map(|byte| byte.to_string()));
+        codes.push("0".to_string());
+    }
+    format!(
+        "      <Array type=\"String\" Name=\"{}\" NumberOfTuples=\"{}\" format=\"ascii\">{}</Array>",
+        xml_attribute(name),
+        strings.len(),
+        codes.join(" ")
+    )
+}
+
+impl<W: Write> MeshWriter for VtuWriter<W> {
+    fn begin_geometry(&mut self, header: &MeshHeader) -> io::Result<()> {
+        self.nb_cells = header.nb_cells;
+        let w = &mut self.writer;
+        writeln!(w, "<?xml version=\"1.0\"?>")?;
+        writeln!(w, "<VTKFile type=\"UnstructuredGrid\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt32\">")?;
+        writeln!(w, "  <UnstructuredGrid>")?;
+        writeln!(w, "    <FieldData>")?;
+        writeln!(w, "      <DataArray type=\"Float64\" Name=\"TIME\" NumberOfTuples=\"1\" format=\"ascii\">{}</DataArray>", header.time)?;
+        for (name, strings) in &header.string_tables {
+            writeln!(w, "{}", string_array(name, strings))?;
+        }
+        for (name, min, max) in &header.ranges {
+            writeln!(
+                w,
+                "      <DataArray type=\"Float64\" Name=\"RANGE_{}\" NumberOfComponents=\"2\" NumberOfTuples=\"1\" format=\"ascii\">{} {}</DataArray>",
+                xml_attribute(name),
+                min,
+                max
+            )?;
+        }
+        writeln!(w, "    </FieldData>")?;
+        writeln!(w, "    <Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">", header.nb_points, header.nb_cells)
+    }
+
+    fn write_points_chunk(&mut self, coor: &[f32]) -> io::Result<()> {
+        self.points.extend_from_slice(coor);
+        Ok(())
+    }
+
+    fn write_cells_chunk(&mut self, cells: &mut dyn Iterator<Item = (i32, &[i32])>) -> io::Result<()> {
+        for (cell_type, nodes) in cells {
+            self.connectivity.extend_from_slice(nodes);
+            self.offsets.push(self.connectivity.len() as i32);
+            self.types.push(cell_type as u8);
+        }
+        Ok(())
+    }
+
+    fn write_point_field(&mut self, name: &str, kind: FieldKind, values: PointValues) -> io::Result<()> {
+        self.enter(Section::PointData)?;
+        match values {
+            PointValues::Int(values) => self.array(name, components(kind), values),
+            PointValues::Float(values) => self.array(name, components(kind), values),
+        }
+    }
+
+    fn write_cell_field(&mut self, name: &str, kind: FieldKind, blocks: &[CellValues]) -> io::Result<()> {
+        self.enter(Section::CellData)?;
+        match collect_cell_field(kind, blocks, self.nb_cells) {
+            CellArray::Int(values) => self.array(name, 1, &values),
+            CellArray::Float(values) => self.array(name, components(kind), &values),
+            CellArray::Rgb(values) => self.array(name, 3, &values),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // points and cells of a mesh without fields too
+        self.close_section()?;
+        let w = &mut self.writer;
+        writeln!(w, "    </Piece>")?;
+        writeln!(w, "  </UnstructuredGrid>")?;
+        writeln!(w, "</VTKFile>")?;
+        w.flush()
+    }
+}
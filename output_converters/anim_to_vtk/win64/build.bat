@@ -11,6 +11,8 @@ cargo build --release
 set error_var=%errorlevel%
 if %error_var%==0 (
   copy target\release\anim_to_vtk.exe ..\..\..\exec\anim_to_vtk_win64.exe
+  copy target\release\anim_to_vtk.exe ..\..\..\exec\anim_header_diff_win64.exe
+  copy target\release\anim_to_vtk.exe ..\..\..\exec\anim_info_win64.exe
   echo.
   echo Build succeeded
   echo.
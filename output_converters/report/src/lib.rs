@@ -0,0 +1,560 @@
+//Copyright>
+//Copyright> Copyright (C) 1986-2026 Altair Engineering Inc.
+//Copyright>
+//Copyright> Permission is hereby granted, free of charge, to any person obtaining
+//Copyright> a copy of this software and associated documentation files (the "Software"),
+//Copyright> to deal in the Software without restriction, including without limitation
+//Copyright> the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+//Copyright> sell copies of the Software, and to permit persons to whom the Software is
+//Copyright> furnished to do so, subject to the following conditions:
+//Copyright>
+//Copyright> The above copyright notice and this permission notice shall be included in all
+//Copyright> copies or substantial portions of the Software.
+//Copyright>
+//Copyright> THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//Copyright> IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//Copyright> FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//Copyright> AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY,
+//Copyright> WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//Copyright> IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+//Copyright>
+
+// ****************************************
+// Console and report output shared by the output converters:
+// progress, summary tables, JSON reports and exit codes, so that
+// wrapper scripts see the same behavior from every tool
+// ****************************************
+
+use std::cell::RefCell;
+use std::fmt::{Display, Write};
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::process;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+// exit codes
+pub const EXIT_SUCCESS: i32 = 0; // every input converted
+pub const EXIT_FAILURE: i32 = 1; // usage error, or at least one input failed
+
+// print "Error: <message>" and leave with EXIT_FAILURE
+pub fn fail(message: impl Display) -> ! {
+    eprintln!("Error: {}", message);
+    process::exit(EXIT_FAILURE);
+}
+
+// ****************************************
+// Console lines - on stderr, or kept while the thread captures them:
+// a worker converting one file among others captures its lines, which
+// are then printed in one block instead of interleaving with the lines
+// of the other files. Its diagnostic records are kept the same way.
+// ****************************************
+#[derive(Default)]
+pub struct Captured {
+    lines: String,
+    records: String, // JSON lines of the diagnostics file
+}
+
+impl Captured {
+    // prints the lines and appends the records to the diagnostics file
+    pub fn print(&self) {
+        eprint!("{}", self.lines);
+        append_records(&self.records);
+    }
+
+    // console lines kept, as they would have been printed
+    pub fn lines(&self) -> &str {
+        &self.lines
+    }
+}
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Captured>> = const { RefCell::new(None) };
+}
+
+pub fn write_line(message: impl Display) {
+    CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(captured) => {
+            let _ = writeln!(captured.lines, "{}", message);
+        }
+        None => eprintln!("{}", message),
+    });
+}
+
+// runs f, returns its result and what it wrote
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Captured) {
+    let previous = CAPTURED.with(|captured| captured.replace(Some(Captured::default())));
+    let result = f();
+    let captured = CAPTURED.with(|captured| captured.replace(previous)).unwrap_or_default();
+    (result, captured)
+}
+
+// ****************************************
+// Diagnostics - warnings and errors with a stable code, printed as
+// "Warning [W001]: ..." and, once open_diagnostics is called, appended
+// to a JSON lines file, so that pipelines can categorize and track
+// recurring conversion issues:
+//   {"severity": "warning", "code": "W001", "input": "runA001", "message": "..."}
+// The input is the file being converted, null for the run itself.
+// ****************************************
+pub mod codes {
+    // input data, the file is converted
+    pub const MISSING_ID_ARRAY: &str = "W001"; // no node or element user ids
+    pub const UNKNOWN_FLAGS: &str = "W002"; // flags unknown to the format layout, blocks skipped
+    pub const TRAILING_BYTES: &str = "W003"; // bytes after the last section
+    pub const MISSING_PART_TABLE: &str = "W004"; // elements without part table
+    pub const PART_TABLE_MISMATCH: &str = "W005"; // part table not ending at the element count
+    pub const NODES_OUT_OF_RANGE: &str = "W006"; // elements referring to nodes out of range
+    pub const TIME_UNKNOWN: &str = "W007"; // time not read from the header
+    pub const TIME_ORDER: &str = "W008"; // times not monotonic, repeated or unevenly spaced
+    pub const NO_MASS: &str = "W009"; // no masses, unit masses used
+    pub const UNPAIRED_NODES: &str = "W010"; // nodes without mirror
+    pub const MISSING_NODES: &str = "W011"; // tracked nodes not in the state
+    pub const NO_MATERIAL: &str = "W012"; // elements without material
+    pub const NO_SPH_RADIUS: &str = "W013"; // no SPH diameter, or mass and density
+    pub const PART_NAME_UNPARSABLE: &str = "W014"; // part title without a leading id
+    // options
+    pub const NO_EFFECT: &str = "W101"; // option without effect in this run
+    pub const NO_MATCH: &str = "W102"; // no array, part or variable matches a name given
+    pub const INVALID_INPUT_NAME: &str = "W103"; // input file name skipped
+    // input files that can't be converted
+    pub const CANT_OPEN: &str = "E200"; // missing or unreadable file
+    pub const TRUNCATED_SECTION: &str = "E201"; // section larger than what is left of the file
+    pub const UNKNOWN_VERSION: &str = "E202"; // magic number of no known format version
+    pub const INVALID_COUNT: &str = "E203"; // negative count
+    pub const READ_FAILED: &str = "E204"; // I/O error while reading a section
+    pub const INCONSISTENT_DATA: &str = "E205"; // anomaly rejected by the parse mode
+    pub const CONVERSION_FAILED: &str = "E206"; // data the requested conversion can't use
+    // outputs
+    pub const WRITE_FAILED: &str = "E301"; // output file that can't be created or written
+}
+
+// a message with its code, as an error travels up to the run
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, message: impl Display) -> Self {
+        Diagnostic { code, message: message.to_string() }
+    }
+
+    pub fn print(&self) {
+        error(self.code, &self.message);
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+thread_local! {
+    static INPUT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static DIAGNOSTICS: OnceLock<Mutex<fs::File>> = OnceLock::new();
+
+// creates the diagnostics file, the records of the run go to it
+pub fn open_diagnostics(path: &str) -> io::Result<()> {
+    let file = fs::File::create(path)?;
+    let _ = DIAGNOSTICS.set(Mutex::new(file));
+    Ok(())
+}
+
+fn append_records(records: &str) {
+    if let (Some(file), false) = (DIAGNOSTICS.get(), records.is_empty()) {
+        let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = io::Write::write_all(&mut *file, records.as_bytes());
+    }
+}
+
+// runs f with the diagnostics it gives attributed to the input file
+pub fn for_input<T>(input: &str, f: impl FnOnce() -> T) -> T {
+    let previous = INPUT.with(|current| current.replace(Some(input.to_string())));
+    let result = f();
+    INPUT.with(|current| current.replace(previous));
+    result
+}
+
+fn record(severity: &str, code: &str, message: &str) {
+    if DIAGNOSTICS.get().is_none() {
+        return;
+    }
+    let input = INPUT.with(|current| current.borrow().as_deref().map_or("null".to_string(), json_string));
+    let json = format!(
+        "{{\"severity\": \"{}\", \"code\": \"{}\", \"input\": {}, \"message\": {}}}\n",
+        severity,
+        code,
+        input,
+        json_string(message)
+    );
+    CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(captured) => captured.records.push_str(&json),
+        None => append_records(&json),
+    });
+}
+
+// warning about the input being converted (indented under its lines), or the run
+pub fn warning(code: &'static str, message: impl Display) {
+    let message = message.to_string();
+    let indent = if INPUT.with(|current| current.borrow().is_some()) { "  " } else { "" };
+    write_line(format_args!("{}Warning [{}]: {}", indent, code, message));
+    record("warning", code, &message);
+}
+
+// error of the input being converted, prefixed by its name, or of the run
+pub fn error(code: &'static str, message: impl Display) {
+    let message = message.to_string();
+    match INPUT.with(|current| current.borrow().clone()) {
+        Some(input) => write_line(format_args!("Error [{}]: {}: {}", code, input, message)),
+        None => write_line(format_args!("Error [{}]: {}", code, message)),
+    }
+    record("error", code, &message);
+}
+
+// ****************************************
+// Colors - only on an interactive stderr, never when NO_COLOR is set
+// ****************************************
+#[derive(Clone, Copy)]
+pub enum Color {
+    Green,
+    Yellow,
+    Red,
+}
+
+fn interactive() -> bool {
+    static INTERACTIVE: OnceLock<bool> = OnceLock::new();
+    *INTERACTIVE.get_or_init(|| io::stderr().is_terminal())
+}
+
+pub fn use_color() -> bool {
+    interactive() && std::env::var_os("NO_COLOR").is_none()
+}
+
+pub fn paint(text: &str, color: Color) -> String {
+    if !use_color() {
+        return text.to_string();
+    }
+    let code = match color {
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Red => 31,
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+// ****************************************
+// Progress - a bar in front of the messages on a terminal,
+// the bare messages otherwise (log files, wrapper scripts)
+// ****************************************
+const BAR_WIDTH: usize = 20;
+
+pub struct Progress {
+    total: usize,
+    current: usize,
+    bar: bool,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Self {
+        Progress { total, current: 0, bar: total > 1 && interactive() }
+    }
+
+    // move to the next item
+    pub fn next(&mut self) {
+        self.current = (self.current + 1).min(self.total);
+    }
+
+    // progress at the item of the given index, for a worker converting it
+    pub fn at(&self, index: usize) -> Progress {
+        Progress { total: self.total, current: (index + 1).min(self.total), bar: self.bar }
+    }
+
+    pub fn message(&self, message: impl Display) {
+        if !self.bar {
+            write_line(message);
+            return;
+        }
+        let filled = BAR_WIDTH * self.current / self.total;
+        let width = self.total.to_string().len();
+        write_line(format_args!(
+            "[{}{}] {:>width$}/{} {}",
+            paint(&"#".repeat(filled), Color::Green),
+            " ".repeat(BAR_WIDTH - filled),
+            self.current,
+            self.total,
+            message,
+            width = width
+        ));
+    }
+}
+
+// ****************************************
+// Table - left aligned columns on stderr, optionally one color per row
+// ****************************************
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<(Vec<String>, Option<Color>)>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Table { headers: headers.iter().map(|h| h.to_string()).collect(), rows: Vec::new() }
+    }
+
+    pub fn add_row(&mut self, cells: Vec<String>, color: Option<Color>) {
+        self.rows.push((cells, color));
+    }
+
+    pub fn print(&self) {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for (cells, _) in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let line = |cells: &[String]| {
+            let padded: Vec<String> = cells.iter().zip(&widths).map(|(cell, &w)| format!("{:<w$}", cell, w = w)).collect();
+            format!("  {}", padded.join("  ").trim_end())
+        };
+        write_line(line(&self.headers));
+        for (cells, color) in &self.rows {
+            let text = line(cells);
+            match color {
+                Some(color) => write_line(paint(&text, *color)),
+                None => write_line(text),
+            }
+        }
+    }
+}
+
+// ****************************************
+// Report - outcome of every input of a run: console summary,
+// JSON file for wrapper scripts and process exit code
+// ****************************************
+struct Entry {
+    input: String,
+    outputs: Vec<String>,
+    seconds: f64,
+    error: Option<String>,
+}
+
+pub struct Report {
+    tool: String,
+    entries: Vec<Entry>,
+}
+
+// quoted JSON string, escaped
+pub fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+impl Report {
+    pub fn new(tool: &str) -> Self {
+        Report { tool: tool.to_string(), entries: Vec::new() }
+    }
+
+    pub fn success(&mut self, input: &str, outputs: &[String], duration: Duration) {
+        self.entries.push(Entry {
+            input: input.to_string(),
+            outputs: outputs.to_vec(),
+            seconds: duration.as_secs_f64(),
+            error: None,
+        });
+    }
+
+    pub fn failure(&mut self, input: &str, error: impl Display) {
+        self.entries.push(Entry {
+            input: input.to_string(),
+            outputs: Vec::new(),
+            seconds: 0.0,
+            error: Some(error.to_string()),
+        });
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.error.is_none()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.entries.len() - self.succeeded()
+    }
+
+    // (input, outputs, error) of every input, in the order they were reported
+    pub fn outcomes(&self) -> impl Iterator<Item = (&str, &[String], Option<&str>)> {
+        self.entries.iter().map(|entry| (entry.input.as_str(), entry.outputs.as_slice(), entry.error.as_deref()))
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if self.failed() > 0 {
+            EXIT_FAILURE
+        } else {
+            EXIT_SUCCESS
+        }
+    }
+
+    pub fn print_summary(&self) {
+        let failed = self.failed();
+        if failed > 0 {
+            eprintln!("\nConversion summary: {} succeeded, {} failed", self.succeeded(), failed);
+            eprintln!("Failed files:");
+            let mut table = Table::new(&["file", "error"]);
+            for entry in &self.entries {
+                if let Some(error) = &entry.error {
+                    table.add_row(vec![entry.input.clone(), error.clone()], Some(Color::Red));
+                }
+            }
+            table.print();
+        } else if self.succeeded() > 1 {
+            eprintln!("\nConversion complete: {} files converted successfully", self.succeeded());
+        }
+    }
+
+    // written to a temporary file then renamed, readers never see a partial file
+    pub fn write_json(&self, path: &str) -> io::Result<()> {
+        let mut json = format!(
+            "{{\n  \"tool\": {},\n  \"succeeded\": {},\n  \"failed\": {},\n  \"exit_code\": {},\n  \"files\": [",
+            json_string(&self.tool),
+            self.succeeded(),
+            self.failed(),
+            self.exit_code()
+        );
+        for (i, entry) in self.entries.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            json.push_str(&format!("    {{\"input\": {}, ", json_string(&entry.input)));
+            match &entry.error {
+                None => {
+                    let outputs: Vec<String> = entry.outputs.iter().map(|output| json_string(output)).collect();
+                    json.push_str(&format!(
+                        "\"status\": \"ok\", \"outputs\": [{}], \"seconds\": {:.6}}}",
+                        outputs.join(", "),
+                        entry.seconds
+                    ));
+                }
+                Some(error) => json.push_str(&format!("\"status\": \"failed\", \"error\": {}}}", json_string(error))),
+            }
+        }
+        json.push_str(if self.entries.is_empty() { "]\n}\n" } else { "\n  ]\n}\n" });
+
+        let temporary = format!("{}.tmp", path);
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::Value;
+
+    // the diagnostics file of the tests, opened once for the process
+    fn diagnostics_path() -> &'static str {
+        static PATH: OnceLock<String> = OnceLock::new();
+        PATH.get_or_init(|| {
+            let path = std::env::temp_dir().join(format!("report_diagnostics_{}.jsonl", std::process::id()));
+            let path = path.to_string_lossy().into_owned();
+            open_diagnostics(&path).unwrap();
+            path
+        })
+    }
+
+    fn parse_records(records: &str) -> Vec<Value> {
+        records.lines().map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{}: {}", line, e))).collect()
+    }
+
+    #[test]
+    fn captured_lines_stay_in_order_per_thread() {
+        diagnostics_path();
+        let inputs: Vec<String> = (0..8).map(|i| format!("orderA{:03}", i + 1)).collect();
+        let workers: Vec<_> = inputs
+            .iter()
+            .cloned()
+            .map(|input| {
+                std::thread::spawn(move || {
+                    capture(|| {
+                        for_input(&input, || {
+                            for i in 0..50 {
+                                write_line(format_args!("{} line {}", input, i));
+                                if i % 10 == 9 {
+                                    warning(codes::NO_MATCH, format_args!("{} warning {}", input, i));
+                                }
+                            }
+                        })
+                    })
+                    .1
+                })
+            })
+            .collect();
+        let captured: Vec<Captured> = workers.into_iter().map(|worker| worker.join().unwrap()).collect();
+
+        for (input, captured) in inputs.iter().zip(&captured) {
+            let mut expected = String::new();
+            for i in 0..50 {
+                expected.push_str(&format!("{} line {}\n", input, i));
+                if i % 10 == 9 {
+                    expected.push_str(&format!("  Warning [W102]: {} warning {}\n", input, i));
+                }
+            }
+            assert_eq!(captured.lines, expected);
+            let messages: Vec<Value> = parse_records(&captured.records).into_iter().map(|record| record["message"].clone()).collect();
+            assert_eq!(messages, [9, 19, 29, 39, 49].map(|i| Value::from(format!("{} warning {}", input, i))));
+        }
+        // nothing left captured on this thread
+        assert!(CAPTURED.with(|captured| captured.borrow().is_none()));
+
+        // printed in the order of the inputs, the records of a file stay together
+        for captured in &captured {
+            captured.print();
+        }
+        let records = parse_records(&fs::read_to_string(diagnostics_path()).unwrap());
+        let order: Vec<&str> = records.iter().filter_map(|record| record["input"].as_str()).filter(|input| input.starts_with("orderA")).collect();
+        let expected: Vec<&str> = inputs.iter().flat_map(|input| [input.as_str(); 5]).collect();
+        assert_eq!(order, expected);
+    }
+
+    #[test]
+    fn diagnostic_records_are_json_lines() {
+        diagnostics_path();
+        let message = "quote \" backslash \\ newline \n tab \t bell \u{7} accent é";
+        let ((), captured) = capture(|| {
+            warning(codes::NO_EFFECT, "run level");
+            for_input("C:\\runs\\\"crash\"A001", || {
+                warning(codes::MISSING_ID_ARRAY, message);
+                error(codes::WRITE_FAILED, message);
+            });
+        });
+
+        assert_eq!(captured.records.lines().count(), 3);
+        let records = parse_records(&captured.records);
+        let fields = |record: &Value| {
+            let object = record.as_object().unwrap();
+            assert_eq!(object.keys().collect::<Vec<_>>(), ["code", "input", "message", "severity"]);
+            (record["severity"].clone(), record["code"].clone(), record["input"].clone(), record["message"].clone())
+        };
+        assert_eq!(fields(&records[0]), ("warning".into(), "W101".into(), Value::Null, "run level".into()));
+        assert_eq!(
+            fields(&records[1]),
+            ("warning".into(), "W001".into(), "C:\\runs\\\"crash\"A001".into(), message.into())
+        );
+        assert_eq!(fields(&records[2]), ("error".into(), "E301".into(), "C:\\runs\\\"crash\"A001".into(), message.into()));
+    }
+}